@@ -56,11 +56,269 @@ use-bright-bold = Make bold text brighter
 splits = Splits
 focus-follow-mouse = Typing focus follows mouse
 
+### Profiles
+config-profiles = Profiles
+config-profile = Active profile
+config-profile-automatic = Automatic
+config-profile-active = Selected automatically: {$name}
+config-profile-none = No profile matches this machine right now.
+config-profile-keys = Settings taken from the profile
+
+### Background
+background = Background
+scene = Scene
+frame-rate = Frame rate
+frame-rate-fps = {$fps} fps
+scene-earth = Earth
+scene-plasma = Plasma
+scene-starfield = Starfield
+scene-waveform = Waveform
+idle-throttling = Slow down while idle
+idle-throttling-description = Redraw once a second without input while unfocused or on battery.
+
+### Resource monitor
+resource-monitor = Resource monitor
+temperature-unit = Temperature unit
+celsius = Celsius
+fahrenheit = Fahrenheit
+byte-unit = Memory unit
+byte-unit-decimal = GB, MB
+byte-unit-binary = GiB, MiB
+clock-format = Clock
+clock-24h = 24-hour
+clock-12h = 12-hour
+clock-seconds = Show seconds
+clock-date = Show date
+sun-moon = Show sunrise, sunset and moon phase
+process-sort-default = Sort processes by
+sort-cpu = CPU
+sort-ram = RAM
+monitor-font = Font
+monitor-font-size = Text size
+monitor-layout = Layout
+one-column = One column
+two-columns = Two columns
+horizontal = Horizontal bar
+monitor-tabs = Show tabs
+compact-mode = Compact mode
+touch-targets = Larger touch targets
+monitor-page-overview = Overview
+monitor-page-processes = Processes
+monitor-page-cores = Cores
+section-clock = Show clock
+section-agenda = Show agenda
+section-headlines = Show headlines
+section-github = Show GitHub
+section-mail = Show mail
+section-todo = Show tasks
+section-ticker = Show ticker
+section-keyboard = Show keyboard
+section-workspaces = Show workspaces
+section-screen-time = Show screen time
+section-quote = Show quote
+section-weather = Show weather
+section-pomodoro = Show pomodoro timer
+section-media = Show media
+section-spectrum = Show audio spectrum
+section-system = Show system
+section-graph-range = Show graph range
+section-cpu = Show CPU
+section-memory = Show memory
+section-gpu = Show GPU
+section-remote = Show remote hosts
+section-agents = Show agents
+section-processes = Show processes
+monitor-header-clock = CLOCK
+monitor-header-agenda = AGENDA
+monitor-header-headlines = HEADLINES
+monitor-header-github = GITHUB
+monitor-header-mail = MAIL
+monitor-header-todo = TODO
+monitor-header-ticker = TICKER
+monitor-header-keyboard = KEYBOARD
+monitor-header-workspaces = WORKSPACES
+monitor-header-screen-time = SCREEN TIME
+monitor-header-quote = QUOTE
+monitor-header-weather = WEATHER
+monitor-header-pomodoro = POMODORO
+monitor-header-media = MEDIA
+monitor-header-spectrum = SPECTRUM
+monitor-header-system = SYSTEM
+monitor-header-graph-range = GRAPHS
+monitor-header-cpu = CPU
+monitor-header-memory = MEMORY
+monitor-header-gpu = GPU
+monitor-header-remote = REMOTE HOSTS
+monitor-header-agents = AGENTS
+monitor-header-processes = PROCESSES
+
 ### Advanced
 advanced = Advanced
 show-headerbar = Show header
 show-header-description = Reveal the header from the right-click menu.
 
+## Shader tweaks
+shader-tweaks = Tweak background
+shader-speed = Speed
+shader-intensity = Intensity
+shader-hue = Hue shift
+
+# Resource monitor
+monitor-os = OS
+monitor-kernel = KERNEL
+monitor-render-unavailable = RENDER unavailable
+monitor-cpu-avg = CPU AVG
+monitor-cpu-max = CPU MAX
+monitor-cpu-freq = CPU FRQ
+monitor-mem-used = MEM USE
+monitor-gpu-util = GPU UTL
+monitor-gpu-freq = GPU FRQ
+monitor-gpu-mem = GPU MEM
+monitor-gpu-power = GPU PWR
+monitor-gpu-temp = GPU TMP
+monitor-range = RANGE
+monitor-process-name = NAME
+monitor-process-cpu = CPU
+monitor-process-ram = RAM
+process-terminate = Terminate
+process-detail-close = Close
+process-pid = PID {$pid}
+process-pids = PID {$pid} and {$count ->
+    [one] one more
+   *[other] {$count} more
+}
+process-terminate-confirm = Terminate {$count ->
+    [one] {$name}?
+   *[other] all {$count} processes named {$name}?
+}
+process-terminate-cancel = Cancel
+process-terminated = Asked {$count ->
+    [one] {$name}
+   *[other] {$count} processes named {$name}
+} to terminate
+process-terminate-failed = Failed to terminate {$name} ({$pids})
+process-terminate-unsupported = Terminating processes is not supported on this system
+process-none-selected = Select a process to terminate first
+monitor-paused = PAUSED
+monitor-overlay-cpu = CPU
+monitor-overlay-gpu = GPU
+monitor-overlay-mem = MEM
+
+# Descriptions of the metrics for screen readers
+a11y-metric = {$name} {$value} {$unit}, {$trend}
+a11y-cpu-avg = CPU average
+a11y-cpu-max = CPU maximum
+a11y-mem-used = Memory used
+a11y-gpu-util = GPU utilization
+a11y-gpu-temp = GPU temperature
+a11y-percent = percent
+a11y-rising = rising
+a11y-falling = falling
+a11y-steady = steady
+graph-now = now
+graph-min = min
+graph-avg = avg
+graph-max = max
+# chrono formats of the clock and of times next to graphs, see
+# https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+clock-format-24h = %H : %M : %S
+clock-format-12h = %-I:%M:%S %p
+clock-format-24h-minutes = %H : %M
+clock-format-12h-minutes = %-I:%M %p
+date-format = %A, %-d %B %Y
+calendar-title = %B %Y
+calendar-weekdays = Mo Tu We Th Fr Sa Su
+time-format-24h = %H:%M:%S
+time-format-12h = %-I:%M:%S %p
+short-time-format-24h = %H:%M
+short-time-format-12h = %-I:%M %p
+hour-format-24h = %H
+hour-format-12h = %-I%P
+agenda-day-format = %a %-d %b
+agenda-all-day = all day
+agenda-now = now
+agenda-in = in {$time}
+agenda-empty = No upcoming events
+headlines-empty = No headlines yet
+headlines-ago = {$time} ago
+github-loading = Loading…
+github-notifications = Notifications: {$count} unread
+github-no-runs = no workflow runs
+github-offline = offline
+mail-unread = {$count} unread
+mail-offline = not connected
+mail-unavailable = Built without mail support
+todo-empty = Nothing to do
+todo-due = due {$date}
+ticker-loading = Loading…
+ticker-stale = as of {$time}
+keyboard-layout = Layout
+keyboard-caps-lock = CAPS
+keyboard-num-lock = NUM
+workspaces-none = No COSMIC workspaces
+workspaces-unavailable = Built without Wayland support
+screen-time-none = Nothing focused yet today
+screen-time-hours = {$hours}h {$minutes}m
+screen-time-minutes = {$minutes}m
+screen-time-total = Total
+screen-time-unavailable = Built without Wayland support
+quote-none = No quotes found
+quote-attribution = — {$source}
+remote-none = No hosts in remote_hosts
+remote-offline = not connected
+remote-load = load {$load}
+remote-usage = CPU {$cpu}%  MEM {$memory}%
+agents-none = No agents in agents
+agents-offline = {$agent} is not connected
+agents-unavailable = Built without agent support
+agents-cpu = CPU
+agents-cpu-max = Busiest CPU
+agents-memory = Memory
+agents-gpu = GPU
+agents-gpu-temp = GPU temperature
+agents-gpu-memory = GPU memory
+agents-load = Load
+agents-uptime = Up {$days}d {$hours}h
+alert-fired = Alert: {$rule}
+alert-value = Now at {$value}
+sun-times = ↑ {$sunrise}  ↓ {$sunset}
+sun-always-up = Sun up all day
+sun-always-down = Sun down all day
+moon-phase = {$phase} {$illumination}%
+moon-new = New moon
+moon-waxing-crescent = Waxing crescent
+moon-first-quarter = First quarter
+moon-waxing-gibbous = Waxing gibbous
+moon-full = Full moon
+moon-waning-gibbous = Waning gibbous
+moon-last-quarter = Last quarter
+moon-waning-crescent = Waning crescent
+weather-loading = Fetching the weather…
+weather-offline = offline, as of {$time}
+weather-clear = Clear
+weather-partly-cloudy = Partly cloudy
+weather-overcast = Overcast
+weather-fog = Fog
+weather-drizzle = Drizzle
+weather-rain = Rain
+weather-snow = Snow
+weather-thunderstorm = Thunderstorm
+pomodoro-work = Focus
+pomodoro-break = Short break
+pomodoro-long-break = Long break
+pomodoro-work-body = Time to focus again.
+pomodoro-break-body = Take a few minutes off.
+pomodoro-long-break-body = Well done, take a longer rest.
+pomodoro-start = Start
+pomodoro-pause = Pause
+pomodoro-reset = Reset
+alarm-due = Alarm {$time}
+alarm-next = Alarm {$time} {$label}
+countdown-due = {$label} is now
+countdown-in = {$label} in {$time}
+media-nothing = Nothing playing
+media-unavailable = Built without MPRIS support
+
 # Find
 find-placeholder = Find...
 find-previous = Find previous
@@ -96,5 +354,18 @@ split-horizontal = Split horizontal
 split-vertical = Split vertical
 pane-toggle-maximize = Toggle maximized
 menu-color-schemes = Color schemes...
+next-scene = Next background scene
+menu-shader-tweaks = Tweak background...
+toggle-overlay = Show or hide the overlay
+toggle-pip = Show or hide the mini monitor
+tray-show-window = Show window
+tray-hide-window = Hide window
+tray-pause = Pause monitoring
+next-config-profile = Next profile
 menu-settings = Settings...
+export-metrics = Export metrics
+export-metrics-graphs = Export metrics and graphs
+export-background = Save background as PNG
+background-saved = Background saved
+background-save-failed = Failed to save the background
 menu-about = About ...
@@ -0,0 +1,132 @@
+//! Accessible names for the parts of the monitor assistive technology cannot make sense of by
+//! itself. A metric's line and its graph, drawn by a shader or in braille, are exposed as a
+//! single meter node that reads like "CPU average 43 percent, rising" instead of glyphs.
+
+use cosmic::iced::{
+    event::{Event, Status},
+    mouse, Length, Rectangle, Size, Vector,
+};
+use cosmic::iced_accessibility::{
+    accesskit::{NodeBuilder, Rect, Role},
+    A11yTree,
+};
+use cosmic::iced_core::{
+    clipboard::Clipboard,
+    layout::{self, Layout},
+    overlay, renderer,
+    widget::{operation::Operation, Id, Tree, Widget},
+    Shell,
+};
+use cosmic::{Element, Renderer, Theme};
+
+/// Content that assistive technology reads as one node of the given name
+pub struct Labelled<'a, Message> {
+    content: Element<'a, Message>,
+    id: Id,
+    name: String,
+}
+
+/// Expose `content` by `name`, with an `id` that stays the same across frames so the node
+/// keeps its focus in the screen reader while the value changes
+pub fn labelled<'a, Message:'a>(content:impl Into<Element<'a, Message>>, id:impl Into<String>, name:String)->Element<'a, Message>{
+    Labelled { content: content.into(), id: Id::new(id.into()), name }.into()
+}
+
+impl<'a, Message> Widget<Message, Theme, Renderer> for Labelled<'a, Message> {
+    fn size(&self)->Size<Length>{
+        self.content.as_widget().size()
+    }
+
+    fn size_hint(&self)->Size<Length>{
+        self.content.as_widget().size_hint()
+    }
+
+    fn children(&self)->Vec<Tree>{
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&mut self, tree:&mut Tree){
+        tree.diff_children(std::slice::from_mut(&mut self.content));
+    }
+
+    fn layout(&self, tree:&mut Tree, renderer:&Renderer, limits:&layout::Limits)->layout::Node{
+        self.content.as_widget().layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(&self, tree:&mut Tree, layout:Layout<'_>, renderer:&Renderer, operation:&mut dyn Operation){
+        self.content.as_widget().operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree:&mut Tree,
+        event:Event,
+        layout:Layout<'_>,
+        cursor:mouse::Cursor,
+        renderer:&Renderer,
+        clipboard:&mut dyn Clipboard,
+        shell:&mut Shell<'_, Message>,
+        viewport:&Rectangle,
+    )->Status{
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0], event, layout, cursor, renderer, clipboard, shell, viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree:&Tree,
+        layout:Layout<'_>,
+        cursor:mouse::Cursor,
+        viewport:&Rectangle,
+        renderer:&Renderer,
+    )->mouse::Interaction{
+        self.content.as_widget().mouse_interaction(&tree.children[0], layout, cursor, viewport, renderer)
+    }
+
+    fn draw(
+        &self,
+        tree:&Tree,
+        renderer:&mut Renderer,
+        theme:&Theme,
+        style:&renderer::Style,
+        layout:Layout<'_>,
+        cursor:mouse::Cursor,
+        viewport:&Rectangle,
+    ){
+        self.content.as_widget().draw(&tree.children[0], renderer, theme, style, layout, cursor, viewport);
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree:&'b mut Tree,
+        layout:Layout<'_>,
+        renderer:&Renderer,
+        translation:Vector,
+    )->Option<overlay::Element<'b, Message, Theme, Renderer>>{
+        self.content.as_widget_mut().overlay(&mut tree.children[0], layout, renderer, translation)
+    }
+
+    fn id(&self)->Option<Id>{
+        Some(self.id.clone())
+    }
+
+    fn set_id(&mut self, id:Id){
+        self.id = id;
+    }
+
+    /// A single meter node in place of the nodes of the content
+    fn a11y_nodes(&self, layout:Layout<'_>, _tree:&Tree, _cursor:mouse::Cursor)->A11yTree{
+        let Rectangle { x, y, width, height } = layout.bounds();
+        let mut node = NodeBuilder::new(Role::Meter);
+        node.set_bounds(Rect::new(x as f64, y as f64, (x + width) as f64, (y + height) as f64));
+        node.set_name(self.name.clone());
+        A11yTree::leaf(node, self.id.clone())
+    }
+}
+
+impl<'a, Message:'a> From<Labelled<'a, Message>> for Element<'a, Message> {
+    fn from(labelled:Labelled<'a, Message>)->Self{
+        Element::new(labelled)
+    }
+}
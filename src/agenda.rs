@@ -0,0 +1,493 @@
+//! Agenda of upcoming events from ICS calendars, given as files, URLs or CalDAV collections in
+//! `Config::agenda_sources`. They are fetched through `curl` on a background thread every
+//! `Config::agenda_refresh_minutes` and cached, so the events stay known while offline.
+//! Credentials of CalDAV servers are taken from `~/.netrc`.
+//!
+//! Recurring events are expanded for the common rules: a daily, weekly, monthly or yearly
+//! frequency with an interval and a count or an end, the weekdays of weekly events, excluded
+//! dates and moved occurrences.
+
+use std::any::TypeId;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use cosmic::iced::Subscription;
+
+use crate::{util, worker, Message};
+
+/// Prefix of a source that is a CalDAV collection rather than an ICS file
+const CALDAV_PREFIX:&str = "caldav:";
+/// Days ahead recurring events are expanded for
+const HORIZON_DAYS:i64 = 62;
+/// Periods of a recurrence rule looked at before giving up, e.g. 270 years of a daily event
+const MAX_PERIODS:u32 = 100_000;
+
+/// Query for all events of a CalDAV collection with their ICS
+const CALDAV_QUERY:&str = r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><c:calendar-data/></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT"/></c:comp-filter></c:filter>
+</c:calendar-query>"#;
+
+/// Occurrence of an event
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// The event takes whole days and is shown without a time
+    pub all_day: bool,
+}
+
+/// Time zone a time of a calendar is given in
+#[derive(Clone, Copy, Debug)]
+enum Zone {
+    Utc,
+    Tz(chrono_tz::Tz),
+    /// Floating times and dates are in the local time zone
+    Local,
+}
+
+impl Zone {
+    /// `None` for times skipped by a change to daylight saving time
+    fn to_utc(self, time:NaiveDateTime)->Option<DateTime<Utc>>{
+        match self {
+            Zone::Utc => Some(time.and_utc()),
+            Zone::Tz(tz) => tz.from_local_datetime(&time).earliest().map(|time| time.with_timezone(&Utc)),
+            Zone::Local => Local.from_local_datetime(&time).earliest().map(|time| time.with_timezone(&Utc)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Frequency { Daily, Weekly, Monthly, Yearly }
+
+/// Recurrence rule of an event
+#[derive(Debug)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<DateTime<Utc>>,
+    /// Weekdays of a weekly event, in order from Monday
+    weekdays: Vec<Weekday>,
+}
+
+impl Rule {
+    fn parse(value:&str)->Option<Self>{
+        let mut rule = Rule { frequency: Frequency::Daily, interval: 1, count: None, until: None, weekdays: Vec::new() };
+        let mut frequency = None;
+        for part in value.split(';') {
+            let (key, value) = part.split_once('=')?;
+            match key {
+                "FREQ" => frequency = Some(match value {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    // hourly and finer rules are rare in calendars
+                    _ => return None,
+                }),
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|interval| *interval > 0)?,
+                "COUNT" => rule.count = value.parse().ok(),
+                "UNTIL" => rule.until = parse_time("", value).and_then(|(time, zone, _)| zone.to_utc(time)),
+                "BYDAY" => rule.weekdays = value.split(',').filter_map(weekday).collect(),
+                _ => (),
+            }
+        }
+        rule.frequency = frequency?;
+        rule.weekdays.sort_by_key(|day| day.num_days_from_monday());
+        Some(rule)
+    }
+
+    /// Starts of the occurrences in the order they follow `start`, as long as `keep` holds and
+    /// they are within the dates chrono can represent
+    fn occurrences(&self, start:NaiveDateTime, mut keep:impl FnMut(NaiveDateTime)->bool){
+        let days = |days:u32| chrono::Duration::try_days(days.into());
+        let weeks = |weeks:u32| chrono::Duration::try_weeks(weeks.into());
+        let mut emitted = 0;
+        for period in 0..MAX_PERIODS {
+            let step = period.saturating_mul(self.interval);
+            let candidates:Option<Vec<NaiveDateTime>> = match self.frequency {
+                Frequency::Daily => days(step).and_then(|days| start.checked_add_signed(days)).map(|time| vec![time]),
+                Frequency::Weekly if self.weekdays.is_empty() => weeks(step).and_then(|weeks| start.checked_add_signed(weeks)).map(|time| vec![time]),
+                Frequency::Weekly => days(start.weekday().num_days_from_monday())
+                    .and_then(|days| start.date().checked_sub_signed(days))
+                    .zip(weeks(step))
+                    .and_then(|(monday, weeks)| monday.checked_add_signed(weeks))
+                    .and_then(|monday| self.weekdays.iter()
+                        .map(|day| days(day.num_days_from_monday())
+                            .and_then(|days| monday.checked_add_signed(days))
+                            .map(|date| date.and_time(start.time())))
+                        .collect::<Option<Vec<_>>>())
+                    .map(|times| times.into_iter().filter(|time| *time >= start).collect()),
+                Frequency::Monthly => start.checked_add_months(Months::new(step)).map(|time| vec![time]),
+                Frequency::Yearly => start.checked_add_months(Months::new(step.saturating_mul(12))).map(|time| vec![time]),
+            };
+            let Some(candidates) = candidates else { return };
+            for time in candidates {
+                if self.count.is_some_and(|count| emitted >= count) || !keep(time) {
+                    return;
+                }
+                emitted += 1;
+            }
+        }
+    }
+}
+
+fn weekday(value:&str)->Option<Weekday>{
+    // a leading ordinal, like in `2MO`, only applies to monthly rules, which ignore the days
+    match value.trim_start_matches(|c:char| c == '-' || c == '+' || c.is_ascii_digit()) {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Value of a parameter like `TZID` in the parameters of a property
+fn param<'a>(params:&'a str, key:&str)->Option<&'a str>{
+    params.split(';')
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, value)| value.trim_matches('"'))
+}
+
+/// Time of a `DTSTART`, `DTEND`, `EXDATE` or `RECURRENCE-ID` with its parameters, its zone and
+/// whether it is a whole day
+fn parse_time(params:&str, value:&str)->Option<(NaiveDateTime, Zone, bool)>{
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.and_hms_opt(0, 0, 0)?, Zone::Local, true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        return Some((NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?, Zone::Utc, false));
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    // unknown zones, like the Windows names of some servers, are taken as local
+    let zone = param(params, "TZID")
+        .and_then(|tz| tz.parse().ok())
+        .map_or(Zone::Local, Zone::Tz);
+    Some((time, zone, false))
+}
+
+/// Length of a `DURATION` like `PT1H30M` or `P1D`
+fn parse_duration(value:&str)->Option<chrono::Duration>{
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.trim_start_matches('+')),
+    };
+    let (mut seconds, mut number) = (0i64, 0i64);
+    for c in value.strip_prefix('P')?.chars() {
+        let unit = match c {
+            '0'..='9' => {
+                number = number.checked_mul(10)?.checked_add(i64::from(c.to_digit(10)?))?;
+                continue;
+            },
+            'T' => continue,
+            'W' => 7 * 86400,
+            'D' => 86400,
+            'H' => 3600,
+            'M' => 60,
+            'S' => 1,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(number.checked_mul(unit)?)?;
+        number = 0;
+    }
+    chrono::Duration::try_seconds(sign * seconds)
+}
+
+/// Text of a `SUMMARY` without its escapes, on one line
+fn unescape_text(value:&str)->String{
+    value.replace("\\n", " ").replace("\\N", " ").replace("\\,", ",").replace("\\;", ";").replace("\\\\", "\\")
+}
+
+/// Lines of ICS with the folded ones joined
+fn unfold(ics:&str)->Vec<String>{
+    let mut lines:Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Properties of a `VEVENT` before its occurrences are known
+#[derive(Default)]
+struct VEvent {
+    uid: String,
+    summary: String,
+    start: Option<(NaiveDateTime, Zone, bool)>,
+    end: Option<DateTime<Utc>>,
+    duration: Option<chrono::Duration>,
+    rule: Option<Rule>,
+    exdates: Vec<DateTime<Utc>>,
+    recurrence_id: Option<DateTime<Utc>>,
+    cancelled: bool,
+}
+
+/// Occurrences of the events in the calendar that end after `now` and start before the horizon.
+/// Events whose times cannot be represented are skipped.
+pub fn parse(ics:&str, now:DateTime<Utc>)->Vec<Event>{
+    let mut vevents = Vec::new();
+    let mut current:Option<VEvent> = None;
+    // alarms and other components inside an event have properties of their own
+    let mut nested = 0;
+    for line in unfold(ics) {
+        let Some((head, value)) = line.split_once(':') else { continue };
+        let (name, params) = head.split_once(';').unwrap_or((head, ""));
+        match (name, value, current.as_mut()) {
+            ("BEGIN", "VEVENT", None) => current = Some(VEvent::default()),
+            ("BEGIN", _, Some(_)) => nested += 1,
+            ("END", "VEVENT", Some(_)) if nested == 0 => vevents.extend(current.take()),
+            ("END", _, Some(_)) => nested -= 1,
+            (_, _, Some(vevent)) if nested == 0 => match name {
+                "UID" => vevent.uid = value.to_string(),
+                "SUMMARY" => vevent.summary = unescape_text(value),
+                "DTSTART" => vevent.start = parse_time(params, value),
+                "DTEND" => vevent.end = parse_time(params, value).and_then(|(time, zone, _)| zone.to_utc(time)),
+                "DURATION" => vevent.duration = parse_duration(value),
+                "RRULE" => vevent.rule = Rule::parse(value),
+                "EXDATE" => vevent.exdates.extend(value.split(',')
+                    .filter_map(|value| parse_time(params, value))
+                    .filter_map(|(time, zone, _)| zone.to_utc(time))),
+                "RECURRENCE-ID" => vevent.recurrence_id = parse_time(params, value).and_then(|(time, zone, _)| zone.to_utc(time)),
+                "STATUS" => vevent.cancelled = value == "CANCELLED",
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    // occurrences that were moved are replaced by their own event
+    let moved:Vec<(String, DateTime<Utc>)> = vevents.iter()
+        .filter_map(|vevent| Some((vevent.uid.clone(), vevent.recurrence_id?)))
+        .collect();
+    let horizon = now + chrono::Duration::days(HORIZON_DAYS);
+    let mut events = Vec::new();
+    for vevent in vevents.into_iter().filter(|vevent| !vevent.cancelled) {
+        let Some((start, zone, all_day)) = vevent.start else { continue };
+        let Some(first) = zone.to_utc(start) else { continue };
+        let length = match (vevent.end, vevent.duration) {
+            (Some(end), _) => end - first,
+            (None, Some(duration)) => duration,
+            (None, None) if all_day => chrono::Duration::days(1),
+            (None, None) => chrono::Duration::zero(),
+        };
+        let mut push = |start:DateTime<Utc>| {
+            let Some(end) = start.checked_add_signed(length) else { return };
+            if end > now && start < horizon {
+                events.push(Event { summary: vevent.summary.clone(), start, end, all_day });
+            }
+        };
+        match (&vevent.rule, vevent.recurrence_id) {
+            (Some(rule), None) => rule.occurrences(start, |time| {
+                let Some(time) = zone.to_utc(time) else { return true };
+                if time >= horizon || rule.until.is_some_and(|until| time > until) {
+                    return false;
+                }
+                let excluded = vevent.exdates.contains(&time)
+                    || moved.iter().any(|(uid, moved)| *uid == vevent.uid && *moved == time);
+                if !excluded {
+                    push(time);
+                }
+                true
+            }),
+            _ => push(first),
+        }
+    }
+    events.sort_by_key(|event| event.start);
+    events
+}
+
+/// Contents of the `calendar-data` elements of a CalDAV response, which are the ICS of the events
+fn calendar_data(xml:&str)->String{
+    let mut ics = String::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find("calendar-data") {
+        rest = &rest[start..];
+        let Some(open) = rest.find('>') else { break };
+        let empty = rest[..open].ends_with('/');
+        rest = &rest[open + 1..];
+        if empty {continue;}
+        let end = rest.find("</").unwrap_or(rest.len());
+        let data = rest[..end].trim();
+        let data = data.strip_prefix("<![CDATA[").and_then(|data| data.strip_suffix("]]>")).unwrap_or(data);
+        ics.push_str(&data.replace("&#13;", "").replace("&lt;", "<").replace("&gt;", ">")
+            .replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&"));
+        ics.push('\n');
+        // past the closing tag, which names the element again
+        rest = &rest[end..];
+        match rest.find('>') {
+            Some(close) => rest = &rest[close + 1..],
+            None => break,
+        }
+    }
+    ics
+}
+
+fn curl(args:&[&str])->Result<String, String>{
+    let output = Command::new("curl")
+        .args(["--silent", "--fail", "--location", "--netrc-optional", "--max-time", "30"])
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run curl: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("curl exited with {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// ICS of a source: a CalDAV collection after `caldav:`, an http, https or webcal URL, or a file
+fn read(source:&str)->Result<String, String>{
+    if let Some(url) = source.strip_prefix(CALDAV_PREFIX) {
+        let xml = curl(&[
+            "--request", "REPORT",
+            "--header", "Depth: 1",
+            "--header", "Content-Type: application/xml; charset=utf-8",
+            "--data", CALDAV_QUERY,
+            url,
+        ])?;
+        Ok(calendar_data(&xml))
+    } else if let Some(url) = source.strip_prefix("webcal://") {
+        curl(&[&format!("https://{}", url)])
+    } else if source.starts_with("http://") || source.starts_with("https://") {
+        curl(&[source])
+    } else {
+        let path = util::expand(source);
+        fs::read_to_string(&path).map_err(|err| format!("failed to read {}: {}", path.display(), err))
+    }
+}
+
+/// File the last ICS of a source is kept in
+fn cache_path(source:&str)->Option<PathBuf>{
+    Some(dirs::cache_dir()?.join("eos").join("agenda").join(format!("{}.ics", util::cache_name(source))))
+}
+
+/// ICS of a source, from the cache if it cannot be read now
+fn fetch(source:&str)->Option<String>{
+    let cache = cache_path(source);
+    match read(source) {
+        Ok(ics) => {
+            if let Some(cache) = &cache {
+                let written = cache.parent().map_or(Ok(()), fs::create_dir_all).and_then(|()| fs::write(cache, &ics));
+                if let Err(err) = written {
+                    log::warn!("failed to cache the calendar {}: {}", source, err);
+                }
+            }
+            Some(ics)
+        },
+        Err(err) => {
+            log::warn!("failed to fetch the calendar {}, using the cached events: {}", source, err);
+            fs::read_to_string(cache?).ok()
+        },
+    }
+}
+
+/// Emits the upcoming events of the sources every `refresh_minutes`, see [`Message::Agenda`]
+pub fn listen(sources:Vec<String>, refresh_minutes:u16)->Subscription<Message>{
+    struct AgendaSubscription;
+    worker::listen(
+        (TypeId::of::<AgendaSubscription>(), sources.clone(), refresh_minutes),
+        Message::Agenda,
+        move |tx| worker::every(&tx, Duration::from_secs(60 * u64::from(refresh_minutes)), || {
+            let now = Utc::now();
+            let mut events:Vec<Event> = sources.iter()
+                .filter_map(|source| fetch(source))
+                .flat_map(|ics| parse(&ics, now))
+                .collect();
+            events.sort_by_key(|event| event.start);
+            events
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(text:&str)->DateTime<Utc>{
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    fn calendar(events:&[&str])->String{
+        let events:String = events.iter().map(|event| format!("BEGIN:VEVENT\r\n{}\r\nEND:VEVENT\r\n", event)).collect();
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", events)
+    }
+
+    #[test]
+    fn parses_events_in_the_window(){
+        let ics = calendar(&[
+            "UID:1\r\nSUMMARY:Review\\, part 2\r\nDTSTART:20240105T090000Z\r\nDTEND:20240105T100000Z",
+            "UID:2\r\nSUMMARY:Past\r\nDTSTART:20231201T090000Z\r\nDURATION:PT1H",
+            "UID:3\r\nSUMMARY:Cancelled\r\nSTATUS:CANCELLED\r\nDTSTART:20240106T090000Z",
+            "UID:4\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20240110",
+        ]);
+        let events = parse(&ics, utc("2024-01-01T00:00:00Z"));
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], Event {
+            summary: "Review, part 2".into(),
+            start: utc("2024-01-05T09:00:00Z"),
+            end: utc("2024-01-05T10:00:00Z"),
+            all_day: false,
+        });
+        assert_eq!(events[1].summary, "Holiday");
+        assert!(events[1].all_day);
+        assert_eq!(events[1].end - events[1].start, chrono::Duration::days(1));
+    }
+
+    #[test]
+    fn expands_recurring_events(){
+        let ics = calendar(&[
+            "UID:w\r\nSUMMARY:Standup\r\nDTSTART:20240101T090000Z\r\nDURATION:PT15M\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4\r\nEXDATE:20240103T090000Z",
+            "UID:w\r\nSUMMARY:Standup moved\r\nRECURRENCE-ID:20240108T090000Z\r\nDTSTART:20240108T110000Z\r\nDURATION:PT15M",
+        ]);
+        let events = parse(&ics, utc("2024-01-01T00:00:00Z"));
+        let starts:Vec<_> = events.iter().map(|event| (event.summary.as_str(), event.start)).collect();
+        assert_eq!(starts, [
+            ("Standup", utc("2024-01-01T09:00:00Z")),
+            ("Standup moved", utc("2024-01-08T11:00:00Z")),
+            ("Standup", utc("2024-01-10T09:00:00Z")),
+        ]);
+    }
+
+    #[test]
+    fn skips_malformed_and_overflowing_events(){
+        let ics = calendar(&[
+            "UID:1\r\nSUMMARY:No start",
+            "UID:2\r\nSUMMARY:Bad start\r\nDTSTART:2024-01-05",
+            "UID:3\r\nSUMMARY:Huge interval\r\nDTSTART:20240105T090000Z\r\nRRULE:FREQ=DAILY;INTERVAL=4294967295",
+            "UID:4\r\nSUMMARY:Huge duration\r\nDTSTART:20240105T090000Z\r\nDURATION:P99999999999999999999D",
+            "UID:5\r\nSUMMARY:Endless\r\nDTSTART:20240105T090000Z\r\nDURATION:P9000000000000000S",
+            "UID:7\r\nSUMMARY:Weekly at the end\r\nDTSTART:20240105T090000Z\r\nRRULE:FREQ=WEEKLY;BYDAY=FR;INTERVAL=2000000000",
+            "garbage without a colon",
+        ]);
+        let events = parse(&ics, utc("2024-01-01T00:00:00Z"));
+        let summaries:Vec<_> = events.iter().map(|event| event.summary.as_str()).collect();
+        // a duration that cannot be represented leaves the event without a length, while one
+        // that ends after the last date chrono knows skips the event
+        assert_eq!(summaries, ["Huge interval", "Huge duration", "Weekly at the end"]);
+    }
+
+    #[test]
+    fn parses_durations(){
+        assert_eq!(parse_duration("PT1H30M"), Some(chrono::Duration::minutes(90)));
+        assert_eq!(parse_duration("-P1DT1S"), Some(chrono::Duration::seconds(-86401)));
+        assert_eq!(parse_duration("+P2W"), Some(chrono::Duration::weeks(2)));
+        assert_eq!(parse_duration("1H"), None);
+        assert_eq!(parse_duration("PT1X"), None);
+        assert_eq!(parse_duration("P9223372036854775807S"), None);
+        assert_eq!(parse_duration("P99999999999999999999D"), None);
+    }
+}
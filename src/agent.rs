@@ -0,0 +1,312 @@
+//! Agent mode, splitting the collection of the metrics from their display. `eos --agent` runs
+//! without a window and serves the metrics of its machine at `Config::agent_address`, and the
+//! interface of another eos subscribes to the agents of `Config::agents`, showing them in
+//! [`crate::config::Section::Agents`] with a switcher between the hosts.
+//!
+//! Both ends authenticate each other with mutual TLS: each presents `Config::agent_cert` and
+//! `Config::agent_key`, and takes only certificates signed by `Config::agent_ca`, so the
+//! metrics are neither readable nor spoofable by anyone without a certificate of that CA. An
+//! agent writes a JSON line with its [`Status`] every `Config::agent_interval_secs` to each
+//! connected client until it disconnects. The status carries that interval, so the client
+//! knows when an agent that went quiet is gone, whatever its own config says.
+
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use cosmic::iced::Subscription;
+use nvml_wrapper::{enum_wrappers::device::TemperatureSensor, error::NvmlError, Nvml};
+use rustls::crypto::{ring, CryptoProvider};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, ServerConfig, ServerConnection, StreamOwned};
+use serde::{Deserialize, Serialize};
+use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+use tokio::sync::mpsc;
+
+use crate::config::Config;
+use crate::{util, worker, Message};
+
+/// Time before connecting again after the connection to an agent failed or dropped
+const RETRY_INTERVAL:Duration = Duration::from_secs(10);
+/// Longest wait for the TLS handshake, and on the client for the first status that follows it
+const HANDSHAKE_TIMEOUT:Duration = Duration::from_secs(10);
+/// Longest wait for a client to take a status before it is dropped
+const WRITE_TIMEOUT:Duration = Duration::from_secs(10);
+/// Longest wait for the connection to an agent
+const CONNECT_TIMEOUT:Duration = Duration::from_secs(10);
+/// Most clients an agent serves at once, counting those still in the handshake, further
+/// connections are closed right away
+const MAX_CLIENTS:usize = 32;
+
+/// Latest measurements of an agent
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Status {
+    /// Name of the machine the agent runs on
+    pub host: String,
+    /// Metrics by name like `cpu_avg`, in percent or °C, leaving out those that are unknown
+    pub metrics: BTreeMap<String, f32>,
+    /// Load average over 1, 5 and 15 minutes
+    pub load: [f32; 3],
+    pub uptime_secs: u64,
+    /// Seconds until the next status, `Config::agent_interval_secs` of the agent
+    pub interval_secs: u16,
+}
+
+fn certificates(path:&str)->Result<Vec<CertificateDer<'static>>, String>{
+    let file = File::open(util::expand(path)).map_err(|err| format!("failed to read {}: {}", path, err))?;
+    let certificates = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("invalid certificate in {}: {}", path, err))?;
+    if certificates.is_empty() {
+        return Err(format!("no certificate in {}", path));
+    }
+    Ok(certificates)
+}
+
+fn private_key(path:&str)->Result<PrivateKeyDer<'static>, String>{
+    let file = File::open(util::expand(path)).map_err(|err| format!("failed to read {}: {}", path, err))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .map_err(|err| format!("invalid private key in {}: {}", path, err))?
+        .ok_or_else(|| format!("no private key in {}", path))
+}
+
+/// Certificates of the CA at `path`, the only ones trusted
+fn roots(path:&str)->Result<RootCertStore, String>{
+    let mut roots = RootCertStore::empty();
+    for certificate in certificates(path)? {
+        roots.add(certificate).map_err(|err| format!("invalid CA certificate in {}: {}", path, err))?;
+    }
+    Ok(roots)
+}
+
+fn provider()->Arc<CryptoProvider>{
+    Arc::new(ring::default_provider())
+}
+
+/// TLS of the agent, taking only clients with a certificate of the CA
+fn server_config(config:&Config)->Result<ServerConfig, String>{
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots(&config.agent_ca)?), provider())
+        .build()
+        .map_err(|err| err.to_string())?;
+    ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certificates(&config.agent_cert)?, private_key(&config.agent_key)?)
+        .map_err(|err| err.to_string())
+}
+
+/// TLS of the interface, taking only agents with a certificate of the CA
+fn client_config((cert, key, ca):&(String, String, String))->Result<ClientConfig, String>{
+    ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?
+        .with_root_certificates(roots(ca)?)
+        .with_client_auth_cert(certificates(cert)?, private_key(key)?)
+        .map_err(|err| err.to_string())
+}
+
+/// Measures the machine the agent runs on
+struct Collector {
+    sys: System,
+    nv: Option<Nvml>,
+    host: String,
+    interval_secs: u16,
+}
+
+impl Collector {
+    fn new(interval_secs:u16)->Self{
+        let refresh = RefreshKind::nothing()
+            .with_cpu(CpuRefreshKind::nothing().with_cpu_usage())
+            .with_memory(MemoryRefreshKind::nothing().with_ram());
+        let nv = Nvml::init().map_err(|err| log::info!("no NVIDIA GPU is measured: {}", err)).ok();
+        Self {
+            sys: System::new_with_specifics(refresh),
+            nv,
+            host: System::host_name().unwrap_or_else(|| "unknown".to_string()),
+            interval_secs,
+        }
+    }
+
+    /// Utilization, temperature and used memory of the first GPU
+    fn gpu(&self)->Result<[f32; 3], NvmlError>{
+        let device = self.nv.as_ref().ok_or(NvmlError::NoData)?.device_by_index(0)?;
+        let memory = device.memory_info()?;
+        Ok([
+            device.utilization_rates()?.gpu as f32,
+            device.temperature(TemperatureSensor::Gpu)? as f32,
+            if memory.total == 0 {0.} else {memory.used as f32 / memory.total as f32 * 100.},
+        ])
+    }
+
+    fn measure(&mut self)->Status{
+        self.sys.refresh_cpu_usage();
+        self.sys.refresh_memory();
+        let mut metrics = BTreeMap::new();
+        metrics.insert("cpu_avg".to_string(), self.sys.global_cpu_usage());
+        metrics.insert("cpu_max".to_string(), self.sys.cpus().iter().map(|cpu| cpu.cpu_usage()).fold(0., f32::max));
+        let total = self.sys.total_memory();
+        if total > 0 {
+            metrics.insert("ram".to_string(), self.sys.used_memory() as f32 / total as f32 * 100.);
+        }
+        if let Ok([util, temp, mem]) = self.gpu() {
+            metrics.extend([("gpu_util".to_string(), util), ("gpu_temp".to_string(), temp), ("gpu_mem".to_string(), mem)]);
+        }
+        let load = System::load_average();
+        Status {
+            host: self.host.clone(),
+            metrics,
+            load: [load.one as f32, load.five as f32, load.fifteen as f32],
+            uptime_secs: System::uptime(),
+            interval_secs: self.interval_secs,
+        }
+    }
+}
+
+/// Counts a client while it is served
+struct Served(Arc<AtomicUsize>);
+
+impl Drop for Served {
+    fn drop(&mut self){
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Send the status to a client every interval until it disconnects
+fn serve_client(tcp:TcpStream, tls:Arc<ServerConfig>, status:&Mutex<Status>, interval:Duration)->Result<(), String>{
+    let peer = tcp.peer_addr().map_err(|err| err.to_string())?;
+    tcp.set_write_timeout(Some(WRITE_TIMEOUT)).map_err(|err| err.to_string())?;
+    let connection = ServerConnection::new(tls).map_err(|err| err.to_string())?;
+    let mut stream = StreamOwned::new(connection, tcp);
+    // a deadline for the whole handshake, so a client sending a byte at a time cannot hold it open
+    let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+    while stream.conn.is_handshaking() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(format!("{}: the TLS handshake timed out", peer));
+        }
+        stream.sock.set_read_timeout(Some(remaining)).map_err(|err| err.to_string())?;
+        stream.conn.complete_io(&mut stream.sock).map_err(|err| format!("{}: {}", peer, err))?;
+    }
+    log::info!("serving the metrics to {}", peer);
+    loop {
+        let line = serde_json::to_string(&*status.lock().map_err(|_| "the collector stopped")?)
+            .map_err(|err| err.to_string())?;
+        if let Err(err) = writeln!(stream, "{}", line).and_then(|()| stream.flush()) {
+            log::info!("stopped serving {}: {}", peer, err);
+            return Ok(());
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// `eos --agent`: serve the metrics of this machine at `Config::agent_address` until killed
+pub fn serve(config:&Config)->Result<(), String>{
+    let tls = Arc::new(server_config(config)?);
+    let listener = TcpListener::bind(&config.agent_address)
+        .map_err(|err| format!("failed to listen at {}: {}", config.agent_address, err))?;
+    let interval_secs = config.agent_interval_secs;
+    let interval = Duration::from_secs(interval_secs.into());
+    let status = Arc::new(Mutex::new(Status { interval_secs, ..Status::default() }));
+    let measured = status.clone();
+    std::thread::spawn(move || {
+        let mut collector = Collector::new(interval_secs);
+        loop {
+            let current = collector.measure();
+            if let Ok(mut status) = measured.lock() {
+                *status = current;
+            }
+            std::thread::sleep(interval);
+        }
+    });
+    log::info!("serving the metrics at {}", config.agent_address);
+    let clients = Arc::new(AtomicUsize::new(0));
+    for tcp in listener.incoming() {
+        let tcp = match tcp {
+            Ok(tcp) => tcp,
+            Err(err) => {
+                log::warn!("failed to accept a client: {}", err);
+                continue;
+            },
+        };
+        if clients.fetch_add(1, Ordering::Relaxed) >= MAX_CLIENTS {
+            clients.fetch_sub(1, Ordering::Relaxed);
+            let peer = tcp.peer_addr().map_or_else(|_| "a client".to_string(), |peer| peer.to_string());
+            log::warn!("refused {}, already serving {} clients", peer, MAX_CLIENTS);
+            continue;
+        }
+        let (served, tls, status) = (Served(clients.clone()), tls.clone(), status.clone());
+        std::thread::spawn(move || {
+            let _served = served;
+            if let Err(err) = serve_client(tcp, tls, &status, interval) {
+                log::warn!("failed to serve a client: {}", err);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Connect to the first address `address` resolves to that answers in time
+fn connect(address:&str)->Result<TcpStream, String>{
+    let mut error = format!("{} resolves to no address", address);
+    for socket in address.to_socket_addrs().map_err(|err| format!("failed to resolve {}: {}", address, err))? {
+        match TcpStream::connect_timeout(&socket, CONNECT_TIMEOUT) {
+            Ok(tcp) => return Ok(tcp),
+            Err(err) => error = format!("failed to connect to {}: {}", socket, err),
+        }
+    }
+    Err(error)
+}
+
+/// Receive the status of the agent at `address` until the connection ends, returning why
+fn watch(address:&str, tls:Arc<ClientConfig>, tx:&mpsc::UnboundedSender<Option<Status>>)->Result<(), String>{
+    let host = address.rsplit_once(':').map_or(address, |(host, _)| host).trim_start_matches('[').trim_end_matches(']');
+    let name = ServerName::try_from(host.to_string()).map_err(|err| format!("invalid host name {}: {}", host, err))?;
+    let tcp = connect(address)?;
+    tcp.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).map_err(|err| err.to_string())?;
+    tcp.set_write_timeout(Some(WRITE_TIMEOUT)).map_err(|err| err.to_string())?;
+    let connection = ClientConnection::new(tls, name).map_err(|err| err.to_string())?;
+    let mut reader = BufReader::new(StreamOwned::new(connection, tcp));
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).map_err(|err| err.to_string())? == 0 {
+            return Err("the agent closed the connection".to_string());
+        }
+        let status = serde_json::from_str::<Status>(&line).map_err(|err| format!("invalid status: {}", err))?;
+        // an agent that stopped writing for a few of its intervals is taken for gone
+        let interval = Duration::from_secs(status.interval_secs.max(1).into());
+        reader.get_ref().sock.set_read_timeout(Some(interval * 3 + HANDSHAKE_TIMEOUT)).map_err(|err| err.to_string())?;
+        if tx.send(Some(status)).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Emits the status of the agent at `address`, at `index` of `Config::agents`, and `None`
+/// while it cannot be reached, see [`Message::Agent`]
+pub fn listen(index:usize, address:String, config:&Config)->Subscription<Message>{
+    struct AgentSubscription;
+    let certificates = (config.agent_cert.clone(), config.agent_key.clone(), config.agent_ca.clone());
+    worker::listen(
+        (TypeId::of::<AgentSubscription>(), index, address.clone(), certificates.clone()),
+        move |status| Message::Agent(index, status),
+        move |tx| {
+            let tls = match client_config(&certificates) {
+                Ok(tls) => Arc::new(tls),
+                Err(err) => {
+                    log::warn!("agent {}: {}", address, err);
+                    let _ = tx.send(None);
+                    return;
+                },
+            };
+            worker::retry(&tx, RETRY_INTERVAL, &format!("agent {}", address), |tx| watch(&address, tls.clone(), tx));
+        },
+    )
+}
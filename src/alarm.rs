@@ -0,0 +1,46 @@
+//! Alarms and countdowns of the config, which go off when the clock passes their time. They are
+//! checked on every clock tick against the time of the previous one, so an alarm is not missed
+//! when a tick is late and does not go off twice within its minute.
+
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone};
+use itertools::Itertools;
+
+use crate::config::Config;
+use crate::fl;
+
+/// Local time of a naive one, the earlier if it is ambiguous
+fn local(time:NaiveDateTime)->Option<DateTime<Local>>{
+    Local.from_local_datetime(&time).earliest()
+}
+
+/// Next time an alarm goes off after `now`, with its label
+pub fn next(config:&Config, now:DateTime<Local>)->Option<(DateTime<Local>, &str)>{
+    config.alarms.iter()
+        .filter_map(|alarm| {
+            let time = alarm.time()?;
+            // the coming week holds every day an alarm can be on
+            (0..8).map(|days| now.date_naive() + chrono::Duration::days(days))
+                .filter(|day| alarm.on(day.weekday()))
+                .filter_map(|day| local(day.and_time(time)))
+                .find(|at| *at > now)
+                .map(|at| (at, alarm.label.as_str()))
+        })
+        .min_by_key(|(at, _)| *at)
+}
+
+/// Summary and body of a notification for each alarm and countdown due after `from` up to `to`
+pub fn due(config:&Config, from:DateTime<Local>, to:DateTime<Local>)->Vec<(String, String)>{
+    let due = |at:DateTime<Local>| from < at && at <= to;
+    let alarms = config.alarms.iter().filter(|alarm| {
+        let Some(time) = alarm.time() else {return false};
+        [from.date_naive(), to.date_naive()].into_iter()
+            .dedup()
+            .filter(|day| alarm.on(day.weekday()))
+            .filter_map(|day| local(day.and_time(time)))
+            .any(due)
+    }).map(|alarm| (fl!("alarm-due", time = alarm.time.clone()), alarm.label.clone()));
+    let countdowns = config.countdowns.iter()
+        .filter(|countdown| countdown.until().and_then(local).is_some_and(due))
+        .map(|countdown| (fl!("countdown-due", label = countdown.label.clone()), String::new()));
+    alarms.chain(countdowns).collect()
+}
@@ -0,0 +1,267 @@
+//! Alerts of `Config::alerts`, rules like `cpu_avg > 90 for 30s`, `gpu_temp > 85` or
+//! `disk / > 95%`, checked on every poll of the CPU and GPU, the disks every [`DISK_INTERVAL`].
+//! A rule fires a desktop notification once its condition has held for its duration, and fires
+//! again only after the value went back past its limit by `Config::alert_hysteresis`, so a value
+//! hovering around the limit does not announce itself on every poll.
+//!
+//! Each rule may have shell commands run when it fires and when it clears, on a background
+//! thread with the rule, the value and the event in `EOS_ALERT_RULE`, `EOS_ALERT_VALUE` and
+//! `EOS_ALERT_EVENT`. What they print is logged.
+
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use sysinfo::Disks;
+
+use crate::config::{Alert, Config};
+use crate::fl;
+use crate::localize::number;
+
+/// How often the usage of the disks is measured for rules that look at one, which is slower
+/// than the other sources and changes slowly
+const DISK_INTERVAL:Duration = Duration::from_secs(30);
+
+/// Value a rule looks at, in percent or, for the GPU temperature, in °C
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    CpuAvg,
+    CpuMax,
+    GpuUtil,
+    GpuTemp,
+    GpuMem,
+    Ram,
+    /// Used space of the file system mounted at a path
+    Disk(String),
+}
+
+impl Source {
+    const NAMES: [&'static str; 7] = ["cpu_avg", "cpu_max", "gpu_util", "gpu_temp", "gpu_mem", "ram", "disk"];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Above,
+    Below,
+}
+
+/// Rule like `cpu_avg > 90 for 30s`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub source: Source,
+    comparison: Comparison,
+    limit: f32,
+    /// Time the condition must hold before the rule fires
+    duration: Duration,
+}
+
+impl Rule {
+    fn holds(&self, value:f32, margin:f32)->bool{
+        match self.comparison {
+            Comparison::Above => value > self.limit - margin,
+            Comparison::Below => value < self.limit + margin,
+        }
+    }
+}
+
+/// Duration like `30s`, `5m` or `1h`
+fn duration(text:&str)->Option<Duration>{
+    let unit = text.find(|c:char| !c.is_ascii_digit())?;
+    let (amount, unit) = text.split_at(unit);
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return None,
+    };
+    Some(Duration::from_secs(amount.parse::<u64>().ok()? * seconds))
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(rule:&str)->Result<Self, Self::Err>{
+        let mut words = rule.split_whitespace();
+        let source = match words.next() {
+            Some("cpu_avg") => Source::CpuAvg,
+            Some("cpu_max") => Source::CpuMax,
+            Some("gpu_util") => Source::GpuUtil,
+            Some("gpu_temp") => Source::GpuTemp,
+            Some("gpu_mem") => Source::GpuMem,
+            Some("ram") => Source::Ram,
+            Some("disk") => Source::Disk(words.next().ok_or("a disk needs its mount point")?.to_string()),
+            _ => return Err(format!("expected one of {}", Source::NAMES.join(", "))),
+        };
+        let comparison = match words.next() {
+            Some(">") | Some(">=") => Comparison::Above,
+            Some("<") | Some("<=") => Comparison::Below,
+            _ => return Err("expected > or <".to_string()),
+        };
+        let limit = words.next()
+            .and_then(|limit| limit.trim_end_matches(['%', '°', 'C']).parse::<f32>().ok())
+            .ok_or("expected a number to compare with")?;
+        let duration = match (words.next(), words.next()) {
+            (None, _) => Duration::ZERO,
+            (Some("for"), Some(time)) => duration(time).ok_or("expected a duration like 30s, 5m or 1h")?,
+            _ => return Err("expected `for` and a duration".to_string()),
+        };
+        if words.next().is_some() {
+            return Err("unexpected text after the duration".to_string());
+        }
+        Ok(Rule { source, comparison, limit, duration })
+    }
+}
+
+#[derive(Debug, Default)]
+struct RuleState {
+    /// Since when the condition holds
+    since: Option<Instant>,
+    /// The rule fired and the value has not gone back past its limit since
+    fired: bool,
+}
+
+/// Run the command of a rule with `sh`, logging its output
+fn run_hook(command:String, rule:String, value:f32, event:&'static str){
+    std::thread::spawn(move || {
+        let output = Command::new("sh")
+            .args(["-c", &command])
+            .env("EOS_ALERT_RULE", &rule)
+            .env("EOS_ALERT_VALUE", value.to_string())
+            .env("EOS_ALERT_EVENT", event)
+            .stdin(Stdio::null())
+            .output();
+        let output = match output {
+            Ok(output) => output,
+            Err(err) => {
+                log::warn!("failed to run the {} command of {:?}: {}", event, rule, err);
+                return;
+            },
+        };
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            log::info!("{} command of {:?}: {}", event, rule, line);
+        }
+        for line in String::from_utf8_lossy(&output.stderr).lines() {
+            log::warn!("{} command of {:?}: {}", event, rule, line);
+        }
+        if !output.status.success() {
+            log::warn!("{} command of {:?} exited with {}", event, rule, output.status);
+        }
+    });
+}
+
+pub struct Alerts {
+    /// Rules with their entry in the config
+    rules: Vec<(Alert, Rule, RuleState)>,
+    /// Mounted file systems, only listed while a rule looks at one, and when they were measured
+    disks: Option<(Disks, Instant)>,
+}
+
+impl Alerts {
+    pub fn new(config:&Config)->Self{
+        let mut alerts = Self { rules: Vec::new(), disks: None };
+        alerts.update_config(config);
+        alerts
+    }
+
+    /// To be called when the config changes. Rules that stay keep their state, so editing one
+    /// does not fire the others again.
+    pub fn update_config(&mut self, config:&Config){
+        let mut previous = std::mem::take(&mut self.rules);
+        self.rules = config.alerts.iter().filter_map(|alert| {
+            if let Some(index) = previous.iter().position(|(previous, _, _)| previous == alert) {
+                return Some(previous.swap_remove(index));
+            }
+            let rule = alert.rule.parse::<Rule>().ok()?;
+            Some((alert.clone(), rule, RuleState::default()))
+        }).collect();
+        let disks = self.rules.iter().any(|(_, rule, _)| matches!(rule.source, Source::Disk(_)));
+        if disks != self.disks.is_some() {
+            self.disks = disks.then(|| (Disks::new_with_refreshed_list(), Instant::now()));
+        }
+    }
+
+    /// Used space of the file system mounted at `mount` in percent
+    fn disk_usage(&self, mount:&str)->Option<f32>{
+        let disk = self.disks.as_ref()?.0.list().iter()
+            .find(|disk| disk.mount_point().as_os_str() == mount)?;
+        let total = disk.total_space();
+        (total > 0).then(|| (total - disk.available_space()) as f32 / total as f32 * 100.)
+    }
+
+    /// Check the rules against the current `value` of each source, or `None` where it is
+    /// unknown, running the commands of those that fire or clear and returning the summary and
+    /// body of a notification for each rule that fires
+    pub fn check(&mut self, config:&Config, value:impl Fn(&Source)->Option<f32>)->Vec<(String, String)>{
+        if self.rules.is_empty() {return Vec::new();}
+        let now = Instant::now();
+        if let Some((disks, refreshed)) = &mut self.disks {
+            if now.duration_since(*refreshed) >= DISK_INTERVAL {
+                disks.refresh(true);
+                *refreshed = now;
+            }
+        }
+        let hysteresis = f32::from(config.alert_hysteresis);
+        let values:Vec<Option<f32>> = self.rules.iter().map(|(_, rule, _)| match &rule.source {
+            Source::Disk(mount) => self.disk_usage(mount),
+            source => value(source),
+        }).collect();
+        let mut fired = Vec::new();
+        for ((alert, rule, state), value) in self.rules.iter_mut().zip(values) {
+            let Some(value) = value else {
+                *state = RuleState::default();
+                continue;
+            };
+            // once fired, the rule counts as holding until the value is back past the margin
+            if !rule.holds(value, if state.fired {hysteresis} else {0.}) {
+                if state.fired && !alert.on_clear.is_empty() {
+                    run_hook(alert.on_clear.clone(), alert.rule.clone(), value, "clear");
+                }
+                *state = RuleState::default();
+                continue;
+            }
+            let since = *state.since.get_or_insert(now);
+            if !state.fired && now.duration_since(since) >= rule.duration {
+                state.fired = true;
+                if !alert.on_trigger.is_empty() {
+                    run_hook(alert.on_trigger.clone(), alert.rule.clone(), value, "trigger");
+                }
+                fired.push((fl!("alert-fired", rule = alert.rule.clone()), fl!("alert-value", value = number(value, 1))));
+            }
+        }
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rules(){
+        assert_eq!("cpu_avg > 90 for 30s".parse(), Ok(Rule {
+            source: Source::CpuAvg,
+            comparison: Comparison::Above,
+            limit: 90.0,
+            duration: Duration::from_secs(30),
+        }));
+        assert_eq!("gpu_temp >= 85°C".parse(), Ok(Rule {
+            source: Source::GpuTemp,
+            comparison: Comparison::Above,
+            limit: 85.0,
+            duration: Duration::ZERO,
+        }));
+        assert_eq!("disk /home < 10% for 5m".parse(), Ok(Rule {
+            source: Source::Disk("/home".into()),
+            comparison: Comparison::Below,
+            limit: 10.0,
+            duration: Duration::from_secs(300),
+        }));
+    }
+
+    #[test]
+    fn rejects_malformed_rules(){
+        for rule in ["", "swap > 50", "disk", "ram = 50", "ram > lots", "ram > 50 for", "ram > 50 for 5d", "ram > 50 during 5m", "ram > 50 for 5m now"] {
+            assert!(rule.parse::<Rule>().is_err(), "{:?}", rule);
+        }
+    }
+}
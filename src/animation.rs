@@ -0,0 +1,182 @@
+//! Animated textures: the frames of an APNG, GIF or animated WebP, or of a short video decoded
+//! through `ffmpeg`, played in a loop and bound to the background shader like a still image.
+//! Decoding takes a while, so textures are opened on a thread, and large images and animations
+//! are scaled down and cut short to bound the memory they take.
+
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use image::codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder};
+use image::imageops::{self, FilterType};
+use image::{AnimationDecoder, Frames, ImageFormat, RgbaImage};
+
+/// File extensions decoded as video through `ffmpeg`
+const VIDEO_EXTENSIONS:[&str;5] = ["mp4", "webm", "mkv", "mov", "avi"];
+/// Frame rate videos are decoded at, and the most frames kept of them
+const VIDEO_FPS:u32 = 15;
+const VIDEO_MAX_FRAMES:usize = 150;
+/// Longest side videos are scaled down to, keeping their aspect ratio
+const VIDEO_MAX_SIDE:u32 = 384;
+/// Memory the frames of a video may take, fewer frames are kept of larger videos
+const VIDEO_MAX_BYTES:usize = 32 << 20;
+/// Longest side of still images, larger ones are scaled down to fit the GPU and memory
+const MAX_TEXTURE_SIDE:u32 = 4096;
+/// Largest image file opened, to refuse anything that would take ages to decode
+const MAX_FILE_BYTES:u64 = 64 << 20;
+/// Most frames kept of animated images, and their longest side, like for videos
+const ANIMATION_MAX_FRAMES:usize = 300;
+const ANIMATION_MAX_SIDE:u32 = 1024;
+/// Memory the frames of an animated image may take, the rest of the loop is dropped
+const ANIMATION_MAX_BYTES:usize = 64 << 20;
+/// Shortest frame delay, since some GIFs specify zero to mean "as fast as possible"
+const MIN_DELAY:Duration = Duration::from_millis(20);
+
+#[derive(Debug)]
+pub struct Animation {
+    frames: Vec<(Arc<RgbaImage>, Duration)>,
+    /// Length of one loop
+    total: Duration,
+    start: Instant,
+}
+
+/// Whether the file at `path` is decoded as a video
+fn is_video(path:&str)->bool{
+    let extension = Path::new(path).extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    VIDEO_EXTENSIONS.contains(&extension.as_str())
+}
+
+impl Animation {
+    /// Decode the frames of the animation or video at `path`
+    pub fn open(path:&str)->Result<Self, String>{
+        let frames = if is_video(path) {
+            decode_video(path)?
+        } else {
+            decode_image(path)?
+        };
+        if frames.is_empty() {
+            return Err("no frames".into());
+        }
+        let total = frames.iter().map(|(_, delay)| *delay).sum();
+        Ok(Self { frames, total, start: Instant::now() })
+    }
+
+    /// Whether there is more than one frame, otherwise the file is better treated as a still image
+    pub fn is_animated(&self)->bool{
+        self.frames.len() > 1
+    }
+
+    /// The frame to show now, the same image as long as the frame is shown
+    pub fn frame(&self)->Arc<RgbaImage>{
+        let mut elapsed = Duration::from_nanos((self.start.elapsed().as_nanos() % self.total.as_nanos()) as u64);
+        for (frame, delay) in &self.frames {
+            if elapsed < *delay {return frame.clone();}
+            elapsed -= *delay;
+        }
+        self.frames[0].0.clone()
+    }
+}
+
+/// Frames of an APNG, GIF or animated WebP, a single frame for any other image
+fn decode_image(path:&str)->Result<Vec<(Arc<RgbaImage>, Duration)>, String>{
+    let size = fs::metadata(path).map_err(|err| err.to_string())?.len();
+    if size > MAX_FILE_BYTES {
+        return Err(format!("the file takes {} MiB, more than the {} MiB allowed", size >> 20, MAX_FILE_BYTES >> 20));
+    }
+    let reader = || File::open(path).map(BufReader::new).map_err(|err| err.to_string());
+    let frames = match ImageFormat::from_path(path) {
+        Ok(ImageFormat::Gif) => GifDecoder::new(reader()?).map(|decoder| decoder.into_frames()),
+        Ok(ImageFormat::Png) => match PngDecoder::new(reader()?) {
+            Ok(decoder) if decoder.is_apng().unwrap_or(false) => decoder.apng()
+                .map(|decoder| decoder.into_frames()),
+            _ => return still(path),
+        },
+        Ok(ImageFormat::WebP) => match WebPDecoder::new(reader()?) {
+            Ok(decoder) if decoder.has_animation() => Ok(decoder.into_frames()),
+            _ => return still(path),
+        },
+        _ => return still(path),
+    }.map_err(|err| err.to_string())?;
+    collect_frames(frames)
+}
+
+/// Decode frames one at a time, scaled down to [`ANIMATION_MAX_SIDE`], until
+/// [`ANIMATION_MAX_FRAMES`] or [`ANIMATION_MAX_BYTES`] are reached
+fn collect_frames(frames:Frames)->Result<Vec<(Arc<RgbaImage>, Duration)>, String>{
+    let mut collected = Vec::new();
+    let mut bytes = 0;
+    for frame in frames.take(ANIMATION_MAX_FRAMES) {
+        let frame = frame.map_err(|err| err.to_string())?;
+        let (numerator, denominator) = frame.delay().numer_denom_ms();
+        let delay = Duration::from_micros(1000 * numerator as u64 / denominator.max(1) as u64);
+        let mut buffer = frame.into_buffer();
+        let (width, height) = buffer.dimensions();
+        if width.max(height) > ANIMATION_MAX_SIDE {
+            let scale = ANIMATION_MAX_SIDE as f32 / width.max(height) as f32;
+            let side = |side:u32| ((side as f32 * scale) as u32).max(1);
+            buffer = imageops::resize(&buffer, side(width), side(height), FilterType::Triangle);
+        }
+        bytes += buffer.len();
+        if bytes > ANIMATION_MAX_BYTES && !collected.is_empty() {
+            log::warn!("animation takes more than {} MiB, keeping its first {} frames", ANIMATION_MAX_BYTES >> 20, collected.len());
+            break;
+        }
+        collected.push((Arc::new(buffer), delay.max(MIN_DELAY)));
+    }
+    Ok(collected)
+}
+
+fn still(path:&str)->Result<Vec<(Arc<RgbaImage>, Duration)>, String>{
+    let mut image = image::open(path).map_err(|err| err.to_string())?;
+    if image.width().max(image.height()) > MAX_TEXTURE_SIDE {
+        image = image.resize(MAX_TEXTURE_SIDE, MAX_TEXTURE_SIDE, FilterType::Triangle);
+    }
+    Ok(vec![(Arc::new(image.to_rgba8()), Duration::from_secs(1))])
+}
+
+/// Width and height of the first video stream, from `ffprobe`
+fn video_size(path:&str)->Result<(u32, u32), String>{
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "stream=width,height", "-of", "csv=p=0", path])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run ffprobe: {}", err))?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    text.trim().split_once(',')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|&(width, height)| width > 0 && height > 0)
+        .ok_or_else(|| format!("no video stream: {}", String::from_utf8_lossy(&output.stderr).trim()))
+}
+
+/// Frames of the start of a video, scaled down and read from `ffmpeg` as raw RGBA
+fn decode_video(path:&str)->Result<Vec<(Arc<RgbaImage>, Duration)>, String>{
+    let (width, height) = video_size(path)?;
+    // even sizes, which some scalers need
+    let scale = (VIDEO_MAX_SIDE as f32 / width.max(height) as f32).min(1.);
+    let even = |side:u32| ((side as f32 * scale / 2.).round() as u32 * 2).max(2);
+    let (width, height) = (even(width), even(height));
+    let frame_bytes = (width * height * 4) as usize;
+    let frames = VIDEO_MAX_FRAMES.min(VIDEO_MAX_BYTES / frame_bytes).max(1);
+    let output = Command::new("ffmpeg")
+        .args(["-v", "error", "-i", path])
+        .args(["-vf", &format!("fps={},scale={}:{}", VIDEO_FPS, width, height)])
+        .args(["-frames:v", &frames.to_string()])
+        .args(["-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run ffmpeg: {}", err))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let delay = Duration::from_secs(1) / VIDEO_FPS;
+    Ok(output.stdout.chunks_exact(frame_bytes)
+        .filter_map(|frame| RgbaImage::from_raw(width, height, frame.to_vec()))
+        .map(|frame| (Arc::new(frame), delay))
+        .collect())
+}
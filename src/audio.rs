@@ -0,0 +1,172 @@
+//! Audio-reactive shader input and the spectrum section. Records what is playing on the
+//! default output through PipeWire's `pw-record` and reduces it to a few frequency band levels
+//! for the shader, and to the bars of a spectrum on a logarithmic frequency scale.
+
+use std::any::TypeId;
+use std::f32::consts::PI;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use tokio::sync::mpsc;
+
+use crate::Message;
+
+/// Number of frequency bands passed to the shader
+pub const AUDIO_BANDS:usize = 4;
+const SAMPLE_RATE:u32 = 48000;
+/// Samples per transform, a power of two
+const FFT_SIZE:usize = 1024;
+/// Samples between two transforms, the windows overlap by half
+const HOP:usize = FFT_SIZE / 2;
+/// Edges of the bass, low mid, high mid and treble bands in Hz
+const BAND_EDGES:[f32;AUDIO_BANDS+1] = [20., 250., 1000., 4000., 16000.];
+/// Quietest level in dB that still registers, mapped to 0
+const FLOOR_DB:f32 = -60.;
+/// Factor a band level falls by per transform, so peaks fade out instead of flickering
+const DECAY:f32 = 0.85;
+/// Lowest and highest frequency of the spectrum in Hz
+const SPECTRUM_RANGE:(f32, f32) = (40., 16000.);
+/// Shortest time between two spectra sent to the interface, which redraws for each
+const SPECTRUM_INTERVAL:Duration = Duration::from_millis(33);
+
+/// Latest band levels, read by the shader when it draws instead of sending a message for each
+/// of the ~94 transforms per second
+static LEVELS:Mutex<[f32;AUDIO_BANDS]> = Mutex::new([0.;AUDIO_BANDS]);
+
+/// Band levels of the audio playing on the default output in 0 to 1, all 0 unless
+/// `Config::audio_reactive` is set
+pub fn levels()->[f32;AUDIO_BANDS]{
+    *LEVELS.lock().unwrap()
+}
+
+/// In-place iterative radix-2 FFT of the complex signal `re + i im`
+fn fft(re:&mut [f32], im:&mut [f32]){
+    let n = re.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -2. * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// Magnitude of each frequency bin of the Hann-windowed samples
+fn magnitudes(samples:&[f32])->Vec<f32>{
+    let mut re:Vec<f32> = samples.iter().enumerate()
+        .map(|(i, s)| s * 0.5 * (1. - (2. * PI * i as f32 / (FFT_SIZE - 1) as f32).cos()))
+        .collect();
+    let mut im = vec![0.; FFT_SIZE];
+    fft(&mut re, &mut im);
+    re.iter().zip(&im).take(FFT_SIZE / 2 + 1).map(|(re, im)| (re * re + im * im).sqrt()).collect()
+}
+
+/// Level in 0 to 1 of the mean magnitude of the bins between two frequencies, on a
+/// logarithmic scale
+fn level(magnitudes:&[f32], from_hz:f32, to_hz:f32)->f32{
+    let bin = |hz:f32| ((hz * FFT_SIZE as f32 / SAMPLE_RATE as f32) as usize).clamp(1, FFT_SIZE / 2);
+    let (from, to) = (bin(from_hz), bin(to_hz).max(bin(from_hz) + 1));
+    let magnitude = magnitudes[from..to].iter().sum::<f32>() / (to - from) as f32 * 4. / FFT_SIZE as f32;
+    let db = 20. * magnitude.max(1e-9).log10();
+    (1. - db / FLOOR_DB).clamp(0., 1.)
+}
+
+/// Level of each band in 0 to 1
+fn band_levels(magnitudes:&[f32])->[f32;AUDIO_BANDS]{
+    std::array::from_fn(|band| level(magnitudes, BAND_EDGES[band], BAND_EDGES[band + 1]))
+}
+
+/// Level of each of `bars` bars in 0 to 1, spaced evenly in octaves over `SPECTRUM_RANGE`
+fn spectrum_levels(magnitudes:&[f32], bars:usize)->Vec<f32>{
+    let (lo, hi) = SPECTRUM_RANGE;
+    let edge = |bar:usize| lo * (hi / lo).powf(bar as f32 / bars as f32);
+    (0..bars).map(|bar| level(magnitudes, edge(bar), edge(bar + 1))).collect()
+}
+
+/// Record the default output, storing its band levels for [`levels`] if `bands` is set and
+/// sending a spectrum of `bars` bars, until the receiver is dropped
+fn record(tx:mpsc::UnboundedSender<Vec<f32>>, bands:bool, bars:usize){
+    let child = Command::new("pw-record")
+        .args(["-P", "{ stream.capture.sink = true }"])
+        .args(["--format", "f32", "--channels", "1", "--rate", &SAMPLE_RATE.to_string(), "-"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => {
+            log::warn!("failed to start pw-record for the audio input: {}", err);
+            return;
+        },
+    };
+    let Some(mut stdout) = child.stdout.take() else {return;};
+    let mut window = vec![0.; FFT_SIZE];
+    let mut levels = [0.; AUDIO_BANDS];
+    let mut spectrum = vec![0.; bars];
+    let mut buf = [0u8; HOP * 4];
+    while stdout.read_exact(&mut buf).is_ok() {
+        window.copy_within(HOP.., 0);
+        for (sample, bytes) in window[FFT_SIZE - HOP..].iter_mut().zip(buf.chunks_exact(4)) {
+            *sample = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        let magnitudes = magnitudes(&window);
+        for (level, new) in levels.iter_mut().zip(band_levels(&magnitudes)) {
+            *level = new.max(*level * DECAY);
+        }
+        for (level, new) in spectrum.iter_mut().zip(spectrum_levels(&magnitudes, bars)) {
+            *level = new.max(*level * DECAY);
+        }
+        if bands {
+            *LEVELS.lock().unwrap() = levels;
+        }
+        if tx.send(spectrum.clone()).is_err() {break;}
+    }
+    if bands {
+        *LEVELS.lock().unwrap() = [0.;AUDIO_BANDS];
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Records the audio playing on the default output, keeping its band levels for [`levels`] if
+/// `bands` is set, and emits its spectrum in `bars` bars unless that is 0, see
+/// [`Message::AudioSpectrum`]
+pub fn listen(bands:bool, bars:usize)->Subscription<Message>{
+    struct AudioSubscription;
+    Subscription::run_with_id(
+        (TypeId::of::<AudioSubscription>(), bands, bars),
+        stream::channel(4, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            std::thread::spawn(move || record(tx, bands, bars));
+            let mut sent = Instant::now();
+            while let Some(spectrum) = rx.recv().await {
+                if bars > 0 && sent.elapsed() >= SPECTRUM_INTERVAL {
+                    sent = Instant::now();
+                    let _ = output.send(Message::AudioSpectrum(spectrum)).await;
+                }
+            }
+        }),
+    )
+}
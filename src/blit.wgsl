@@ -0,0 +1,19 @@
+// Upscales the scene rendered at a reduced resolution onto the window
+@group(0) @binding(0) var src: texture_2d<f32>;
+@group(0) @binding(1) var src_sampler: sampler;
+
+struct VertexOut {
+	@builtin(position) position: vec4f,
+	@location(0) uv: vec2f,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOut {
+	let uv = vec2f(vec2u((vertex_index << 1) & 2, vertex_index & 2));
+	return VertexOut(vec4f(uv * 2. - 1., 0., 1.), vec2f(uv.x, 1. - uv.y));
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4f {
+	return textureSample(src, src_sampler, in.uv);
+}
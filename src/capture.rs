@@ -0,0 +1,109 @@
+//! Saving the rendered background as a PNG. The frame is drawn at the full resolution of the
+//! widget to the offscreen target of [`crate::render_scale::ScaledTarget`], copied into a buffer
+//! and read back once the GPU is done with it. A notification tells where it was saved.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cosmic::iced::wgpu;
+use image::RgbaImage;
+
+use crate::{fl, notification};
+
+pub struct Capture {
+    path: PathBuf,
+    buffer: Arc<wgpu::Buffer>,
+    size: (u32, u32),
+    /// Whether the target stores its channels as blue, green, red, alpha
+    bgra: bool,
+    /// Whether the copy into the buffer was recorded, so it can be read back after the frame was submitted
+    copied: bool,
+}
+
+/// Bytes per row of the copy, which wgpu requires to be aligned
+fn padded_row(width: u32) -> u32 {
+    (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+}
+
+impl Capture {
+    /// Prepare a capture of a target of `size` and `format` to be saved at `path`
+    pub fn new(device: &wgpu::Device, path: PathBuf, size: (u32, u32), format: wgpu::TextureFormat) -> Result<Self, String> {
+        let bgra = match format {
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            format => return Err(format!("cannot save a target of format {:?}", format)),
+        };
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("capture_buffer"),
+            size: (padded_row(size.0) * size.1) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Ok(Self { path, buffer: Arc::new(buffer), size, bgra, copied: false })
+    }
+
+    pub fn copied(&self) -> bool {
+        self.copied
+    }
+
+    /// Record the copy of `texture` into the buffer, after the frame was drawn to it
+    pub fn copy(&mut self, encoder: &mut wgpu::CommandEncoder, texture: &wgpu::Texture) {
+        let (width, height) = self.size;
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row(width)),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+        self.copied = true;
+    }
+
+    /// Map the buffer once the GPU finished the copy and save it, to be called after the frame was submitted
+    pub fn read_back(self) {
+        let Self { path, buffer, size: (width, height), bgra, .. } = self;
+        let mapped = buffer.clone();
+        buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            if let Err(err) = result {
+                log::warn!("failed to read back the background: {}", err);
+                return;
+            }
+            let mut data = Vec::with_capacity((width * height * 4) as usize);
+            for row in mapped.slice(..).get_mapped_range().chunks_exact(padded_row(width) as usize) {
+                data.extend_from_slice(&row[..(width * 4) as usize]);
+            }
+            mapped.unmap();
+            // encode off the render thread
+            std::thread::spawn(move || {
+                for pixel in data.chunks_exact_mut(4) {
+                    if bgra {pixel.swap(0, 2);}
+                    // the scene was blended onto a transparent target, so its colours are premultiplied
+                    let a = pixel[3] as f32 / 255.;
+                    if a > 0. {
+                        for c in &mut pixel[..3] {
+                            *c = (*c as f32 / a).min(255.) as u8;
+                        }
+                    }
+                }
+                let result = RgbaImage::from_raw(width, height, data)
+                    .ok_or_else(|| "buffer too small".to_string())
+                    .and_then(|image| image.save(&path).map_err(|err| err.to_string()));
+                match result {
+                    Ok(()) => {
+                        log::info!("saved the background to {}", path.display());
+                        notification::send(fl!("background-saved"), path.display().to_string());
+                    },
+                    Err(err) => {
+                        log::warn!("failed to save the background to {}: {}", path.display(), err);
+                        notification::send(fl!("background-save-failed"), err);
+                    },
+                }
+            });
+        });
+    }
+}
@@ -8,10 +8,12 @@ use cosmic_text::{Metrics, Stretch, Weight};
 use hex_color::HexColor;
 use serde::{Deserialize, Serialize};
 
+use itertools::Itertools;
+
 use std::collections::BTreeMap;
 use std::sync::OnceLock;
 
-use crate::{fl, localize::LANGUAGE_SORTER};
+use crate::{alert, fl, localize::LANGUAGE_SORTER, Action, metric_log, metric_store::Metric, resource_monitor::{self, ProcessBy}};
 
 pub const CONFIG_VERSION: u64 = 1;
 pub const COSMIC_THEME_DARK: &str = "COSMIC Dark";
@@ -218,6 +220,554 @@ impl Default for Profile {
     }
 }
 
+/// Built-in background shader
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Scene {
+    /// Rotating earth with a halo following the CPU utilization
+    #[default]
+    Earth,
+    Plasma,
+    Starfield,
+    Waveform,
+}
+
+impl Scene {
+    pub const ALL: [Scene; 4] = [Scene::Earth, Scene::Plasma, Scene::Starfield, Scene::Waveform];
+
+    /// The scene after this one, wrapping around
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|s| *s == self).unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// Background drawn on one output in place of the configured one, see `Config::output_backgrounds`
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct OutputBackground {
+    pub scene: Scene,
+    /// Like `Config::shader_path`, empty for the scene
+    pub shader_path: String,
+    /// Like `Config::texture_path`, empty for the embedded texture
+    pub texture_path: String,
+}
+
+/// Metric fed into one of the generic uniform slots of the background shader, normalized to 0 to 1
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ShaderInput {
+    /// Leave the slot at zero
+    #[default]
+    None,
+    CpuAvg,
+    CpuMax,
+    CpuFreq,
+    GpuUtil,
+    GpuPower,
+    GpuClock,
+    GpuTemp,
+    GpuMem,
+    RamUsed,
+}
+
+/// How a graph in the resource monitor is drawn
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum GraphStyle {
+    /// Braille characters, matching the terminal look
+    #[default]
+    Braille,
+    /// Bars of block characters, coarser but legible in more fonts. Overlays are left out.
+    Block,
+    /// Anti-aliased line and area chart rendered by a shader
+    Smooth,
+}
+
+/// Where the tasks of [`Section::Todo`] are kept, see [`crate::todo`]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum TodoBackend {
+    /// The file at `Config::todo_file` in the todo.txt format
+    #[default]
+    TodoTxt,
+    /// Taskwarrior through its `task` command
+    Taskwarrior,
+}
+
+/// Where the prices of [`Section::Ticker`] come from, see [`crate::ticker`]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum TickerProvider {
+    /// Yahoo Finance, for stocks like `AAPL` and pairs like `BTC-USD`
+    #[default]
+    Yahoo,
+    /// CoinGecko, for coins by id like `bitcoin` in `Config::ticker_currency`
+    CoinGecko,
+    /// `Config::ticker_url` with the price and change at JSON pointers
+    Custom,
+}
+
+/// Block of the resource monitor, which can be moved or turned off in `Config::sections`
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Section {
+    Clock,
+    /// Upcoming events of `Config::agenda_sources`
+    Agenda,
+    /// Recent headlines of `Config::feeds`
+    Headlines,
+    /// Unread GitHub notifications and the latest workflow run of `Config::github_repos`
+    GitHub,
+    /// Unread messages of `Config::mail_accounts`
+    Mail,
+    /// Pending tasks of todo.txt or Taskwarrior
+    Todo,
+    /// Prices and daily changes of `Config::tickers`
+    Ticker,
+    /// Keyboard layout and lock keys, left out by default
+    Keyboard,
+    /// Workspaces of COSMIC with the active one, left out by default
+    Workspaces,
+    /// Applications focused the longest today, left out by default
+    ScreenTime,
+    /// Quote from `Config::quote_file` or `fortune`, left out by default
+    Quote,
+    /// Current weather and forecast at `Config::latitude_mul_100` and
+    /// `Config::longitude_mul_100`, left out by default as it is fetched from Open-Meteo
+    Weather,
+    /// Focus timer, see [`crate::pomodoro`], left out by default
+    Pomodoro,
+    /// Track of the active media player with its controls, left out by default
+    Media,
+    /// Bars of the audio spectrum of the default output, left out by default
+    Spectrum,
+    /// Operating system and kernel
+    System,
+    /// Buttons selecting the time range of the graphs
+    GraphRange,
+    Cpu,
+    Memory,
+    Gpu,
+    /// CPU, memory and load of `Config::remote_hosts` over SSH, left out by default
+    Remote,
+    /// Metrics of the `eos --agent` instances of `Config::agents` with a switcher between them,
+    /// left out by default
+    Agents,
+    /// List of the busiest processes below the background
+    Processes,
+}
+
+impl Section {
+    pub const ALL: [Section; 23] = [
+        Section::Clock, Section::Agenda, Section::Headlines, Section::GitHub, Section::Mail, Section::Todo, Section::Ticker,
+        Section::Keyboard, Section::Workspaces, Section::ScreenTime, Section::Quote, Section::Weather, Section::Pomodoro,
+        Section::Media, Section::Spectrum, Section::System, Section::GraphRange, Section::Cpu, Section::Memory,
+        Section::Gpu, Section::Remote, Section::Agents, Section::Processes,
+    ];
+}
+
+/// How the sections of the resource monitor are arranged
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum MonitorLayout {
+    /// One section below the other
+    #[default]
+    OneColumn,
+    /// Two sections side by side in each row, for wide windows
+    TwoColumns,
+    /// All sections side by side in a bar above the terminal, for placement along the top or
+    /// bottom of the screen
+    Horizontal,
+}
+
+impl MonitorLayout {
+    pub const ALL: [MonitorLayout; 3] = [MonitorLayout::OneColumn, MonitorLayout::TwoColumns, MonitorLayout::Horizontal];
+}
+
+/// Page of the side bar, chosen with the tabs above the monitor
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum MonitorPage {
+    /// The sections, the background and the process list
+    #[default]
+    Overview,
+    /// The process list over the whole height
+    Processes,
+    /// The CPU section with a graph of each core
+    Cores,
+}
+
+impl MonitorPage {
+    pub const ALL: [MonitorPage; 3] = [MonitorPage::Overview, MonitorPage::Processes, MonitorPage::Cores];
+}
+
+/// Whether eos is a regular window or a desktop widget on a layer below the windows,
+/// see [`crate::desktop_widget`]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum WidgetLayer {
+    #[default]
+    Window,
+    /// Above the wallpaper and below the windows
+    Bottom,
+    /// In place of the wallpaper
+    Background,
+}
+
+/// Edge or corner of the output a desktop widget or the overlay is placed at
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum WidgetAnchor {
+    /// The whole output, ignoring the size
+    Fill,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+/// Situation in which a profile of `Config::config_profiles` is selected automatically
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ProfileCondition {
+    /// The chassis reported by the firmware is not portable
+    Desktop,
+    /// The chassis is a laptop, notebook, tablet or convertible
+    Laptop,
+    OnAc,
+    OnBattery,
+}
+
+impl ProfileCondition {
+    /// Whether the condition holds, given whether the machine runs on battery as last polled
+    pub fn holds(self, on_battery: bool) -> bool {
+        match self {
+            ProfileCondition::Desktop => !resource_monitor::is_laptop(),
+            ProfileCondition::Laptop => resource_monitor::is_laptop(),
+            ProfileCondition::OnAc => !on_battery,
+            ProfileCondition::OnBattery => on_battery,
+        }
+    }
+}
+
+/// Declares the settings a profile can override, each like the field of [`Config`] of the same name
+macro_rules! config_profile {
+    ($($name:ident: $ty:ty,)*) => {
+        /// Named set of settings that take precedence over the rest of the config while the
+        /// profile is active, see `Config::config_profiles`
+        #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+        #[serde(default)]
+        pub struct ConfigProfile {
+            /// Activate the profile in this situation while no profile is chosen by hand
+            pub auto_select: Option<ProfileCondition>,
+            $(pub $name: Option<$ty>,)*
+        }
+
+        impl ConfigProfile {
+            fn apply(&self, config: &mut Config) {
+                $(if let Some(value) = &self.$name {config.$name = value.clone();})*
+            }
+
+            /// Keys of the settings the profile overrides
+            pub fn keys(&self) -> Vec<&'static str> {
+                let mut keys = Vec::new();
+                $(if self.$name.is_some() {keys.push(stringify!($name));})*
+                keys
+            }
+        }
+    };
+}
+
+config_profile! {
+    sections: Vec<Section>,
+    monitor_layout: MonitorLayout,
+    frame_time_ms: u16,
+    cpu_gpu_interval_ms: u16,
+    memory_interval_ms: u16,
+    process_interval_ms: u16,
+    idle_throttling: bool,
+    scene: Scene,
+    shader_path: String,
+    texture_path: String,
+    audio_reactive: bool,
+    shader_particles: bool,
+    render_scale_percent: u8,
+    shader_speed_percent: u16,
+    shader_intensity_percent: u16,
+}
+
+/// Unit temperatures are shown in. Thresholds are always in degrees Celsius.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    pub const ALL: [TemperatureUnit; 2] = [TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit];
+
+    /// Convert a temperature in degrees Celsius to this unit
+    pub fn convert(self, celsius: f32) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 1.8 + 32.,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    }
+}
+
+/// Prefixes of the units memory sizes are shown in
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ByteUnit {
+    /// Powers of 1000, GB and MB
+    #[default]
+    Decimal,
+    /// Powers of 1024, GiB and MiB, as most system tools report
+    Binary,
+}
+
+impl ByteUnit {
+    pub const ALL: [ByteUnit; 2] = [ByteUnit::Decimal, ByteUnit::Binary];
+
+    fn kilo(self) -> u64 {
+        match self {
+            ByteUnit::Decimal => 1000,
+            ByteUnit::Binary => 1024,
+        }
+    }
+
+    pub fn gigabytes(self, bytes: u64) -> f32 {
+        (bytes as f64 / self.kilo().pow(3) as f64) as f32
+    }
+
+    pub fn megabytes(self, bytes: u64) -> u64 {
+        bytes / self.kilo().pow(2)
+    }
+
+    pub fn gigabyte_symbol(self) -> &'static str {
+        match self {
+            ByteUnit::Decimal => "GB",
+            ByteUnit::Binary => "GiB",
+        }
+    }
+
+    pub fn megabyte_symbol(self) -> &'static str {
+        match self {
+            ByteUnit::Decimal => "MB",
+            ByteUnit::Binary => "MiB",
+        }
+    }
+}
+
+/// How times of day are shown, on the clock and when hovering graphs
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ClockFormat {
+    #[default]
+    TwentyFourHour,
+    TwelveHour,
+}
+
+impl ClockFormat {
+    pub const ALL: [ClockFormat; 2] = [ClockFormat::TwentyFourHour, ClockFormat::TwelveHour];
+
+    /// `chrono` format of the large clock, from the translations
+    pub fn clock(self, seconds: bool) -> String {
+        match (self, seconds) {
+            (ClockFormat::TwentyFourHour, true) => fl!("clock-format-24h"),
+            (ClockFormat::TwentyFourHour, false) => fl!("clock-format-24h-minutes"),
+            (ClockFormat::TwelveHour, true) => fl!("clock-format-12h"),
+            (ClockFormat::TwelveHour, false) => fl!("clock-format-12h-minutes"),
+        }
+    }
+
+    /// `chrono` format of a time of day without seconds, from the translations
+    pub fn short_time(self) -> String {
+        match self {
+            ClockFormat::TwentyFourHour => fl!("short-time-format-24h"),
+            ClockFormat::TwelveHour => fl!("short-time-format-12h"),
+        }
+    }
+
+    /// `chrono` format of a full hour in a few characters, from the translations
+    pub fn hour(self) -> String {
+        match self {
+            ClockFormat::TwentyFourHour => fl!("hour-format-24h"),
+            ClockFormat::TwelveHour => fl!("hour-format-12h"),
+        }
+    }
+
+    /// `chrono` format of a time of day in running text, from the translations
+    pub fn time(self) -> String {
+        match self {
+            ClockFormat::TwentyFourHour => fl!("time-format-24h"),
+            ClockFormat::TwelveHour => fl!("time-format-12h"),
+        }
+    }
+}
+
+/// Day of an entry of `Config::calendar_events` in the year, given as `YYYY-MM-DD` or as
+/// `MM-DD` for every year
+pub fn event_day(entry: &str, year: i32) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(entry, "%Y-%m-%d")
+        .or_else(|_| chrono::NaiveDate::parse_from_str(&format!("{}-{}", year, entry), "%Y-%m-%d"))
+        .ok()
+}
+
+/// Whether `chrono` can format a time with the format, which it would panic on otherwise
+fn valid_strftime(format: &str) -> bool {
+    use chrono::format::{Item, StrftimeItems};
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// Extra clock below the main one, in a time zone of the IANA database like `Asia/Tokyo`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WorldClock {
+    pub label: String,
+    pub time_zone: String,
+}
+
+/// Alarm going off every day at `time`, like `07:30`, or only on `days` like `["Mon", "Fri"]`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Alarm {
+    pub time: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub days: Vec<String>,
+}
+
+impl Alarm {
+    pub fn time(&self) -> Option<chrono::NaiveTime> {
+        chrono::NaiveTime::parse_from_str(&self.time, "%H:%M").ok()
+    }
+
+    /// Whether the alarm goes off on a day of the week
+    pub fn on(&self, day: chrono::Weekday) -> bool {
+        self.days.is_empty()
+            || self.days.iter().any(|name| name.parse::<chrono::Weekday>() == Ok(day))
+    }
+}
+
+/// IMAP account whose unread messages in `mailbox` are counted, logged in over TLS with the
+/// password stored in the keyring for `host` and `user`, see [`crate::mail`]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct MailAccount {
+    pub label: String,
+    pub host: String,
+    #[serde(default = "MailAccount::default_port")]
+    pub port: u16,
+    pub user: String,
+    #[serde(default = "MailAccount::default_mailbox")]
+    pub mailbox: String,
+}
+
+impl MailAccount {
+    fn default_port() -> u16 {
+        993
+    }
+
+    fn default_mailbox() -> String {
+        "INBOX".to_string()
+    }
+}
+
+/// Named countdown to a local date like `2025-12-24` or a time like `2025-12-24 18:00`
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Countdown {
+    pub label: String,
+    pub until: String,
+}
+
+impl Countdown {
+    pub fn until(&self) -> Option<chrono::NaiveDateTime> {
+        chrono::NaiveDateTime::parse_from_str(&self.until, "%Y-%m-%d %H:%M")
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDate::parse_from_str(&self.until, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| date.and_time(chrono::NaiveTime::MIN))
+            })
+    }
+}
+
+/// Rule of `Config::alerts` like `cpu_avg > 90 for 30s`, with shell commands run when it fires
+/// and when it clears. Written as the rule alone where there are no commands.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(from = "AlertEntry")]
+pub struct Alert {
+    pub rule: String,
+    pub on_trigger: String,
+    pub on_clear: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AlertEntry {
+    Rule(String),
+    Hooks {
+        rule: String,
+        #[serde(default)]
+        on_trigger: String,
+        #[serde(default)]
+        on_clear: String,
+    },
+}
+
+impl From<AlertEntry> for Alert {
+    fn from(entry: AlertEntry) -> Self {
+        match entry {
+            AlertEntry::Rule(rule) => Alert { rule, on_trigger: String::new(), on_clear: String::new() },
+            AlertEntry::Hooks { rule, on_trigger, on_clear } => Alert { rule, on_trigger, on_clear },
+        }
+    }
+}
+
+/// Values at which a metric is coloured as a warning or as critical
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Threshold {
+    pub warning: u32,
+    pub critical: u32,
+}
+
+/// How a metric is visualized below its value in the resource monitor
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum MetricView {
+    /// Only the value as text
+    #[default]
+    Text,
+    /// Graph of the selected time range
+    Graph,
+    /// Radial gauge of the current value
+    Gauge,
+    /// Horizontal bar meter of the current value
+    Bar,
+}
+
+/// Format of the files of the metric log, see [`crate::metric_log`]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum MetricLogFormat {
+    /// Nothing is logged
+    #[default]
+    Off,
+    /// A column per metric after the timestamp
+    Csv,
+    /// A JSON object per line
+    Jsonl,
+}
+
+/// Protocol of the server at `Config::push_address`, see [`crate::push`]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum PushProtocol {
+    /// InfluxDB line protocol, a line per sample with the metrics as fields
+    #[default]
+    Influx,
+    /// Graphite plaintext protocol, a line per metric
+    Graphite,
+}
+
 #[derive(Clone, CosmicConfigEntry, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Config {
     pub app_theme: AppTheme,
@@ -233,11 +783,287 @@ pub struct Config {
     pub opacity: u8,
     pub profiles: BTreeMap<ProfileId, Profile>,
     pub show_headerbar: bool,
+    /// Show the resource monitor and the shader as a desktop widget instead of a window with
+    /// the terminal, applied on the next start
+    pub widget_layer: WidgetLayer,
+    /// Placement of the desktop widget, its size in logical pixels, 0 to fit the content,
+    /// and its distance from the anchored edges
+    pub widget_anchor: WidgetAnchor,
+    pub widget_width: u16,
+    pub widget_height: u16,
+    pub widget_margin: u16,
+    /// Names of the outputs a desktop widget is shown on, `*` for every output, empty for the
+    /// active one, which also shows it while none of them is connected
+    pub widget_outputs: Vec<String>,
+    /// Let the pointer pass through the desktop widget to the desktop below
+    pub widget_click_through: bool,
+    /// Show a tray icon with a summary of the usage and a menu, see [`crate::tray`]
+    pub tray_icon: bool,
+    /// Publish the metrics and controls as `org.eos.Monitor` on the session bus, see
+    /// [`crate::dbus`]
+    pub dbus_service: bool,
+    /// Take commands like `eos ctl set-scene plasma` on a Unix socket, see [`crate::ipc`]
+    pub control_socket: bool,
+    /// Corner of the always-on-top overlay with a one-line readout, see [`crate::overlay`],
+    /// and the opacity of its background in percent
+    pub overlay_anchor: WidgetAnchor,
+    pub overlay_opacity: u8,
+    /// Metric graphed by the picture-in-picture mini monitor, see [`crate::pip`], and the corner
+    /// of the active output it is shown in on Wayland
+    pub pip_metric: Metric,
+    pub pip_anchor: WidgetAnchor,
     pub use_bright_bold: bool,
     pub syntax_theme_dark: String,
     pub syntax_theme_light: String,
     pub focus_follow_mouse: bool,
     pub default_profile: Option<ProfileId>,
+    /// Built-in background shader
+    pub scene: Scene,
+    /// WGSL file drawn as the background instead of the built-in scene, empty for the scene
+    pub shader_path: String,
+    /// Image bound as the shader texture instead of the embedded earth texture, empty for the embedded one
+    pub texture_path: String,
+    /// Up to four images bound as additional shader textures, like ShaderToy's `iChannel0` to `iChannel3`
+    pub texture_channels: Vec<String>,
+    /// Scene, shader and texture per output name, e.g. `DP-2`, replacing the ones above
+    /// on the output eos was started on with `--monitor`
+    pub output_backgrounds: BTreeMap<String, OutputBackground>,
+    /// Metric in each generic uniform slot of the shader, slots beyond the list stay zero
+    pub uniform_slots: Vec<ShaderInput>,
+    /// Feed frequency bands of the audio playing on the default output into the shader
+    pub audio_reactive: bool,
+    /// Feed the cloud cover, precipitation and fog at the location below into the shader,
+    /// fetched from Open-Meteo
+    pub weather_reactive: bool,
+    /// Take the monitor's accent and status colours and the shader tint from the palette pywal
+    /// or wallust generated from the wallpaper
+    pub wal_palette: bool,
+    /// Tint the shader with the dominant colour of the cover art of the playing track in place
+    /// of the palette's accent
+    pub media_art_tint: bool,
+    /// Location the sun position passed to the shader is computed for, in hundredths of a degree,
+    /// north and east are positive
+    pub latitude_mul_100: i32,
+    pub longitude_mul_100: i32,
+    /// Draw sparks over the background that spawn with the CPU and rise with the GPU utilization
+    pub shader_particles: bool,
+    /// Percentage of the window resolution the background is rendered at before it is upscaled,
+    /// lower values save GPU power on large monitors
+    pub render_scale_percent: u8,
+    /// Tweaks of the background from the tweak panel: speed and intensity in percent
+    /// and a hue shift in degrees, passed to the shader as `u.params`
+    pub shader_speed_percent: u16,
+    pub shader_intensity_percent: u16,
+    pub shader_hue_degrees: u16,
+    /// Milliseconds between redraws of the background
+    pub frame_time_ms: u16,
+    /// Time constant in milliseconds with which the values shown in the monitor follow the
+    /// measurements, 0 shows them as they are measured
+    pub display_smoothing_ms: u16,
+    /// Like `display_smoothing_ms` for the CPU metrics passed to the shader, which animate its
+    /// speed and glow and look best changing slowly
+    pub shader_smoothing_ms: u16,
+    /// Milliseconds between polls of the CPU and GPU, which is also the resolution of the graphs
+    /// of the last minute, at least the interval sysinfo needs to measure the CPU usage
+    pub cpu_gpu_interval_ms: u16,
+    /// Milliseconds between polls of the memory usage and of the process list
+    pub memory_interval_ms: u16,
+    pub process_interval_ms: u16,
+    /// Redraw the background only once a second while there is no input and the window is unfocused
+    /// or the machine is on battery
+    pub idle_throttling: bool,
+    /// How many hours of metric history are kept on disk
+    pub history_retention_hours: u16,
+    /// Seconds of samples averaged into one entry of the long-term history
+    pub history_resolution_secs: u16,
+    /// Monospace font and base text size of the resource monitor, the font falls back to
+    /// the default if it is not installed or not monospace
+    pub monitor_font_name: String,
+    pub monitor_font_size: u16,
+    pub cpu_graph_style: GraphStyle,
+    pub gpu_graph_style: GraphStyle,
+    pub mem_graph_style: GraphStyle,
+    /// Scale utilization graphs to their visible data instead of 0 to 100 %
+    pub graph_auto_scale: bool,
+    /// Overlay the busiest core's utilization on the CPU average graph
+    pub cpu_graph_overlay_max: bool,
+    /// Annotate graphs with the current, minimum, average and maximum value of the visible window
+    pub graph_annotations: bool,
+    /// Show a one-line block graph per physical core below the CPU section
+    pub per_core_graphs: bool,
+    pub thresholds: BTreeMap<Metric, Threshold>,
+    /// Rules like `cpu_avg > 90 for 30s` announced by a notification and `alert_sound` when
+    /// they are met, and their commands, see [`crate::alert`]
+    pub alerts: Vec<Alert>,
+    /// Margin by which the value of a rule that fired must go back past its limit before it
+    /// fires again
+    pub alert_hysteresis: u8,
+    /// Sound file played when an alert fires, empty for none
+    pub alert_sound: String,
+    /// Address like `127.0.0.1:9187` serving the metrics at `/metrics` in the Prometheus text
+    /// format, see [`crate::prometheus`], empty for none
+    pub prometheus_address: String,
+    /// Append samples of `metric_log_metrics` every `metric_log_interval_secs` to a file per
+    /// day, deleted after `metric_log_retention_days`, see [`crate::metric_log`]
+    pub metric_log: MetricLogFormat,
+    pub metric_log_metrics: Vec<String>,
+    pub metric_log_interval_secs: u16,
+    pub metric_log_retention_days: u16,
+    /// Server like `influx.lan:8094` the metrics are pushed to over TCP every
+    /// `push_interval_secs`, in batches of `push_batch` samples, see [`crate::push`], empty for
+    /// none, and the measurement or the first part of the Graphite path
+    pub push_address: String,
+    pub push_protocol: PushProtocol,
+    pub push_interval_secs: u16,
+    pub push_batch: u8,
+    pub push_prefix: String,
+    /// MQTT broker like `broker.lan:1883` the metrics are published to every
+    /// `mqtt_interval_secs` with Home Assistant discovery configs under `mqtt_discovery_prefix`,
+    /// see [`crate::mqtt`], empty for none, and the user logged in as, empty for none
+    pub mqtt_broker: String,
+    pub mqtt_user: String,
+    pub mqtt_interval_secs: u16,
+    pub mqtt_discovery_prefix: String,
+    /// Connect to the broker over TLS, verifying it against the certificate authorities in
+    /// `mqtt_ca`, or those of the system while it is empty
+    pub mqtt_tls: bool,
+    pub mqtt_ca: String,
+    /// Blocks shown in the resource monitor in the order they are listed, the others are turned off
+    pub sections: Vec<Section>,
+    pub monitor_layout: MonitorLayout,
+    /// Show only the clock and a line with a sparkline for each metric, in a narrow strip
+    /// instead of the terminal and the side bar
+    pub compact: bool,
+    /// Sections reduced to their header by clicking it
+    pub collapsed_sections: Vec<Section>,
+    /// Show tabs above the monitor switching between its pages, and the page last shown
+    pub monitor_tabs: bool,
+    pub monitor_page: MonitorPage,
+    /// Larger section headers, tabs and buttons in the monitor for touch screens
+    pub touch_targets: bool,
+    pub temperature_unit: TemperatureUnit,
+    pub byte_unit: ByteUnit,
+    pub clock_format: ClockFormat,
+    /// Show the seconds on the clock, and a line with the date below it
+    pub clock_seconds: bool,
+    pub clock_date: bool,
+    /// `chrono` formats replacing the clock of `clock_format` and `clock_seconds` and the date
+    /// from the translations, empty for those
+    pub clock_custom_format: String,
+    pub date_custom_format: String,
+    /// Alarms and countdowns shown below the clock, announced by a notification and
+    /// `alarm_sound` when they are due, see [`crate::alarm`]
+    pub alarms: Vec<Alarm>,
+    pub countdowns: Vec<Countdown>,
+    /// Sound file played when an alarm goes off or a countdown ends, empty for none
+    pub alarm_sound: String,
+    /// Show the times of sunrise and sunset at the configured location and the phase of the
+    /// moon below the clock, see [`crate::sun::daylight`]
+    pub sun_moon: bool,
+    /// Clocks of other time zones listed below the main clock
+    pub world_clocks: Vec<WorldClock>,
+    /// Show a calendar of the month below the clock, toggled by clicking the date, with the
+    /// days of `calendar_events` marked, see [`event_day`]
+    pub calendar: bool,
+    pub calendar_events: Vec<String>,
+    /// ICS files, http, https or webcal URLs and CalDAV collections after `caldav:` whose
+    /// upcoming events are listed in [`Section::Agenda`], see [`crate::agenda`]
+    pub agenda_sources: Vec<String>,
+    /// Number of events listed, and minutes between fetches of the sources
+    pub agenda_events: u8,
+    pub agenda_refresh_minutes: u16,
+    /// RSS or Atom feeds given as URLs or files, whose recent headlines are cycled through in
+    /// [`Section::Headlines`], see [`crate::feed`]
+    pub feeds: Vec<String>,
+    /// Number of the newest headlines cycled through, seconds each is shown, and minutes
+    /// between fetches of the feeds
+    pub feed_headlines: u8,
+    pub feed_cycle_seconds: u16,
+    pub feed_refresh_minutes: u16,
+    /// Repositories as `owner/name` whose latest workflow run is shown in [`Section::GitHub`],
+    /// see [`crate::github`]
+    pub github_repos: Vec<String>,
+    /// File holding a GitHub token in its first line, needed for the notifications and private
+    /// repositories
+    pub github_token_file: String,
+    pub github_refresh_minutes: u16,
+    /// IMAP accounts whose unread messages are counted in [`Section::Mail`]
+    pub mail_accounts: Vec<MailAccount>,
+    /// Command starting the mail client when an account is clicked, like `thunderbird`
+    pub mail_client: String,
+    /// Where the tasks of [`Section::Todo`] come from, the todo.txt file if it is `TodoTxt`,
+    /// and the number of tasks shown
+    pub todo_backend: TodoBackend,
+    pub todo_file: String,
+    pub todo_tasks: u8,
+    /// Symbols whose prices are shown in [`Section::Ticker`], in the form the provider expects
+    pub tickers: Vec<String>,
+    pub ticker_provider: TickerProvider,
+    /// Currency of the prices of CoinGecko, like `usd`
+    pub ticker_currency: String,
+    /// URL with `{symbol}` in it and JSON pointers to the price and the change in percent,
+    /// for the `Custom` provider
+    pub ticker_url: String,
+    pub ticker_price_pointer: String,
+    pub ticker_change_pointer: String,
+    pub ticker_refresh_minutes: u16,
+    /// Switch to a workspace of [`Section::Workspaces`] when it is clicked
+    pub workspaces_clickable: bool,
+    /// Applications listed in [`Section::ScreenTime`]
+    pub screen_time_apps: u8,
+    /// File of quotes for [`Section::Quote`] in the format of the fortune database or one per
+    /// line, `fortune` is asked when empty
+    pub quote_file: String,
+    pub quote_refresh_minutes: u16,
+    /// SSH destinations like `nas` or `me@build.lan` watched in [`Section::Remote`], and the
+    /// seconds between their samples
+    pub remote_hosts: Vec<String>,
+    pub remote_interval_secs: u16,
+    /// Addresses like `build.lan:7870` of the `eos --agent` instances shown in
+    /// [`Section::Agents`], see [`crate::agent`]
+    pub agents: Vec<String>,
+    /// Address `eos --agent` listens at, and the seconds between its samples
+    pub agent_address: String,
+    pub agent_interval_secs: u16,
+    /// PEM files of the certificate and key presented by both the agent and the interface, and
+    /// of the CA whose certificates are the only ones taken from the other end
+    pub agent_cert: String,
+    pub agent_key: String,
+    pub agent_ca: String,
+    /// Hours of the forecast in [`Section::Weather`]
+    pub weather_forecast_hours: u8,
+    /// Minutes of the work sessions and breaks of [`Section::Pomodoro`], and the number of
+    /// sessions before a long break
+    pub pomodoro_work_minutes: u16,
+    pub pomodoro_break_minutes: u16,
+    pub pomodoro_long_break_minutes: u16,
+    pub pomodoro_rounds: u8,
+    /// Calm the background down during breaks
+    pub pomodoro_shader: bool,
+    /// How the bars of [`Section::Spectrum`] are drawn, and their height in lines of text
+    pub spectrum_style: GraphStyle,
+    pub spectrum_lines: u8,
+    /// Order of the process list when eos starts
+    pub process_sort_by: ProcessBy,
+    /// Shortcuts like `Ctrl+Alt+K` replacing the default key bindings of their actions,
+    /// see [`crate::key_bind::parse`]
+    pub key_bindings: BTreeMap<String, Action>,
+    /// Visualization per metric, metrics that are not listed are shown as text
+    pub metric_views: BTreeMap<Metric, MetricView>,
+    /// Named sets of sections, refresh rates and shader settings overriding the ones above
+    pub config_profiles: BTreeMap<String, ConfigProfile>,
+    /// Name of the profile chosen by hand, empty to select one by `ConfigProfile::auto_select`
+    pub config_profile: String,
+}
+
+/// Window geometry remembered across restarts in the state of cosmic-config, which unlike
+/// [`Config`] is not meant to be edited
+#[derive(Clone, CosmicConfigEntry, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct State {
+    /// Logical size of the main window when it was last resized
+    pub window_size: Option<(u32, u32)>,
+    /// Position of the main window, which Wayland does not report or let apps choose
+    pub window_position: Option<(i32, i32)>,
 }
 
 impl Default for Config {
@@ -257,15 +1083,212 @@ impl Default for Config {
             opacity: 100,
             profiles: BTreeMap::new(),
             show_headerbar: true,
+            widget_layer: WidgetLayer::default(),
+            widget_anchor: WidgetAnchor::default(),
+            widget_width: 260,
+            widget_height: 0,
+            widget_margin: 20,
+            widget_outputs: Vec::new(),
+            widget_click_through: false,
+            tray_icon: false,
+            dbus_service: false,
+            control_socket: false,
+            overlay_anchor: WidgetAnchor::TopLeft,
+            overlay_opacity: 60,
+            pip_metric: Metric::GpuUtil,
+            pip_anchor: WidgetAnchor::TopRight,
             syntax_theme_dark: COSMIC_THEME_DARK.to_string(),
             syntax_theme_light: COSMIC_THEME_LIGHT.to_string(),
             use_bright_bold: false,
             default_profile: None,
+            scene: Scene::default(),
+            shader_path: String::new(),
+            texture_path: String::new(),
+            texture_channels: Vec::new(),
+            output_backgrounds: BTreeMap::new(),
+            uniform_slots: vec![ShaderInput::GpuUtil, ShaderInput::GpuMem, ShaderInput::RamUsed],
+            audio_reactive: false,
+            weather_reactive: false,
+            wal_palette: false,
+            media_art_tint: false,
+            latitude_mul_100: 0,
+            longitude_mul_100: 0,
+            shader_particles: false,
+            render_scale_percent: 100,
+            shader_speed_percent: 100,
+            shader_intensity_percent: 100,
+            shader_hue_degrees: 0,
+            frame_time_ms: 33,
+            display_smoothing_ms: 650,
+            shader_smoothing_ms: 3300,
+            cpu_gpu_interval_ms: sysinfo::MINIMUM_CPU_UPDATE_INTERVAL.as_millis() as u16,
+            memory_interval_ms: 1000,
+            process_interval_ms: 2000,
+            idle_throttling: true,
+            history_retention_hours: 24,
+            history_resolution_secs: 10,
+            monitor_font_name: DEFAULT_FONT_NAME.to_string(),
+            monitor_font_size: 14,
+            cpu_graph_style: GraphStyle::default(),
+            gpu_graph_style: GraphStyle::default(),
+            mem_graph_style: GraphStyle::default(),
+            graph_auto_scale: false,
+            cpu_graph_overlay_max: false,
+            graph_annotations: true,
+            per_core_graphs: false,
+            thresholds: BTreeMap::from([
+                (Metric::CpuAvg, Threshold { warning: 80, critical: 95 }),
+                (Metric::CpuMax, Threshold { warning: 95, critical: 100 }),
+                (Metric::GpuUtil, Threshold { warning: 90, critical: 98 }),
+                (Metric::GpuTemp, Threshold { warning: 80, critical: 90 }),
+            ]),
+            alerts: Vec::new(),
+            alert_hysteresis: 5,
+            alert_sound: String::new(),
+            prometheus_address: String::new(),
+            metric_log: MetricLogFormat::Off,
+            metric_log_metrics: metric_log::METRICS.iter().map(|name| name.to_string()).collect(),
+            metric_log_interval_secs: 10,
+            metric_log_retention_days: 30,
+            push_address: String::new(),
+            push_protocol: PushProtocol::Influx,
+            push_interval_secs: 10,
+            push_batch: 6,
+            push_prefix: "eos".to_string(),
+            mqtt_broker: String::new(),
+            mqtt_user: String::new(),
+            mqtt_interval_secs: 30,
+            mqtt_discovery_prefix: "homeassistant".to_string(),
+            mqtt_tls: false,
+            mqtt_ca: String::new(),
+            sections: Section::ALL
+                .into_iter()
+                .filter(|section| !matches!(section, Section::Keyboard | Section::Workspaces | Section::ScreenTime | Section::Quote | Section::Weather | Section::Pomodoro | Section::Media | Section::Spectrum | Section::Remote | Section::Agents))
+                .collect(),
+            monitor_layout: MonitorLayout::default(),
+            compact: false,
+            collapsed_sections: Vec::new(),
+            monitor_tabs: true,
+            monitor_page: MonitorPage::default(),
+            touch_targets: false,
+            temperature_unit: TemperatureUnit::default(),
+            byte_unit: ByteUnit::default(),
+            clock_format: ClockFormat::default(),
+            clock_seconds: true,
+            clock_date: false,
+            sun_moon: false,
+            alarms: Vec::new(),
+            countdowns: Vec::new(),
+            alarm_sound: String::new(),
+            clock_custom_format: String::new(),
+            date_custom_format: String::new(),
+            world_clocks: Vec::new(),
+            calendar: false,
+            calendar_events: Vec::new(),
+            agenda_sources: Vec::new(),
+            agenda_events: 5,
+            agenda_refresh_minutes: 15,
+            feeds: Vec::new(),
+            feed_headlines: 20,
+            feed_cycle_seconds: 10,
+            feed_refresh_minutes: 30,
+            github_repos: Vec::new(),
+            github_token_file: String::new(),
+            github_refresh_minutes: 5,
+            mail_accounts: Vec::new(),
+            mail_client: String::new(),
+            todo_backend: TodoBackend::default(),
+            todo_file: String::new(),
+            todo_tasks: 5,
+            tickers: Vec::new(),
+            ticker_provider: TickerProvider::default(),
+            ticker_currency: "usd".to_string(),
+            ticker_url: String::new(),
+            ticker_price_pointer: String::new(),
+            ticker_change_pointer: String::new(),
+            ticker_refresh_minutes: 5,
+            workspaces_clickable: true,
+            screen_time_apps: 5,
+            quote_file: String::new(),
+            quote_refresh_minutes: 60,
+            remote_hosts: Vec::new(),
+            remote_interval_secs: 5,
+            agents: Vec::new(),
+            agent_address: "127.0.0.1:7870".to_string(),
+            agent_interval_secs: 2,
+            agent_cert: String::new(),
+            agent_key: String::new(),
+            agent_ca: String::new(),
+            weather_forecast_hours: 6,
+            pomodoro_work_minutes: 25,
+            pomodoro_break_minutes: 5,
+            pomodoro_long_break_minutes: 15,
+            pomodoro_rounds: 4,
+            pomodoro_shader: false,
+            spectrum_style: GraphStyle::Block,
+            spectrum_lines: 4,
+            process_sort_by: ProcessBy::default(),
+            key_bindings: BTreeMap::new(),
+            metric_views: BTreeMap::from([
+                (Metric::CpuAvg, MetricView::Graph),
+                (Metric::GpuUtil, MetricView::Graph),
+                (Metric::RamUsed, MetricView::Graph),
+            ]),
+            config_profiles: BTreeMap::from([
+                // chosen by hand, or on battery once `auto_select` is set
+                ("laptop".to_string(), ConfigProfile {
+                    frame_time_ms: Some(66),
+                    cpu_gpu_interval_ms: Some(1000),
+                    process_interval_ms: Some(5000),
+                    idle_throttling: Some(true),
+                    shader_particles: Some(false),
+                    render_scale_percent: Some(50),
+                    ..ConfigProfile::default()
+                }),
+                ("presentation".to_string(), ConfigProfile {
+                    sections: Some(vec![Section::Clock, Section::Cpu, Section::Memory, Section::Gpu]),
+                    shader_particles: Some(false),
+                    shader_intensity_percent: Some(60),
+                    ..ConfigProfile::default()
+                }),
+            ]),
+            config_profile: String::new(),
         }
     }
 }
 
 impl Config {
+    /// Name of the profile in effect, the one chosen by hand or else the first one, by name,
+    /// whose `auto_select` condition holds
+    pub fn active_profile(&self, on_battery: bool) -> Option<&str> {
+        if !self.config_profile.is_empty() {
+            return self.config_profiles.contains_key(&self.config_profile)
+                .then_some(self.config_profile.as_str());
+        }
+        self.config_profiles.iter()
+            .find(|(_, profile)| profile.auto_select.is_some_and(|condition| condition.holds(on_battery)))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Override the settings with those of the active profile
+    pub fn apply_profile(&mut self, on_battery: bool) {
+        if let Some(profile) = self.active_profile(on_battery).and_then(|name| self.config_profiles.get(name)) {
+            profile.clone().apply(self);
+        }
+    }
+
+    /// Background of `output`, its entry in `output_backgrounds` or else the global one
+    pub fn background(&self, output: Option<&str>) -> OutputBackground {
+        match output.and_then(|output| self.output_backgrounds.get(output)) {
+            Some(background) => background.clone(),
+            None => OutputBackground {
+                scene: self.scene,
+                shader_path: self.shader_path.clone(),
+                texture_path: self.texture_path.clone(),
+            },
+        }
+    }
+
     pub fn color_schemes(
         &self,
         color_scheme_kind: ColorSchemeKind,
@@ -331,6 +1354,204 @@ impl Config {
         Metrics::new(font_size, line_height)
     }
 
+    /// Reset values outside of their valid range to their default, returning a message for each
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut default = Self::default();
+        let mut errors = Vec::new();
+        macro_rules! check {
+            ($name:ident, $valid:expr, $expected:expr) => {
+                if !$valid(&self.$name) {
+                    errors.push(format!(
+                        "`{}` must be {}, using the default instead of {:?}",
+                        stringify!($name), $expected, self.$name,
+                    ));
+                    std::mem::swap(&mut self.$name, &mut default.$name);
+                }
+            };
+        }
+        check!(opacity, |v: &u8| *v <= 100, "at most 100");
+        check!(widget_width, |v: &u16| *v <= 8192, "at most 8192");
+        check!(widget_height, |v: &u16| *v <= 8192, "at most 8192");
+        check!(overlay_opacity, |v: &u8| *v <= 100, "at most 100");
+        check!(frame_time_ms, |v: &u16| (1..=1000).contains(v), "between 1 and 1000");
+        check!(monitor_font_size, |v: &u16| (4..=32).contains(v), "between 4 and 32");
+        check!(display_smoothing_ms, |v: &u16| *v <= 10000, "at most 10000");
+        check!(shader_smoothing_ms, |v: &u16| *v <= 30000, "at most 30000");
+        let min_cpu_interval = sysinfo::MINIMUM_CPU_UPDATE_INTERVAL.as_millis() as u16;
+        check!(
+            cpu_gpu_interval_ms,
+            |v: &u16| (min_cpu_interval..=10000).contains(v),
+            format!("between {} and 10000", min_cpu_interval)
+        );
+        check!(memory_interval_ms, |v: &u16| (100..=60000).contains(v), "between 100 and 60000");
+        check!(process_interval_ms, |v: &u16| (500..=60000).contains(v), "between 500 and 60000");
+        check!(history_retention_hours, |v: &u16| *v >= 1, "at least 1");
+        check!(history_resolution_secs, |v: &u16| *v >= 1, "at least 1");
+        check!(render_scale_percent, |v: &u8| (10..=100).contains(v), "between 10 and 100");
+        check!(shader_speed_percent, |v: &u16| *v <= 300, "at most 300");
+        check!(shader_intensity_percent, |v: &u16| *v <= 200, "at most 200");
+        check!(shader_hue_degrees, |v: &u16| *v < 360, "below 360");
+        check!(latitude_mul_100, |v: &i32| (-9000..=9000).contains(v), "between -9000 and 9000");
+        check!(longitude_mul_100, |v: &i32| (-18000..=18000).contains(v), "between -18000 and 18000");
+        check!(
+            texture_channels,
+            |v: &Vec<String>| v.len() <= crate::shader::TEXTURE_CHANNELS,
+            format!("at most {} paths", crate::shader::TEXTURE_CHANNELS)
+        );
+        check!(
+            uniform_slots,
+            |v: &Vec<ShaderInput>| v.len() <= crate::shader::UNIFORM_SLOTS,
+            format!("at most {} metrics", crate::shader::UNIFORM_SLOTS)
+        );
+        check!(
+            sections,
+            |v: &Vec<Section>| v.iter().all_unique(),
+            "listed at most once"
+        );
+        check!(
+            config_profile,
+            |v: &String| v.is_empty() || self.config_profiles.contains_key(v),
+            "empty or the name of a profile in `config_profiles`"
+        );
+        check!(
+            key_bindings,
+            |v: &BTreeMap<String, Action>| v.keys().all(|shortcut| crate::key_bind::parse(shortcut).is_ok()),
+            "shortcuts of modifiers and a key joined by `+`, e.g. `Ctrl+Shift+T`"
+        );
+        check!(clock_custom_format, |v: &String| valid_strftime(v), "a chrono format like `%H:%M`");
+        check!(date_custom_format, |v: &String| valid_strftime(v), "a chrono format like `%Y-%m-%d`");
+        check!(
+            world_clocks,
+            |v: &Vec<WorldClock>| v.iter().all(|clock| clock.time_zone.parse::<chrono_tz::Tz>().is_ok()),
+            "clocks with a time zone of the IANA database like `Asia/Tokyo`"
+        );
+        check!(
+            alarms,
+            |v: &Vec<Alarm>| v.iter().all(|alarm| alarm.time().is_some()
+                && alarm.days.iter().all(|day| day.parse::<chrono::Weekday>().is_ok())),
+            "alarms with a time like `07:30` and days like `Mon`"
+        );
+        check!(
+            countdowns,
+            |v: &Vec<Countdown>| v.iter().all(|countdown| countdown.until().is_some()),
+            "countdowns until a date like `2025-12-24` or a time like `2025-12-24 18:00`"
+        );
+        check!(
+            calendar_events,
+            // a leap year, so `02-29` is accepted
+            |v: &Vec<String>| v.iter().all(|entry| event_day(entry, 2000).is_some()),
+            "dates like `2025-12-24`, or `12-24` for every year"
+        );
+        check!(agenda_events, |v: &u8| (1..=20).contains(v), "between 1 and 20");
+        check!(agenda_refresh_minutes, |v: &u16| (1..=1440).contains(v), "between 1 and 1440");
+        check!(feed_headlines, |v: &u8| (1..=100).contains(v), "between 1 and 100");
+        check!(feed_cycle_seconds, |v: &u16| (2..=3600).contains(v), "between 2 and 3600");
+        check!(feed_refresh_minutes, |v: &u16| (5..=1440).contains(v), "between 5 and 1440");
+        check!(
+            github_repos,
+            |v: &Vec<String>| v.iter().all(|repo| repo.split('/').filter(|part| !part.is_empty()).count() == 2),
+            "repositories given as owner/name"
+        );
+        check!(github_refresh_minutes, |v: &u16| (1..=1440).contains(v), "between 1 and 1440");
+        check!(
+            mail_accounts,
+            |v: &Vec<MailAccount>| v.iter().all(|account| !account.host.is_empty() && !account.user.is_empty()),
+            "accounts with a host and a user"
+        );
+        check!(todo_tasks, |v: &u8| (1..=30).contains(v), "between 1 and 30");
+        check!(ticker_refresh_minutes, |v: &u16| (1..=1440).contains(v), "between 1 and 1440");
+        check!(screen_time_apps, |v: &u8| (1..=20).contains(v), "between 1 and 20");
+        check!(quote_refresh_minutes, |v: &u16| (1..=1440).contains(v), "between 1 and 1440");
+        check!(
+            remote_hosts,
+            |v: &Vec<String>| v.iter().all(|host| !host.is_empty() && !host.starts_with('-') && !host.contains(char::is_whitespace)),
+            "SSH destinations like `nas` or `me@build.lan`"
+        );
+        check!(remote_interval_secs, |v: &u16| (1..=600).contains(v), "between 1 and 600");
+        check!(
+            agents,
+            |v: &Vec<String>| v.iter().all(|agent| agent.rsplit_once(':').is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())),
+            "addresses with a port like `build.lan:7870`"
+        );
+        check!(
+            agent_address,
+            |v: &String| v.parse::<std::net::SocketAddr>().is_ok(),
+            "an address with a port like `0.0.0.0:7870`"
+        );
+        check!(agent_interval_secs, |v: &u16| (1..=60).contains(v), "between 1 and 60");
+        check!(weather_forecast_hours, |v: &u8| (1..=8).contains(v), "between 1 and 8");
+        check!(pomodoro_work_minutes, |v: &u16| (1..=240).contains(v), "between 1 and 240");
+        check!(pomodoro_break_minutes, |v: &u16| (1..=60).contains(v), "between 1 and 60");
+        check!(pomodoro_long_break_minutes, |v: &u16| (1..=120).contains(v), "between 1 and 120");
+        check!(pomodoro_rounds, |v: &u8| (1..=12).contains(v), "between 1 and 12");
+        check!(spectrum_lines, |v: &u8| (1..=16).contains(v), "between 1 and 16");
+        check!(
+            thresholds,
+            |v: &BTreeMap<Metric, Threshold>| v.values().all(|t| t.warning <= t.critical),
+            "warnings at most as high as the critical values"
+        );
+        check!(
+            alerts,
+            |v: &Vec<Alert>| v.iter().all(|entry| entry.rule.parse::<alert::Rule>().is_ok()),
+            "rules like `cpu_avg > 90 for 30s`, `gpu_temp > 85` or `disk / > 95%`"
+        );
+        check!(alert_hysteresis, |v: &u8| *v <= 50, "at most 50");
+        check!(
+            prometheus_address,
+            |v: &String| v.is_empty() || v.parse::<std::net::SocketAddr>().is_ok(),
+            "an address with a port like `127.0.0.1:9187`"
+        );
+        check!(
+            metric_log_metrics,
+            |v: &Vec<String>| v.iter().all(|name| metric_log::METRICS.contains(&name.as_str())),
+            "a list of cpu_avg, cpu_max, gpu_util, gpu_temp, gpu_mem and ram"
+        );
+        check!(metric_log_interval_secs, |v: &u16| (1..=3600).contains(v), "between 1 and 3600");
+        check!(metric_log_retention_days, |v: &u16| (1..=3650).contains(v), "between 1 and 3650");
+        check!(
+            push_address,
+            |v: &String| v.is_empty() || v.rsplit_once(':').is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok()),
+            "a host with a port like `influx.lan:8094`"
+        );
+        check!(push_interval_secs, |v: &u16| (1..=3600).contains(v), "between 1 and 3600");
+        check!(push_batch, |v: &u8| (1..=100).contains(v), "between 1 and 100");
+        check!(
+            push_prefix,
+            |v: &String| !v.is_empty() && v.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'),
+            "a name of letters, digits, `_`, `-` and `.`"
+        );
+        check!(
+            mqtt_broker,
+            |v: &String| !v.contains(['/', ' ', '#', '+']),
+            "a host like `broker.lan` or `broker.lan:1883`"
+        );
+        check!(mqtt_interval_secs, |v: &u16| (1..=3600).contains(v), "between 1 and 3600");
+        check!(
+            mqtt_discovery_prefix,
+            |v: &String| !v.is_empty() && !v.contains(['#', '+']) && !v.starts_with('/') && !v.ends_with('/'),
+            "a topic like `homeassistant`"
+        );
+        errors
+    }
+
+    /// `chrono` format of the large clock
+    pub fn clock_strftime(&self) -> String {
+        if self.clock_custom_format.is_empty() {
+            self.clock_format.clock(self.clock_seconds)
+        } else {
+            self.clock_custom_format.clone()
+        }
+    }
+
+    /// `chrono` format of the date below the clock, if it is shown
+    pub fn date_strftime(&self) -> Option<String> {
+        match (self.clock_date, self.date_custom_format.is_empty()) {
+            (false, _) => None,
+            (true, true) => Some(fl!("date-format")),
+            (true, false) => Some(self.date_custom_format.clone()),
+        }
+    }
+
     pub fn opacity_ratio(&self) -> f32 {
         f32::from(self.opacity) / 100.0
     }
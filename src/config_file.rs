@@ -0,0 +1,633 @@
+//! Hand-editable TOML configuration at `$XDG_CONFIG_HOME/eos/eos.toml`, layered over the
+//! settings saved by the app. Keys that are missing keep their value from the app, keys
+//! that are set take precedence. A documented template is written on the first start.
+//! The file records the `version` of its keys, and older files are migrated to the current keys.
+//! Environment variables like `EOS_FRAME_TIME_MS` override the keys of the file in turn.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex, OnceLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Alarm, Alert, ByteUnit, ClockFormat, Config, ConfigProfile, Countdown, GraphStyle, MailAccount, MetricLogFormat, MetricView, MonitorLayout, OutputBackground, PushProtocol, Scene, Section, ShaderInput, TemperatureUnit, Threshold, TickerProvider, TodoBackend, WidgetAnchor, WidgetLayer, WorldClock};
+use crate::metric_store::Metric;
+use crate::Action;
+use crate::resource_monitor::ProcessBy;
+
+const FILE_NAME:&str = "eos.toml";
+/// Prefix of the environment variables overriding keys, followed by the key in upper case
+const ENV_PREFIX:&str = "EOS_";
+/// Written to the config directory if there is no config file yet, every key commented out
+const TEMPLATE:&str = include_str!("../res/eos.toml");
+/// Time a setting changed in the app has to stay unchanged before it is written to the file
+const WRITE_DELAY:Duration = Duration::from_millis(500);
+/// Migration to each version of the keys of the file from the one before, starting with
+/// version 1. Files without a version were written before versioning, which is version 0.
+const MIGRATIONS:&[Migration] = &[
+    // only records the version
+    Migration {renamed: &[], filled: &[]},
+];
+/// Version of the keys of the file, increased by adding a migration
+const VERSION:u32 = MIGRATIONS.len() as u32;
+
+/// Changes to the top-level keys of the file made by a version
+struct Migration {
+    /// Keys renamed, as the old and the new name
+    renamed: &'static [(&'static str, &'static str)],
+    /// Keys added to files that do not set them, as the key and its value in TOML, for new keys
+    /// whose default would change the behaviour of older files
+    filled: &'static [(&'static str, &'static str)],
+}
+
+/// Declares the keys of the file, each overriding the field of [`Config`] of the same name
+macro_rules! config_file {
+    ($($name:ident: $ty:ty,)*) => {
+        /// Contents of the config file, `None` for missing keys
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(default, deny_unknown_fields)]
+        struct ConfigFile {
+            $($name: Option<$ty>,)*
+        }
+
+        impl ConfigFile {
+            fn apply(self, config:&mut Config){
+                $(if let Some(value) = self.$name {config.$name = value;})*
+            }
+        }
+    };
+}
+
+config_file! {
+    // appearance
+    opacity: u8,
+    show_headerbar: bool,
+    widget_layer: WidgetLayer,
+    widget_anchor: WidgetAnchor,
+    widget_width: u16,
+    widget_height: u16,
+    widget_margin: u16,
+    widget_outputs: Vec<String>,
+    widget_click_through: bool,
+    tray_icon: bool,
+    dbus_service: bool,
+    control_socket: bool,
+    overlay_anchor: WidgetAnchor,
+    overlay_opacity: u8,
+    pip_metric: Metric,
+    pip_anchor: WidgetAnchor,
+    // refresh rates
+    frame_time_ms: u16,
+    display_smoothing_ms: u16,
+    shader_smoothing_ms: u16,
+    cpu_gpu_interval_ms: u16,
+    memory_interval_ms: u16,
+    process_interval_ms: u16,
+    idle_throttling: bool,
+    history_retention_hours: u16,
+    history_resolution_secs: u16,
+    // thresholds
+    thresholds: BTreeMap<Metric, Threshold>,
+    alerts: Vec<Alert>,
+    alert_hysteresis: u8,
+    alert_sound: String,
+    prometheus_address: String,
+    metric_log: MetricLogFormat,
+    metric_log_metrics: Vec<String>,
+    metric_log_interval_secs: u16,
+    metric_log_retention_days: u16,
+    push_address: String,
+    push_protocol: PushProtocol,
+    push_interval_secs: u16,
+    push_batch: u8,
+    push_prefix: String,
+    mqtt_broker: String,
+    mqtt_user: String,
+    mqtt_interval_secs: u16,
+    mqtt_discovery_prefix: String,
+    mqtt_tls: bool,
+    mqtt_ca: String,
+    // layout
+    sections: Vec<Section>,
+    monitor_layout: MonitorLayout,
+    compact: bool,
+    collapsed_sections: Vec<Section>,
+    monitor_tabs: bool,
+    touch_targets: bool,
+    metric_views: BTreeMap<Metric, MetricView>,
+    monitor_font_name: String,
+    monitor_font_size: u16,
+    cpu_graph_style: GraphStyle,
+    gpu_graph_style: GraphStyle,
+    mem_graph_style: GraphStyle,
+    graph_auto_scale: bool,
+    cpu_graph_overlay_max: bool,
+    graph_annotations: bool,
+    per_core_graphs: bool,
+    temperature_unit: TemperatureUnit,
+    byte_unit: ByteUnit,
+    clock_format: ClockFormat,
+    clock_seconds: bool,
+    clock_date: bool,
+    sun_moon: bool,
+    alarms: Vec<Alarm>,
+    countdowns: Vec<Countdown>,
+    alarm_sound: String,
+    clock_custom_format: String,
+    date_custom_format: String,
+    world_clocks: Vec<WorldClock>,
+    calendar: bool,
+    calendar_events: Vec<String>,
+    agenda_sources: Vec<String>,
+    agenda_events: u8,
+    agenda_refresh_minutes: u16,
+    feeds: Vec<String>,
+    feed_headlines: u8,
+    feed_cycle_seconds: u16,
+    feed_refresh_minutes: u16,
+    github_repos: Vec<String>,
+    github_token_file: String,
+    github_refresh_minutes: u16,
+    mail_accounts: Vec<MailAccount>,
+    mail_client: String,
+    todo_backend: TodoBackend,
+    todo_file: String,
+    todo_tasks: u8,
+    tickers: Vec<String>,
+    ticker_provider: TickerProvider,
+    ticker_currency: String,
+    ticker_url: String,
+    ticker_price_pointer: String,
+    ticker_change_pointer: String,
+    ticker_refresh_minutes: u16,
+    workspaces_clickable: bool,
+    screen_time_apps: u8,
+    quote_file: String,
+    quote_refresh_minutes: u16,
+    remote_hosts: Vec<String>,
+    remote_interval_secs: u16,
+    agents: Vec<String>,
+    agent_address: String,
+    agent_interval_secs: u16,
+    agent_cert: String,
+    agent_key: String,
+    agent_ca: String,
+    weather_forecast_hours: u8,
+    pomodoro_work_minutes: u16,
+    pomodoro_break_minutes: u16,
+    pomodoro_long_break_minutes: u16,
+    pomodoro_rounds: u8,
+    pomodoro_shader: bool,
+    spectrum_style: GraphStyle,
+    spectrum_lines: u8,
+    process_sort_by: ProcessBy,
+    key_bindings: BTreeMap<String, Action>,
+    // shader
+    scene: Scene,
+    shader_path: String,
+    texture_path: String,
+    texture_channels: Vec<String>,
+    output_backgrounds: BTreeMap<String, OutputBackground>,
+    uniform_slots: Vec<ShaderInput>,
+    audio_reactive: bool,
+    weather_reactive: bool,
+    wal_palette: bool,
+    media_art_tint: bool,
+    latitude_mul_100: i32,
+    longitude_mul_100: i32,
+    shader_particles: bool,
+    render_scale_percent: u8,
+    shader_speed_percent: u16,
+    shader_intensity_percent: u16,
+    shader_hue_degrees: u16,
+    // profiles
+    config_profiles: BTreeMap<String, ConfigProfile>,
+    config_profile: String,
+}
+
+/// Settings given on the command line, which take precedence over the config file, profiles
+/// and environment variables
+#[derive(Debug, Default)]
+pub struct Overrides {
+    /// Config file to read instead of the one in the config directory
+    pub path: Option<PathBuf>,
+    pub config_profile: Option<String>,
+    pub frame_time_ms: Option<u16>,
+}
+
+static OVERRIDES:OnceLock<Overrides> = OnceLock::new();
+/// Contents of the file when eos last changed a key in it, see [`written_by_app`]
+static WRITTEN:Mutex<Option<String>> = Mutex::new(None);
+/// Values passed to [`set`] that are not written to the file yet
+static PENDING:Mutex<BTreeMap<String, toml::Value>> = Mutex::new(BTreeMap::new());
+
+/// To be called once at startup, before the config is loaded
+pub fn set_overrides(overrides:Overrides){
+    let _ = OVERRIDES.set(overrides);
+}
+
+/// Location of the config file
+pub fn path()->Option<PathBuf>{
+    match OVERRIDES.get().and_then(|overrides| overrides.path.clone()) {
+        Some(path) => Some(path),
+        None => dirs::config_dir().map(|dir| dir.join("eos").join(FILE_NAME)),
+    }
+}
+
+/// Override `config` with the keys set in the config file, recording the current version in it
+/// if it is of an older version. On a syntax error nothing is applied and the error is returned with the line
+/// it occurred on, unknown keys and keys with a value of the wrong type are logged and skipped.
+pub fn apply(config:&mut Config)->Result<(), String>{
+    let Some(path) = path() else {return Ok(());};
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            write_template(&path);
+            return Ok(());
+        },
+        Err(err) => return Err(format!("failed to read {}: {}", path.display(), err)),
+    };
+    let mut table:toml::Table = toml::from_str(&text)
+        .map_err(|err| format!("invalid config file {}: {}", path.display(), err))?;
+    let version = match table.remove("version") {
+        None => 0,
+        Some(toml::Value::Integer(version)) => u32::try_from(version).unwrap_or(VERSION),
+        Some(value) => {
+            log::warn!("invalid version {} in {}", value, path.display());
+            VERSION
+        },
+    };
+    if version > VERSION {
+        log::warn!("{} was written by a newer version of eos, keys it does not know are ignored", path.display());
+    } else if version < VERSION {
+        let migrated = migrate(&text, version, MIGRATIONS);
+        match toml::from_str(&migrated) {
+            Ok(mut migrated_table) => {
+                migrated_table.remove("version");
+                table = migrated_table;
+                write_migrated(&path, &text, &migrated, version);
+            },
+            Err(err) => log::warn!("failed to migrate {} from version {}: {}", path.display(), version, err),
+        }
+    }
+    // settings changed in the app win over the file until they are written to it
+    for (key, value) in PENDING.lock().unwrap().iter() {
+        if let Some(old) = table.get_mut(key) {
+            *old = value.clone();
+        }
+    }
+    parse_keys(table, path.display()).apply(config);
+    Ok(())
+}
+
+/// `text` of a file of `version` with the keys renamed and filled by the `migrations` since,
+/// and the version they lead to recorded, keeping its comments
+fn migrate(text:&str, version:u32, migrations:&[Migration])->String{
+    let mut lines:Vec<String> = text.lines().map(String::from).collect();
+    let migrations = migrations.iter().skip(usize::try_from(version).unwrap_or(usize::MAX));
+    for migration in migrations.clone() {
+        let mut top_level = true;
+        for line in &mut lines {
+            let trimmed = line.trim_start();
+            top_level &= !trimmed.starts_with('[');
+            let Some((name, value)) = trimmed.split_once('=') else {continue;};
+            let renamed = migration.renamed.iter().find(|(old, _)| *old == name.trim());
+            if let Some((_, new)) = renamed.filter(|_| top_level) {
+                *line = format!("{} ={}", new, value);
+            }
+        }
+        let set:toml::Table = toml::from_str(&lines.join("\n")).unwrap_or_default();
+        for (key, value) in migration.filled.iter().filter(|(key, _)| !set.contains_key(*key)) {
+            lines.insert(first_key(&lines), format!("{} = {}", key, value));
+        }
+    }
+    let version = version + u32::try_from(migrations.count()).unwrap_or(0);
+    let mut has_version = false;
+    let mut top_level = true;
+    for line in &mut lines {
+        let trimmed = line.trim_start();
+        top_level &= !trimmed.starts_with('[');
+        if trimmed.split_once('=').is_some_and(|(name, _)| top_level && name.trim() == "version") {
+            has_version = true;
+            *line = format!("version = {}", version);
+        }
+    }
+    if !has_version {
+        lines.insert(first_key(&lines), format!("version = {}", version));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Index of the first line that is not a comment, above which keys are at the top level
+fn first_key(lines:&[String])->usize{
+    lines.iter()
+        .position(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .unwrap_or(lines.len())
+}
+
+/// Replace the file of `version` by its `migrated` text if that differs, after saving the
+/// original next to it as `eos.toml.v<version>.bak`
+fn write_migrated(path:&Path, text:&str, migrated:&str, version:u32){
+    if migrated == text {return;}
+    let backup = path.with_file_name(format!("{}.v{}.bak", FILE_NAME, version));
+    if let Err(err) = std::fs::write(&backup, text) {
+        log::warn!("failed to back up {} to {}, not migrating it: {}", path.display(), backup.display(), err);
+        return;
+    }
+    *WRITTEN.lock().unwrap() = Some(migrated.to_string());
+    match std::fs::write(path, migrated) {
+        Ok(()) => log::info!("migrated {} from version {} to {}, the original is kept as {}", path.display(), version, VERSION, backup.display()),
+        Err(err) => log::warn!("failed to migrate {}: {}", path.display(), err),
+    }
+}
+
+/// Keys of `table` that eos knows and whose values have the right type, the others are logged
+/// with where they come from and left out, so that one outdated key does not discard the rest
+fn parse_keys(table:toml::Table, source:impl std::fmt::Display)->ConfigFile{
+    let table:toml::Table = table.into_iter().filter(|(key, value)| {
+        let single = toml::Table::from_iter([(key.clone(), value.clone())]);
+        match toml::Value::Table(single).try_into::<ConfigFile>() {
+            Ok(_) => true,
+            Err(err) => {
+                log::warn!("ignoring `{}` in {}: {}", key, source, err.message());
+                false
+            },
+        }
+    }).collect();
+    toml::Value::Table(table).try_into().unwrap_or_default()
+}
+
+/// Keys set in environment variables, `EOS_` followed by the key in upper case. Values are
+/// written like in the file, and taken as a string if they are not valid TOML, like a bare path.
+fn env_keys()->ConfigFile{
+    parse_env(std::env::vars_os())
+}
+
+fn parse_env(vars:impl Iterator<Item=(OsString, OsString)>)->ConfigFile{
+    let table:toml::Table = vars
+        .filter_map(|(name, value)| {
+            let key = name.to_str()?.strip_prefix(ENV_PREFIX)?.to_ascii_lowercase();
+            let value = value.into_string().ok()?;
+            let value = toml::from_str::<toml::Table>(&format!("value = {}", value))
+                .ok()
+                .and_then(|mut table| table.remove("value"))
+                .unwrap_or(toml::Value::String(value));
+            Some((key, value))
+        })
+        .collect();
+    parse_keys(table, "the environment")
+}
+
+/// Replace the value of the top-level `key` if the config file sets it, so that a setting
+/// changed in the app is not overridden by the file. Tables are left to be edited by hand.
+/// The file is written on a thread once the setting stopped changing for [`WRITE_DELAY`],
+/// so dragging a slider writes it once.
+pub fn set<T:Serialize>(key:&str, value:&T){
+    static WRITER:OnceLock<mpsc::Sender<(String, toml::Value)>> = OnceLock::new();
+    let value = match toml::Value::try_from(value) {
+        Ok(toml::Value::Table(_)) | Err(_) => return,
+        Ok(value) => value,
+    };
+    let writer = WRITER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || write_changes(rx));
+        tx
+    });
+    PENDING.lock().unwrap().insert(key.to_string(), value.clone());
+    let _ = writer.send((key.to_string(), value));
+}
+
+/// Write the keys received from [`set`] in batches, after no more changes arrived for a while
+fn write_changes(rx:mpsc::Receiver<(String, toml::Value)>){
+    while let Ok((key, value)) = rx.recv() {
+        let mut changes = BTreeMap::from([(key, value)]);
+        while let Ok((key, value)) = rx.recv_timeout(WRITE_DELAY) {
+            changes.insert(key, value);
+        }
+        write_keys(&changes);
+        let mut pending = PENDING.lock().unwrap();
+        pending.retain(|key, value| changes.get(key) != Some(value));
+    }
+}
+
+/// Replace `changes` in the file, see [`replace_keys`]
+fn write_keys(changes:&BTreeMap<String, toml::Value>){
+    let Some(path) = path() else {return;};
+    let Ok(text) = std::fs::read_to_string(&path) else {return;};
+    let Some(text) = replace_keys(&text, changes) else {return;};
+    // recorded first, since the watcher may see the write before it returns
+    *WRITTEN.lock().unwrap() = Some(text.clone());
+    if let Err(err) = std::fs::write(&path, text) {
+        log::warn!("failed to update {}: {}", path.display(), err);
+    }
+}
+
+/// `text` with the values of the top-level keys in `changes` replaced, `None` if it sets none of them
+fn replace_keys(text:&str, changes:&BTreeMap<String, toml::Value>)->Option<String>{
+    let mut changed = false;
+    let mut top_level = true;
+    let lines:Vec<String> = text.lines().map(|line| {
+        let trimmed = line.trim_start();
+        top_level &= !trimmed.starts_with('[');
+        let change = trimmed.split_once('=')
+            .and_then(|(name, _)| changes.get_key_value(name.trim()))
+            // values spanning several lines are left alone
+            .filter(|_| top_level && toml::from_str::<toml::Table>(trimmed).is_ok());
+        match change {
+            Some((key, value)) => {
+                changed = true;
+                format!("{} = {}", key, value)
+            },
+            None => line.to_string(),
+        }
+    }).collect();
+    changed.then(|| lines.join("\n") + "\n")
+}
+
+/// Whether the file holds what eos last wrote to it, so the change it caused need not be loaded
+pub fn written_by_app()->bool{
+    let Some(path) = path() else {return false;};
+    let written = WRITTEN.lock().unwrap();
+    written.is_some() && std::fs::read_to_string(path).ok() == *written
+}
+
+/// Override `config` with the config file, the active profile, the environment and the command
+/// line and reset invalid values, logging what went wrong. Profiles are selected by whether the
+/// machine runs `on_battery`.
+pub fn load(config:&mut Config, on_battery:bool){
+    if let Err(err) = apply(config) {
+        log::warn!("{}", err);
+    }
+    let mut env = env_keys();
+    // the profile is chosen before it is applied, and the other keys override it
+    if let Some(profiles) = env.config_profiles.take() {
+        config.config_profiles = profiles;
+    }
+    if let Some(name) = env.config_profile.take() {
+        config.config_profile = name;
+    }
+    let overrides = OVERRIDES.get();
+    if let Some(name) = overrides.and_then(|overrides| overrides.config_profile.clone()) {
+        config.config_profile = name;
+    }
+    config.apply_profile(on_battery);
+    env.apply(config);
+    if let Some(frame_time_ms) = overrides.and_then(|overrides| overrides.frame_time_ms) {
+        config.frame_time_ms = frame_time_ms;
+    }
+    for err in config.validate() {
+        log::warn!("invalid config: {}", err);
+    }
+}
+
+fn write_template(path:&PathBuf){
+    let result = path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(path, TEMPLATE));
+    if let Err(err) = result {
+        log::warn!("failed to write the config file template to {}: {}", path.display(), err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text:&str)->toml::Table{
+        toml::from_str(text).unwrap()
+    }
+
+    fn env(vars:&[(&str, &str)])->ConfigFile{
+        parse_env(vars.iter().map(|(name, value)| (OsString::from(name), OsString::from(value))))
+    }
+
+    #[test]
+    fn keeps_valid_keys_and_skips_the_others(){
+        let keys = parse_keys(table("opacity = 80\nframe_time_ms = \"fast\"\nno_such_key = 1\nshader_path = \"/tmp/a.wgsl\""), "test");
+        assert_eq!(keys.opacity, Some(80));
+        assert_eq!(keys.frame_time_ms, None);
+        assert_eq!(keys.shader_path.as_deref(), Some("/tmp/a.wgsl"));
+    }
+
+    #[test]
+    fn skips_values_out_of_range(){
+        let keys = parse_keys(table("opacity = 300\nframe_time_ms = -1\nwidget_width = 99999999999"), "test");
+        assert_eq!(keys.opacity, None);
+        assert_eq!(keys.frame_time_ms, None);
+        assert_eq!(keys.widget_width, None);
+    }
+
+    #[test]
+    fn applies_only_the_keys_that_are_set(){
+        let mut config = Config::default();
+        let opacity = config.opacity;
+        parse_keys(table("frame_time_ms = 16"), "test").apply(&mut config);
+        assert_eq!(config.frame_time_ms, 16);
+        assert_eq!(config.opacity, opacity);
+    }
+
+    #[test]
+    fn reads_prefixed_environment_variables(){
+        let keys = env(&[
+            ("EOS_FRAME_TIME_MS", "16"),
+            ("EOS_SHADER_PATH", "/home/me/sea.wgsl"),
+            ("EOS_WIDGET_OUTPUTS", "[\"DP-1\", \"HDMI-A-1\"]"),
+            ("FRAME_TIME_MS", "50"),
+            ("XEOS_OPACITY", "10"),
+        ]);
+        assert_eq!(keys.frame_time_ms, Some(16));
+        // not valid TOML, so taken as a string
+        assert_eq!(keys.shader_path.as_deref(), Some("/home/me/sea.wgsl"));
+        assert_eq!(keys.widget_outputs, Some(vec!["DP-1".to_string(), "HDMI-A-1".to_string()]));
+        assert_eq!(keys.opacity, None);
+    }
+
+    #[test]
+    fn skips_malformed_environment_variables(){
+        let keys = env(&[
+            ("EOS_OPACITY", "lots"),
+            ("EOS_FRAME_TIME_MS", "70000"),
+            ("EOS_NO_SUCH_KEY", "1"),
+            ("EOS_", "1"),
+        ]);
+        assert_eq!(keys.opacity, None);
+        assert_eq!(keys.frame_time_ms, None);
+    }
+
+    #[test]
+    fn replaces_only_top_level_keys_that_are_set(){
+        let text = "# opacity = 10\n#opacity = 20\nopacity = 30\nframe_time_ms = 16\n\n[config_profiles.laptop]\nopacity = 40\n";
+        let changes = BTreeMap::from([("opacity".to_string(), toml::Value::Integer(50))]);
+        assert_eq!(
+            replace_keys(text, &changes).as_deref(),
+            Some("# opacity = 10\n#opacity = 20\nopacity = 50\nframe_time_ms = 16\n\n[config_profiles.laptop]\nopacity = 40\n"),
+        );
+    }
+
+    #[test]
+    fn leaves_files_without_the_keys_alone(){
+        let changes = BTreeMap::from([("opacity".to_string(), toml::Value::Integer(50))]);
+        assert_eq!(replace_keys("#opacity = 100\nframe_time_ms = 16\n", &changes), None);
+        // values spanning several lines are not rewritten
+        assert_eq!(replace_keys("opacity = [\n  1,\n]\n", &changes), None);
+    }
+
+    const TEST_MIGRATIONS:&[Migration] = &[
+        Migration {renamed: &[], filled: &[]},
+        Migration {renamed: &[("frame_time", "frame_time_ms")], filled: &[("audio_reactive", "false")]},
+        Migration {renamed: &[("fps_cap", "frame_time")], filled: &[("scene", "\"Plasma\"")]},
+    ];
+
+    #[test]
+    fn migrates_renames_and_fills_keys_in_order(){
+        let text = "# comment\nframe_time = 16\nscene = \"Earth\"\n\n[key_bindings]\nframe_time = \"x\"\n";
+        assert_eq!(
+            migrate(text, 0, TEST_MIGRATIONS),
+            "# comment\nversion = 3\naudio_reactive = false\nframe_time_ms = 16\nscene = \"Earth\"\n\n[key_bindings]\nframe_time = \"x\"\n",
+        );
+        // only the migrations since the version of the file apply
+        assert_eq!(
+            migrate("version = 2\nfps_cap = 30\n", 2, TEST_MIGRATIONS),
+            "scene = \"Plasma\"\nversion = 3\nframe_time = 30\n",
+        );
+    }
+
+    #[test]
+    fn migrating_a_current_file_changes_nothing(){
+        let text = "# comment\nversion = 3\nframe_time_ms = 16\n";
+        assert_eq!(migrate(text, 3, TEST_MIGRATIONS), text);
+        assert_eq!(migrate(TEMPLATE, VERSION, MIGRATIONS), TEMPLATE);
+    }
+
+    #[test]
+    fn every_key_of_the_template_is_known(){
+        // uncomment the top-level keys and the lines of their values, leaving out the example tables
+        let mut text = String::new();
+        let mut in_table = false;
+        for line in TEMPLATE.lines() {
+            if line.starts_with("#[") {
+                in_table = true;
+            } else if line.is_empty() || line.starts_with("# ") {
+                in_table = false;
+            }
+            match line.strip_prefix('#') {
+                Some(key) if !in_table && (key.starts_with(|c:char| c.is_ascii_lowercase() || c == ']') || key.starts_with("  ")) => {
+                    text += key;
+                },
+                _ => text += line,
+            }
+            text.push('\n');
+        }
+        let mut keys = table(&text);
+        assert_eq!(keys.remove("version"), Some(toml::Value::Integer(VERSION.into())));
+        assert!(keys.len() > 100);
+        for (key, value) in keys {
+            let single = toml::Table::from_iter([(key.clone(), value)]);
+            if let Err(err) = toml::Value::Table(single).try_into::<ConfigFile>() {
+                panic!("`{}` in the template: {}", key, err);
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Optional session D-Bus service `org.eos.Monitor` for scripts and other desktop tools. The
+//! object `/org/eos/Monitor` implements `org.eos.Monitor1`, with the current metrics, whether
+//! the monitor is paused, the scene and the page as properties, and methods switching the page
+//! and the scene, pausing the monitor and refreshing it.
+//!
+//! The app pushes a [`Status`] through a [`Handle`] after every poll, which the properties
+//! read, and the methods are forwarded to the app as messages. The properties do not announce
+//! their changes, as the metrics change on every poll, so they are read when needed:
+//!
+//! ```sh
+//! busctl --user get-property org.eos.Monitor /org/eos/Monitor org.eos.Monitor1 Metrics
+//! busctl --user call org.eos.Monitor /org/eos/Monitor org.eos.Monitor1 SetScene s Plasma
+//! ```
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use cosmic::iced::futures::channel::mpsc::Sender;
+use cosmic::iced::{futures::SinkExt, stream, Subscription};
+use zbus::{fdo, interface};
+
+use crate::config::{MonitorPage, Scene};
+use crate::Message;
+
+const NAME:&str = "org.eos.Monitor";
+const PATH:&str = "/org/eos/Monitor";
+
+/// What the properties show
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Status {
+    /// Metrics by name like `cpu_avg`, in percent or °C, leaving out those that are unknown
+    pub metrics: Vec<(&'static str, f32)>,
+    pub paused: bool,
+    pub scene: Scene,
+    pub page: MonitorPage,
+}
+
+struct Monitor {
+    status: Arc<Mutex<Status>>,
+    output: Sender<Message>,
+}
+
+impl Monitor {
+    fn status(&self)->Status{
+        self.status.lock().map(|status| status.clone()).unwrap_or_default()
+    }
+
+    async fn send(&self, message:Message)->fdo::Result<()>{
+        self.output.clone().send(message).await.map_err(|_| fdo::Error::Failed("eos is closing".to_string()))
+    }
+}
+
+#[interface(name = "org.eos.Monitor1")]
+impl Monitor {
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn metrics(&self)->HashMap<String, f64>{
+        self.status().metrics.into_iter().map(|(name, value)| (name.to_string(), value.into())).collect()
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn paused(&self)->bool{
+        self.status().paused
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn scene(&self)->String{
+        format!("{:?}", self.status().scene)
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn page(&self)->String{
+        format!("{:?}", self.status().page)
+    }
+
+    /// Show the page `Overview`, `Processes` or `Cores`
+    async fn set_page(&self, page:String)->fdo::Result<()>{
+        let page = MonitorPage::ALL.into_iter().find(|known| format!("{:?}", known).eq_ignore_ascii_case(&page))
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown page {:?}", page)))?;
+        self.send(Message::MonitorPage(page)).await
+    }
+
+    /// Show a built-in scene like `Plasma`
+    async fn set_scene(&self, scene:String)->fdo::Result<()>{
+        let index = Scene::ALL.iter().position(|known| format!("{:?}", known).eq_ignore_ascii_case(&scene))
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("unknown scene {:?}", scene)))?;
+        self.send(Message::Scene(index)).await
+    }
+
+    async fn next_scene(&self)->fdo::Result<()>{
+        self.send(Message::SceneNext).await
+    }
+
+    /// Suspend or resume polling
+    async fn set_paused(&self, paused:bool)->fdo::Result<()>{
+        if paused == self.status().paused {return Ok(());}
+        self.send(Message::PauseUpdates).await
+    }
+
+    /// Poll the memory and the processes right away
+    async fn refresh(&self)->fdo::Result<()>{
+        self.send(Message::Refresh).await
+    }
+}
+
+/// Sends the status to the service
+#[derive(Clone)]
+pub struct Handle(Arc<Mutex<Status>>);
+
+impl Handle {
+    pub fn update(&self, status:Status){
+        if let Ok(mut current) = self.0.lock() {
+            *current = status;
+        }
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>)->fmt::Result{
+        f.write_str("dbus::Handle")
+    }
+}
+
+/// Publish the service, sending `Message::DBusReady` with its handle and then the messages of
+/// the methods called. The name is released when the subscription ends.
+pub fn listen()->Subscription<Message>{
+    struct DBusSubscription;
+    Subscription::run_with_id(
+        TypeId::of::<DBusSubscription>(),
+        stream::channel(16, |mut output| async move {
+            let status = Arc::new(Mutex::new(Status::default()));
+            let monitor = Monitor { status: status.clone(), output: output.clone() };
+            let connection = async {
+                zbus::connection::Builder::session()?
+                    .name(NAME)?
+                    .serve_at(PATH, monitor)?
+                    .build()
+                    .await
+            }.await;
+            // kept until the subscription is dropped
+            let _connection = match connection {
+                Ok(connection) => {
+                    let _ = output.send(Message::DBusReady(Handle(status))).await;
+                    Some(connection)
+                },
+                Err(err) => {
+                    log::warn!("failed to publish {} on the session bus: {}", NAME, err);
+                    None
+                },
+            };
+            std::future::pending().await
+        }),
+    )
+}
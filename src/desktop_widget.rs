@@ -0,0 +1,235 @@
+//! Desktop widget mode: instead of a regular window, the resource monitor and the shader are
+//! drawn on a wlr-layer-shell surface on the background or bottom layer, like conky.
+//! The surface is placed by `Config::widget_anchor`, `widget_margin` and its size.
+//!
+//! With `Config::widget_outputs` a surface is opened on every listed output, following the
+//! outputs as they are connected and disconnected. While none of them is connected, the
+//! widget is shown on the active output instead, so it never disappears with a monitor. That
+//! surface is opened again as outputs come and go, to follow the output that is active.
+//!
+//! With `Config::widget_click_through` the pointer passes through the widgets to the desktop
+//! below. `eos --toggle-widget-input` makes them take the pointer again until it is repeated,
+//! e.g. to scroll the process list.
+
+use cosmic::app::Task;
+use cosmic::iced::window;
+#[cfg(feature = "wayland")]
+use cosmic::cctk::wayland_client::protocol::wl_output::WlOutput;
+#[cfg(feature = "wayland")]
+use cosmic::iced::event::wayland::OutputEvent;
+#[cfg(feature = "wayland")]
+use cosmic::iced::platform_specific::{
+    runtime::wayland::layer_surface::{IcedMargin, IcedOutput, SctkLayerSurfaceSettings},
+    shell::commands::layer_surface::{
+        destroy_layer_surface, get_layer_surface, Anchor, KeyboardInteractivity, Layer,
+    },
+};
+
+#[cfg(feature = "wayland")]
+use crate::config::WidgetAnchor;
+use crate::config::{Config, WidgetLayer};
+use crate::Message;
+
+/// Namespace compositors may match the surface by, e.g. to blur it
+#[cfg(feature = "wayland")]
+const NAMESPACE:&str = "eos";
+
+/// Entry of `Config::widget_outputs` that puts a widget on every output
+#[cfg(feature = "wayland")]
+const ALL_OUTPUTS:&str = "*";
+
+/// Whether eos runs as a desktop widget, which the build has to support
+pub fn enabled(config:&Config)->bool{
+    config.widget_layer != WidgetLayer::Window && cfg!(feature = "wayland")
+}
+
+/// Edges of the output a layer surface is attached to
+#[cfg(feature = "wayland")]
+pub fn anchor(anchor:WidgetAnchor)->Anchor{
+    match anchor {
+        WidgetAnchor::Fill => Anchor::TOP | Anchor::BOTTOM | Anchor::LEFT | Anchor::RIGHT,
+        WidgetAnchor::Top => Anchor::TOP,
+        WidgetAnchor::Bottom => Anchor::BOTTOM,
+        WidgetAnchor::Left => Anchor::LEFT,
+        WidgetAnchor::Right => Anchor::RIGHT,
+        WidgetAnchor::TopLeft => Anchor::TOP | Anchor::LEFT,
+        WidgetAnchor::TopRight => Anchor::TOP | Anchor::RIGHT,
+        WidgetAnchor::BottomLeft => Anchor::BOTTOM | Anchor::LEFT,
+        WidgetAnchor::BottomRight => Anchor::BOTTOM | Anchor::RIGHT,
+        WidgetAnchor::Center => Anchor::empty(),
+    }
+}
+
+/// Whether the output of this name should show a widget
+#[cfg(feature = "wayland")]
+fn targeted(config:&Config, name:&str)->bool{
+    config.widget_outputs.iter().any(|output| output == ALL_OUTPUTS || output == name)
+}
+
+/// Layer surfaces of the desktop widget and the outputs they are shown on
+#[derive(Debug, Default)]
+pub struct DesktopWidgets {
+    /// Surfaces by the name of their output, `None` for the one on the active output
+    surfaces: Vec<(window::Id, Option<String>)>,
+    /// Connected outputs by name
+    #[cfg(feature = "wayland")]
+    outputs: Vec<(String, WlOutput)>,
+    /// Whether the surfaces were created without an input region
+    click_through: bool,
+    /// Interaction was re-enabled from the command line despite `Config::widget_click_through`
+    interactive: bool,
+}
+
+impl DesktopWidgets {
+    /// Open the widget on the active output if eos runs as one. Surfaces on the outputs in
+    /// `Config::widget_outputs` replace it as the outputs are announced.
+    pub fn open(config:&Config)->Option<(Self, Task<Message>)>{
+        if !enabled(config) {
+            #[cfg(not(feature = "wayland"))]
+            if config.widget_layer != WidgetLayer::Window {
+                log::warn!("eos was built without Wayland support, so it cannot be shown as a desktop widget");
+            }
+            return None;
+        }
+        let mut widgets = Self { click_through: config.widget_click_through, ..Self::default() };
+        let task = widgets.open_surface(config, None);
+        Some((widgets, task))
+    }
+
+    /// Whether the window is one of the widget's surfaces, which `view_window` draws
+    pub fn contains(&self, id:window::Id)->bool{
+        self.surfaces.iter().any(|(surface, _)| *surface == id)
+    }
+
+    /// Recreate the surfaces if they should start or stop passing the pointer through, which
+    /// cannot be changed on an existing surface
+    pub fn apply_config(&mut self, config:&Config)->Task<Message>{
+        let click_through = config.widget_click_through && !self.interactive;
+        if click_through == self.click_through {
+            return Task::none();
+        }
+        self.click_through = click_through;
+        self.reopen(config)
+    }
+
+    /// Temporarily take the pointer despite `Config::widget_click_through`, or stop doing so
+    pub fn toggle_interaction(&mut self, config:&Config)->Task<Message>{
+        self.interactive = !self.interactive;
+        self.apply_config(config)
+    }
+
+    /// Close all surfaces and open them again on the same outputs
+    #[cfg(feature = "wayland")]
+    fn reopen(&mut self, config:&Config)->Task<Message>{
+        let outputs:Vec<Option<String>> = self.surfaces.iter().map(|(_, on)| on.clone()).collect();
+        let close = self.close_surfaces(|_| true);
+        let open:Vec<_> = outputs.into_iter().map(|name| {
+            let output = name.and_then(|name| self.outputs.iter().find(|(known, _)| *known == name).cloned());
+            self.open_surface(config, output)
+        }).collect();
+        Task::batch(std::iter::once(close).chain(open))
+    }
+
+    /// Open the widget on the active output again, if it is shown there, so the compositor
+    /// picks the output anew
+    #[cfg(feature = "wayland")]
+    fn reopen_on_active(&mut self, config:&Config)->Task<Message>{
+        if !self.surfaces.iter().any(|(_, on)| on.is_none()) {
+            return Task::none();
+        }
+        let close = self.close_surfaces(|on| on.is_none());
+        Task::batch([close, self.open_surface(config, None)])
+    }
+
+    #[cfg(not(feature = "wayland"))]
+    fn reopen(&mut self, _config:&Config)->Task<Message>{
+        Task::none()
+    }
+
+    /// Open a widget on a newly connected output it targets and close the one on the active
+    /// output, or move the widgets of a disconnected output to the active output if no other
+    /// targeted output is left. A widget on the active output is opened again whenever an
+    /// output is connected or disconnected, since which output is active may have changed.
+    #[cfg(feature = "wayland")]
+    pub fn output_event(&mut self, config:&Config, event:OutputEvent, output:WlOutput)->Task<Message>{
+        match event {
+            OutputEvent::Created(Some(info)) | OutputEvent::InfoUpdate(info) => {
+                let Some(name) = info.name else { return Task::none() };
+                let connected = !self.outputs.iter().any(|(_, known)| *known == output);
+                self.outputs.retain(|(_, known)| *known != output);
+                self.outputs.push((name.clone(), output.clone()));
+                let shown = self.surfaces.iter().any(|(_, on)| on.as_deref() == Some(name.as_str()));
+                if shown || !targeted(config, &name) {
+                    return if connected {self.reopen_on_active(config)} else {Task::none()};
+                }
+                log::info!("showing the desktop widget on output {}", name);
+                let open = self.open_surface(config, Some((name, output)));
+                Task::batch([open, self.close_surfaces(|on| on.is_none())])
+            },
+            OutputEvent::Created(None) => Task::none(),
+            OutputEvent::Removed => {
+                let Some(index) = self.outputs.iter().position(|(_, known)| *known == output) else {
+                    return Task::none();
+                };
+                let (name, _) = self.outputs.remove(index);
+                let close = self.close_surfaces(|on| on.as_deref() == Some(name.as_str()));
+                if !self.surfaces.is_empty() {
+                    return Task::batch([close, self.reopen_on_active(config)]);
+                }
+                log::info!("output {} was disconnected, showing the desktop widget on the active output", name);
+                Task::batch([close, self.open_surface(config, None)])
+            },
+        }
+    }
+
+    /// Create a layer surface on the named output or on the active one
+    #[cfg(feature = "wayland")]
+    fn open_surface(&mut self, config:&Config, output:Option<(String, WlOutput)>)->Task<Message>{
+        let id = window::Id::unique();
+        let layer = match config.widget_layer {
+            WidgetLayer::Background => Layer::Background,
+            WidgetLayer::Bottom | WidgetLayer::Window => Layer::Bottom,
+        };
+        // a size of 0 leaves the side to the content, a filling widget takes the whole output
+        let side = |size:u16| (size > 0).then_some(u32::from(size));
+        let size = match config.widget_anchor {
+            WidgetAnchor::Fill => None,
+            _ => Some((side(config.widget_width), side(config.widget_height))),
+        };
+        let margin = i32::from(config.widget_margin);
+        let (name, output) = match output {
+            Some((name, output)) => (Some(name), IcedOutput::Output(output)),
+            None => (None, IcedOutput::Active),
+        };
+        self.surfaces.push((id, name));
+        get_layer_surface(SctkLayerSurfaceSettings {
+            id,
+            layer,
+            // the widget stays out of the way of the windows and never takes the keyboard
+            keyboard_interactivity: KeyboardInteractivity::None,
+            pointer_interactivity: !self.click_through,
+            anchor: anchor(config.widget_anchor),
+            output,
+            namespace: NAMESPACE.to_string(),
+            margin: IcedMargin { top: margin, right: margin, bottom: margin, left: margin },
+            size,
+            exclusive_zone: -1,
+            ..Default::default()
+        })
+    }
+
+    #[cfg(not(feature = "wayland"))]
+    fn open_surface(&mut self, _config:&Config, _output:Option<()>)->Task<Message>{
+        Task::none()
+    }
+
+    /// Destroy the surfaces whose output matches
+    #[cfg(feature = "wayland")]
+    fn close_surfaces(&mut self, on:impl Fn(Option<&str>)->bool)->Task<Message>{
+        let (closed, kept):(Vec<_>, Vec<_>) = std::mem::take(&mut self.surfaces)
+            .into_iter()
+            .partition(|(_, output)| on(output.as_deref()));
+        self.surfaces = kept;
+        Task::batch(closed.into_iter().map(|(id, _)| destroy_layer_surface(id)))
+    }
+}
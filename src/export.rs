@@ -0,0 +1,174 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use image::{Rgba, RgbaImage};
+
+use crate::{history::History, metric_store::Metric};
+
+/// Size of a single graph panel in the exported PNG, in pixels
+const PANEL_WIDTH: u32 = 640;
+const PANEL_HEIGHT: u32 = 120;
+const PANEL_MARGIN: u32 = 8;
+
+/// Equally spaced samples of several metrics, most recent sample first
+pub struct Samples<'a> {
+    pub interval: Duration,
+    pub series: Vec<(Metric, &'a History<f32>)>,
+}
+
+/// A graph to render into the exported PNG, most recent sample first
+pub struct Plot {
+    pub data: Vec<f32>,
+    pub range: (f32, f32),
+    pub colour: [u8; 4],
+}
+
+/// Directory the CSV and JSON dumps are written to
+pub fn data_dir() -> io::Result<PathBuf> {
+    dirs::data_dir()
+        .map(|dir| dir.join("eos").join("exports"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))
+}
+
+/// Directory the rendered graphs are written to, `~/Pictures` or the home directory as a fallback
+pub fn picture_dir() -> io::Result<PathBuf> {
+    dirs::picture_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no picture directory"))
+}
+
+/// Common file name stem of one export, e.g. `eos-metrics-2025-01-31_14-03-59`
+pub fn file_stem(now: DateTime<Local>) -> String {
+    format!("eos-metrics-{}", now.format("%Y-%m-%d_%H-%M-%S"))
+}
+
+/// File name of a saved background, e.g. `eos-background-2025-01-31_14-03-59.png`
+pub fn background_file_name(now: DateTime<Local>) -> String {
+    format!("eos-background-{}.png", now.format("%Y-%m-%d_%H-%M-%S"))
+}
+
+/// Timestamp of the sample `i` steps before `now`
+fn timestamp(now: DateTime<Local>, interval: Duration, i: usize) -> String {
+    let ago = chrono::Duration::from_std(interval * i as u32).unwrap_or_default();
+    (now - ago).to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
+}
+
+/// Format a value, leaving cells of missing or invalid samples empty
+fn cell(value: Option<f32>) -> String {
+    match value {
+        Some(v) if v.is_finite() => format!("{:.2}", v),
+        _ => String::new(),
+    }
+}
+
+/// Write one row per sample, oldest first. The series are aligned at their most
+/// recent sample, shorter ones leave their older cells empty.
+pub fn write_csv(path: &Path, now: DateTime<Local>, samples: &Samples) -> io::Result<()> {
+    let rows = samples.series.iter().map(|(_, h)| h.len()).max().unwrap_or(0);
+    let mut csv = String::from("timestamp");
+    for (metric, _) in &samples.series {
+        let _ = write!(csv, ",{}", metric.key());
+    }
+    csv.push('\n');
+    let columns: Vec<Vec<f32>> = samples.series.iter().map(|(_, h)| h.iter().collect()).collect();
+    for i in (0..rows).rev() {
+        csv.push_str(&timestamp(now, samples.interval, i));
+        for column in &columns {
+            let _ = write!(csv, ",{}", cell(column.get(i).copied()));
+        }
+        csv.push('\n');
+    }
+    fs::write(path, csv)
+}
+
+/// JSON object of one set of samples, each series as an array of values, oldest first
+fn json_samples(json: &mut String, now: DateTime<Local>, samples: &Samples) {
+    let _ = write!(
+        json,
+        "{{\"interval_ms\":{},\"end\":\"{}\",\"series\":{{",
+        samples.interval.as_millis(),
+        timestamp(now, samples.interval, 0),
+    );
+    for (n, (metric, history)) in samples.series.iter().enumerate() {
+        if n > 0 {json.push(',');}
+        let _ = write!(json, "\"{}\":[", metric.key());
+        for (i, v) in history.iter().rev().enumerate() {
+            if i > 0 {json.push(',');}
+            match v.is_finite() {
+                true => {let _ = write!(json, "{}", v);},
+                false => json.push_str("null"),
+            }
+        }
+        json.push(']');
+    }
+    json.push_str("}}");
+}
+
+/// Write the live and long-term samples into a single JSON document
+pub fn write_json(path: &Path, now: DateTime<Local>, live: &Samples, history: &Samples) -> io::Result<()> {
+    let mut json = String::new();
+    let _ = write!(json, "{{\"exported_at\":\"{}\",\"live\":", now.to_rfc3339());
+    json_samples(&mut json, now, live);
+    json.push_str(",\"history\":");
+    json_samples(&mut json, now, history);
+    json.push_str("}\n");
+    fs::write(path, json)
+}
+
+/// Blend `colour` onto the pixel at `(x, y)` with the given coverage
+fn blend(img: &mut RgbaImage, x: u32, y: u32, colour: [u8; 4], coverage: f32) {
+    if x >= img.width() || y >= img.height() {return;}
+    let a = coverage.clamp(0., 1.) * colour[3] as f32 / 255.;
+    let Rgba(dst) = img.get_pixel_mut(x, y);
+    for c in 0..3 {
+        dst[c] = (dst[c] as f32 * (1. - a) + colour[c] as f32 * a).round() as u8;
+    }
+}
+
+/// Render the plots as stacked area charts, most recent sample on the left like in the monitor
+pub fn write_png(path: &Path, plots: &[Plot], background: [u8; 4]) -> image::ImageResult<()> {
+    let count = plots.len().max(1) as u32;
+    let mut img = RgbaImage::from_pixel(
+        PANEL_WIDTH + 2 * PANEL_MARGIN,
+        count * (PANEL_HEIGHT + PANEL_MARGIN) + PANEL_MARGIN,
+        Rgba(background),
+    );
+    for (n, plot) in plots.iter().enumerate() {
+        let top = PANEL_MARGIN + n as u32 * (PANEL_HEIGHT + PANEL_MARGIN);
+        let (lo, hi) = plot.range;
+        // faint frame and quarter grid lines
+        for q in 0..=4 {
+            let y = top + q * (PANEL_HEIGHT - 1) / 4;
+            for x in 0..PANEL_WIDTH {
+                blend(&mut img, PANEL_MARGIN + x, y, plot.colour, 0.15);
+            }
+        }
+        if plot.data.is_empty() || hi <= lo {continue;}
+        let height = |x: u32| {
+            let t = x as f32 * (plot.data.len() - 1) as f32 / (PANEL_WIDTH - 1) as f32;
+            let (i0, i1) = (t.floor() as usize, (t.ceil() as usize).min(plot.data.len() - 1));
+            let v = plot.data[i0] + (plot.data[i1] - plot.data[i0]) * t.fract();
+            ((v - lo) / (hi - lo)).clamp(0., 1.) * (PANEL_HEIGHT - 1) as f32
+        };
+        let mut previous = height(0);
+        for x in 0..PANEL_WIDTH {
+            let h = height(x);
+            // area below the line, then the line itself spanning the step from the previous column
+            for y in 0..h as u32 {
+                blend(&mut img, PANEL_MARGIN + x, top + PANEL_HEIGHT - 1 - y, plot.colour, 0.25);
+            }
+            let (from, to) = (previous.min(h), previous.max(h));
+            for y in from.floor() as u32..=to.ceil() as u32 {
+                blend(&mut img, PANEL_MARGIN + x, top + PANEL_HEIGHT - 1 - y.min(PANEL_HEIGHT - 1), plot.colour, 1.);
+            }
+            previous = h;
+        }
+    }
+    img.save(path)
+}
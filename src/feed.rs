@@ -0,0 +1,232 @@
+//! Headlines of the RSS and Atom feeds in `Config::feeds`, cycled through in
+//! [`crate::config::Section::Headlines`]. Feeds are fetched through `curl` on a background
+//! thread every `Config::feed_refresh_minutes` and cached, so the headlines stay known while
+//! offline. Only the few elements of a feed that are shown are read, without a full XML parser.
+
+use std::any::TypeId;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use tokio::sync::mpsc;
+
+use crate::{util, Message};
+
+/// Item of a feed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Headline {
+    pub title: String,
+    /// Page of the item, opened in the browser when the headline is clicked
+    pub link: String,
+    /// Title of the feed
+    pub source: String,
+    pub published: Option<DateTime<Utc>>,
+}
+
+/// Attributes and content of each element called `name`, not looking into nested ones of the
+/// same name
+fn elements<'a>(xml:&'a str, name:&str)->Vec<(&'a str, &'a str)>{
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // a longer name like <items> or <title:x> is another element
+        if !after.starts_with(|c:char| c == '>' || c == '/' || c.is_whitespace()) {
+            rest = after;
+            continue;
+        }
+        let Some(end) = after.find('>') else {break};
+        let attributes = &after[..end];
+        if let Some(attributes) = attributes.strip_suffix('/') {
+            found.push((attributes, ""));
+            rest = &after[end + 1..];
+            continue;
+        }
+        let content = &after[end + 1..];
+        let Some(length) = content.find(&close) else {break};
+        found.push((attributes, &content[..length]));
+        rest = &content[length + close.len()..];
+    }
+    found
+}
+
+/// Value of an attribute in the attributes of a tag
+fn attribute<'a>(attributes:&'a str, key:&str)->Option<&'a str>{
+    attributes.split_whitespace().find_map(|pair| {
+        let value = pair.strip_prefix(key)?.strip_prefix('=')?;
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        value[1..].split(quote).next()
+    })
+}
+
+/// Replace the character and entity references of XML and common HTML
+fn unescape(text:&str)->String{
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';').filter(|end| *end <= 10) else {
+            unescaped.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let reference = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            number => number.strip_prefix("#x").or_else(|| number.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| number.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match reference {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Plain text of an element's content: the contents of CDATA sections are taken as they are,
+/// escaped text is unescaped, and tags of HTML titles are left out with the whitespace collapsed
+fn text(content:&str)->String{
+    let mut raw = String::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<![CDATA[") {
+        raw.push_str(&unescape(&rest[..start]));
+        rest = &rest[start + 9..];
+        let end = rest.find("]]>").unwrap_or(rest.len());
+        raw.push_str(&rest[..end]);
+        rest = rest.get(end + 3..).unwrap_or("");
+    }
+    raw.push_str(&unescape(rest));
+    let mut plain = String::new();
+    let mut in_tag = false;
+    for c in raw.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => (),
+        }
+    }
+    plain.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Content of the first element called `name` as text
+fn child(xml:&str, name:&str)->Option<String>{
+    elements(xml, name).first().map(|(_, content)| text(content)).filter(|text| !text.is_empty())
+}
+
+/// Headlines of an RSS or Atom feed in the order they are listed
+pub fn parse(xml:&str)->Vec<Headline>{
+    let first_item = ["<item", "<entry"].iter().filter_map(|tag| xml.find(tag)).min().unwrap_or(xml.len());
+    let source = child(&xml[..first_item], "title").unwrap_or_default();
+    let date = |item:&str, names:&[&str]| names.iter().find_map(|name| {
+        let date = child(item, name)?;
+        DateTime::parse_from_rfc2822(&date).or_else(|_| DateTime::parse_from_rfc3339(&date)).ok()
+    }).map(|date| date.with_timezone(&Utc));
+    let rss = elements(xml, "item").into_iter().filter_map(|(_, item)| Some(Headline {
+        title: child(item, "title")?,
+        link: child(item, "link").or_else(|| child(item, "guid")).unwrap_or_default(),
+        source: source.clone(),
+        published: date(item, &["pubDate", "dc:date"]),
+    }));
+    let atom = elements(xml, "entry").into_iter().filter_map(|(_, entry)| Some(Headline {
+        title: child(entry, "title")?,
+        // the alternate link is the page of the entry, others point at comments or media
+        link: elements(entry, "link").into_iter()
+            .filter(|(attributes, _)| attribute(attributes, "rel").map_or(true, |rel| rel == "alternate"))
+            .find_map(|(attributes, _)| attribute(attributes, "href"))
+            .map(unescape)
+            .unwrap_or_default(),
+        source: source.clone(),
+        published: date(entry, &["published", "updated"]),
+    }));
+    rss.chain(atom).collect()
+}
+
+/// XML of a feed at an http or https URL, or in a file
+fn read(url:&str)->Result<String, String>{
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        let path = util::expand(url);
+        return fs::read_to_string(&path).map_err(|err| format!("failed to read {}: {}", path.display(), err));
+    }
+    let output = Command::new("curl")
+        .args(["--silent", "--fail", "--location", "--compressed", "--max-time", "30", url])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run curl: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("curl exited with {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// File the last XML of a feed is kept in
+fn cache_path(url:&str)->Option<PathBuf>{
+    Some(dirs::cache_dir()?.join("eos").join("feeds").join(format!("{}.xml", util::cache_name(url))))
+}
+
+/// XML of a feed, from the cache if it cannot be read now
+fn fetch(url:&str)->Option<String>{
+    let cache = cache_path(url);
+    match read(url) {
+        Ok(xml) => {
+            if let Some(cache) = &cache {
+                let written = cache.parent().map_or(Ok(()), fs::create_dir_all).and_then(|()| fs::write(cache, &xml));
+                if let Err(err) = written {
+                    log::warn!("failed to cache the feed {}: {}", url, err);
+                }
+            }
+            Some(xml)
+        },
+        Err(err) => {
+            log::warn!("failed to fetch the feed {}, using the cached headlines: {}", url, err);
+            fs::read_to_string(cache?).ok()
+        },
+    }
+}
+
+/// Emits the headlines of the feeds every `refresh_minutes`, the newest first and those
+/// without a date after them, see [`Message::Headlines`]
+pub fn listen(feeds:Vec<String>, refresh_minutes:u16)->Subscription<Message>{
+    struct FeedSubscription;
+    Subscription::run_with_id(
+        (TypeId::of::<FeedSubscription>(), feeds.clone(), refresh_minutes),
+        stream::channel(1, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            std::thread::spawn(move || loop {
+                let mut headlines:Vec<Headline> = feeds.iter()
+                    .filter_map(|url| fetch(url))
+                    .flat_map(|xml| parse(&xml))
+                    .collect();
+                // stable, so undated items keep the order of their feed
+                headlines.sort_by(|a, b| b.published.cmp(&a.published));
+                if tx.send(headlines).is_err() {break;}
+                std::thread::sleep(Duration::from_secs(60 * u64::from(refresh_minutes)));
+            });
+            while let Some(headlines) = rx.recv().await {
+                let _ = output.send(Message::Headlines(headlines)).await;
+            }
+        }),
+    )
+}
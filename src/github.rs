@@ -0,0 +1,219 @@
+//! Unread GitHub notifications and the latest workflow run of each repository in
+//! `Config::github_repos`, polled through the REST API with `curl` every
+//! `Config::github_refresh_minutes`. The token is read from `Config::github_token_file` and
+//! handed to `curl` on its standard input, so it does not show up in the process list.
+//! Notifications need a token, public repositories do not.
+
+use std::any::TypeId;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use cosmic::iced::Subscription;
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::{util, worker, Message};
+
+const API:&str = "https://api.github.com";
+/// Notifications counted at most, more are shown as this many and a plus
+pub const MAX_NOTIFICATIONS:usize = 50;
+
+/// Outcome of a workflow run, or that it has not finished yet
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RunState {
+    Pending,
+    Success,
+    Failure,
+    Cancelled,
+}
+
+/// Latest workflow run of a repository
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    pub workflow: String,
+    pub branch: String,
+    pub state: RunState,
+    /// Page of the run, opened in the browser when it is clicked
+    pub url: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Status {
+    /// Unread notifications up to [`MAX_NOTIFICATIONS`], `None` without a token
+    pub notifications: Option<usize>,
+    /// Repository as `owner/name` and its latest run, `None` while it is unknown
+    pub runs: Vec<(String, Option<Run>)>,
+    /// The last poll failed for some of the above, which are from an earlier one
+    pub offline: bool,
+}
+
+/// Response listing the workflow runs of a repository, the latest first
+#[derive(Deserialize)]
+struct WorkflowRuns {
+    workflow_runs: Vec<WorkflowRun>,
+}
+
+#[derive(Deserialize)]
+struct WorkflowRun {
+    name: Option<String>,
+    head_branch: Option<String>,
+    /// `queued`, `in_progress`, `completed` and the like
+    status: Option<String>,
+    /// Outcome once the run is completed
+    conclusion: Option<String>,
+    html_url: Option<String>,
+}
+
+/// Entry of the notifications of the user
+#[derive(Deserialize)]
+struct Notification {
+    unread: bool,
+}
+
+/// Body of a GET request to the API, authorized with `token` if there is one
+fn get(path:&str, token:Option<&str>)->Result<String, String>{
+    let mut child = Command::new("curl")
+        .args(["--silent", "--fail", "--location", "--max-time", "30"])
+        .args(["--header", "Accept: application/vnd.github+json"])
+        .args(["--header", "X-GitHub-Api-Version: 2022-11-28"])
+        .args(["--header", "@-", &format!("{}{}", API, path)])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("failed to run curl: {}", err))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let header = token.map(|token| format!("Authorization: Bearer {}\n", token)).unwrap_or_default();
+        stdin.write_all(header.as_bytes()).map_err(|err| format!("failed to pass the token to curl: {}", err))?;
+    }
+    let output = child.wait_with_output().map_err(|err| format!("failed to run curl: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("curl exited with {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Latest workflow run of a repository given as `owner/name`, `None` if it has none
+fn latest_run(repo:&str, token:Option<&str>)->Result<Option<Run>, String>{
+    let json = get(&format!("/repos/{}/actions/runs?per_page=1", repo), token)?;
+    parse_runs(&json, repo)
+}
+
+/// Latest run of the workflow runs of `repo` in `json`
+fn parse_runs(json:&str, repo:&str)->Result<Option<Run>, String>{
+    let runs:WorkflowRuns = serde_json::from_str(json).map_err(|err| format!("invalid workflow runs: {}", err))?;
+    Ok(runs.workflow_runs.into_iter().next().map(|run| {
+        let state = match (run.status.as_deref(), run.conclusion.as_deref()) {
+            (Some("completed"), Some("success" | "neutral" | "skipped")) => RunState::Success,
+            (Some("completed"), Some("cancelled" | "stale")) => RunState::Cancelled,
+            (Some("completed"), _) => RunState::Failure,
+            _ => RunState::Pending,
+        };
+        Run {
+            workflow: run.name.unwrap_or_default(),
+            branch: run.head_branch.unwrap_or_default(),
+            state,
+            url: run.html_url.unwrap_or_else(|| format!("https://github.com/{}/actions", repo)),
+        }
+    }))
+}
+
+/// Number of unread notifications in `json`
+fn count_unread(json:&str)->Result<usize, String>{
+    let notifications:Vec<Notification> = serde_json::from_str(json).map_err(|err| format!("invalid notifications: {}", err))?;
+    Ok(notifications.iter().filter(|notification| notification.unread).count())
+}
+
+/// Token in the first line of a file, `~/` standing for the home directory
+fn read_token(path:&str)->Option<String>{
+    if path.is_empty() {return None;}
+    let path = util::expand(path);
+    match std::fs::read_to_string(&path) {
+        Ok(token) => token.lines().next().map(str::trim).filter(|token| !token.is_empty()).map(str::to_string),
+        Err(err) => {
+            log::warn!("failed to read the GitHub token from {}: {}", path.display(), err);
+            None
+        },
+    }
+}
+
+/// Poll the notifications and runs, keeping what an earlier poll found where this one fails
+fn poll(repos:&[String], token:Option<&str>, last:&Status)->Status{
+    let mut offline = false;
+    let notifications = token.and_then(|token| {
+        match get(&format!("/notifications?per_page={}", MAX_NOTIFICATIONS), Some(token)).and_then(|json| count_unread(&json)) {
+            Ok(unread) => Some(unread),
+            Err(err) => {
+                log::warn!("failed to fetch the GitHub notifications: {}", err);
+                offline = true;
+                last.notifications
+            },
+        }
+    });
+    let runs = repos.iter().map(|repo| {
+        let run = latest_run(repo, token).unwrap_or_else(|err| {
+            log::warn!("failed to fetch the workflow runs of {}: {}", repo, err);
+            offline = true;
+            last.runs.iter().find(|(last, _)| last == repo).and_then(|(_, run)| run.clone())
+        });
+        (repo.clone(), run)
+    }).collect();
+    Status { notifications, runs, offline }
+}
+
+/// There are repositories or a token to poll, otherwise the section is left out
+pub fn configured(config:&Config)->bool{
+    !config.github_repos.is_empty() || !config.github_token_file.is_empty()
+}
+
+/// Emits the notifications and runs every `refresh_minutes`, see [`Message::GitHub`]
+pub fn listen(repos:Vec<String>, token_file:String, refresh_minutes:u16)->Subscription<Message>{
+    struct GitHubSubscription;
+    worker::listen(
+        (TypeId::of::<GitHubSubscription>(), repos.clone(), token_file.clone(), refresh_minutes),
+        Message::GitHub,
+        move |tx| {
+            let mut status = Status::default();
+            worker::every(&tx, Duration::from_secs(60 * u64::from(refresh_minutes)), || {
+                // read every time, so a renewed token is picked up
+                let token = read_token(&token_file);
+                status = poll(&repos, token.as_deref(), &status);
+                status.clone()
+            });
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_latest_run(){
+        let json = r#"{"total_count": 2, "workflow_runs": [
+            {"name": "CI \"main\"", "head_branch": "main", "status": "completed", "conclusion": "success",
+             "html_url": "https://github.com/o/r/actions/runs/2", "repository": {"name": "r"}},
+            {"name": "CI", "head_branch": "main", "status": "completed", "conclusion": "failure"}
+        ]}"#;
+        assert_eq!(parse_runs(json, "o/r"), Ok(Some(Run {
+            workflow: "CI \"main\"".into(),
+            branch: "main".into(),
+            state: RunState::Success,
+            url: "https://github.com/o/r/actions/runs/2".into(),
+        })));
+        let pending = r#"{"workflow_runs": [{"name": "CI", "head_branch": null, "status": "queued", "conclusion": null}]}"#;
+        let run = parse_runs(pending, "o/r").unwrap().unwrap();
+        assert_eq!(run.state, RunState::Pending);
+        assert_eq!(run.url, "https://github.com/o/r/actions");
+        assert_eq!(parse_runs(r#"{"total_count": 0, "workflow_runs": []}"#, "o/r"), Ok(None));
+        assert!(parse_runs(r#"{"message": "Not Found"}"#, "o/r").is_err());
+    }
+
+    #[test]
+    fn counts_unread_notifications(){
+        let json = r#"[{"unread": true, "subject": {"title": "\"unread\": false"}}, {"unread": false}, {"unread": true}]"#;
+        assert_eq!(count_unread(json), Ok(2));
+        assert!(count_unread("{}").is_err());
+    }
+}
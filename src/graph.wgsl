@@ -0,0 +1,92 @@
+// ------------- UNIFORMS -------------
+@group(0) @binding(0) var<uniform> u: Uniforms;
+struct Uniforms {
+	res: vec2f,
+	top_left: vec2f,
+	colour: vec4f,
+	// alpha is zero if there is no secondary series
+	secondary_colour: vec4f,
+	// x: 0 for a line chart, 1 for a gauge, y: value of the gauge in [0,1]
+	params: vec4f,
+	// samples in [0,1], packed four per vector, most recent sample first
+	samples: array<vec4f, 16>,
+	secondary: array<vec4f, 16>,
+}
+const SAMPLES: u32 = 64u;
+
+// ---------- VERTEX CREATION ----------
+struct VertexIn {@builtin(vertex_index) vertex_index: u32,}
+struct VertexOut {@builtin(position) position: vec4f,}
+@vertex
+fn vs_main(in: VertexIn) -> VertexOut {
+	let uv = vec2f(vec2u((in.vertex_index << 1) & 2, in.vertex_index & 2));
+	let position = vec4f(uv * 2. - 1., 0., 1.);
+	return VertexOut(position);
+}
+
+// ------------ MAIN PROGRAM -----------
+// line width in pixels
+const LINE_WIDTH: f32 = 1.5;
+// opacity of the area below the line
+const FILL_ALPHA: f32 = 0.25;
+// gauge arc thickness in pixels and the angle it spans
+const GAUGE_WIDTH: f32 = 5.;
+const GAUGE_SWEEP: f32 = 4.712389;
+const PI: f32 = 3.1415926535;
+
+fn sample(i: u32, secondary: bool) -> f32 {
+	let j = min(i, SAMPLES - 1u);
+	if secondary {
+		return u.secondary[j / 4u][j % 4u];
+	}
+	return u.samples[j / 4u][j % 4u];
+}
+
+// height of the series in the column of `p` and the coverage of its line at `p`
+fn line(p: vec2f, secondary: bool) -> vec2f {
+	let x = clamp(p.x / u.res.x, 0., 1.) * f32(SAMPLES - 1u);
+	let i = u32(floor(x));
+	let a = sample(i, secondary) * u.res.y;
+	let b = sample(i + 1u, secondary) * u.res.y;
+	let y = mix(a, b, fract(x));
+
+	// approximate distance to the line segment, accounting for its slope
+	let slope = (b - a) / (u.res.x / f32(SAMPLES - 1u));
+	let dist = abs(p.y - y) / sqrt(1. + slope * slope);
+	return vec2f(y, 1. - smoothstep(LINE_WIDTH * 0.5 - 0.5, LINE_WIDTH * 0.5 + 0.5, dist));
+}
+
+// radial gauge centred in the widget, opening towards the bottom
+fn gauge(p: vec2f) -> vec4f {
+	let radius = 0.5 * min(u.res.x, u.res.y) - GAUGE_WIDTH;
+	let d = p - 0.5 * u.res;
+	let ring = 1. - smoothstep(0.5 * GAUGE_WIDTH - 0.5, 0.5 * GAUGE_WIDTH + 0.5, abs(length(d) - radius));
+	// angle measured clockwise from the start of the arc at the bottom left (225 degrees)
+	let angle = (1.25 * PI - atan2(d.y, d.x)) % (2. * PI);
+	let t = angle / GAUGE_SWEEP;
+	let on_arc = step(0., t) * step(t, 1.);
+	let filled = step(t, u.params.y);
+	let alpha = ring * on_arc * mix(0.2, 1., filled) * u.colour.a;
+	return vec4f(u.colour.rgb, alpha);
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4f {
+	// pixel coordinates inside the widget, y pointing up
+	let p = vec2f(in.position.x - u.top_left.x, u.res.y - (in.position.y - u.top_left.y));
+	if u.params.x > 0.5 {
+		return gauge(p);
+	}
+
+	let primary = line(p, false);
+	// soft filled area below the primary line, fading towards the bottom
+	let area = select(0., FILL_ALPHA * (p.y / max(primary.x, 1.)), p.y < primary.x);
+	var colour = vec4f(u.colour.rgb, max(primary.y, area) * u.colour.a);
+
+	// secondary series as a dashed line on top
+	let dash = step(0.5, fract(p.x / 6.));
+	let secondary_alpha = line(p, true).y * dash * u.secondary_colour.a;
+	colour = mix(colour, vec4f(u.secondary_colour.rgb, 1.), secondary_alpha);
+	colour.a = max(colour.a, secondary_alpha);
+	return colour;
+}
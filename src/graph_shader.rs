@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use cosmic::iced::advanced::Shell;
+use cosmic::iced::event::Status;
+use cosmic::iced::mouse::{self, Cursor};
+use cosmic::iced::wgpu::{BlendState, PipelineCompilationOptions};
+use cosmic::iced::widget::shader::{self, Event};
+use cosmic::iced::Rectangle;
+use cosmic::iced_wgpu::graphics::Viewport;
+use crate::iced::wgpu;
+use crate::Message;
+
+/// Number of samples drawn by a smooth graph
+pub const GRAPH_SAMPLES: usize = 64;
+
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct GraphUniformsCRepr {
+    resolution: [f32;2],
+    top_left: [f32;2],
+    colour: [f32;4],
+    secondary_colour: [f32;4],
+    /// x: 0 for a line chart, 1 for a gauge, y: value of the gauge in 0 to 1
+    params: [f32;4],
+    samples: [[f32;4]; GRAPH_SAMPLES/4],
+    secondary: [[f32;4]; GRAPH_SAMPLES/4],
+}
+
+/// Pack samples four per vector to satisfy the uniform array stride
+fn pack(samples: &[f32; GRAPH_SAMPLES]) -> [[f32;4]; GRAPH_SAMPLES/4] {
+    let mut packed = [[0.;4]; GRAPH_SAMPLES/4];
+    for (i, v) in samples.iter().enumerate() {
+        packed[i / 4][i % 4] = *v;
+    }
+    packed
+}
+
+/// Resample `data` to [`GRAPH_SAMPLES`] points, normalized so the `(min, max)` range maps to 0 to 1
+fn resample(data: &[f32], (lo, hi): (f32, f32)) -> [f32; GRAPH_SAMPLES] {
+    let mut samples = [0.; GRAPH_SAMPLES];
+    if data.len() == 1 {
+        samples = [data[0]; GRAPH_SAMPLES];
+    } else if data.len() > 1 {
+        for (i, s) in samples.iter_mut().enumerate() {
+            let x = i as f32 * (data.len() - 1) as f32 / (GRAPH_SAMPLES - 1) as f32;
+            let (i0, i1) = (x.floor() as usize, (x.ceil() as usize).min(data.len() - 1));
+            *s = data[i0] + (data[i1] - data[i0]) * x.fract();
+        }
+    }
+    for s in samples.iter_mut() {
+        *s = ((*s - lo) / (hi - lo)).clamp(0., 1.);
+    }
+    samples
+}
+
+/// Per-graph uniform buffer and bind group
+struct GraphInstance {
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+}
+
+/// Render pipeline shared by all smooth graphs, with one [`GraphInstance`] per graph id
+struct GraphPipeline {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    instances: HashMap<u64, GraphInstance>,
+}
+
+impl GraphPipeline {
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("GraphPipeline shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
+                "graph.wgsl"
+            ))),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("graph_bind_group_layout"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Graph Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("GraphPipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            instances: HashMap::new(),
+        }
+    }
+
+    fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, id: u64, uniforms: &GraphUniformsCRepr) {
+        let layout = &self.bind_group_layout;
+        let instance = self.instances.entry(id).or_insert_with(|| {
+            let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("graph_uniform_buffer"),
+                size: std::mem::size_of::<GraphUniformsCRepr>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }],
+                label: Some("graph_uniform_bind_group"),
+            });
+            GraphInstance { uniform_buffer, uniform_bind_group }
+        });
+        queue.write_buffer(&instance.uniform_buffer, 0, bytemuck::bytes_of(uniforms));
+    }
+
+    fn render(
+        &self,
+        id: u64,
+        target: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+        viewport: Rectangle<u32>,
+    ) {
+        let Some(instance) = self.instances.get(&id) else { return };
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("graph"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_viewport(
+            viewport.x as f32,
+            viewport.y as f32,
+            viewport.width as f32,
+            viewport.height as f32,
+            0.0,
+            1.0,
+        );
+        pass.set_bind_group(0, &instance.uniform_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+#[derive(Debug)]
+pub struct GraphPrimitive {
+    id: u64,
+    gauge: bool,
+    samples: [f32; GRAPH_SAMPLES],
+    secondary: Option<[f32; GRAPH_SAMPLES]>,
+    colour: [f32; 4],
+}
+
+impl shader::Primitive for GraphPrimitive {
+    fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        storage: &mut shader::Storage,
+        bounds: &Rectangle,
+        viewport: &Viewport,
+    ) {
+        if !storage.has::<GraphPipeline>() {
+            storage.store(GraphPipeline::new(device, format));
+        }
+        let scale = viewport.scale_factor() as f32;
+        // the secondary series is drawn dimmed, or not at all if there is none
+        let [r, g, b, a] = self.colour;
+        let secondary_colour = [r, g, b, if self.secondary.is_some() {a * 0.6} else {0.}];
+        let pipeline = storage.get_mut::<GraphPipeline>().unwrap();
+        pipeline.update(
+            device,
+            queue,
+            self.id,
+            &GraphUniformsCRepr {
+                resolution: [bounds.width * scale, bounds.height * scale],
+                top_left: [bounds.x * scale, bounds.y * scale],
+                colour: self.colour,
+                secondary_colour,
+                params: [if self.gauge {1.} else {0.}, self.samples[0], 0., 0.],
+                samples: pack(&self.samples),
+                secondary: pack(&self.secondary.unwrap_or([0.; GRAPH_SAMPLES])),
+            },
+        );
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        storage: &shader::Storage,
+        target: &wgpu::TextureView,
+        clip_bounds: &Rectangle<u32>,
+    ) {
+        let pipeline = storage.get::<GraphPipeline>().unwrap();
+        pipeline.render(self.id, target, encoder, *clip_bounds);
+    }
+}
+
+/// Anti-aliased line and area chart, rendered as an alternative to the braille graphs,
+/// or a radial gauge of a single value.
+/// Every graph on screen needs a distinct `id` so its uniforms are kept apart.
+#[derive(Debug)]
+pub struct GraphProgram {
+    id: u64,
+    gauge: bool,
+    samples: [f32; GRAPH_SAMPLES],
+    secondary: Option<[f32; GRAPH_SAMPLES]>,
+    colour: [f32; 4],
+}
+
+impl GraphProgram {
+    /// Graph `data` (most recent first) and optionally a `secondary` series overlaid on it,
+    /// mapping the `(min, max)` range to the height of the widget
+    pub fn new(id: u64, data: &[f32], secondary: Option<&[f32]>, range: (f32, f32), colour: [f32; 4]) -> Self {
+        Self {
+            id,
+            gauge: false,
+            samples: resample(data, range),
+            secondary: secondary.map(|secondary| resample(secondary, range)),
+            colour,
+        }
+    }
+
+    /// Radial gauge showing `value` within the `(min, max)` range
+    pub fn gauge(id: u64, value: f32, range: (f32, f32), colour: [f32; 4]) -> Self {
+        Self {
+            id,
+            gauge: true,
+            samples: resample(&[value], range),
+            secondary: None,
+            colour,
+        }
+    }
+}
+
+impl shader::Program<Message> for GraphProgram {
+    /// Whether the cursor is currently over the graph
+    type State = bool;
+    type Primitive = GraphPrimitive;
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        _cursor: mouse::Cursor,
+        _bounds: Rectangle,
+    ) -> Self::Primitive {
+        GraphPrimitive {
+            id: self.id,
+            gauge: self.gauge,
+            samples: self.samples,
+            secondary: self.secondary,
+            colour: self.colour,
+        }
+    }
+
+    /// Report the horizontal cursor position over the graph, as a fraction of its width,
+    /// so the monitor can show the value below the cursor
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+        _shell: &mut Shell<'_, Message>,
+    ) -> (Status, Option<Message>) {
+        if self.gauge {
+            return (Status::Ignored, None);
+        }
+        if let Event::Mouse(mouse::Event::CursorMoved { .. }) = event {
+            match cursor.position_in(bounds) {
+                Some(position) => {
+                    *state = true;
+                    let fraction = (position.x / bounds.width).clamp(0., 1.);
+                    return (Status::Ignored, Some(Message::GraphHover(self.id, Some(fraction))));
+                }
+                None if *state => {
+                    *state = false;
+                    return (Status::Ignored, Some(Message::GraphHover(self.id, None)));
+                }
+                None => {}
+            }
+        }
+        (Status::Ignored, None)
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+
+/// Fixed-capacity ring buffer of metric samples.
+///
+/// New samples are pushed to the front, so iteration runs from the most
+/// recent sample to the oldest one, which is the order graphs are drawn in.
+/// Once the buffer is full, pushing a sample drops the oldest one.
+#[derive(Clone, Debug)]
+pub struct History<T>{
+    samples: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T:Copy> History<T>{
+    /// Create an empty history holding at most `capacity` samples
+    pub fn new(capacity:usize)->Self{
+        Self { samples: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    /// Create a history that is already filled with `capacity` copies of `value`
+    pub fn filled(capacity:usize, value:T)->Self{
+        Self { samples: std::iter::repeat(value).take(capacity).collect(), capacity }
+    }
+
+    /// Add the most recent sample, dropping the oldest one if the buffer is full
+    pub fn push(&mut self, value:T){
+        if self.capacity == 0 {return;}
+        if self.samples.len() == self.capacity {
+            self.samples.pop_back();
+        }
+        self.samples.push_front(value);
+    }
+
+    /// Add the most recent sample `count` times, for a value that held over several sampling
+    /// intervals, so the samples stay evenly spaced in time
+    pub fn push_for(&mut self, value:T, count:usize){
+        for _ in 0..count.min(self.capacity) {
+            self.push(value);
+        }
+    }
+
+    /// Iterate from the most recent sample to the oldest
+    pub fn iter(&self)->impl DoubleEndedIterator<Item = T> + ExactSizeIterator + '_{
+        self.samples.iter().copied()
+    }
+
+    /// The most recent sample, if any
+    pub fn latest(&self)->Option<T>{
+        self.samples.front().copied()
+    }
+
+    pub fn len(&self)->usize{
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.samples.is_empty()
+    }
+
+    pub fn capacity(&self)->usize{
+        self.capacity
+    }
+
+    /// Change the capacity, dropping the oldest samples if it shrinks
+    pub fn set_capacity(&mut self, capacity:usize){
+        self.samples.truncate(capacity);
+        self.capacity = capacity;
+    }
+
+    pub fn clear(&mut self){
+        self.samples.clear();
+    }
+}
+
+impl<T:Copy + PartialOrd> History<T>{
+    pub fn min(&self)->Option<T>{
+        self.iter().reduce(|a, b| if b < a {b} else {a})
+    }
+
+    pub fn max(&self)->Option<T>{
+        self.iter().reduce(|a, b| if b > a {b} else {a})
+    }
+}
+
+/// Summary of a window of samples
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats{
+    pub current: f32,
+    pub min: f32,
+    pub avg: f32,
+    pub max: f32,
+}
+
+impl Stats{
+    /// Apply a monotonic conversion, e.g. to another unit, to each value
+    pub fn map(self, f:impl Fn(f32)->f32)->Self{
+        Stats { current: f(self.current), min: f(self.min), avg: f(self.avg), max: f(self.max) }
+    }
+}
+
+impl History<f32>{
+    /// Current value, minimum, average and maximum of the most recent `window` samples
+    pub fn stats(&self, window:usize)->Option<Stats>{
+        let window = window.min(self.len());
+        if window == 0 {return None;}
+        let (min, max, sum) = self.iter().take(window).fold(
+            (f32::INFINITY, f32::NEG_INFINITY, 0.),
+            |(min, max, sum), v| (min.min(v), max.max(v), sum + v),
+        );
+        Some(Stats { current: self.latest()?, min, avg: sum / window as f32, max })
+    }
+
+    /// Arithmetic mean of all samples, `None` if the history is empty
+    pub fn avg(&self)->Option<f32>{
+        if self.is_empty() {return None;}
+        Some(self.iter().sum::<f32>() / self.len() as f32)
+    }
+
+    /// Average the most recent `window` samples into at most `buckets` values,
+    /// most recent bucket first. If fewer samples than buckets are available,
+    /// the samples are returned as they are.
+    pub fn downsample(&self, window:usize, buckets:usize)->Vec<f32>{
+        let window = window.min(self.len());
+        if buckets == 0 || window == 0 {return vec![];}
+        if window <= buckets {
+            return self.iter().take(window).collect();
+        }
+        (0..buckets).map(|i| {
+            let start = i * window / buckets;
+            let end = ((i + 1) * window / buckets).max(start + 1);
+            let sum:f32 = self.samples.range(start..end).sum();
+            sum / (end - start) as f32
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(values:&[f32], capacity:usize)->History<f32>{
+        let mut history = History::new(capacity);
+        for &value in values {
+            history.push(value);
+        }
+        history
+    }
+
+    #[test]
+    fn push_drops_the_oldest_sample(){
+        let history = history(&[1., 2., 3., 4.], 3);
+        assert_eq!(history.iter().collect::<Vec<_>>(), [4., 3., 2.]);
+        assert_eq!(history.latest(), Some(4.));
+        assert_eq!(history.len(), 3);
+    }
+
+    #[test]
+    fn push_for_fills_the_intervals_up_to_the_capacity(){
+        let mut history = history(&[1.], 4);
+        history.push_for(2., 2);
+        assert_eq!(history.iter().collect::<Vec<_>>(), [2., 2., 1.]);
+        history.push_for(3., usize::MAX);
+        assert_eq!(history.iter().collect::<Vec<_>>(), [3., 3., 3., 3.]);
+        history.push_for(4., 0);
+        assert_eq!(history.latest(), Some(3.));
+    }
+
+    #[test]
+    fn zero_capacity_stays_empty(){
+        let history = history(&[1.], 0);
+        assert!(history.is_empty());
+        assert_eq!(history.latest(), None);
+    }
+
+    #[test]
+    fn shrinking_keeps_the_newest_samples(){
+        let mut history = history(&[1., 2., 3.], 3);
+        history.set_capacity(2);
+        assert_eq!(history.iter().collect::<Vec<_>>(), [3., 2.]);
+        history.push(4.);
+        assert_eq!(history.iter().collect::<Vec<_>>(), [4., 3.]);
+    }
+
+    #[test]
+    fn filled_is_full(){
+        let history = History::filled(4, 0.5);
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.avg(), Some(0.5));
+    }
+
+    #[test]
+    fn min_max_and_avg(){
+        let history = history(&[3., 1., 2.], 5);
+        assert_eq!(history.min(), Some(1.));
+        assert_eq!(history.max(), Some(3.));
+        assert_eq!(history.avg(), Some(2.));
+        assert_eq!(History::<f32>::new(5).avg(), None);
+    }
+
+    #[test]
+    fn stats_of_a_window(){
+        let history = history(&[10., 0., 4., 2.], 10);
+        assert_eq!(history.stats(2), Some(Stats { current: 2., min: 2., avg: 3., max: 4. }));
+        // a window longer than the history covers all of it
+        assert_eq!(history.stats(100), Some(Stats { current: 2., min: 0., avg: 4., max: 10. }));
+        assert_eq!(history.stats(0), None);
+        assert_eq!(History::<f32>::new(3).stats(3), None);
+    }
+
+    #[test]
+    fn downsample_averages_buckets(){
+        let history = history(&[1., 2., 3., 4., 5., 6.], 10);
+        assert_eq!(history.downsample(6, 3), [5.5, 3.5, 1.5]);
+        assert_eq!(history.downsample(4, 2), [5.5, 3.5]);
+        // fewer samples than buckets are returned as they are
+        assert_eq!(history.downsample(2, 5), [6., 5.]);
+        assert!(history.downsample(6, 0).is_empty());
+        assert!(History::<f32>::new(3).downsample(3, 3).is_empty());
+    }
+}
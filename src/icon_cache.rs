@@ -33,6 +33,7 @@ impl IconCache {
         bundle!("dialog-error-symbolic", 16);
         bundle!("edit-clear-symbolic", 16);
         bundle!("edit-delete-symbolic", 16);
+        bundle!("emblem-system-symbolic", 16);
         bundle!("list-add-symbolic", 16);
         bundle!("go-down-symbolic", 16);
         bundle!("go-up-symbolic", 16);
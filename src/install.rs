@@ -0,0 +1,106 @@
+//! `eos --install` sets eos up to start on login: it writes a systemd user unit that restarts
+//! eos if it crashes and an autostart entry that starts the unit, for desktops that do not start
+//! `graphical-session.target`. Starting an active unit again does nothing, so eos runs once
+//! either way. `eos --uninstall` removes both.
+
+use std::{fs, io, path::PathBuf, process::Command};
+
+const UNIT_NAME:&str = "eos.service";
+const AUTOSTART_NAME:&str = "de.juliankarrer.eos.desktop";
+
+fn unit_path()->Option<PathBuf>{
+    dirs::config_dir().map(|dir| dir.join("systemd").join("user").join(UNIT_NAME))
+}
+
+fn autostart_path()->Option<PathBuf>{
+    dirs::config_dir().map(|dir| dir.join("autostart").join(AUTOSTART_NAME))
+}
+
+/// Quote an argument of `ExecStart`, which systemd splits like a shell
+fn quote(arg:&str)->String{
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\"").replace('%', "%%"))
+}
+
+fn unit(args:&[String])->io::Result<String>{
+    let exe = std::env::current_exe()?;
+    let exec = std::iter::once(exe.to_string_lossy().into_owned())
+        .chain(args.iter().cloned())
+        .map(|arg| quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(format!("\
+[Unit]
+Description=eos resource monitor
+PartOf=graphical-session.target
+After=graphical-session.target
+# give up on a broken setup instead of restarting forever
+StartLimitIntervalSec=60
+StartLimitBurst=5
+
+[Service]
+ExecStart={exec}
+Restart=on-failure
+RestartSec=5
+
+[Install]
+WantedBy=graphical-session.target
+"))
+}
+
+fn autostart()->String{
+    format!("\
+[Desktop Entry]
+Name=EOS
+Comment=Start the eos user service
+Exec=systemctl --user start {UNIT_NAME}
+Terminal=false
+Type=Application
+Icon=de.juliankarrer.eos
+X-GNOME-Autostart-enabled=true
+")
+}
+
+/// Run `systemctl --user` with the arguments, reporting instead of failing if it cannot, e.g.
+/// on systems without systemd, where the autostart entry is left to start eos
+fn systemctl(args:&[&str]){
+    match Command::new("systemctl").arg("--user").args(args).status() {
+        Ok(status) if status.success() => (),
+        Ok(status) => eprintln!("systemctl --user {} failed: {}", args.join(" "), status),
+        Err(err) => eprintln!("cannot run systemctl --user {}: {}", args.join(" "), err),
+    }
+}
+
+fn write(path:&PathBuf, contents:&str)->io::Result<()>{
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+/// Write the unit, which runs eos with `args`, and the autostart entry, and enable the unit
+pub fn install(args:&[String])->io::Result<()>{
+    let (Some(unit_path), Some(autostart_path)) = (unit_path(), autostart_path()) else {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "no config directory"));
+    };
+    write(&unit_path, &unit(args)?)?;
+    write(&autostart_path, &autostart())?;
+    systemctl(&["daemon-reload"]);
+    systemctl(&["enable", UNIT_NAME]);
+    Ok(())
+}
+
+/// Disable and remove the unit and the autostart entry, ignoring those that do not exist
+pub fn uninstall()->io::Result<()>{
+    systemctl(&["disable", UNIT_NAME]);
+    for path in [unit_path(), autostart_path()].into_iter().flatten() {
+        match fs::remove_file(&path) {
+            Ok(()) => println!("removed {}", path.display()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(err),
+        }
+    }
+    systemctl(&["daemon-reload"]);
+    Ok(())
+}
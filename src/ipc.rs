@@ -0,0 +1,236 @@
+//! Control socket of a running eos, for keybind daemons and scripts. Requests and responses are
+//! JSON lines on the Unix socket at [`socket_path`], like
+//!
+//! ```text
+//! {"command": "set-scene", "args": ["plasma"]}
+//! {"ok": true}
+//! ```
+//!
+//! `status` answers with the current metrics, whether the monitor is paused, the scene and the
+//! page in `result`, and a failed request with `"ok": false` and an `error`. `eos ctl` sends a
+//! request from the command line, see [`client`].
+
+use std::any::TypeId;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{fmt, fs};
+
+use cosmic::iced::{futures::SinkExt, stream, Subscription};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use crate::config::{MonitorPage, Scene};
+use crate::Message;
+
+/// Time between two looks for connections, and to notice the subscription is gone
+const ACCEPT_INTERVAL:Duration = Duration::from_millis(200);
+/// Commands with the arguments they take, for `help` and errors
+const COMMANDS:&str = "toggle-overlay, toggle-widget-input, toggle-pip, toggle-pause, set-paused true|false, \
+    next-scene, set-scene NAME, set-page overview|processes|cores, refresh, status";
+
+/// What `status` answers with
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Status {
+    /// Metrics by name like `cpu_avg`, in percent or °C, leaving out those that are unknown
+    pub metrics: BTreeMap<&'static str, f32>,
+    pub paused: bool,
+    pub scene: Scene,
+    pub page: MonitorPage,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Request {
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+/// `$XDG_RUNTIME_DIR/eos.sock`, or in the cache directory where there is no runtime directory
+pub fn socket_path()->Option<PathBuf>{
+    dirs::runtime_dir()
+        .or_else(|| Some(dirs::cache_dir()?.join("eos")))
+        .map(|dir| dir.join("eos.sock"))
+}
+
+/// Name like `Plasma` or `processes` of one of `all`, ignoring case
+fn find<T:Copy + fmt::Debug>(all:&[T], name:&str)->Option<T>{
+    all.iter().copied().find(|known| format!("{:?}", known).eq_ignore_ascii_case(name))
+}
+
+/// Message a request stands for, `None` for `status`
+fn message(request:&Request, status:&Status)->Result<Option<Message>, String>{
+    let arg = || request.args.first().map(String::as_str).ok_or_else(|| format!("{} needs an argument", request.command));
+    let message = match request.command.as_str() {
+        "status" => return Ok(None),
+        "toggle-overlay" => Message::ToggleOverlay,
+        "toggle-widget-input" => Message::ToggleWidgetInteraction,
+        "toggle-pip" => Message::TogglePip,
+        "toggle-pause" => Message::PauseUpdates,
+        "set-paused" => {
+            let paused = arg()?.parse::<bool>().map_err(|_| "set-paused takes true or false".to_string())?;
+            if paused == status.paused {return Ok(None);}
+            Message::PauseUpdates
+        },
+        "next-scene" => Message::SceneNext,
+        "set-scene" => {
+            let name = arg()?;
+            let scene = find(&Scene::ALL, name).ok_or_else(|| format!("unknown scene {:?}", name))?;
+            Message::Scene(Scene::ALL.iter().position(|known| *known == scene).unwrap_or_default())
+        },
+        "set-page" => {
+            let name = arg()?;
+            Message::MonitorPage(find(&MonitorPage::ALL, name).ok_or_else(|| format!("unknown page {:?}", name))?)
+        },
+        "refresh" => Message::Refresh,
+        command => return Err(format!("unknown command {:?}, expected one of {}", command, COMMANDS)),
+    };
+    Ok(Some(message))
+}
+
+/// Answer the requests of a client, one per line, until it disconnects
+fn serve(stream:UnixStream, status:&Mutex<Status>, tx:&mpsc::UnboundedSender<Message>)->io::Result<()>{
+    let mut writer = &stream;
+    for line in BufReader::new(&stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {continue;}
+        let status = status.lock().map(|status| status.clone()).unwrap_or_default();
+        let response = match serde_json::from_str::<Request>(&line) {
+            Err(err) => json!({ "ok": false, "error": format!("invalid request: {}", err) }),
+            Ok(request) => match message(&request, &status) {
+                Err(err) => json!({ "ok": false, "error": err }),
+                Ok(message) => {
+                    if let Some(message) = message {
+                        if tx.send(message).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    if request.command == "status" {json!({ "ok": true, "result": status })} else {json!({ "ok": true })}
+                },
+            },
+        };
+        writeln!(writer, "{}", response)?;
+    }
+    Ok(())
+}
+
+/// Removes the socket when the subscription ends, e.g. because it was turned off
+struct Remove(PathBuf);
+
+impl Drop for Remove {
+    fn drop(&mut self){
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn bind()->io::Result<(UnixListener, PathBuf)>{
+    let path = socket_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no runtime directory"))?;
+    if UnixStream::connect(&path).is_ok() {
+        return Err(io::Error::new(io::ErrorKind::AddrInUse, format!("another eos listens at {}", path.display())));
+    }
+    // left behind by an eos that did not exit cleanly
+    let _ = fs::remove_file(&path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    Ok((listener, path))
+}
+
+/// Sends the status to the socket
+#[derive(Clone)]
+pub struct Handle(Arc<Mutex<Status>>);
+
+impl Handle {
+    pub fn update(&self, status:Status){
+        if let Ok(mut current) = self.0.lock() {
+            *current = status;
+        }
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>)->fmt::Result{
+        f.write_str("ipc::Handle")
+    }
+}
+
+/// Listen on the socket, sending `Message::IpcReady` with its handle and then the messages of
+/// the requests
+pub fn listen()->Subscription<Message>{
+    struct IpcSubscription;
+    Subscription::run_with_id(
+        TypeId::of::<IpcSubscription>(),
+        stream::channel(16, |mut output| async move {
+            let (listener, path) = match bind() {
+                Ok(bound) => bound,
+                Err(err) => {
+                    log::warn!("failed to open the control socket: {}", err);
+                    return std::future::pending().await;
+                },
+            };
+            let _remove = Remove(path);
+            let status = Arc::new(Mutex::new(Status::default()));
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let shared = status.clone();
+            if let Err(err) = listener.set_nonblocking(true) {
+                log::warn!("failed to open the control socket: {}", err);
+                return std::future::pending().await;
+            }
+            std::thread::spawn(move || loop {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let _ = stream.set_nonblocking(false);
+                        let (status, tx) = (shared.clone(), tx.clone());
+                        std::thread::spawn(move || {
+                            if let Err(err) = serve(stream, &status, &tx) {
+                                log::debug!("control socket client: {}", err);
+                            }
+                        });
+                    },
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        // the subscription is gone, the socket is closed with the listener
+                        if tx.is_closed() {break;}
+                        std::thread::sleep(ACCEPT_INTERVAL);
+                    },
+                    Err(err) => log::debug!("failed to accept a control socket client: {}", err),
+                }
+            });
+            if output.send(Message::IpcReady(Handle(status))).await.is_ok() {
+                while let Some(message) = rx.recv().await {
+                    if output.send(message).await.is_err() {break;}
+                }
+            }
+            std::future::pending().await
+        }),
+    )
+}
+
+/// `eos ctl`: send a command with its arguments to the running eos and print the result of
+/// `status`, returning the error of a failed request
+pub fn client(command:&[String])->Result<(), String>{
+    let Some((command, args)) = command.split_first() else {
+        return Err(format!("expected a command: {}", COMMANDS));
+    };
+    let path = socket_path().ok_or("no runtime directory")?;
+    let stream = UnixStream::connect(&path).map_err(|err| format!("eos is not running with control_socket = true at {}: {}", path.display(), err))?;
+    let request = serde_json::to_string(&Request { command: command.clone(), args: args.to_vec() }).map_err(|err| err.to_string())?;
+    let mut writer = &stream;
+    writeln!(writer, "{}", request).map_err(|err| err.to_string())?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line).map_err(|err| err.to_string())?;
+    let response:Value = serde_json::from_str(&line).map_err(|err| format!("invalid response: {}", err))?;
+    if response["ok"] != Value::Bool(true) {
+        return Err(response["error"].as_str().unwrap_or("the request failed").to_string());
+    }
+    if let Some(result) = response.get("result") {
+        println!("{}", serde_json::to_string_pretty(result).map_err(|err| err.to_string())?);
+    }
+    Ok(())
+}
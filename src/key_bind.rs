@@ -2,10 +2,76 @@ use cosmic::widget::menu::key_bind::{KeyBind, Modifier};
 use cosmic::{iced::keyboard::Key, iced_core::keyboard::key::Named};
 use std::collections::HashMap;
 
-use crate::Action;
+use crate::{config::Config, Action};
 
-//TODO: load from config
-pub fn key_binds() -> HashMap<KeyBind, Action> {
+/// Keys that are not characters by the name they are written with in the config
+const NAMED_KEYS: [(&str, Named); 27] = [
+    ("Tab", Named::Tab),
+    ("Enter", Named::Enter),
+    ("Escape", Named::Escape),
+    ("Space", Named::Space),
+    ("Backspace", Named::Backspace),
+    ("Delete", Named::Delete),
+    ("Insert", Named::Insert),
+    ("Home", Named::Home),
+    ("End", Named::End),
+    ("PageUp", Named::PageUp),
+    ("PageDown", Named::PageDown),
+    ("ArrowUp", Named::ArrowUp),
+    ("ArrowDown", Named::ArrowDown),
+    ("ArrowLeft", Named::ArrowLeft),
+    ("ArrowRight", Named::ArrowRight),
+    ("F1", Named::F1),
+    ("F2", Named::F2),
+    ("F3", Named::F3),
+    ("F4", Named::F4),
+    ("F5", Named::F5),
+    ("F6", Named::F6),
+    ("F7", Named::F7),
+    ("F8", Named::F8),
+    ("F9", Named::F9),
+    ("F10", Named::F10),
+    ("F11", Named::F11),
+    ("F12", Named::F12),
+];
+
+/// Parse a shortcut from `Config::key_bindings`, modifiers and a key joined by `+`,
+/// e.g. `Ctrl+Shift+T`, `Ctrl+Tab` or `Ctrl++`
+pub fn parse(shortcut: &str) -> Result<KeyBind, String> {
+    let (modifiers, key) = match shortcut.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None => shortcut.rsplit_once('+').unwrap_or(("", shortcut)),
+    };
+    let modifiers = modifiers
+        .split('+')
+        .map(str::trim)
+        .filter(|modifier| !modifier.is_empty())
+        .map(|modifier| match modifier.to_lowercase().as_str() {
+            "super" => Ok(Modifier::Super),
+            "ctrl" | "control" => Ok(Modifier::Ctrl),
+            "alt" => Ok(Modifier::Alt),
+            "shift" => Ok(Modifier::Shift),
+            _ => Err(format!("unknown modifier `{}` in `{}`", modifier, shortcut)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let key = key.trim();
+    let key = match NAMED_KEYS.iter().find(|(name, _)| name.eq_ignore_ascii_case(key)) {
+        Some((_, named)) => Key::Named(*named),
+        None if key.chars().count() == 1 => {
+            // letters are upper case with Shift held, like the keys the keyboard reports
+            if modifiers.contains(&Modifier::Shift) {
+                Key::Character(key.to_uppercase().into())
+            } else {
+                Key::Character(key.to_lowercase().into())
+            }
+        }
+        None => return Err(format!("unknown key `{}` in `{}`", key, shortcut)),
+    };
+    Ok(KeyBind { modifiers, key })
+}
+
+/// The default key bindings, of which those for actions in `Config::key_bindings` are replaced
+pub fn key_binds(config: &Config) -> HashMap<KeyBind, Action> {
     let mut key_binds = HashMap::new();
 
     macro_rules! bind {
@@ -75,8 +141,83 @@ pub fn key_binds() -> HashMap<KeyBind, Action> {
     bind!([Ctrl, Shift], Key::Named(Named::ArrowRight), PaneFocusRight);
     bind!([Ctrl, Shift], Key::Character("L".into()), PaneFocusRight);
 
+    // Ctrl+Alt+P cycles through the config profiles
+    bind!([Ctrl, Alt], Key::Character("p".into()), ConfigProfileNext);
+
     // CTRL+Alt+L clears the scrollback.
     bind!([Ctrl, Alt], Key::Character("L".into()), ClearScrollback);
 
+    // Ctrl+Alt+Space pauses the resource monitor, Ctrl+Alt+S changes the order of the process list,
+    // Ctrl+Alt+Up and Down select a process and Ctrl+Alt+K asks to terminate it
+    bind!([Ctrl, Alt], Key::Named(Named::Space), PauseUpdates);
+    bind!([Ctrl, Alt], Key::Character("s".into()), ProcessSortNext);
+    bind!([Ctrl, Alt], Key::Named(Named::ArrowUp), ProcessSelectPrev);
+    bind!([Ctrl, Alt], Key::Named(Named::ArrowDown), ProcessSelectNext);
+    bind!([Ctrl, Alt], Key::Named(Named::PageUp), ProcessSelectPageUp);
+    bind!([Ctrl, Alt], Key::Named(Named::PageDown), ProcessSelectPageDown);
+    bind!([Ctrl, Alt], Key::Named(Named::Enter), ProcessDetail);
+    bind!([Ctrl, Alt], Key::Character("k".into()), ProcessKill);
+
+    // Ctrl+Alt+O shows or hides the overlay and Ctrl+Alt+I the mini monitor, Ctrl+Alt+M switches
+    // to the compact mode and back
+    bind!([Ctrl, Alt], Key::Character("o".into()), ToggleOverlay);
+    bind!([Ctrl, Alt], Key::Character("i".into()), TogglePip);
+    bind!([Ctrl, Alt], Key::Character("m".into()), ToggleCompact);
+
+    // bindings from the config replace the default ones of their actions, invalid shortcuts
+    // are reported by `Config::validate`
+    let configured: Vec<(KeyBind, Action)> = config
+        .key_bindings
+        .iter()
+        .filter_map(|(shortcut, action)| Some((parse(shortcut).ok()?, *action)))
+        .collect();
+    key_binds.retain(|_, action| !configured.iter().any(|(_, configured)| configured == action));
+    key_binds.extend(configured);
+
     key_binds
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bind(modifiers: Vec<Modifier>, key: Key) -> KeyBind {
+        KeyBind { modifiers, key }
+    }
+
+    #[test]
+    fn parses_modifiers_and_keys() {
+        assert_eq!(
+            parse("Ctrl+Shift+t"),
+            Ok(bind(vec![Modifier::Ctrl, Modifier::Shift], Key::Character("T".into())))
+        );
+        assert_eq!(
+            parse("control + alt + K"),
+            Ok(bind(vec![Modifier::Ctrl, Modifier::Alt], Key::Character("k".into())))
+        );
+        assert_eq!(parse("Super+pageup"), Ok(bind(vec![Modifier::Super], Key::Named(Named::PageUp))));
+        assert_eq!(parse("F12"), Ok(bind(vec![], Key::Named(Named::F12))));
+        assert_eq!(parse("Ctrl++"), Ok(bind(vec![Modifier::Ctrl], Key::Character("+".into()))));
+    }
+
+    #[test]
+    fn rejects_malformed_shortcuts() {
+        assert!(parse("").is_err());
+        assert!(parse("Ctrl+").is_err());
+        assert!(parse("Hyper+X").is_err());
+        assert!(parse("Ctrl+Shift+Tabs").is_err());
+        assert!(parse("Ctrl+ab").is_err());
+    }
+
+    #[test]
+    fn configured_bindings_replace_the_defaults() {
+        let config = Config {
+            key_bindings: [("Ctrl+Alt+J".to_string(), Action::ProcessKill)].into(),
+            ..Config::default()
+        };
+        let key_binds = key_binds(&config);
+        let configured = parse("Ctrl+Alt+J").unwrap();
+        assert_eq!(key_binds.get(&configured), Some(&Action::ProcessKill));
+        assert!(!key_binds.contains_key(&parse("Ctrl+Alt+K").unwrap()));
+    }
+}
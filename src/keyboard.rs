@@ -0,0 +1,198 @@
+//! Keyboard layout and lock keys for [`crate::config::Section::Keyboard`], followed through the
+//! wl_keyboard of each seat on a Wayland connection of its own, on a background thread. The
+//! layouts are named after the keymap the compositor sends, and Caps Lock, Num Lock and the
+//! active layout group come with its modifiers events.
+
+use std::any::TypeId;
+
+use cosmic::cctk::sctk::{
+    self,
+    reexports::{calloop, calloop_wayland_source::WaylandSource},
+    registry::{ProvidesRegistryState, RegistryState},
+    seat::{
+        keyboard::{KeyEvent, KeyboardHandler, Keymap, Keysym, Modifiers},
+        Capability, SeatHandler, SeatState,
+    },
+};
+use cosmic::cctk::wayland_client::{
+    globals::registry_queue_init,
+    protocol::{wl_keyboard, wl_seat, wl_surface},
+    Connection, QueueHandle,
+};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use tokio::sync::mpsc;
+
+use crate::Message;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct State {
+    /// Layouts like `us` or `de(nodeadkeys)`, the active one first
+    pub layouts: Vec<String>,
+    pub caps_lock: bool,
+    pub num_lock: bool,
+}
+
+/// Layouts of a keymap in the order of their groups, from its symbols like
+/// `pc+us+de(nodeadkeys):2+inet(evdev)`
+fn layouts(keymap:&str)->Vec<String>{
+    let Some(symbols) = keymap.lines()
+        .find_map(|line| line.trim().strip_prefix("xkb_symbols"))
+        .and_then(|symbols| symbols.trim().strip_prefix('"')?.split('"').next()) else {return Vec::new()};
+    let mut groups:Vec<(usize, &str)> = Vec::new();
+    for part in symbols.split('+') {
+        // the first layout has no group, the others are numbered and options come after them
+        match part.rsplit_once(':').and_then(|(layout, group)| Some((layout, group.parse::<usize>().ok()?))) {
+            Some((layout, group)) => groups.push((group, layout)),
+            None if part != "pc" && !groups.iter().any(|(group, _)| *group == 1) => groups.push((1, part)),
+            None => {},
+        }
+    }
+    groups.sort_by_key(|(group, _)| *group);
+    groups.into_iter().map(|(_, layout)| layout.to_string()).collect()
+}
+
+struct Listener {
+    registry_state: RegistryState,
+    seat_state: SeatState,
+    keyboards: Vec<(wl_seat::WlSeat, wl_keyboard::WlKeyboard)>,
+    /// Layouts of the keymap in the order of their groups
+    layouts: Vec<String>,
+    group: usize,
+    caps_lock: bool,
+    num_lock: bool,
+    tx: mpsc::UnboundedSender<State>,
+    sent: Option<State>,
+    closed: bool,
+}
+
+impl Listener {
+    /// Sends the state if it changed
+    fn send(&mut self){
+        let mut layouts = self.layouts.clone();
+        if self.group < layouts.len() {
+            layouts.rotate_left(self.group);
+        }
+        let state = State { layouts, caps_lock: self.caps_lock, num_lock: self.num_lock };
+        if self.sent.as_ref() != Some(&state) {
+            self.closed = self.tx.send(state.clone()).is_err();
+            self.sent = Some(state);
+        }
+    }
+}
+
+impl KeyboardHandler for Listener {
+    fn enter(&mut self, _:&Connection, _:&QueueHandle<Self>, _:&wl_keyboard::WlKeyboard, _:&wl_surface::WlSurface, _:u32, _:&[u32], _:&[Keysym]){}
+    fn leave(&mut self, _:&Connection, _:&QueueHandle<Self>, _:&wl_keyboard::WlKeyboard, _:&wl_surface::WlSurface, _:u32){}
+    fn press_key(&mut self, _:&Connection, _:&QueueHandle<Self>, _:&wl_keyboard::WlKeyboard, _:u32, _:KeyEvent){}
+    fn release_key(&mut self, _:&Connection, _:&QueueHandle<Self>, _:&wl_keyboard::WlKeyboard, _:u32, _:KeyEvent){}
+
+    fn update_modifiers(&mut self, _:&Connection, _:&QueueHandle<Self>, _:&wl_keyboard::WlKeyboard, _:u32, modifiers:Modifiers, group:u32){
+        self.caps_lock = modifiers.caps_lock;
+        self.num_lock = modifiers.num_lock;
+        self.group = group as usize;
+        self.send();
+    }
+
+    /// Sent when the keyboard is bound and again when the layouts are changed
+    fn update_keymap(&mut self, _:&Connection, _:&QueueHandle<Self>, _:&wl_keyboard::WlKeyboard, keymap:Keymap<'_>){
+        self.layouts = layouts(&keymap.as_string());
+        self.send();
+    }
+}
+
+impl SeatHandler for Listener {
+    fn seat_state(&mut self)->&mut SeatState{
+        &mut self.seat_state
+    }
+    fn new_seat(&mut self, _:&Connection, _:&QueueHandle<Self>, _:wl_seat::WlSeat){}
+
+    fn new_capability(&mut self, _:&Connection, qh:&QueueHandle<Self>, seat:wl_seat::WlSeat, capability:Capability){
+        if capability != Capability::Keyboard || self.keyboards.iter().any(|(keyboard_seat, _)| *keyboard_seat == seat) {return;}
+        match self.seat_state.get_keyboard(qh, &seat, None) {
+            Ok(keyboard) => self.keyboards.push((seat, keyboard)),
+            Err(err) => log::warn!("failed to follow the keyboard: {}", err),
+        }
+    }
+
+    fn remove_capability(&mut self, _:&Connection, _:&QueueHandle<Self>, seat:wl_seat::WlSeat, capability:Capability){
+        if capability != Capability::Keyboard {return;}
+        self.keyboards.retain(|(keyboard_seat, keyboard)| {
+            if *keyboard_seat != seat {return true;}
+            keyboard.release();
+            false
+        });
+    }
+
+    fn remove_seat(&mut self, conn:&Connection, qh:&QueueHandle<Self>, seat:wl_seat::WlSeat){
+        self.remove_capability(conn, qh, seat, Capability::Keyboard);
+    }
+}
+
+impl ProvidesRegistryState for Listener {
+    fn registry(&mut self)->&mut RegistryState{
+        &mut self.registry_state
+    }
+    sctk::registry_handlers!(SeatState);
+}
+
+sctk::delegate_keyboard!(Listener);
+sctk::delegate_seat!(Listener);
+sctk::delegate_registry!(Listener);
+
+/// Follow the keyboards until the receiver is dropped
+fn run(tx:mpsc::UnboundedSender<State>)->Result<(), String>{
+    let connection = Connection::connect_to_env().map_err(|err| err.to_string())?;
+    let (globals, event_queue) = registry_queue_init::<Listener>(&connection).map_err(|err| err.to_string())?;
+    let qh = event_queue.handle();
+    let mut listener = Listener {
+        registry_state: RegistryState::new(&globals),
+        seat_state: SeatState::new(&globals, &qh),
+        keyboards: Vec::new(),
+        layouts: Vec::new(),
+        group: 0,
+        caps_lock: false,
+        num_lock: false,
+        tx,
+        sent: None,
+        closed: false,
+    };
+    let mut event_loop = calloop::EventLoop::<Listener>::try_new().map_err(|err| err.to_string())?;
+    WaylandSource::new(connection, event_queue).insert(event_loop.handle()).map_err(|err| err.to_string())?;
+    while !listener.closed {
+        event_loop.dispatch(None, &mut listener).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+/// Emits the layouts and lock keys whenever they change, see [`Message::Keyboard`]
+pub fn listen()->Subscription<Message>{
+    struct KeyboardSubscription;
+    Subscription::run_with_id(
+        TypeId::of::<KeyboardSubscription>(),
+        stream::channel(1, |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            std::thread::spawn(move || {
+                if let Err(err) = run(tx) {
+                    log::warn!("failed to follow the keyboard: {}", err);
+                }
+            });
+            while let Some(state) = rx.recv().await {
+                let _ = output.send(Message::Keyboard(state)).await;
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_the_layouts_of_a_keymap(){
+        let keymap = "xkb_keymap {\n\txkb_keycodes \"evdev+aliases(qwerty)\" {\n\t};\n\txkb_symbols \"pc+us+de(nodeadkeys):2+fr:3+inet(evdev)+group(alt_shift_toggle)\" {\n";
+        assert_eq!(layouts(keymap), ["us", "de(nodeadkeys)", "fr"]);
+        assert_eq!(layouts("\txkb_symbols \"pc+us+inet(evdev)\" {"), ["us"]);
+        assert!(layouts("xkb_keymap {").is_empty());
+    }
+}
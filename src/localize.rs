@@ -6,7 +6,12 @@ use i18n_embed::{
     fluent::{fluent_language_loader, FluentLanguageLoader},
     DefaultLocalizer, LanguageLoader, Localizer,
 };
+use fixed_decimal::FixedDecimal;
 use icu_collator::{Collator, CollatorOptions, Numeric};
+use icu_decimal::{
+    options::{FixedDecimalFormatterOptions, GroupingStrategy},
+    FixedDecimalFormatter,
+};
 use icu_provider::DataLocale;
 use rust_embed::RustEmbed;
 
@@ -43,6 +48,32 @@ lazy_static::lazy_static! {
         };
 }
 
+lazy_static::lazy_static! {
+    /// Formats the numbers in the resource monitor with the digits and decimal separator of the
+    /// current language, without grouping so the columns stay aligned
+    static ref NUMBER_FORMATTER: FixedDecimalFormatter = {
+        let mut options = FixedDecimalFormatterOptions::default();
+        options.grouping_strategy = GroupingStrategy::Never;
+
+        DataLocale::from_str(&LANGUAGE_LOADER.current_language().to_string())
+            .ok()
+            .and_then(|locale| FixedDecimalFormatter::try_new(&locale, options).ok())
+            .or_else(|| {
+                let locale = DataLocale::from_str("en-US").expect("en-US is a valid BCP-47 tag");
+                FixedDecimalFormatter::try_new(&locale, options).ok()
+            })
+            .expect("Creating a number formatter from the system's current language or American English should succeed")
+    };
+}
+
+/// `value` rounded to `decimals` digits after the separator, formatted for the current language
+pub fn number(value: f32, decimals: u8) -> String {
+    let scaled = (f64::from(value) * 10f64.powi(decimals.into())).round() as i64;
+    let mut decimal = FixedDecimal::from(scaled).multiplied_pow10(-i16::from(decimals));
+    decimal.pad_end(-i16::from(decimals));
+    NUMBER_FORMATTER.format_to_string(&decimal)
+}
+
 #[macro_export]
 macro_rules! fl {
     ($message_id:literal) => {{
@@ -0,0 +1,62 @@
+//! Unread mail of the IMAP accounts in `Config::mail_accounts`. Each account keeps a
+//! connection on a background thread and waits in IMAP IDLE, so new mail is counted as soon
+//! as the server announces it, without polling. Passwords are looked up in the system keyring
+//! through `secret-tool`, under the attributes `service eos-mail`, `host` and `user`.
+
+use std::any::TypeId;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use cosmic::iced::Subscription;
+use tokio::sync::mpsc;
+
+use crate::config::MailAccount;
+use crate::{util, worker, Message};
+
+/// Time before connecting again after the connection failed or dropped
+const RETRY_INTERVAL:Duration = Duration::from_secs(60);
+
+/// Log in and send the number of unread messages whenever the server reports a change, until
+/// the connection fails or the receiver is dropped
+fn watch(account:&MailAccount, tx:&mpsc::UnboundedSender<Option<usize>>)->Result<(), String>{
+    let tls = native_tls::TlsConnector::new().map_err(|err| err.to_string())?;
+    let client = imap::connect((account.host.as_str(), account.port), &account.host, &tls)
+        .map_err(|err| format!("failed to connect to {}: {}", account.host, err))?;
+    let mut session = client.login(&account.user, util::password("eos-mail", &account.host, &account.user)?)
+        .map_err(|(err, _)| format!("failed to log in as {}: {}", account.user, err))?;
+    session.select(&account.mailbox).map_err(|err| format!("failed to open {}: {}", account.mailbox, err))?;
+    loop {
+        let unread = session.search("UNSEEN").map_err(|err| err.to_string())?.len();
+        if tx.send(Some(unread)).is_err() {
+            let _ = session.logout();
+            return Ok(());
+        }
+        // returns on any change of the mailbox, and is renewed before servers time it out
+        session.idle().and_then(|idle| idle.wait_keepalive()).map_err(|err| err.to_string())?;
+    }
+}
+
+/// Emits the number of unread messages of the account, the `index` of `Config::mail_accounts`,
+/// or `None` while it cannot be reached, see [`Message::MailUnread`]
+pub fn listen(index:usize, account:MailAccount)->Subscription<Message>{
+    struct MailSubscription;
+    worker::listen(
+        (TypeId::of::<MailSubscription>(), index, account.clone()),
+        move |unread| Message::MailUnread(index, unread),
+        move |tx| worker::retry(&tx, RETRY_INTERVAL, &format!("mail account {}", account.label), |tx| watch(&account, tx)),
+    )
+}
+
+/// Start the mail client, a command with arguments like `thunderbird -mail`
+pub fn open_client(command:&str){
+    let Some(mut args) = shlex::split(command).filter(|args| !args.is_empty()) else {
+        log::warn!("invalid mail client command {:?}", command);
+        return;
+    };
+    let program = args.remove(0);
+    match Command::new(&program).args(args).stdin(Stdio::null()).spawn() {
+        // waited for, so it does not linger as a zombie once closed
+        Ok(mut child) => {std::thread::spawn(move || child.wait());},
+        Err(err) => log::warn!("failed to start the mail client {}: {}", program, err),
+    }
+}
@@ -4,6 +4,7 @@
 use alacritty_terminal::tty::Options;
 use alacritty_terminal::{event::Event as TermEvent, term, term::color::Colors as TermColors, tty};
 use chrono::{DateTime, Local};
+use clap::Parser;
 use cosmic::iced::clipboard::dnd::DndAction;
 use cosmic::iced_widget::{column, container, row};
 use cosmic::widget::menu::action::MenuAction;
@@ -21,7 +22,7 @@ use cosmic::{
         futures::SinkExt,
         keyboard::{Event as KeyEvent, Key, Modifiers},
         mouse::{Button as MouseButton, Event as MouseEvent},
-        stream, window, Alignment, Color, Event, Length, Limits, Padding, Point, Subscription,
+        stream, window, Alignment, Color, Event, Length, Limits, Padding, Point, Size, Subscription,
     },
     style,
     widget::{self, button, pane_grid, segmented_button, PaneGrid},
@@ -30,27 +31,92 @@ use cosmic::{
 use cosmic_files::dialog::{Dialog, DialogKind, DialogMessage, DialogResult};
 use cosmic_text::{fontdb::FaceInfo, Family, Stretch, Weight};
 use localize::LANGUAGE_SORTER;
-use resource_monitor::{ProcessBy, ResourceMonitor};
-use shader::{FragmentShaderProgram, FRAME_TIME};
-use std::time::Duration;
+use resource_monitor::{GraphRange, ProcessBy, ResourceMonitor, HIDDEN_UPDATE_INTERVAL};
+use serde::{Deserialize, Serialize};
+use shader::{FragmentShaderProgram, IDLE_FRAME_TIME};
+use std::time::{Duration, Instant};
 use std::{
     any::TypeId,
     cmp,
     collections::{BTreeMap, BTreeSet, HashMap},
-    env, fs, process,
+    env,
+    ffi::OsString,
+    fs,
+    path::PathBuf,
+    process,
     rc::Rc,
-    sync::{atomic::Ordering, Mutex},
+    sync::{atomic::Ordering, Arc, Mutex},
 };
 use tokio::sync::mpsc;
 
 use config::{
-    AppTheme, ColorScheme, ColorSchemeId, ColorSchemeKind, Config, Profile, ProfileId, CONFIG_VERSION, DEFAULT_FONT
+    AppTheme, ByteUnit, ClockFormat, ColorScheme, ColorSchemeId, ColorSchemeKind, Config, Profile, ProfileId, MetricLogFormat, MonitorLayout, MonitorPage, Scene, Section, State, TemperatureUnit, WidgetAnchor,
+    CONFIG_VERSION, DEFAULT_FONT, DEFAULT_FONT_NAME
 };
+use desktop_widget::DesktopWidgets;
 
+mod animation;
 mod shader;
+mod capture;
+mod audio;
+mod sun;
+mod weather;
+mod agenda;
+mod feed;
+mod github;
+mod todo;
+mod ticker;
+#[cfg(feature = "wayland")]
+mod keyboard;
+mod quote;
+mod remote;
+mod alarm;
+mod alert;
+mod notification;
+mod pomodoro;
+mod wal;
+mod particles;
+mod render_scale;
+mod shadertoy;
+mod graph_shader;
 mod resource_monitor;
+mod history;
+mod metric_store;
+mod prometheus;
+mod metric_log;
+mod push;
+mod export;
+mod accessible;
+mod touch;
+mod util;
+mod worker;
 
 mod config;
+mod config_file;
+mod desktop_widget;
+mod install;
+mod overlay;
+mod pip;
+#[cfg(unix)]
+mod signals;
+#[cfg(feature = "tray")]
+mod tray;
+#[cfg(feature = "mpris")]
+mod media;
+#[cfg(feature = "mail")]
+mod mail;
+#[cfg(feature = "dbus")]
+mod dbus;
+#[cfg(unix)]
+mod ipc;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "agent")]
+mod agent;
+#[cfg(feature = "wayland")]
+mod workspaces;
+#[cfg(feature = "wayland")]
+mod screen_time;
 mod mouse_reporter;
 
 use icon_cache::IconCache;
@@ -85,34 +151,147 @@ pub fn icon_cache_get(name: &'static str, size: u16) -> widget::icon::Icon {
     icon_cache.get(name, size)
 }
 
+/// Terminal with a resource monitor and a shader background
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    /// Config file to read instead of ~/.config/eos/eos.toml
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Profile of `config_profiles` to use instead of selecting one
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+    /// Don't render the background shader
+    #[arg(long)]
+    no_shader: bool,
+    /// Milliseconds between redraws of the background
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..=1000))]
+    refresh_ms: Option<u16>,
+    /// Name of the output the window is shown on, for `output_backgrounds`
+    #[arg(long, value_name = "OUTPUT", alias = "output")]
+    monitor: Option<String>,
+    /// Show the overlay with the CPU and GPU readout above all windows from the start
+    #[arg(long)]
+    overlay: bool,
+    /// Show or hide the overlay of the running eos and exit
+    #[cfg(unix)]
+    #[arg(long)]
+    toggle_overlay: bool,
+    /// Let the pointer interact with the click-through desktop widget of the running eos, or
+    /// stop it, and exit
+    #[cfg(unix)]
+    #[arg(long)]
+    toggle_widget_input: bool,
+    /// Start eos on login with the other flags given, through a systemd user unit and an
+    /// autostart entry, and exit
+    #[arg(long, conflicts_with = "uninstall")]
+    install: bool,
+    /// Stop starting eos on login and exit
+    #[arg(long)]
+    uninstall: bool,
+    /// Serve the metrics of this machine to the eos instances listing it in `agents`, without a
+    /// window, at `agent_address`
+    #[cfg(feature = "agent")]
+    #[arg(long)]
+    agent: bool,
+    /// Don't fork into the background
+    #[arg(long = "no-daemon", hide = true)]
+    _no_daemon: bool,
+    /// Accepted before the command for compatibility with other terminals
+    #[arg(short = 'e', long = "command")]
+    _command: bool,
+    /// Program to run instead of the shell, followed by its arguments
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true, value_name = "COMMAND")]
+    shell: Vec<String>,
+}
+
+/// Width of the side bar at the default monitor font size
+const SIDEBAR_WIDTH:f32 = 260.;
+/// Narrowest terminal next to the side bar
+const MIN_TERMINAL_WIDTH:f32 = 100.;
+
+/// Width of the side bar with the monitor and the shader, widened so the graphs and the process
+/// list fit in larger monitor fonts and two columns. A desktop `widget` is as wide as its
+/// `Config::widget_width` if that is set.
+fn sidebar_width(config:&Config, widget:bool)->f32{
+    if widget && config.widget_width > 0 && config.widget_anchor != WidgetAnchor::Fill {
+        return f32::from(config.widget_width);
+    }
+    SIDEBAR_WIDTH.max(resource_monitor::min_width(config.monitor_font_size, config.monitor_layout))
+}
+
+impl Cli {
+    /// Flags to start eos with on login, see `--install`
+    fn login_args(&self)->Vec<String>{
+        let mut args = Vec::new();
+        if let Some(path) = &self.config {
+            // the unit runs from another directory
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.clone());
+            args.extend(["--config".to_string(), path.to_string_lossy().into_owned()]);
+        }
+        if let Some(profile) = &self.profile {
+            args.extend(["--profile".to_string(), profile.clone()]);
+        }
+        if self.no_shader {
+            args.push("--no-shader".to_string());
+        }
+        if let Some(refresh_ms) = self.refresh_ms {
+            args.extend(["--refresh-ms".to_string(), refresh_ms.to_string()]);
+        }
+        if let Some(monitor) = &self.monitor {
+            args.extend(["--monitor".to_string(), monitor.clone()]);
+        }
+        if self.overlay {
+            args.push("--overlay".to_string());
+        }
+        #[cfg(feature = "agent")]
+        if self.agent {
+            args.push("--agent".to_string());
+        }
+        args
+    }
+}
+
 /// Runs application with these settings
 #[rustfmt::skip]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut shell_program_opt = None;
-    let mut shell_args = Vec::new();
-    let mut parse_flags = true;
-    let mut daemonize = false;
-    for arg in env::args().skip(1) {
-        if parse_flags {
-            match arg.as_str() {
-                // These flags indicate the end of parsing flags
-                "-e" | "--command" | "--" => {
-                    parse_flags = false;
-                }
-                "--no-daemon" => {
-                    daemonize = false;
-                }
-                _ => {
-                    //TODO: should this throw an error?
-                    log::warn!("ignored argument {:?}", arg);
-                }
-            }
-        } else if shell_program_opt.is_none() {
-            shell_program_opt = Some(arg);
-        } else {
-            shell_args.push(arg);
+    // `eos ctl <command>` talks to the running eos, see `ipc::client`
+    #[cfg(unix)]
+    if std::env::args().nth(1).as_deref() == Some("ctl") {
+        let command:Vec<String> = std::env::args().skip(2).collect();
+        if let Err(err) = ipc::client(&command) {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    let cli = Cli::parse();
+    if cli.install || cli.uninstall {
+        let result = if cli.install {install::install(&cli.login_args())} else {install::uninstall()};
+        if let Err(err) = result {
+            eprintln!("failed to set up starting on login: {}", err);
+            process::exit(1);
         }
+        return Ok(());
     }
+    #[cfg(unix)]
+    if cli.toggle_overlay || cli.toggle_widget_input {
+        let signal = if cli.toggle_overlay {sysinfo::Signal::User1} else {sysinfo::Signal::User2};
+        if signals::send_running(signal) == 0 {
+            eprintln!("eos is not running");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    let mut shell = cli.shell.into_iter();
+    let shell_program_opt = shell.next();
+    let shell_args:Vec<String> = shell.collect();
+    let daemonize = false;
+    config_file::set_overrides(config_file::Overrides {
+        path: cli.config,
+        config_profile: cli.profile,
+        frame_time_ms: cli.refresh_ms,
+    });
 
     #[cfg(all(unix, not(target_os = "redox")))]
     if daemonize {
@@ -132,18 +311,44 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let (config_handler, config) = match cosmic_config::Config::new(App::APP_ID, CONFIG_VERSION) {
         Ok(config_handler) => {
-            let config = match Config::get_entry(&config_handler) {
+            let mut config = match Config::get_entry(&config_handler) {
                 Ok(ok) => ok,
                 Err((errs, config)) => {
                     log::info!("errors loading config: {:?}", errs);
                     config
                 }
             };
+            config_file::load(&mut config, resource_monitor::on_battery());
             (Some(config_handler), config)
         }
         Err(err) => {
             log::error!("failed to create config handler: {}", err);
-            (None, Config::default())
+            let mut config = Config::default();
+            config_file::load(&mut config, resource_monitor::on_battery());
+            (None, config)
+        }
+    };
+    #[cfg(feature = "agent")]
+    if cli.agent {
+        if let Err(err) = agent::serve(&config) {
+            eprintln!("failed to serve the metrics: {}", err);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // the window geometry of the last run
+    let (state_handler, state) = match cosmic_config::Config::new_state(App::APP_ID, CONFIG_VERSION) {
+        Ok(state_handler) => {
+            let state = State::get_entry(&state_handler).unwrap_or_else(|(errs, state)| {
+                log::info!("errors loading state: {:?}", errs);
+                state
+            });
+            (Some(state_handler), state)
+        }
+        Err(err) => {
+            log::warn!("failed to create state handler: {}", err);
+            (None, State::default())
         }
     };
 
@@ -167,15 +372,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     settings = settings
         .theme(config.app_theme.theme())
         .size_limits(
-            Limits::NONE.min_width(360.0).min_height(180.0)
+            Limits::NONE.min_width(if config.compact {
+                resource_monitor::compact_width(config.monitor_font_size)
+            } else {
+                sidebar_width(&config, false) + MIN_TERMINAL_WIDTH
+            }).min_height(180.0)
         )
         .default_font(DEFAULT_FONT);
+    if let Some((width, height)) = state.window_size {
+        settings = settings.size(Size::new(width as f32, height as f32));
+    }
+    // the desktop widget is a layer surface opened by the app instead of the main window
+    if desktop_widget::enabled(&config) {
+        settings = settings.no_main_window(true);
+    }
 
     let flags = Flags {
         config_handler,
         config,
+        state_handler,
+        state,
         startup_options,
         term_config,
+        output: cli.monitor,
+        no_shader: cli.no_shader,
+        overlay: cli.overlay,
     };
     cosmic::app::run::<App>(settings, flags)?;
 
@@ -186,18 +407,28 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 pub struct Flags {
     config_handler: Option<cosmic_config::Config>,
     config: Config,
+    state_handler: Option<cosmic_config::Config>,
+    state: State,
     startup_options: Option<tty::Options>,
     term_config: term::Config,
+    output: Option<String>,
+    no_shader: bool,
+    overlay: bool,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Commands of the menus and key bindings, which can be bound to keys in `Config::key_bindings`
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Action {
     About,
     ClearScrollback,
     ColorSchemes(ColorSchemeKind),
+    ConfigProfileNext,
     Copy,
     CopyOrSigint,
     CopyPrimary,
+    ExportBackground,
+    ExportMetrics,
+    ExportMetricsGraphs,
     Find,
     PaneFocusDown,
     PaneFocusLeft,
@@ -208,10 +439,20 @@ pub enum Action {
     PaneToggleMaximized,
     Paste,
     PastePrimary,
+    PauseUpdates,
+    ProcessDetail,
+    ProcessKill,
+    ProcessSelectNext,
+    ProcessSelectPageDown,
+    ProcessSelectPageUp,
+    ProcessSelectPrev,
+    ProcessSortNext,
     ProfileOpen(ProfileId),
     Profiles,
+    SceneNext,
     SelectAll,
     Settings,
+    ShaderTweaks,
     ShowHeaderBar(bool),
     TabActivate0,
     TabActivate1,
@@ -227,6 +468,9 @@ pub enum Action {
     TabNewNoProfile,
     TabNext,
     TabPrev,
+    ToggleCompact,
+    ToggleOverlay,
+    TogglePip,
     WindowClose,
     WindowNew,
     ZoomIn,
@@ -242,9 +486,13 @@ impl Action {
             Self::ColorSchemes(color_scheme_kind) => {
                 Message::ToggleContextPage(ContextPage::ColorSchemes(*color_scheme_kind))
             }
+            Self::ConfigProfileNext => Message::ConfigProfileNext,
             Self::Copy => Message::Copy(entity_opt),
             Self::CopyOrSigint => Message::CopyOrSigint(entity_opt),
             Self::CopyPrimary => Message::CopyPrimary(entity_opt),
+            Self::ExportBackground => Message::ExportBackground,
+            Self::ExportMetrics => Message::ExportMetrics(false),
+            Self::ExportMetricsGraphs => Message::ExportMetrics(true),
             Self::Find => Message::Find(true),
             Self::PaneFocusDown => Message::PaneFocusAdjacent(pane_grid::Direction::Down),
             Self::PaneFocusLeft => Message::PaneFocusAdjacent(pane_grid::Direction::Left),
@@ -255,10 +503,20 @@ impl Action {
             Self::PaneToggleMaximized => Message::PaneToggleMaximized,
             Self::Paste => Message::Paste(entity_opt),
             Self::PastePrimary => Message::PastePrimary(entity_opt),
+            Self::PauseUpdates => Message::PauseUpdates,
+            Self::ProcessDetail => Message::ProcessDetail,
+            Self::ProcessKill => Message::ProcessKill,
+            Self::ProcessSelectNext => Message::ProcessSelectNext,
+            Self::ProcessSelectPageDown => Message::ProcessSelectPage(true),
+            Self::ProcessSelectPageUp => Message::ProcessSelectPage(false),
+            Self::ProcessSelectPrev => Message::ProcessSelectPrev,
+            Self::ProcessSortNext => Message::ProcessSortNext,
             Self::ProfileOpen(profile_id) => Message::ProfileOpen(*profile_id),
             Self::Profiles => Message::ToggleContextPage(ContextPage::Profiles),
+            Self::SceneNext => Message::SceneNext,
             Self::SelectAll => Message::SelectAll(entity_opt),
             Self::Settings => Message::ToggleContextPage(ContextPage::Settings),
+            Self::ShaderTweaks => Message::ToggleContextPage(ContextPage::ShaderTweaks),
             Self::ShowHeaderBar(show_headerbar) => Message::ShowHeaderBar(*show_headerbar),
             Self::TabActivate0 => Message::TabActivateJump(0),
             Self::TabActivate1 => Message::TabActivateJump(1),
@@ -274,6 +532,9 @@ impl Action {
             Self::TabNewNoProfile => Message::TabNewNoProfile,
             Self::TabNext => Message::TabNext,
             Self::TabPrev => Message::TabPrev,
+            Self::ToggleCompact => Message::ToggleCompact,
+            Self::ToggleOverlay => Message::ToggleOverlay,
+            Self::TogglePip => Message::TogglePip,
             Self::WindowClose => Message::WindowClose,
             Self::WindowNew => Message::WindowNew,
             Self::ZoomIn => Message::ZoomIn,
@@ -294,16 +555,33 @@ impl MenuAction for Action {
 #[derive(Clone, Debug)]
 pub enum TickType{
     ResourceUpdate,
+    MemoryUpdate,
     VisualUpdate,
     ClockUpdate,
     ProcessUpdate,
+    HistorySave,
 }
 
 /// Messages that are used specifically by our [`App`].
 #[derive(Clone, Debug)]
 pub enum Message {
+    /// Upcoming events of the calendars in `Config::agenda_sources`
+    Agenda(Vec<agenda::Event>),
+    /// Status of the agent at an index of `Config::agents`, `None` while it cannot be reached,
+    /// see [`Section::Agents`]
+    #[cfg(feature = "agent")]
+    Agent(usize, Option<agent::Status>),
+    /// Show the agent at an index of `Config::agents` in [`Section::Agents`]
+    #[cfg(feature = "agent")]
+    AgentSelect(usize),
     AppTheme(AppTheme),
+    /// Bar levels of the audio spectrum in 0 to 1, see [`Section::Spectrum`]
+    AudioSpectrum(Vec<f32>),
+    ByteUnit(usize),
     ClearScrollback(Option<segmented_button::Entity>),
+    ClockDate(bool),
+    ClockFormat(usize),
+    ClockSeconds(bool),
     ColorSchemeCollapse,
     ColorSchemeDelete(ColorSchemeKind, ColorSchemeId),
     ColorSchemeExpand(ColorSchemeKind, Option<ColorSchemeId>),
@@ -314,10 +592,16 @@ pub enum Message {
     ColorSchemeRename(ColorSchemeKind, ColorSchemeId, String),
     ColorSchemeRenameSubmit,
     ColorSchemeTabActivate(widget::segmented_button::Entity),
+    Compact(bool),
     Config(Config),
+    ConfigFileChanged,
+    ConfigProfile(usize),
+    ConfigProfileNext,
     Copy(Option<segmented_button::Entity>),
     CopyOrSigint(Option<segmented_button::Entity>),
     CopyPrimary(Option<segmented_button::Entity>),
+    #[cfg(feature = "dbus")]
+    DBusReady(dbus::Handle),
     DefaultBoldFontWeight(usize),
     DefaultDimFontWeight(usize),
     DefaultFont(usize),
@@ -327,17 +611,63 @@ pub enum Message {
     DefaultZoomStep(usize),
     DialogMessage(DialogMessage),
     Drop(Option<(pane_grid::Pane, segmented_button::Entity, DndDrop)>),
+    ExportBackground,
+    ExportMetrics(bool),
     Find(bool),
     FindNext,
     FindPrevious,
     FindSearchValueChanged(String),
+    /// Weather of [`Section::Weather`]
+    Forecast(weather::Forecast),
+    FrameRate(usize),
     MiddleClick(pane_grid::Pane, Option<segmented_button::Entity>),
     FocusFollowMouse(bool),
+    /// Notifications and workflow runs of `Config::github_repos`
+    GitHub(github::Status),
+    GraphHover(u64, Option<f32>),
+    GraphRange(GraphRange),
+    /// Headlines of the feeds in `Config::feeds`
+    Headlines(Vec<feed::Headline>),
+    IdleThrottling(bool),
+    /// The control socket is open, see `Config::control_socket`
+    #[cfg(unix)]
+    IpcReady(ipc::Handle),
     Key(Modifiers, Key),
+    /// Keyboard layouts and lock keys, see [`Section::Keyboard`]
+    #[cfg(feature = "wayland")]
+    Keyboard(keyboard::State),
     LaunchUrl(String),
+    /// Track of the active media player, see [`Section::Media`]
+    #[cfg(feature = "mpris")]
+    Media(Option<media::NowPlaying>),
+    /// Cover art of the track, `None` while it has none or it failed to load
+    #[cfg(feature = "mpris")]
+    MediaArt(Option<media::Art>),
+    #[cfg(feature = "mpris")]
+    MediaControl(media::Control),
+    #[cfg(feature = "mpris")]
+    MediaReady(media::Handle),
+    /// Start `Config::mail_client`
+    #[cfg(feature = "mail")]
+    MailOpen,
+    /// Unread messages of the account at an index of `Config::mail_accounts`, `None` while it
+    /// cannot be reached
+    #[cfg(feature = "mail")]
+    MailUnread(usize, Option<usize>),
     Modifiers(Modifiers),
+    MonitorFont(usize),
+    MonitorFontSize(usize),
+    MonitorLayout(usize),
+    MonitorPage(MonitorPage),
+    MonitorTabs(bool),
     MouseEnter(pane_grid::Pane),
     Opacity(u8),
+    /// An output was connected, renamed or disconnected, which moves the desktop widgets
+    #[cfg(feature = "wayland")]
+    Output(
+        iced::event::wayland::OutputEvent,
+        cosmic::cctk::wayland_client::protocol::wl_output::WlOutput,
+    ),
     PaneClicked(pane_grid::Pane),
     PaneDragged(pane_grid::DragEvent),
     PaneFocusAdjacent(pane_grid::Direction),
@@ -347,7 +677,23 @@ pub enum Message {
     Paste(Option<segmented_button::Entity>),
     PastePrimary(Option<segmented_button::Entity>),
     PasteValue(Option<segmented_button::Entity>, String),
+    PauseUpdates,
+    PipPress,
+    PomodoroReset,
+    PomodoroStartPause,
+    ProcessDetail,
+    ProcessKill,
+    ProcessKillCancel,
+    ProcessKillConfirm,
+    ProcessKillRequest(resource_monitor::KillRequest),
+    ProcessListScrolled(bool),
+    ProcessSelect(OsString),
+    ProcessSelectNext,
+    ProcessSelectPage(bool),
+    ProcessSelectPrev,
     ProcessSortBy(ProcessBy),
+    ProcessSortDefault(usize),
+    ProcessSortNext,
     ProfileCollapse(ProfileId),
     ProfileCommand(ProfileId, String),
     ProfileDirectory(ProfileId, String),
@@ -359,9 +705,29 @@ pub enum Message {
     ProfileRemove(ProfileId),
     ProfileSyntaxTheme(ProfileId, ColorSchemeKind, usize),
     ProfileTabTitle(ProfileId, String),
+    /// Quote drawn for [`Section::Quote`], `None` while there is none
+    Quote(Option<quote::Quote>),
+    /// Prices of `Config::tickers`
+    Quotes(Vec<ticker::Entry>),
+    Refresh,
+    /// Sample of the host at an index of `Config::remote_hosts`, `None` while it cannot be
+    /// reached, see [`Section::Remote`]
+    Remote(usize, Option<remote::Status>),
+    Scene(usize),
+    SceneNext,
+    /// Applications focused today with their time, see [`Section::ScreenTime`]
+    #[cfg(feature = "wayland")]
+    ScreenTime(Vec<(String, Duration)>),
+    SectionCollapse(Section),
+    SectionToggle(Section, bool),
     SelectAll(Option<segmented_button::Entity>),
+    ShaderChanged,
+    ShaderHue(u16),
+    ShaderIntensity(u16),
+    ShaderSpeed(u16),
     ShowAdvancedFontSettings(bool),
     ShowHeaderBar(bool),
+    SunMoon(bool),
     SyntaxTheme(ColorSchemeKind, usize),
     SystemThemeChange,
     TabActivate(segmented_button::Entity),
@@ -373,16 +739,45 @@ pub enum Message {
     TabNewNoProfile,
     TabNext,
     TabPrev,
+    TemperatureUnit(usize),
     TermEvent(pane_grid::Pane, segmented_button::Entity, TermEvent),
     TermEventTx(mpsc::UnboundedSender<(pane_grid::Pane, segmented_button::Entity, TermEvent)>),
+    TextureChanged,
+    /// Image or video decoded for texture `i` at the path, see `FragmentShaderProgram::set_texture`
+    TextureDecoded(usize, String, Result<Arc<animation::Animation>, String>),
     Tick(TickType),
+    /// Pending tasks of `Config::todo_backend`
+    Todo(Vec<todo::Task>),
+    TodoComplete(todo::Task),
+    ToggleCalendar,
+    ToggleCompact,
     ToggleContextPage(ContextPage),
+    ToggleOverlay,
+    TogglePip,
+    ToggleWidgetInteraction,
+    #[cfg(feature = "tray")]
+    ToggleWindow,
+    TouchTargets(bool),
+    #[cfg(feature = "tray")]
+    TrayReady(tray::Handle),
     UpdateDefaultProfile((bool, ProfileId)),
     UseBrightBold(bool),
+    WalPaletteChanged,
+    Weather([f32; 3]),
     WindowClose,
     WindowNew,
     WindowFocused,
+    WindowMoved(window::Id, Point),
+    WindowResized(window::Id, Size),
     WindowUnfocused,
+    /// Switch to the workspace at an index of a group, see [`Section::Workspaces`]
+    #[cfg(feature = "wayland")]
+    WorkspaceActivate((usize, usize)),
+    /// Workspaces of each group of outputs
+    #[cfg(feature = "wayland")]
+    Workspaces(Vec<Vec<workspaces::Workspace>>),
+    #[cfg(feature = "wayland")]
+    WorkspacesReady(workspaces::Handle),
     ZoomIn,
     ZoomOut,
     ZoomReset,
@@ -394,6 +789,7 @@ pub enum ContextPage {
     ColorSchemes(ColorSchemeKind),
     Profiles,
     Settings,
+    ShaderTweaks,
 }
 
 /// The [`App`] stores application-specific state.
@@ -402,6 +798,10 @@ pub struct App {
     pane_model: TerminalPaneGrid,
     config_handler: Option<cosmic_config::Config>,
     config: Config,
+    state_handler: Option<cosmic_config::Config>,
+    /// Window geometry, written to the state when it changed by the next clock tick
+    state: State,
+    state_changed: bool,
     key_binds: HashMap<KeyBind, Action>,
     app_themes: Vec<String>,
     font_names: Vec<String>,
@@ -416,6 +816,18 @@ pub struct App {
     curr_font_stretches: Vec<Stretch>,
     zoom_step_names: Vec<String>,
     zoom_steps: Vec<u16>,
+    scene_names: Vec<String>,
+    frame_rate_names: Vec<String>,
+    frame_times: Vec<u16>,
+    temperature_unit_names: Vec<String>,
+    byte_unit_names: Vec<String>,
+    clock_format_names: Vec<String>,
+    /// Automatic selection followed by the names of `Config::config_profiles`
+    config_profile_names: Vec<String>,
+    /// Profile in effect after the last config update, to notice when another one is selected automatically
+    active_profile: Option<String>,
+    monitor_layout_names: Vec<String>,
+    process_sort_names: Vec<String>,
     theme_names_dark: Vec<String>,
     theme_names_light: Vec<String>,
     themes: HashMap<(String, ColorSchemeKind), TermColors>,
@@ -438,8 +850,91 @@ pub struct App {
     show_advanced_font_settings: bool,
     modifiers: Modifiers,
     frag_shader_program: FragmentShaderProgram,
+    /// Output the window was started on with `--monitor`, see `Config::output_backgrounds`
+    output: Option<String>,
     resource_monitor:ResourceMonitor,
     current_time:DateTime<Local>,
+    window_focused: bool,
+    /// Last key press, terminal output or focus change, which restores the full frame rate
+    last_activity: Instant,
+    /// Layer surfaces the monitor is drawn on instead of the main window in desktop widget mode
+    desktop_widgets: Option<DesktopWidgets>,
+    /// Layer surface of the overlay while it is shown
+    overlay: Option<window::Id>,
+    /// Surface of the picture-in-picture mini monitor while it is shown
+    pip: Option<window::Id>,
+    /// Upcoming events of `Config::agenda_sources` in the order they start
+    agenda: Vec<agenda::Event>,
+    /// Latest status of each agent of `Config::agents` by index, and the one shown in
+    /// [`Section::Agents`]
+    #[cfg(feature = "agent")]
+    agents: HashMap<usize, Option<agent::Status>>,
+    #[cfg(feature = "agent")]
+    agent_selected: usize,
+    /// Headlines of `Config::feeds`, the newest first
+    headlines: Vec<feed::Headline>,
+    /// Last poll of the GitHub API for [`Section::GitHub`]
+    github: Option<github::Status>,
+    /// Unread messages by index of `Config::mail_accounts`, see [`Section::Mail`]
+    #[cfg(feature = "mail")]
+    mail_unread: HashMap<usize, Option<usize>>,
+    /// Latest sample of each host of `Config::remote_hosts` by index, see [`Section::Remote`]
+    remote: HashMap<usize, Option<remote::Status>>,
+    /// Pending tasks for [`Section::Todo`] in the order they are shown
+    todo: Vec<todo::Task>,
+    /// Last prices for [`Section::Ticker`]
+    quotes: Vec<ticker::Entry>,
+    /// Layouts and lock keys while [`Section::Keyboard`] is shown
+    #[cfg(feature = "wayland")]
+    keyboard: keyboard::State,
+    /// Workspaces of each group and the handle switching them, while [`Section::Workspaces`]
+    /// is shown
+    #[cfg(feature = "wayland")]
+    workspaces: Vec<Vec<workspaces::Workspace>>,
+    #[cfg(feature = "wayland")]
+    workspaces_handle: Option<workspaces::Handle>,
+    /// Applications focused today with their time, the longest first, see
+    /// [`Section::ScreenTime`]
+    #[cfg(feature = "wayland")]
+    screen_time: Vec<(String, Duration)>,
+    /// Quote drawn last for [`Section::Quote`]
+    quote: Option<quote::Quote>,
+    /// Bar levels of the audio spectrum while [`Section::Spectrum`] is shown
+    spectrum: Vec<f32>,
+    /// Last forecast for [`Section::Weather`]
+    forecast: Option<weather::Forecast>,
+    pomodoro: pomodoro::Pomodoro,
+    /// State of the rules of `Config::alerts`
+    alerts: alert::Alerts,
+    /// Endpoint at `Config::prometheus_address`, while it is set and could be bound
+    prometheus: Option<prometheus::Exporter>,
+    /// Writer of the metric log, while `Config::metric_log` is not `Off`
+    metric_log: Option<metric_log::Logger>,
+    /// Exporter pushing to `Config::push_address`, while it is set
+    push: Option<push::Pusher>,
+    /// Publisher to `Config::mqtt_broker`, while it is set
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<mqtt::Publisher>,
+    /// The main window was hidden from the tray icon
+    #[cfg(feature = "tray")]
+    window_hidden: bool,
+    /// Tray icon and the status it shows, while `Config::tray_icon` is set
+    #[cfg(feature = "tray")]
+    tray: Option<(tray::Handle, tray::Status)>,
+    /// Service on the session bus and the status it shows, while `Config::dbus_service` is set
+    #[cfg(feature = "dbus")]
+    dbus: Option<dbus::Handle>,
+    /// Control socket and the status it answers with, while `Config::control_socket` is set
+    #[cfg(unix)]
+    ipc: Option<ipc::Handle>,
+    /// Track of the active media player and the handle controlling it, while
+    /// [`Section::Media`] is shown
+    #[cfg(feature = "mpris")]
+    media: Option<media::NowPlaying>,
+    #[cfg(feature = "mpris")]
+    media_art: Option<media::Art>,
+    #[cfg(feature = "mpris")]
+    media_handle: Option<media::Handle>,
 }
 
 impl App {
@@ -510,9 +1005,262 @@ impl App {
         }
     }
 
+    /// Redraw the background at the configured rate, or only once a second while idle
+    fn update_frame_time(&mut self) {
+        /// Time without input after which the background may be throttled
+        const IDLE_DELAY: Duration = Duration::from_secs(5);
+        let idle = self.last_activity.elapsed() > IDLE_DELAY
+            && (!self.window_focused || self.resource_monitor.on_battery());
+        let frame_time = if self.config.idle_throttling && idle {
+            IDLE_FRAME_TIME
+        } else {
+            Duration::from_millis(self.config.frame_time_ms.max(1).into())
+        };
+        self.frag_shader_program.set_frame_time(frame_time);
+    }
+
+    /// Restore the full frame rate right away after input
+    fn mark_activity(&mut self) {
+        self.last_activity = Instant::now();
+        if self.frag_shader_program.frame_time() == IDLE_FRAME_TIME {
+            self.update_frame_time();
+        }
+    }
+
+    /// Resource monitor with the shader between its sections and the process list, next to the
+    /// terminal or on its own as the desktop widget
+    fn view_sidebar(&self) -> Element<Message> {
+        let width = sidebar_width(&self.config, self.desktop_widgets.is_some());
+        // fall back to the plain terminal background if the GPU cannot render the shader or it is turned off
+        let shader: Element<_> = if !self.frag_shader_program.rendered() {
+            widget::Space::new(Length::Fixed(width), Length::Fixed(width)).into()
+        } else {
+            crate::iced::widget::shader(&self.frag_shader_program)
+                .width(Length::Fixed(width))
+                .height(Length::Fixed(width))
+                .into()
+        };
+        // show why a custom shader failed to load on top of the shader that is still running
+        let shader: Element<_> = match self.frag_shader_program.error() {
+            Some(err) => iced::widget::stack![
+                shader,
+                widget::scrollable(
+                    widget::text(err.to_string())
+                        .size(10)
+                        .class(cosmic::theme::Text::Color(
+                            cosmic::theme::active().cosmic().destructive_color().into()
+                        ))
+                ).width(Length::Fixed(width)).height(Length::Fixed(width)),
+            ].into(),
+            None => shader.into(),
+        };
+
+        let [r, g, b, a] = get_term_bg_colour(&self.config);
+        let bg_container_style = container::Style{ 
+            background: Some(
+                iced::Background::Color(Color {r,g,b,a,})), 
+            ..container::Style::default()
+        };
+
+        // the compact mode replaces the side bar and the terminal
+        if self.config.compact {
+            return container(self.resource_monitor.view_compact(self))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(move |_theme| {bg_container_style})
+                .into();
+        }
+
+        // the page chosen with the tabs, the overview without them
+        let page = if self.config.monitor_tabs {self.config.monitor_page} else {MonitorPage::Overview};
+        let tabs = self.config.monitor_tabs.then(|| {
+            let style = bg_container_style.clone();
+            container(self.resource_monitor.view_page_tabs(page))
+                .width(Length::Fill)
+                .style(move |_theme| style.clone())
+                .padding(Padding{top:10., ..Default::default()})
+        });
+        let content: Element<_> = match page {
+            MonitorPage::Overview => {
+                // resource monitor
+                let monitor = self.resource_monitor.view_monitor(&self);
+                let processes = if self.config.sections.contains(&Section::Processes) {
+                    self.resource_monitor.view_processes()
+                } else {
+                    cosmic::iced_widget::Column::new()
+                };
+
+                // piece together the side bar, or a bar along the top of the window
+                let style = bg_container_style.clone();
+                if self.config.monitor_layout == MonitorLayout::Horizontal {
+                    let processes_width = resource_monitor::min_width(self.config.monitor_font_size, MonitorLayout::OneColumn);
+                    let processes = self.config.sections.contains(&Section::Processes).then(|| container(processes)
+                        .width(Length::Fixed(processes_width))
+                        .height(Length::Fill)
+                        .style(move |_theme| {bg_container_style}));
+                    return container(column![].push_maybe(tabs).push(
+                        row![
+                            container(widget::scrollable(monitor))
+                                .width(Length::Fill)
+                                .height(Length::Fill)
+                                .style(move |_theme| {style.clone()})
+                                .padding(Padding{top:10., ..Default::default()}),
+                            shader,
+                        ]
+                        .push_maybe(processes)
+                        .height(Length::Fixed(width))
+                    ))
+                    .width(Length::Fill)
+                    .into();
+                }
+                column![
+                    container(monitor)
+                        .width(Length::Fill)
+                        .height(Length::Shrink)
+                        .style(move |_theme| {style.clone()})
+                        .padding(Padding{top:10., ..Default::default()}), 
+                    shader,
+                    container(processes)
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .style(move |_theme| {bg_container_style})
+                ].into()
+            }
+            MonitorPage::Processes => container(self.resource_monitor.view_processes())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(move |_theme| {bg_container_style})
+                .into(),
+            MonitorPage::Cores => container(widget::scrollable(self.resource_monitor.view_cores(self)))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(move |_theme| {bg_container_style})
+                .padding(Padding{top:10., ..Default::default()})
+                .into(),
+        };
+
+        // main container
+        container(column![].push_maybe(tabs).push(content))
+            .width(Length::Fixed(width)).height(Length::Fill)
+            .into()
+    }
+
+    /// Index of the selected profile in `config_profile_names`, 0 for automatic selection
+    fn config_profile_selected(&self) -> usize {
+        self.config_profile_names
+            .iter()
+            .skip(1)
+            .position(|name| *name == self.config.config_profile)
+            .map_or(0, |index| index + 1)
+    }
+
+    /// Scroll the process list so the selected process is in view
+    fn scroll_to_selected_process(&self) -> Task<Message> {
+        match self.resource_monitor.selected_fraction() {
+            Some(y) => iced::widget::scrollable::snap_to(
+                resource_monitor::process_list_id(),
+                iced::widget::scrollable::RelativeOffset { x: 0.0, y },
+            ),
+            None => Task::none(),
+        }
+    }
+
+    /// Push the current metrics, the scene and the page to the D-Bus service
+    #[cfg(feature = "dbus")]
+    fn update_dbus(&self) {
+        let Some(handle) = &self.dbus else { return };
+        handle.update(dbus::Status {
+            metrics: self.resource_monitor.metrics().collect(),
+            paused: self.resource_monitor.paused(),
+            scene: self.config.scene,
+            page: self.config.monitor_page,
+        });
+    }
+
+    /// Push the current metrics, the scene and the page to the control socket
+    #[cfg(unix)]
+    fn update_ipc(&self) {
+        let Some(handle) = &self.ipc else { return };
+        handle.update(ipc::Status {
+            metrics: self.resource_monitor.metrics().collect(),
+            paused: self.resource_monitor.paused(),
+            scene: self.config.scene,
+            page: self.config.monitor_page,
+        });
+    }
+
+    /// Push the usage summary and the state of the menu to the tray icon if they changed
+    #[cfg(feature = "tray")]
+    fn update_tray(&mut self) {
+        if self.tray.is_none() {
+            return;
+        }
+        let status = tray::Status {
+            summary: self.resource_monitor.summary(&self.config),
+            paused: self.resource_monitor.paused(),
+            window_hidden: self.window_hidden,
+            profiles: self.config_profile_names.clone(),
+            profile: self.config_profile_selected(),
+        };
+        if let Some((handle, shown)) = self.tray.as_mut() {
+            if *shown != status {
+                handle.update(status.clone());
+                *shown = status;
+            }
+        }
+    }
+
     fn update_config(&mut self) -> Task<Message> {
         let theme = self.config.app_theme.theme();
 
+        // Update resource monitor colours, from the pywal or wallust palette if it is enabled
+        let palette = if self.config.wal_palette {wal::load()} else {None};
+        self.resource_monitor.apply_theme(&theme, palette.as_ref());
+        self.frag_shader_program.update_tint(palette.as_ref());
+        #[cfg(feature = "mpris")]
+        if let Some(art) = self.media_art.as_ref().filter(|_| self.config.media_art_tint) {
+            self.frag_shader_program.set_tint(art.colour);
+        }
+        self.pomodoro.update_config(&self.config);
+        self.alerts.update_config(&self.config);
+        #[cfg(feature = "dbus")]
+        if !self.config.dbus_service {
+            self.dbus = None;
+        }
+        #[cfg(unix)]
+        if !self.config.control_socket {
+            self.ipc = None;
+        }
+        if self.prometheus.as_ref().map(|exporter| exporter.address()) != Some(&self.config.prometheus_address) {
+            // dropping the previous exporter stops it, so its port is free again
+            self.prometheus = None;
+            if !self.config.prometheus_address.is_empty() {
+                self.prometheus = prometheus::Exporter::start(&self.config.prometheus_address)
+                    .map_err(|err| log::warn!("failed to serve the metrics at {}: {}", self.config.prometheus_address, err))
+                    .ok();
+            }
+        }
+        if self.config.metric_log == MetricLogFormat::Off {
+            self.metric_log = None;
+        } else if self.metric_log.as_ref().map_or(true, |logger| logger.outdated(&self.config)) {
+            self.metric_log = Some(metric_log::Logger::start(&self.config));
+        }
+        if self.config.push_address.is_empty() {
+            self.push = None;
+        } else if self.push.as_ref().map_or(true, |pusher| pusher.outdated(&self.config)) {
+            // the previous thread ends with its samples once it is dropped
+            self.push = Some(push::Pusher::start(&self.config));
+        }
+        #[cfg(feature = "mqtt")]
+        if self.config.mqtt_broker.is_empty() {
+            self.mqtt = None;
+        } else if self.mqtt.as_ref().map_or(true, |publisher| publisher.outdated(&self.config)) {
+            self.mqtt = Some(mqtt::Publisher::start(&self.config));
+        }
+        if !self.config.pomodoro_shader {
+            self.frag_shader_program.update_focus([0.;2]);
+        }
+
         // Update color schemes
         self.update_color_schemes();
 
@@ -537,11 +1285,54 @@ impl App {
             }
         }
 
+        // Key bindings may have been changed in the config file
+        self.key_binds = key_binds(&self.config);
+
         // Set headerbar state
         self.core.window.show_headerbar = self.config.show_headerbar;
 
+        // List the profiles for the settings
+        self.config_profile_names = std::iter::once(fl!("config-profile-automatic"))
+            .chain(self.config.config_profiles.keys().cloned())
+            .collect();
+        self.active_profile = self.config.active_profile(self.resource_monitor.on_battery()).map(String::from);
+
+        // Resize metric history if the retention settings changed
+        self.resource_monitor.apply_config(&self.config);
+
+        // The graphs of the monitor only line up in one of the monospace fonts
+        let monitor_font_name = if self.config.monitor_font_name == DEFAULT_FONT_NAME
+            || self.font_name_faces_map.contains_key(&self.config.monitor_font_name)
+        {
+            self.config.monitor_font_name.as_str()
+        } else {
+            log::warn!("monitor font {:?} is not an installed monospace font", self.config.monitor_font_name);
+            DEFAULT_FONT_NAME
+        };
+        self.resource_monitor.set_font(monitor_font_name, self.config.monitor_font_size);
+
+        // Load a different background shader or texture if its path changed
+        self.frag_shader_program.update_shader(&self.config);
+        let textures = self.frag_shader_program.update_texture(&self.config);
+        self.frag_shader_program.update_location(&self.config);
+        self.update_frame_time();
+
+        // The subscription removes the tray icon when it is turned off
+        #[cfg(feature = "tray")]
+        if !self.config.tray_icon {
+            self.tray = None;
+        }
+        #[cfg(not(feature = "tray"))]
+        if self.config.tray_icon {
+            log::warn!("eos was built without the tray feature, so it cannot show the tray icon");
+        }
+
+        // Recreate the desktop widgets if they start or stop passing the pointer through
+        let widgets = self.desktop_widgets.as_mut()
+            .map_or_else(Task::none, |widgets| widgets.apply_config(&self.config));
+
         // Update application theme
-        cosmic::app::command::set_theme(theme)
+        Task::batch([textures, widgets, cosmic::app::command::set_theme(theme)])
     }
 
     fn update_render_active_pane_zoom(&mut self, zoom_message: Message) -> Task<Message> {
@@ -1037,6 +1828,28 @@ impl App {
         widget::settings::view_column(sections).into()
     }
 
+    /// Sliders for the generic shader parameters, see `Config::shader_speed_percent`
+    fn shader_tweaks(&self) -> Element<Message> {
+        widget::settings::view_column(vec![widget::settings::section()
+            .add(
+                widget::settings::item::builder(fl!("shader-speed"))
+                    .description(format!("{}%", self.config.shader_speed_percent))
+                    .control(widget::slider(0..=300, self.config.shader_speed_percent, Message::ShaderSpeed)),
+            )
+            .add(
+                widget::settings::item::builder(fl!("shader-intensity"))
+                    .description(format!("{}%", self.config.shader_intensity_percent))
+                    .control(widget::slider(0..=200, self.config.shader_intensity_percent, Message::ShaderIntensity)),
+            )
+            .add(
+                widget::settings::item::builder(fl!("shader-hue"))
+                    .description(format!("{}°", self.config.shader_hue_degrees))
+                    .control(widget::slider(0..=359, self.config.shader_hue_degrees, Message::ShaderHue)),
+            )
+            .into()])
+        .into()
+    }
+
     fn settings(&self) -> Element<Message> {
         let app_theme_selected = match self.config.app_theme {
             AppTheme::Dark => 1,
@@ -1224,10 +2037,214 @@ impl App {
                 .toggler(self.config.show_headerbar, Message::ShowHeaderBar),
         );
 
+        let config_profile_selected = self.config_profile_selected();
+        let mut config_profile_item = widget::settings::item::builder(fl!("config-profile"));
+        if self.config.config_profile.is_empty() {
+            config_profile_item = config_profile_item.description(match &self.active_profile {
+                Some(name) => fl!("config-profile-active", name = name.as_str()),
+                None => fl!("config-profile-none"),
+            });
+        }
+        let mut profile_section = widget::settings::section()
+            .title(fl!("config-profiles"))
+            .add(config_profile_item.control(widget::dropdown(
+                &self.config_profile_names,
+                Some(config_profile_selected),
+                Message::ConfigProfile,
+            )));
+        let profile_keys = self
+            .active_profile
+            .as_ref()
+            .and_then(|name| self.config.config_profiles.get(name))
+            .map(|profile| profile.keys())
+            .unwrap_or_default();
+        if !profile_keys.is_empty() {
+            profile_section = profile_section.add(
+                widget::settings::item::builder(fl!("config-profile-keys"))
+                    .control(widget::text::body(profile_keys.join(", "))),
+            );
+        }
+
+        let scene_selected = Scene::ALL.iter().position(|scene| *scene == self.config.scene);
+        let frame_rate_selected = self
+            .frame_times
+            .iter()
+            .position(|frame_time| frame_time == &self.config.frame_time_ms);
+        let background_section = widget::settings::section()
+            .title(fl!("background"))
+            .add(
+                widget::settings::item::builder(fl!("scene")).control(widget::dropdown(
+                    &self.scene_names,
+                    scene_selected,
+                    Message::Scene,
+                )),
+            )
+            .add(
+                widget::settings::item::builder(fl!("frame-rate")).control(widget::dropdown(
+                    &self.frame_rate_names,
+                    frame_rate_selected,
+                    Message::FrameRate,
+                )),
+            )
+            .add(
+                widget::settings::item::builder(fl!("idle-throttling"))
+                    .description(fl!("idle-throttling-description"))
+                    .toggler(self.config.idle_throttling, Message::IdleThrottling),
+            );
+
+        let temperature_unit_selected = TemperatureUnit::ALL
+            .iter()
+            .position(|unit| *unit == self.config.temperature_unit);
+        let byte_unit_selected = ByteUnit::ALL
+            .iter()
+            .position(|unit| *unit == self.config.byte_unit);
+        let clock_format_selected = ClockFormat::ALL
+            .iter()
+            .position(|format| *format == self.config.clock_format);
+        let process_sort_selected = ProcessBy::ALL
+            .iter()
+            .position(|process_by| *process_by == self.config.process_sort_by);
+        let monitor_font_selected = self
+            .font_names
+            .iter()
+            .position(|font_name| *font_name == self.config.monitor_font_name);
+        let monitor_font_size_selected = self
+            .font_sizes
+            .iter()
+            .position(|font_size| *font_size == self.config.monitor_font_size);
+        let monitor_layout_selected = MonitorLayout::ALL
+            .iter()
+            .position(|layout| *layout == self.config.monitor_layout);
+        let mut monitor_section = widget::settings::section()
+            .title(fl!("resource-monitor"))
+            .add(
+                widget::settings::item::builder(fl!("temperature-unit")).control(
+                    widget::dropdown(
+                        &self.temperature_unit_names,
+                        temperature_unit_selected,
+                        Message::TemperatureUnit,
+                    ),
+                ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("byte-unit")).control(
+                    widget::dropdown(
+                        &self.byte_unit_names,
+                        byte_unit_selected,
+                        Message::ByteUnit,
+                    ),
+                ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("clock-format")).control(
+                    widget::dropdown(
+                        &self.clock_format_names,
+                        clock_format_selected,
+                        Message::ClockFormat,
+                    ),
+                ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("clock-seconds"))
+                    .toggler(self.config.clock_seconds, Message::ClockSeconds),
+            )
+            .add(
+                widget::settings::item::builder(fl!("clock-date"))
+                    .toggler(self.config.clock_date, Message::ClockDate),
+            )
+            .add(
+                widget::settings::item::builder(fl!("sun-moon"))
+                    .toggler(self.config.sun_moon, Message::SunMoon),
+            )
+            .add(
+                widget::settings::item::builder(fl!("process-sort-default")).control(
+                    widget::dropdown(
+                        &self.process_sort_names,
+                        process_sort_selected,
+                        Message::ProcessSortDefault,
+                    ),
+                ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("monitor-font")).control(
+                    widget::dropdown(
+                        &self.font_names,
+                        monitor_font_selected,
+                        Message::MonitorFont,
+                    ),
+                ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("monitor-font-size")).control(
+                    widget::dropdown(
+                        &self.font_size_names,
+                        monitor_font_size_selected,
+                        Message::MonitorFontSize,
+                    ),
+                ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("monitor-layout")).control(
+                    widget::dropdown(
+                        &self.monitor_layout_names,
+                        monitor_layout_selected,
+                        Message::MonitorLayout,
+                    ),
+                ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("monitor-tabs"))
+                    .toggler(self.config.monitor_tabs, Message::MonitorTabs),
+            )
+            .add(
+                widget::settings::item::builder(fl!("compact-mode"))
+                    .toggler(self.config.compact, Message::Compact),
+            )
+            .add(
+                widget::settings::item::builder(fl!("touch-targets"))
+                    .toggler(self.config.touch_targets, Message::TouchTargets),
+            );
+        for section in Section::ALL {
+            let name = match section {
+                Section::Clock => fl!("section-clock"),
+                Section::Agenda => fl!("section-agenda"),
+                Section::Headlines => fl!("section-headlines"),
+                Section::GitHub => fl!("section-github"),
+                Section::Mail => fl!("section-mail"),
+                Section::Todo => fl!("section-todo"),
+                Section::Ticker => fl!("section-ticker"),
+                Section::Keyboard => fl!("section-keyboard"),
+                Section::Workspaces => fl!("section-workspaces"),
+                Section::ScreenTime => fl!("section-screen-time"),
+                Section::Quote => fl!("section-quote"),
+                Section::Weather => fl!("section-weather"),
+                Section::Pomodoro => fl!("section-pomodoro"),
+                Section::Media => fl!("section-media"),
+                Section::Spectrum => fl!("section-spectrum"),
+                Section::System => fl!("section-system"),
+                Section::GraphRange => fl!("section-graph-range"),
+                Section::Cpu => fl!("section-cpu"),
+                Section::Memory => fl!("section-memory"),
+                Section::Gpu => fl!("section-gpu"),
+                Section::Remote => fl!("section-remote"),
+                Section::Agents => fl!("section-agents"),
+                Section::Processes => fl!("section-processes"),
+            };
+            monitor_section = monitor_section.add(
+                widget::settings::item::builder(name).toggler(
+                    self.config.sections.contains(&section),
+                    move |enabled| Message::SectionToggle(section, enabled),
+                ),
+            );
+        }
+
         widget::settings::view_column(vec![
             appearance_section.into(),
             font_section.into(),
             splits_section.into(),
+            profile_section.into(),
+            background_section.into(),
+            monitor_section.into(),
             advanced_section.into(),
         ])
         .into()
@@ -1500,18 +2517,43 @@ impl Application for App {
             zoom_steps.push(zoom_step);
         }
 
+        let scene_names = Scene::ALL.iter().map(|scene| match scene {
+            Scene::Earth => fl!("scene-earth"),
+            Scene::Plasma => fl!("scene-plasma"),
+            Scene::Starfield => fl!("scene-starfield"),
+            Scene::Waveform => fl!("scene-waveform"),
+        }).collect();
+        let mut frame_rate_names = Vec::new();
+        let mut frame_times = Vec::new();
+        for fps in [15, 30, 60, 120] {
+            frame_rate_names.push(fl!("frame-rate-fps", fps = fps));
+            frame_times.push(1000 / fps);
+        }
+        let temperature_unit_names = vec![fl!("celsius"), fl!("fahrenheit")];
+        let byte_unit_names = vec![fl!("byte-unit-decimal"), fl!("byte-unit-binary")];
+        let clock_format_names = vec![fl!("clock-24h"), fl!("clock-12h")];
+        let process_sort_names = vec![fl!("sort-cpu"), fl!("sort-ram")];
+        let monitor_layout_names = vec![fl!("one-column"), fl!("two-columns"), fl!("horizontal")];
+
         let pane_model = TerminalPaneGrid::new(segmented_button::ModelBuilder::default().build());
         let mut terminal_ids = HashMap::new();
         terminal_ids.insert(pane_model.focused(), widget::Id::unique());
 
-        let frag_shader_program = FragmentShaderProgram::new(&flags.config);
+        let frag_shader_program = FragmentShaderProgram::new(&flags.config, flags.output.clone(), flags.no_shader);
+        let resource_monitor = ResourceMonitor::new(&flags.config);
+        let pomodoro = pomodoro::Pomodoro::new(&flags.config);
+        let alerts = alert::Alerts::new(&flags.config);
+        let key_binds = key_binds(&flags.config);
 
         let mut app = Self {
             core,
             pane_model,
             config_handler: flags.config_handler,
             config: flags.config,
-            key_binds: key_binds(),
+            state_handler: flags.state_handler,
+            state: flags.state,
+            state_changed: false,
+            key_binds,
             app_themes,
             font_names,
             font_size_names,
@@ -1525,6 +2567,16 @@ impl Application for App {
             curr_font_stretches: Vec::new(),
             zoom_step_names,
             zoom_steps,
+            scene_names,
+            frame_rate_names,
+            frame_times,
+            temperature_unit_names,
+            byte_unit_names,
+            clock_format_names,
+            config_profile_names: Vec::new(),
+            active_profile: None,
+            monitor_layout_names,
+            process_sort_names,
             theme_names_dark: Vec::new(),
             theme_names_light: Vec::new(),
             themes: HashMap::new(),
@@ -1546,14 +2598,76 @@ impl Application for App {
             show_advanced_font_settings: false,
             modifiers: Modifiers::empty(),
             frag_shader_program: frag_shader_program,
-            resource_monitor: ResourceMonitor::new(),
+            output: flags.output,
+            resource_monitor: resource_monitor,
             current_time: Local::now(),
+            window_focused: true,
+            last_activity: Instant::now(),
+            desktop_widgets: None,
+            overlay: None,
+            pip: None,
+            agenda: Vec::new(),
+            #[cfg(feature = "agent")]
+            agents: HashMap::new(),
+            #[cfg(feature = "agent")]
+            agent_selected: 0,
+            headlines: Vec::new(),
+            github: None,
+            #[cfg(feature = "mail")]
+            mail_unread: HashMap::new(),
+            remote: HashMap::new(),
+            todo: Vec::new(),
+            quotes: Vec::new(),
+            #[cfg(feature = "wayland")]
+            keyboard: keyboard::State::default(),
+            #[cfg(feature = "wayland")]
+            workspaces: Vec::new(),
+            #[cfg(feature = "wayland")]
+            workspaces_handle: None,
+            #[cfg(feature = "wayland")]
+            screen_time: Vec::new(),
+            quote: None,
+            spectrum: Vec::new(),
+            forecast: None,
+            pomodoro,
+            alerts,
+            prometheus: None,
+            metric_log: None,
+            push: None,
+            #[cfg(feature = "mqtt")]
+            mqtt: None,
+            #[cfg(feature = "tray")]
+            window_hidden: false,
+            #[cfg(feature = "tray")]
+            tray: None,
+            #[cfg(feature = "dbus")]
+            dbus: None,
+            #[cfg(unix)]
+            ipc: None,
+            #[cfg(feature = "mpris")]
+            media: None,
+            #[cfg(feature = "mpris")]
+            media_art: None,
+            #[cfg(feature = "mpris")]
+            media_handle: None,
         };
 
         app.set_curr_font_weights_and_stretches();
+        let desktop_widget = DesktopWidgets::open(&app.config).map(|(widgets, task)| {
+            app.desktop_widgets = Some(widgets);
+            task
+        });
+        let overlay = if flags.overlay {app.update(Message::ToggleOverlay)} else {Task::none()};
+        // Wayland ignores this, the compositor places the window
+        let position = app.state.window_position
+            .zip(app.core.main_window_id())
+            .map_or_else(Task::none, |((x, y), id)| window::move_to(id, Point::new(x as f32, y as f32)));
         let command = Task::batch([
             app.update_config(), 
             app.update_title(None),
+            desktop_widget.unwrap_or_else(Task::none),
+            overlay,
+            position,
             // cosmic::iced::window::get_latest().map(|id|message::Message::Cosmic(cosmic::app::cosmic::Message::Maximize))
         ]);
 
@@ -1588,16 +2702,19 @@ impl Application for App {
         // Helper for updating config values efficiently
         macro_rules! config_set {
             ($name: ident, $value: expr) => {
+                let value = $value;
+                // keep the config file from overriding the new value
+                config_file::set(stringify!($name), &value);
                 match &self.config_handler {
                     Some(config_handler) => {
                         if let Err(err) =
-                            paste::paste! { self.config.[<set_ $name>](config_handler, $value) }
+                            paste::paste! { self.config.[<set_ $name>](config_handler, value) }
                         {
                             log::warn!("failed to save config {:?}: {}", stringify!($name), err);
                         }
                     }
                     None => {
-                        self.config.$name = $value;
+                        self.config.$name = value;
                         log::warn!(
                             "failed to save config {:?}: no config handler",
                             stringify!($name)
@@ -1607,10 +2724,30 @@ impl Application for App {
             };
         }
         match message {
+            Message::Agenda(events) => {
+                        self.agenda = events;
+                    }
+            #[cfg(feature = "agent")]
+            Message::Agent(index, status) => {
+                        self.agents.insert(index, status);
+                    }
+            #[cfg(feature = "agent")]
+            Message::AgentSelect(index) => {
+                        self.agent_selected = index;
+                    }
             Message::AppTheme(app_theme) => {
                         config_set!(app_theme, app_theme);
                         return self.update_config();
                     }
+            Message::AudioSpectrum(levels) => {
+                        self.spectrum = levels;
+                    }
+            Message::ByteUnit(index) => {
+                        if let Some(unit) = ByteUnit::ALL.get(index) {
+                            config_set!(byte_unit, *unit);
+                            return self.update_config();
+                        }
+                    }
             Message::ClearScrollback(entity_opt) => {
                         if let Some(tab_model) = self.pane_model.active() {
                             let entity = entity_opt.unwrap_or_else(|| tab_model.active());
@@ -1621,6 +2758,18 @@ impl Application for App {
                             }
                         }
                     }
+            Message::ClockDate(clock_date) => {
+                        config_set!(clock_date, clock_date);
+                    }
+            Message::ClockFormat(index) => {
+                        if let Some(format) = ClockFormat::ALL.get(index) {
+                            config_set!(clock_format, *format);
+                            return self.update_config();
+                        }
+                    }
+            Message::ClockSeconds(clock_seconds) => {
+                        config_set!(clock_seconds, clock_seconds);
+                    }
             Message::ColorSchemeCollapse => {
                         self.color_scheme_expanded = None;
                     }
@@ -1822,7 +2971,11 @@ impl Application for App {
                             }
                         }
                     }
-            Message::Config(config) => {
+            Message::Compact(compact) => {
+                        config_set!(compact, compact);
+                    }
+            Message::Config(mut config) => {
+                        config_file::load(&mut config, self.resource_monitor.on_battery());
                         if config != self.config {
                             log::info!("update config");
                             //TODO: update syntax theme by clearing tabs, only if needed
@@ -1830,6 +2983,49 @@ impl Application for App {
                             return self.update_config();
                         }
                     }
+            Message::ConfigFileChanged => {
+                        // the app's own writes only repeat settings it already applied
+                        if config_file::written_by_app() {
+                            return Task::none();
+                        }
+                        // start over from the settings saved by the app, in case keys were removed from the file
+                        // or another profile is active
+                        let mut config = match &self.config_handler {
+                            Some(config_handler) => Config::get_entry(config_handler).unwrap_or_else(|(errs, config)| {
+                                log::info!("errors loading config: {:?}", errs);
+                                config
+                            }),
+                            None => Config::default(),
+                        };
+                        config_file::load(&mut config, self.resource_monitor.on_battery());
+                        if config != self.config {
+                            log::info!("update config from {:?}", config_file::path());
+                            self.config = config;
+                            return self.update_config();
+                        }
+                    }
+            Message::ConfigProfile(index) => {
+                        // the first entry selects a profile automatically
+                        let name = match index {
+                            0 => String::new(),
+                            _ => match self.config_profile_names.get(index) {
+                                Some(name) => name.clone(),
+                                None => return Task::none(),
+                            },
+                        };
+                        config_set!(config_profile, name);
+                        // the settings the previous profile overrode have to be restored
+                        return self.update(Message::ConfigFileChanged);
+                    }
+            Message::ConfigProfileNext => {
+                        let index = self
+                            .config_profile_names
+                            .iter()
+                            .skip(1)
+                            .position(|name| *name == self.config.config_profile)
+                            .map_or(1, |index| index + 2);
+                        return self.update(Message::ConfigProfile(index % self.config_profile_names.len().max(1)));
+                    }
             Message::Copy(entity_opt) => {
                         if let Some(tab_model) = self.pane_model.active() {
                             let entity = entity_opt.unwrap_or_else(|| tab_model.active());
@@ -1882,6 +3078,11 @@ impl Application for App {
                             log::warn!("Failed to get focused pane");
                         }
                     }
+            #[cfg(feature = "dbus")]
+            Message::DBusReady(handle) => {
+                        self.dbus = Some(handle);
+                        self.update_dbus();
+                    }
             Message::DefaultFont(index) => {
                         match self.font_names.get(index) {
                             Some(font_name) => {
@@ -1987,6 +3188,23 @@ impl Application for App {
                         }
                     }
             Message::Drop(None) => {}
+            Message::ExportBackground => {
+                        match export::picture_dir() {
+                            Ok(dir) => self.frag_shader_program.capture(dir.join(export::background_file_name(Local::now()))),
+                            Err(err) => {
+                                log::warn!("failed to save the background: {}", err);
+                                notification::send(fl!("background-save-failed"), err.to_string());
+                            },
+                        }
+                    }
+            Message::ExportMetrics(graphs) => {
+                        match self.resource_monitor.export_metrics(graphs) {
+                            Ok(paths) => for path in paths {
+                                log::info!("exported metrics to {}", path.display());
+                            },
+                            Err(err) => log::warn!("failed to export metrics: {}", err),
+                        }
+                    }
             Message::Find(find) => {
                         self.find = find;
                         if find {
@@ -2053,21 +3271,112 @@ impl Application for App {
             Message::FocusFollowMouse(focus_follow_mouse) => {
                         config_set!(focus_follow_mouse, focus_follow_mouse);
                     }
+            Message::Forecast(forecast) => {
+                        self.forecast = Some(forecast);
+                    }
+            Message::FrameRate(index) => {
+                        if let Some(frame_time) = self.frame_times.get(index) {
+                            config_set!(frame_time_ms, *frame_time);
+                            return self.update_config();
+                        }
+                    }
+            Message::GitHub(status) => {
+                        self.github = Some(status);
+                    }
+            Message::GraphHover(id, fraction) => {
+                        self.resource_monitor.set_graph_hover(id, fraction);
+                    }
+            Message::GraphRange(range) => {
+                        self.resource_monitor.set_graph_range(range);
+                    }
+            Message::Headlines(headlines) => {
+                        self.headlines = headlines;
+                    }
+            Message::IdleThrottling(idle_throttling) => {
+                        config_set!(idle_throttling, idle_throttling);
+                        return self.update_config();
+                    }
+            #[cfg(unix)]
+            Message::IpcReady(handle) => {
+                        self.ipc = Some(handle);
+                        self.update_ipc();
+                    }
             Message::Key(modifiers, key) => {
+                        self.mark_activity();
                         for (key_bind, action) in &self.key_binds {
                             if key_bind.matches(modifiers, &key) {
                                 return self.update(action.message(None));
                             }
                         }
                     }
+            #[cfg(feature = "wayland")]
+            Message::Keyboard(state) => {
+                        self.keyboard = state;
+                    }
             Message::LaunchUrl(url) => {
                         if let Err(err) = open::that_detached(&url) {
                             log::warn!("failed to open {:?}: {}", url, err);
                         }
                     }
+            #[cfg(feature = "mpris")]
+            Message::Media(playing) => {
+                        self.media = playing;
+                        if self.media.is_none() && self.media_art.take().is_some() && self.config.media_art_tint {
+                            return self.update_config();
+                        }
+                    }
+            #[cfg(feature = "mpris")]
+            Message::MediaArt(art) => {
+                        self.media_art = art;
+                        if self.config.media_art_tint {
+                            return self.update_config();
+                        }
+                    }
+            #[cfg(feature = "mpris")]
+            Message::MediaControl(control) => {
+                        if let Some(handle) = &self.media_handle {
+                            handle.send(control);
+                        }
+                    }
+            #[cfg(feature = "mpris")]
+            Message::MediaReady(handle) => {
+                        self.media_handle = Some(handle);
+                    }
+            #[cfg(feature = "mail")]
+            Message::MailOpen => {
+                        mail::open_client(&self.config.mail_client);
+                    }
+            #[cfg(feature = "mail")]
+            Message::MailUnread(index, unread) => {
+                        self.mail_unread.insert(index, unread);
+                    }
             Message::Modifiers(modifiers) => {
                         self.modifiers = modifiers;
                     }
+            Message::MonitorFont(index) => {
+                        if let Some(font_name) = self.font_names.get(index) {
+                            config_set!(monitor_font_name, font_name.clone());
+                            return self.update_config();
+                        }
+                    }
+            Message::MonitorFontSize(index) => {
+                        if let Some(font_size) = self.font_sizes.get(index) {
+                            config_set!(monitor_font_size, *font_size);
+                            return self.update_config();
+                        }
+                    }
+            Message::MonitorLayout(index) => {
+                        if let Some(layout) = MonitorLayout::ALL.get(index) {
+                            config_set!(monitor_layout, *layout);
+                        }
+                    }
+            Message::MonitorPage(page) => {
+                        // remembered across restarts
+                        config_set!(monitor_page, page);
+                    }
+            Message::MonitorTabs(monitor_tabs) => {
+                        config_set!(monitor_tabs, monitor_tabs);
+                    }
             Message::MouseEnter(pane) => {
                         self.pane_model.set_focus(pane);
                         return self.update_focus();
@@ -2078,6 +3387,12 @@ impl Application for App {
                         // update opacity in fragment shader
                         self.frag_shader_program.update_bg(&self.config);
                     }
+            #[cfg(feature = "wayland")]
+            Message::Output(event, output) => {
+                        if let Some(widgets) = self.desktop_widgets.as_mut() {
+                            return widgets.output_event(&self.config, event, output);
+                        }
+                    }
             Message::PaneClicked(pane) => {
                         self.pane_model.set_focus(pane);
                         return self.update_title(Some(pane));
@@ -2143,9 +3458,78 @@ impl Application for App {
                         }
                         return self.update_focus();
                     }
+            Message::PipPress => {
+                        // a layer surface cannot be dragged, it moves between the corners instead
+                        if let Some(id) = self.pip {
+                            #[cfg(feature = "wayland")]
+                            {
+                                config_set!(pip_anchor, pip::next_corner(self.config.pip_anchor));
+                                let (reopened, task) = pip::reopen(id, &self.config);
+                                self.pip = Some(reopened);
+                                return task;
+                            }
+                            #[cfg(not(feature = "wayland"))]
+                            return window::drag(id);
+                        }
+                    }
+            Message::PauseUpdates => {
+                        self.resource_monitor.toggle_paused();
+                    }
+            Message::PomodoroReset => {
+                        self.pomodoro.reset(&self.config);
+                    }
+            Message::PomodoroStartPause => {
+                        self.pomodoro.start_pause();
+                    }
+            Message::ProcessDetail => {
+                        self.resource_monitor.toggle_process_detail();
+                    }
+            Message::ProcessKill => {
+                        self.resource_monitor.request_kill();
+                    }
+            Message::ProcessKillCancel => {
+                        self.resource_monitor.cancel_kill();
+                    }
+            Message::ProcessKillConfirm => {
+                        self.resource_monitor.kill_requested();
+                    }
+            Message::ProcessKillRequest(request) => {
+                        self.resource_monitor.request_kill_of(request);
+                    }
+            Message::ProcessListScrolled(at_top) => {
+                        self.resource_monitor.set_process_list_at_top(at_top);
+                    }
+            Message::ProcessSelect(name) => {
+                        self.resource_monitor.select_process(name);
+                    }
+            Message::ProcessSelectNext => {
+                        self.resource_monitor.select_adjacent_process(1);
+                        return self.scroll_to_selected_process();
+                    }
+            Message::ProcessSelectPage(down) => {
+                        self.resource_monitor.select_process_page(down);
+                        return self.scroll_to_selected_process();
+                    }
+            Message::ProcessSelectPrev => {
+                        self.resource_monitor.select_adjacent_process(-1);
+                        return self.scroll_to_selected_process();
+                    }
+            Message::ProcessSortDefault(index) => {
+                        if let Some(process_by) = ProcessBy::ALL.get(index) {
+                            config_set!(process_sort_by, *process_by);
+                            self.resource_monitor.set_process_sorting(*process_by);
+                        }
+                    }
             Message::ProcessSortBy(process_by) => {
                 self.resource_monitor.set_process_sorting(process_by)
             },
+            Message::ProcessSortNext => {
+                        let index = ProcessBy::ALL
+                            .iter()
+                            .position(|process_by| *process_by == self.resource_monitor.process_sorting())
+                            .map_or(0, |index| (index + 1) % ProcessBy::ALL.len());
+                        self.resource_monitor.set_process_sorting(ProcessBy::ALL[index]);
+                    }
             Message::ProfileCollapse(_profile_id) => {
                         self.profile_expanded = None;
                     }
@@ -2240,6 +3624,63 @@ impl Application for App {
                             return self.save_profiles();
                         }
                     }
+            Message::Quote(quote) => {
+                        self.quote = quote;
+                    }
+            Message::Quotes(entries) => {
+                        self.quotes = entries;
+                    }
+            Message::Refresh => {
+                        // pulled down by touch, the slow sections need not wait for their tick
+                        self.resource_monitor.update_memory();
+                        self.resource_monitor.update_processes();
+                    }
+            Message::Remote(index, status) => {
+                        self.remote.insert(index, status);
+                    }
+            Message::Scene(index) => {
+                        if let Some(scene) = Scene::ALL.get(index) {
+                            config_set!(scene, *scene);
+                            return self.update_config();
+                        }
+                    }
+            Message::SceneNext => {
+                        // cycle the scene of this output if it has its own
+                        let mut output_backgrounds = self.config.output_backgrounds.clone();
+                        match self.output.as_ref().and_then(|output| output_backgrounds.get_mut(output)) {
+                            Some(background) => {
+                                background.scene = background.scene.next();
+                                config_set!(output_backgrounds, output_backgrounds);
+                            },
+                            None => config_set!(scene, self.config.scene.next()),
+                        }
+                        return self.update_config();
+                    }
+            #[cfg(feature = "wayland")]
+            Message::ScreenTime(ranked) => {
+                        self.screen_time = ranked;
+                    }
+            Message::SectionCollapse(section) => {
+                        let mut collapsed_sections = self.config.collapsed_sections.clone();
+                        match collapsed_sections.iter().position(|s| *s == section) {
+                            Some(index) => {collapsed_sections.remove(index);},
+                            None => collapsed_sections.push(section),
+                        }
+                        config_set!(collapsed_sections, collapsed_sections);
+                    }
+            Message::SectionToggle(section, enabled) => {
+                        // keep the configured order, sections turned on again go last
+                        let mut sections:Vec<Section> = self.config.sections
+                            .iter()
+                            .copied()
+                            .filter(|s| *s != section)
+                            .collect();
+                        if enabled {
+                            sections.push(section);
+                        }
+                        config_set!(sections, sections);
+                        return self.update_config();
+                    }
             Message::SelectAll(entity_opt) => {
                         if let Some(tab_model) = self.pane_model.active() {
                             let entity = entity_opt.unwrap_or_else(|| tab_model.active());
@@ -2262,10 +3703,29 @@ impl Application for App {
                             return self.update_config();
                         }
                     }
+            Message::ShaderChanged => {
+                        self.frag_shader_program.reload_shader();
+                    }
+            Message::ShaderHue(hue) => {
+                        config_set!(shader_hue_degrees, hue);
+                        return self.update_config();
+                    }
+            Message::ShaderIntensity(intensity) => {
+                        config_set!(shader_intensity_percent, intensity);
+                        return self.update_config();
+                    }
+            Message::ShaderSpeed(speed) => {
+                        config_set!(shader_speed_percent, speed);
+                        return self.update_config();
+                    }
             Message::ShowAdvancedFontSettings(show) => {
                         self.show_advanced_font_settings = show;
                     }
+            Message::SunMoon(sun_moon) => {
+                        config_set!(sun_moon, sun_moon);
+                    }
             Message::SystemThemeChange => {
+                        // picks up the new colours in the terminal, the monitor and the shader background
                         return self.update_config();
                     }
             Message::SyntaxTheme(color_scheme_kind, index) => {
@@ -2406,6 +3866,11 @@ impl Application for App {
                             }
                         }
                     }
+            Message::TemperatureUnit(index) => {
+                        if let Some(unit) = TemperatureUnit::ALL.get(index) {
+                            config_set!(temperature_unit, *unit);
+                        }
+                    }
             Message::TabPrev => {
                         if let Some(tab_model) = self.pane_model.active() {
                             let pos = tab_model
@@ -2422,6 +3887,7 @@ impl Application for App {
                         }
                     }
             Message::TermEvent(pane, entity, event) => {
+                        self.mark_activity();
                         match event {
                             TermEvent::Bell => {
                                 //TODO: audible or visible bell options?
@@ -2554,22 +4020,98 @@ impl Application for App {
                         // Set new terminal event channel
                         self.term_event_tx_opt = Some(term_event_tx);
 
-                        // Spawn first tab
-                        return self.update(Message::TabNew);
+                        // Spawn first tab, the desktop widget has no terminal
+                        if self.desktop_widgets.is_none() {
+                            return self.update(Message::TabNew);
+                        }
+                    }
+            Message::TextureChanged => {
+                        return self.frag_shader_program.reload_texture();
+                    }
+            Message::TextureDecoded(i, path, texture) => {
+                        self.frag_shader_program.set_texture(i, &path, texture);
                     }
             Message::Tick(ticktype) => {
                         match ticktype {
                             TickType::ResourceUpdate =>{ 
-                                self.resource_monitor.update_cpu_gpu_mem();
+                                if !self.resource_monitor.paused() {
+                                    self.resource_monitor.update_cpu_gpu();
+                                    let monitor = &self.resource_monitor;
+                                    for (summary, body) in self.alerts.check(&self.config, |source| monitor.alert_value(source)) {
+                                        notification::send(summary, body);
+                                        if !self.config.alert_sound.is_empty() {
+                                            notification::play(self.config.alert_sound.clone());
+                                        }
+                                    }
+                                    if let Some(exporter) = &self.prometheus {
+                                        exporter.update(self.resource_monitor.prometheus());
+                                    }
+                                    if let Some(logger) = &mut self.metric_log {
+                                        logger.sample(self.resource_monitor.metrics());
+                                    }
+                                    if let Some(pusher) = &mut self.push {
+                                        pusher.sample(self.resource_monitor.metrics());
+                                    }
+                                    #[cfg(feature = "mqtt")]
+                                    if let Some(publisher) = &mut self.mqtt {
+                                        publisher.sample(self.resource_monitor.mqtt());
+                                    }
+                                }
+                                #[cfg(feature = "dbus")]
+                                self.update_dbus();
+                                #[cfg(unix)]
+                                self.update_ipc();
+                                self.update_frame_time();
+                                // switch profiles when the machine is plugged in or unplugged
+                                if self.config.active_profile(self.resource_monitor.on_battery()) != self.active_profile.as_deref() {
+                                    return self.update(Message::ConfigFileChanged);
+                                }
                             },
                             TickType::VisualUpdate => {
                                 self.resource_monitor.update_visual(&mut self.frag_shader_program);
+                                if self.config.pomodoro_shader {
+                                    self.frag_shader_program.update_focus(self.pomodoro.shader_focus());
+                                }
+                            },
+                            TickType::MemoryUpdate => {
+                                if !self.resource_monitor.paused() {
+                                    self.resource_monitor.update_memory();
+                                }
                             },
                             TickType::ClockUpdate => {
-                                self.current_time = Local::now();
+                                let previous = std::mem::replace(&mut self.current_time, Local::now());
+                                for (summary, body) in alarm::due(&self.config, previous, self.current_time) {
+                                    notification::send(summary, body);
+                                    if !self.config.alarm_sound.is_empty() {
+                                        notification::play(self.config.alarm_sound.clone());
+                                    }
+                                }
+                                if let Some(phase) = self.pomodoro.tick(&self.config) {
+                                    let (summary, body) = match phase {
+                                        pomodoro::Phase::Work => (fl!("pomodoro-work"), fl!("pomodoro-work-body")),
+                                        pomodoro::Phase::ShortBreak => (fl!("pomodoro-break"), fl!("pomodoro-break-body")),
+                                        pomodoro::Phase::LongBreak => (fl!("pomodoro-long-break"), fl!("pomodoro-long-break-body")),
+                                    };
+                                    notification::send(summary, body);
+                                }
+                                #[cfg(feature = "tray")]
+                                self.update_tray();
+                                // resizing sends many events, so the geometry is saved once it settles
+                                if std::mem::take(&mut self.state_changed) {
+                                    if let Some(state_handler) = &self.state_handler {
+                                        if let Err(err) = self.state.write_entry(state_handler) {
+                                            log::warn!("failed to save the window geometry: {}", err);
+                                        }
+                                    }
+                                }
                             },
                             TickType::ProcessUpdate => {
-                                self.resource_monitor.update_processes();
+                                if !self.resource_monitor.paused() {
+                                    self.resource_monitor.update_processes();
+                                }
+                            },
+                            TickType::HistorySave => {
+                                self.resource_monitor.save_history();
                             },
                         }
                     }
@@ -2606,10 +4148,77 @@ impl Application for App {
                                 });
                         }
                     }
+            Message::Todo(tasks) => {
+                        self.todo = tasks;
+                    }
+            Message::TodoComplete(task) => {
+                        // hidden right away, the watcher brings it back if completing it failed
+                        self.todo.retain(|pending| *pending != task);
+                        todo::complete(self.config.todo_backend, self.config.todo_file.clone(), task);
+                    }
+            Message::ToggleCalendar => {
+                        config_set!(calendar, !self.config.calendar);
+                    }
+            Message::ToggleCompact => {
+                        return self.update(Message::Compact(!self.config.compact));
+                    }
+            Message::ToggleOverlay => {
+                        match self.overlay.take() {
+                            Some(id) => return overlay::close(id),
+                            None => if let Some((id, task)) = overlay::open(&self.config) {
+                                self.overlay = Some(id);
+                                return task;
+                            },
+                        }
+                    }
+            Message::TogglePip => {
+                        match self.pip.take() {
+                            Some(id) => return pip::close(id),
+                            None => {
+                                let (id, task) = pip::open(&self.config);
+                                self.pip = Some(id);
+                                return task;
+                            }
+                        }
+                    }
+            Message::ToggleWidgetInteraction => {
+                        if let Some(widgets) = self.desktop_widgets.as_mut() {
+                            return widgets.toggle_interaction(&self.config);
+                        }
+                    }
+            #[cfg(feature = "tray")]
+            Message::ToggleWindow => {
+                        if let Some(window_id) = self.core.main_window_id() {
+                            self.window_hidden = !self.window_hidden;
+                            self.update_tray();
+                            if self.window_hidden {
+                                return window::change_mode(window_id, window::Mode::Hidden);
+                            }
+                            return Task::batch([
+                                window::change_mode(window_id, window::Mode::Windowed),
+                                window::gain_focus(window_id),
+                            ]);
+                        }
+                    }
+            Message::TouchTargets(touch_targets) => {
+                        config_set!(touch_targets, touch_targets);
+                    }
+            #[cfg(feature = "tray")]
+            Message::TrayReady(handle) => {
+                        self.tray = Some((handle, tray::Status::default()));
+                        self.update_tray();
+                    }
             Message::UpdateDefaultProfile((default, profile_id)) => {
                         config_set!(default_profile, default.then_some(profile_id));
                     }
+            Message::WalPaletteChanged => {
+                        return self.update_config();
+                    }
+            Message::Weather(weather) => {
+                        self.frag_shader_program.update_weather(weather);
+                    }
             Message::WindowClose => {
+                        self.resource_monitor.save_history();
                         if let Some(window_id) = self.core.main_window_id() {
                             return window::close(window_id);
                         }
@@ -2626,12 +4235,43 @@ impl Application for App {
                         }
                     },
             Message::WindowFocused => {
+                        self.window_focused = true;
+                        self.mark_activity();
                         self.pane_model.update_terminal_focus();
                         return self.update_focus();
                     }
+            Message::WindowMoved(window_id, position) => {
+                        if self.core.main_window_id() == Some(window_id) {
+                            let position = Some((position.x as i32, position.y as i32));
+                            self.state_changed |= self.state.window_position != position;
+                            self.state.window_position = position;
+                        }
+                    }
+            Message::WindowResized(window_id, size) => {
+                        if self.core.main_window_id() == Some(window_id) {
+                            let size = Some((size.width as u32, size.height as u32));
+                            self.state_changed |= self.state.window_size != size;
+                            self.state.window_size = size;
+                        }
+                    }
             Message::WindowUnfocused => {
+                        self.window_focused = false;
                         self.pane_model.unfocus_all_terminals();
                     }
+            #[cfg(feature = "wayland")]
+            Message::WorkspaceActivate(id) => {
+                        if let Some(handle) = &self.workspaces_handle {
+                            handle.activate(id);
+                        }
+                    }
+            #[cfg(feature = "wayland")]
+            Message::Workspaces(groups) => {
+                        self.workspaces = groups;
+                    }
+            #[cfg(feature = "wayland")]
+            Message::WorkspacesReady(handle) => {
+                        self.workspaces_handle = Some(handle);
+                    }
             Message::ZoomIn => {
                         return self.update_render_active_pane_zoom(message);
                     }
@@ -2672,6 +4312,11 @@ impl Application for App {
                 Message::ToggleContextPage(ContextPage::Settings),
             )
             .title(fl!("settings")),
+            ContextPage::ShaderTweaks => context_drawer::context_drawer(
+                self.shader_tweaks(),
+                Message::ToggleContextPage(ContextPage::ShaderTweaks),
+            )
+            .title(fl!("shader-tweaks")),
         })
     }
 
@@ -2681,6 +4326,11 @@ impl Application for App {
 
     fn header_end(&self) -> Vec<Element<Self::Message>> {
         vec![
+            widget::button::custom(icon_cache_get("emblem-system-symbolic", 16))
+                .on_press(Message::ToggleContextPage(ContextPage::Settings))
+                .padding(8)
+                .class(style::Button::Icon)
+                .into(),
             // widget::button::custom(icon_cache_get("list-add-symbolic", 16))
             //     .on_press(Message::TabNew)
             //     .padding(8)
@@ -2690,6 +4340,40 @@ impl Application for App {
     }
 
     fn view_window(&self, window_id: window::Id) -> Element<Message> {
+        if self.desktop_widgets.as_ref().is_some_and(|widgets| widgets.contains(window_id)) {
+            return container(self.view_sidebar())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Alignment::Center)
+                .into();
+        }
+        if self.overlay == Some(window_id) {
+            let [r, g, b, _] = get_term_bg_colour(&self.config);
+            let a = f32::from(self.config.overlay_opacity) / 100.;
+            return container(self.resource_monitor.view_overlay(self))
+                .padding([4, 8])
+                .style(move |_theme| container::Style {
+                    background: Some(iced::Background::Color(Color { r, g, b, a })),
+                    border: iced::Border { radius: 4.0.into(), ..Default::default() },
+                    ..container::Style::default()
+                })
+                .into();
+        }
+        if self.pip == Some(window_id) {
+            let [r, g, b, _] = get_term_bg_colour(&self.config);
+            let graph = widget::mouse_area(self.resource_monitor.view_pip(self, self.config.pip_metric))
+                .on_press(Message::PipPress)
+                .on_right_press(Message::TogglePip);
+            return container(graph)
+                .padding(8)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(move |_theme| container::Style {
+                    background: Some(iced::Background::Color(Color { r, g, b, a: 1. })),
+                    ..container::Style::default()
+                })
+                .into();
+        }
         match &self.dialog_opt {
             Some(dialog) => dialog.view(window_id),
             None => widget::text("Unknown window ID").into(),
@@ -2841,47 +4525,15 @@ impl Application for App {
 
         //TODO: apply window border radius xs at bottom of window
 
-
-        let width = 260.;
-        let shader = crate::iced::widget::shader(&self.frag_shader_program)
-            .width(Length::Fixed(width))
-            .height(Length::Fixed(width));
-
-        let [r, g, b, a] = get_term_bg_colour(&self.config);
-        let bg_container_style = container::Style{ 
-            background: Some(
-                iced::Background::Color(Color {r,g,b,a,})), 
-            ..container::Style::default()
-        };
-
-        // resource monitor
-        let monitor = self.resource_monitor.view_monitor(&self);
-        let processes = self.resource_monitor.view_processes();
-
-        // piece together the side bar
-        let sidebar = 
-            // main container
-            container(
-                column![
-                    container(monitor)
-                        .width(Length::Fill)
-                        .height(Length::Shrink)
-                        .style(move |_theme| {bg_container_style.clone()})
-                        .padding(Padding{top:10., ..Default::default()}), 
-                    shader,
-                    container(processes)
-                        .width(Length::Fill)
-                        .height(Length::Fill)
-                        .style(move |_theme| {bg_container_style})
-                ]
-            )
-            .width(Length::Fixed(width)).height(Length::Fill); 
-
-        
-
+        if self.config.compact {
+            return self.view_sidebar();
+        }
+        if self.config.monitor_layout == MonitorLayout::Horizontal {
+            return column![self.view_sidebar(), pane_grid].into();
+        }
         row![
             pane_grid, 
-            sidebar,
+            self.view_sidebar(),
         ].into()
     }
 
@@ -2890,9 +4542,23 @@ impl Application for App {
         struct TerminalEventSubscription;
         struct ThemeSubscription;
         struct ThemeModeSubscription;
+        let background = self.config.background(self.output.as_deref());
+
+        let visible = self.frag_shader_program.visible();
+        // the overlay and the mini monitor are mostly shown while the window is not, over a game,
+        // and the compact mode has no shader that is drawn
+        let readout = self.overlay.is_some() || self.pip.is_some() || self.config.compact;
+        let shown = visible || readout;
+        // without a rendered background nothing needs its files, the audio levels or the weather
+        let rendered = self.frag_shader_program.rendered();
+        let poll_interval = |interval_ms:u16| if shown {
+            Duration::from_millis(interval_ms.into())
+        } else {
+            HIDDEN_UPDATE_INTERVAL.max(Duration::from_millis(interval_ms.into()))
+        };
 
         Subscription::batch([
-            event::listen_with(|event, _status, _window_id| match event {
+            event::listen_with(|event, _status, window_id| match event {
                 Event::Keyboard(KeyEvent::KeyPressed { key, modifiers, .. }) => {
                     Some(Message::Key(modifiers, key))
                 }
@@ -2902,6 +4568,16 @@ impl Application for App {
                 Event::Mouse(MouseEvent::ButtonReleased(MouseButton::Left)) => {
                     Some(Message::CopyPrimary(None))
                 }
+                Event::Window(window::Event::Moved(position)) => {
+                    Some(Message::WindowMoved(window_id, position))
+                }
+                Event::Window(window::Event::Resized(size)) => {
+                    Some(Message::WindowResized(window_id, size))
+                }
+                #[cfg(feature = "wayland")]
+                Event::PlatformSpecific(event::PlatformSpecific::Wayland(
+                    event::wayland::Event::Output(event, output),
+                )) => Some(Message::Output(event, output)),
                 _ => None,
             }),
             Subscription::run_with_id(
@@ -2935,12 +4611,14 @@ impl Application for App {
                 }
                 Message::Config(update.config)
             }),
+            // watch the theme in use, which is not the one of the system mode if a variant is forced
             cosmic_config::config_subscription::<_, cosmic_theme::Theme>(
                 TypeId::of::<ThemeSubscription>(),
-                if self.core.system_theme_mode().is_dark {
-                    cosmic_theme::DARK_THEME_ID
-                } else {
-                    cosmic_theme::LIGHT_THEME_ID
+                match self.config.app_theme {
+                    AppTheme::Dark => cosmic_theme::DARK_THEME_ID,
+                    AppTheme::Light => cosmic_theme::LIGHT_THEME_ID,
+                    AppTheme::System if self.core.system_theme_mode().is_dark => cosmic_theme::DARK_THEME_ID,
+                    AppTheme::System => cosmic_theme::LIGHT_THEME_ID,
                 }
                 .into(),
                 cosmic_theme::Theme::VERSION,
@@ -2959,12 +4637,187 @@ impl Application for App {
             // add a tick subscription for the resource monitor, clock etc.
             iced::time::every(Duration::from_secs(1))
                 .map(|_| Message::Tick(TickType::ClockUpdate)),
-            iced::time::every(Duration::from_secs(2))
-                .map(|_| Message::Tick(TickType::ProcessUpdate)),
-            iced::time::every(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL)
+            // each subsystem is polled at its own rate, less often and without visual updates
+            // while the window is not shown
+            if self.config.sections.contains(&Section::Processes) {
+                iced::time::every(Duration::from_millis(self.config.process_interval_ms.into()))
+                    .map(|_| Message::Tick(TickType::ProcessUpdate))
+            } else {
+                Subscription::none()
+            },
+            iced::time::every(poll_interval(self.config.cpu_gpu_interval_ms))
                 .map(|_| Message::Tick(TickType::ResourceUpdate)),
-            iced::time::every(Duration::from_millis(FRAME_TIME))
-                .map(|_| Message::Tick(TickType::VisualUpdate)),
+            iced::time::every(poll_interval(self.config.memory_interval_ms))
+                .map(|_| Message::Tick(TickType::MemoryUpdate)),
+            if visible && rendered {
+                iced::time::every(self.frag_shader_program.frame_time())
+                    .map(|_| Message::Tick(TickType::VisualUpdate))
+            } else if shown {
+                // keep the smoothed values of the sidebar, the overlay and the compact mode moving
+                iced::time::every(poll_interval(self.config.cpu_gpu_interval_ms))
+                    .map(|_| Message::Tick(TickType::VisualUpdate))
+            } else {
+                Subscription::none()
+            },
+            iced::time::every(Duration::from_secs(60))
+                .map(|_| Message::Tick(TickType::HistorySave)),
+            // apply the config file whenever it is edited
+            match config_file::path() {
+                Some(path) => shader::watch(&path.to_string_lossy(), Message::ConfigFileChanged),
+                None => Subscription::none(),
+            },
+            // reload the background shader and texture whenever their files change
+            if background.shader_path.is_empty() || !rendered {
+                Subscription::none()
+            } else {
+                shader::watch(&background.shader_path, Message::ShaderChanged)
+            },
+            Subscription::batch(
+                std::iter::once(&background.texture_path)
+                    .chain(self.config.texture_channels.iter().take(shader::TEXTURE_CHANNELS))
+                    .filter(|path| rendered && !path.is_empty())
+                    .map(|path| shader::watch(path, Message::TextureChanged)),
+            ),
+            {
+                // one recording feeds both the shader and the spectrum section
+                let spectrum = self.config.sections.contains(&Section::Spectrum)
+                    && !self.config.collapsed_sections.contains(&Section::Spectrum);
+                let bars = if spectrum {resource_monitor::spectrum_bars(self.config.spectrum_style)} else {0};
+                let audio_reactive = self.config.audio_reactive && rendered;
+                if audio_reactive || bars > 0 {
+                    audio::listen(audio_reactive, bars)
+                } else {
+                    Subscription::none()
+                }
+            },
+            if self.config.weather_reactive && rendered {
+                weather::listen([self.config.latitude_mul_100, self.config.longitude_mul_100])
+            } else {
+                Subscription::none()
+            },
+            if self.config.agenda_sources.is_empty() {
+                Subscription::none()
+            } else {
+                agenda::listen(self.config.agenda_sources.clone(), self.config.agenda_refresh_minutes)
+            },
+            if self.config.feeds.is_empty() || !self.config.sections.contains(&Section::Headlines) {
+                Subscription::none()
+            } else {
+                feed::listen(self.config.feeds.clone(), self.config.feed_refresh_minutes)
+            },
+            if self.config.sections.contains(&Section::GitHub) && github::configured(&self.config) {
+                github::listen(
+                    self.config.github_repos.clone(),
+                    self.config.github_token_file.clone(),
+                    self.config.github_refresh_minutes,
+                )
+            } else {
+                Subscription::none()
+            },
+            if self.config.sections.contains(&Section::Weather) {
+                weather::listen_forecast(
+                    [self.config.latitude_mul_100, self.config.longitude_mul_100],
+                    self.config.weather_forecast_hours,
+                )
+            } else {
+                Subscription::none()
+            },
+            // pick up the palette generated for a new wallpaper
+            match wal::path() {
+                Some(path) if self.config.wal_palette => shader::watch(&path.to_string_lossy(), Message::WalPaletteChanged),
+                _ => Subscription::none(),
+            },
+            #[cfg(unix)]
+            signals::listen(tokio::signal::unix::SignalKind::user_defined1(), Message::ToggleOverlay),
+            #[cfg(unix)]
+            signals::listen(tokio::signal::unix::SignalKind::user_defined2(), Message::ToggleWidgetInteraction),
+            #[cfg(feature = "tray")]
+            if self.config.tray_icon {
+                tray::listen()
+            } else {
+                Subscription::none()
+            },
+            #[cfg(feature = "dbus")]
+            if self.config.dbus_service {
+                dbus::listen()
+            } else {
+                Subscription::none()
+            },
+            #[cfg(unix)]
+            if self.config.control_socket {
+                ipc::listen()
+            } else {
+                Subscription::none()
+            },
+            #[cfg(feature = "wayland")]
+            if self.config.sections.contains(&Section::Keyboard) {
+                keyboard::listen()
+            } else {
+                Subscription::none()
+            },
+            #[cfg(feature = "wayland")]
+            if self.config.sections.contains(&Section::Workspaces) {
+                workspaces::listen()
+            } else {
+                Subscription::none()
+            },
+            #[cfg(feature = "wayland")]
+            if self.config.sections.contains(&Section::ScreenTime) {
+                screen_time::listen()
+            } else {
+                Subscription::none()
+            },
+            if self.config.sections.contains(&Section::Quote) {
+                quote::listen(
+                    self.config.quote_file.clone(),
+                    Duration::from_secs(60 * u64::from(self.config.quote_refresh_minutes)),
+                )
+            } else {
+                Subscription::none()
+            },
+            if self.config.sections.contains(&Section::Ticker) && !self.config.tickers.is_empty() {
+                ticker::listen(&self.config)
+            } else {
+                Subscription::none()
+            },
+            if self.config.sections.contains(&Section::Todo) && todo::configured(&self.config) {
+                todo::listen(self.config.todo_backend, self.config.todo_file.clone())
+            } else {
+                Subscription::none()
+            },
+            #[cfg(feature = "mail")]
+            if self.config.sections.contains(&Section::Mail) {
+                Subscription::batch(
+                    self.config.mail_accounts.iter().cloned().enumerate()
+                        .map(|(index, account)| mail::listen(index, account)),
+                )
+            } else {
+                Subscription::none()
+            },
+            if self.config.sections.contains(&Section::Remote) {
+                Subscription::batch(
+                    self.config.remote_hosts.iter().cloned().enumerate().map(|(index, host)| {
+                        remote::listen(index, host, Duration::from_secs(self.config.remote_interval_secs.into()))
+                    }),
+                )
+            } else {
+                Subscription::none()
+            },
+            #[cfg(feature = "agent")]
+            if self.config.sections.contains(&Section::Agents) {
+                Subscription::batch(
+                    self.config.agents.iter().cloned().enumerate()
+                        .map(|(index, address)| agent::listen(index, address, &self.config)),
+                )
+            } else {
+                Subscription::none()
+            },
+            #[cfg(feature = "mpris")]
+            if self.config.sections.contains(&Section::Media) {
+                media::listen()
+            } else {
+                Subscription::none()
+            },
         ])
     }
 }
@@ -2981,9 +4834,10 @@ pub fn get_term_scheme(config:&Config)-> Option<ColorScheme>{
     None
 }
 
+/// Background colour of the terminal as sRGB components, like [`Color`], followed by the opacity
 pub fn get_term_bg_colour(config:&Config)->[f32;4]{
     // fallback: use cosmic window background colour
-    let [mut r,mut g,mut b,_] = cosmic::iced::Color::from(config.app_theme.theme().cosmic().background.base).into_linear();
+    let Color { mut r, mut g, mut b, .. } = Color::from(config.app_theme.theme().cosmic().background.base);
     // attempt to get current profile's terminal background colour
     if let Some(cs) = get_term_scheme(config){
         if let Some(colour) = cs.background{
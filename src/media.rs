@@ -0,0 +1,335 @@
+//! Now-playing section of the active MPRIS media player on the D-Bus session bus. Players are
+//! followed through their property and `Seeked` signals as they appear, change track or stop,
+//! and the buttons of the section are forwarded to the player through a [`Handle`].
+//!
+//! The player that is playing is shown, or otherwise the one that changed last. Its cover art
+//! is loaded on a background thread into a small thumbnail with rounded corners, and downloaded
+//! covers are cached.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use cosmic::iced::futures::{stream::{self, BoxStream, SelectAll}, SinkExt, StreamExt};
+use cosmic::iced::{self, Subscription};
+use cosmic::iced::futures::channel::mpsc::Sender;
+use cosmic::widget::image;
+use ::image::RgbaImage;
+use itertools::Itertools;
+use tokio::sync::mpsc;
+use zbus::zvariant::{OwnedValue, Value};
+use zbus::{fdo::DBusProxy, proxy, Connection};
+
+use crate::{util, Message};
+
+/// Bus names of MPRIS players start with this, followed by the name of the player
+const BUS_PREFIX:&str = "org.mpris.MediaPlayer2.";
+/// Size of the cover art thumbnail and the radius of its corners in pixels
+const ART_SIZE:u32 = 96;
+const ART_RADIUS:f32 = 12.;
+
+#[proxy(interface = "org.mpris.MediaPlayer2.Player", default_path = "/org/mpris/MediaPlayer2")]
+trait Player {
+    fn play_pause(&self)->zbus::Result<()>;
+    fn next(&self)->zbus::Result<()>;
+    fn previous(&self)->zbus::Result<()>;
+
+    #[zbus(property)]
+    fn metadata(&self)->zbus::Result<HashMap<String, OwnedValue>>;
+    #[zbus(property)]
+    fn playback_status(&self)->zbus::Result<String>;
+    /// Not announced when it changes, only read with the other properties
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn position(&self)->zbus::Result<i64>;
+
+    #[zbus(signal)]
+    fn seeked(&self, position:i64)->zbus::Result<()>;
+}
+
+/// Track of the active player
+#[derive(Clone, Debug, PartialEq)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: String,
+    /// URL of the cover art, often a `file://` path, empty if the player has none
+    pub art_url: String,
+    pub playing: bool,
+    pub length: Option<Duration>,
+    /// Position in the track when the player was last asked, at `updated`
+    position: Duration,
+    updated: Instant,
+}
+
+impl NowPlaying {
+    /// Position in the track now, assuming it keeps playing at normal speed
+    pub fn position(&self)->Duration{
+        let position = if self.playing {self.position + self.updated.elapsed()} else {self.position};
+        self.length.map_or(position, |length| position.min(length))
+    }
+}
+
+/// Cover art of the track as a thumbnail, and its dominant colour in sRGB
+#[derive(Clone, Debug)]
+pub struct Art {
+    pub url: String,
+    pub handle: image::Handle,
+    pub colour: [f32;3],
+}
+
+/// Bytes of the image at a `file://` URL, or at an http or https one, from the cache if it was
+/// downloaded before
+fn read_art(url:&str)->Result<Vec<u8>, String>{
+    if url.starts_with("file://") {
+        let path = url::Url::parse(url).ok().and_then(|url| url.to_file_path().ok())
+            .ok_or_else(|| format!("{} is not a file path", url))?;
+        return fs::read(&path).map_err(|err| format!("failed to read {}: {}", path.display(), err));
+    }
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(format!("unsupported cover art URL {}", url));
+    }
+    let cache = dirs::cache_dir().map(|dir| dir.join("eos").join("art").join(util::cache_name(url)));
+    if let Some(bytes) = cache.as_ref().and_then(|cache| fs::read(cache).ok()) {
+        return Ok(bytes);
+    }
+    let output = Command::new("curl")
+        .args(["--silent", "--fail", "--location", "--max-time", "20", url])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run curl: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("curl exited with {}", output.status));
+    }
+    if let Some(cache) = &cache {
+        let written = cache.parent().map_or(Ok(()), fs::create_dir_all).and_then(|()| fs::write(cache, &output.stdout));
+        if let Err(err) = written {
+            log::warn!("failed to cache the cover art: {}", err);
+        }
+    }
+    Ok(output.stdout)
+}
+
+/// Make the corners of a thumbnail transparent outside of quarter circles, with a soft edge
+fn round_corners(image:&mut RgbaImage){
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let (x, y) = (x as f32 + 0.5, y as f32 + 0.5);
+        // distance past the centre of the nearest corner's circle, zero away from the corners
+        let dx = (ART_RADIUS - x).max(x - (width - ART_RADIUS)).max(0.);
+        let dy = (ART_RADIUS - y).max(y - (height - ART_RADIUS)).max(0.);
+        let coverage = (ART_RADIUS + 0.5 - dx.hypot(dy)).clamp(0., 1.);
+        pixel[3] = (pixel[3] as f32 * coverage) as u8;
+    }
+}
+
+/// Most common colour of an image, counting vivid pixels more than grey ones, in 0 to 1
+fn dominant_colour(image:&RgbaImage)->[f32;3]{
+    // weight and colour sum of each bucket of 3 bits per channel
+    let mut buckets = HashMap::<[u8;3], (f32, [f32;3])>::new();
+    for pixel in image.pixels().filter(|pixel| pixel[3] > 0) {
+        let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(|c| c as f32 / 255.);
+        let saturation = r.max(g).max(b) - r.min(g).min(b);
+        let (weight, sum) = buckets.entry([pixel[0] >> 5, pixel[1] >> 5, pixel[2] >> 5]).or_default();
+        let w = 0.1 + saturation;
+        *weight += w;
+        *sum = [sum[0] + r * w, sum[1] + g * w, sum[2] + b * w];
+    }
+    buckets.into_values()
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map_or([0.5;3], |(weight, sum)| sum.map(|c| c / weight))
+}
+
+fn load_art(url:String)->Result<Art, String>{
+    let bytes = read_art(&url)?;
+    let mut thumbnail = ::image::load_from_memory(&bytes)
+        .map_err(|err| format!("failed to decode {}: {}", url, err))?
+        .thumbnail(ART_SIZE, ART_SIZE)
+        .to_rgba8();
+    round_corners(&mut thumbnail);
+    let colour = dominant_colour(&thumbnail);
+    let handle = image::Handle::from_rgba(thumbnail.width(), thumbnail.height(), thumbnail.into_raw());
+    Ok(Art { url, handle, colour })
+}
+
+/// Buttons of the media section
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Control {
+    PlayPause,
+    Next,
+    Previous,
+}
+
+/// Forwards the buttons to the active player
+#[derive(Clone)]
+pub struct Handle(mpsc::UnboundedSender<Control>);
+
+impl Handle {
+    pub fn send(&self, control:Control){
+        let _ = self.0.send(control);
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>)->fmt::Result{
+        f.write_str("media::Handle")
+    }
+}
+
+enum Event {
+    /// A player appeared on or left the bus
+    Owner(String, bool),
+    /// Something about the player of the bus name changed
+    Changed(String),
+    Control(Control),
+    /// Cover art of the URL finished loading
+    Art(String, Option<Art>),
+}
+
+/// Text of a metadata entry, or the first of a list like `xesam:artist`
+fn text(metadata:&HashMap<String, OwnedValue>, key:&str)->String{
+    match metadata.get(key).map(|value| &**value) {
+        Some(Value::Str(text)) => text.to_string(),
+        Some(Value::Array(list)) => list.iter()
+            .filter_map(|item| match item {Value::Str(text) => Some(text.as_str()), _ => None})
+            .join(", "),
+        _ => String::new(),
+    }
+}
+
+async fn now_playing(player:&PlayerProxy<'_>)->zbus::Result<NowPlaying>{
+    let metadata = player.metadata().await?;
+    // in microseconds, as an `x` or, by some players, a `t`
+    let length = match metadata.get("mpris:length").map(|value| &**value) {
+        Some(Value::I64(length)) => Some(*length),
+        Some(Value::U64(length)) => Some(*length as i64),
+        _ => None,
+    };
+    Ok(NowPlaying {
+        title: text(&metadata, "xesam:title"),
+        artist: text(&metadata, "xesam:artist"),
+        art_url: text(&metadata, "mpris:artUrl"),
+        playing: player.playback_status().await? == "Playing",
+        length: length.filter(|length| *length > 0).map(|length| Duration::from_micros(length as u64)),
+        // players without a position, like some browsers, start at the beginning
+        position: Duration::from_micros(player.position().await.unwrap_or(0).max(0) as u64),
+        updated: Instant::now(),
+    })
+}
+
+/// Follow the signals of a player, returning its proxy
+async fn watch<'a>(connection:&Connection, name:String, events:&mut SelectAll<BoxStream<'a, Event>>)->zbus::Result<PlayerProxy<'a>>{
+    let player = PlayerProxy::builder(connection).destination(name.clone())?.build().await?;
+    let metadata = name.clone();
+    events.push(player.receive_metadata_changed().await.map(move |_| Event::Changed(metadata.clone())).boxed());
+    let status = name.clone();
+    events.push(player.receive_playback_status_changed().await.map(move |_| Event::Changed(status.clone())).boxed());
+    events.push(player.receive_seeked().await?.map(move |_| Event::Changed(name.clone())).boxed());
+    Ok(player)
+}
+
+/// The player that is playing, or the one that changed last
+fn active(players:&HashMap<String, (PlayerProxy<'_>, Option<NowPlaying>)>)->Option<(&str, &NowPlaying)>{
+    players.iter()
+        .filter_map(|(name, (_, playing))| Some((name.as_str(), playing.as_ref()?)))
+        .max_by_key(|(_, playing)| (playing.playing, playing.updated))
+}
+
+async fn run(output:&mut Sender<Message>, controls:mpsc::UnboundedReceiver<Control>)->zbus::Result<()>{
+    let connection = Connection::session().await?;
+    let bus = DBusProxy::new(&connection).await?;
+    let mut events:SelectAll<BoxStream<'_, Event>> = SelectAll::new();
+    events.push(stream::unfold(controls, |mut controls| async move {
+        controls.recv().await.map(|control| (Event::Control(control), controls))
+    }).boxed());
+    let (art_loaded, art) = mpsc::unbounded_channel();
+    events.push(stream::unfold(art, |mut art| async move {
+        art.recv().await.map(|loaded| (loaded, art))
+    }).boxed());
+    events.push(bus.receive_name_owner_changed().await?.filter_map(|signal| async move {
+        let args = signal.args().ok()?;
+        args.name().as_str().starts_with(BUS_PREFIX)
+            .then(|| Event::Owner(args.name().to_string(), args.new_owner().is_some()))
+    }).boxed());
+
+    let mut players = HashMap::new();
+    let names = bus.list_names().await?.into_iter().map(|name| name.to_string());
+    let mut appeared:Vec<String> = names.filter(|name| name.starts_with(BUS_PREFIX)).collect();
+    let mut shown:Option<NowPlaying> = None;
+    loop {
+        for name in appeared.drain(..) {
+            match watch(&connection, name.clone(), &mut events).await {
+                Ok(player) => {
+                    let playing = now_playing(&player).await.ok();
+                    players.insert(name, (player, playing));
+                },
+                Err(err) => log::warn!("failed to follow the media player {}: {}", name, err),
+            }
+        }
+        let now = active(&players).map(|(_, playing)| playing.clone());
+        if now != shown {
+            let art_url = now.as_ref().map_or("", |playing| playing.art_url.as_str());
+            if shown.as_ref().map_or("", |playing| playing.art_url.as_str()) != art_url {
+                let url = art_url.to_string();
+                let art_loaded = art_loaded.clone();
+                std::thread::spawn(move || {
+                    let art = (!url.is_empty()).then(|| load_art(url.clone())).and_then(|art| {
+                        art.map_err(|err| log::warn!("failed to load the cover art: {}", err)).ok()
+                    });
+                    let _ = art_loaded.send(Event::Art(url, art));
+                });
+            }
+            shown = now.clone();
+            let _ = output.send(Message::Media(now)).await;
+        }
+        match events.next().await {
+            Some(Event::Owner(name, true)) => appeared.push(name),
+            Some(Event::Owner(name, false)) => {
+                players.remove(&name);
+            },
+            Some(Event::Changed(name)) => {
+                if let Some((player, playing)) = players.get_mut(&name) {
+                    *playing = now_playing(player).await.ok();
+                }
+            },
+            Some(Event::Control(control)) => {
+                let Some((name, _)) = active(&players) else {continue};
+                let player = &players[name].0;
+                let sent = match control {
+                    Control::PlayPause => player.play_pause().await,
+                    Control::Next => player.next().await,
+                    Control::Previous => player.previous().await,
+                };
+                if let Err(err) = sent {
+                    log::warn!("failed to control the media player {}: {}", name, err);
+                }
+            },
+            // art of a track that is no longer shown is dropped
+            Some(Event::Art(url, art)) => {
+                if shown.as_ref().is_some_and(|playing| playing.art_url == url) {
+                    let _ = output.send(Message::MediaArt(art)).await;
+                }
+            },
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Follow the active player, sending `Message::MediaReady` with the handle for its buttons
+/// and then `Message::Media` whenever what it plays changes
+pub fn listen()->Subscription<Message>{
+    struct MediaSubscription;
+    Subscription::run_with_id(
+        TypeId::of::<MediaSubscription>(),
+        iced::stream::channel(16, |mut output| async move {
+            let (controls, received) = mpsc::unbounded_channel();
+            if output.send(Message::MediaReady(Handle(controls))).await.is_ok() {
+                if let Err(err) = run(&mut output, received).await {
+                    log::warn!("failed to follow the media players on D-Bus: {}", err);
+                }
+            }
+            std::future::pending().await
+        }),
+    )
+}
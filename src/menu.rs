@@ -209,8 +209,22 @@ pub fn menu_bar<'a>(config: &Config, key_binds: &HashMap<KeyBind, Action>) -> El
                         None,
                         Action::ColorSchemes(config.color_scheme_kind()),
                     ),
+                    MenuItem::Button(fl!("next-scene"), None, Action::SceneNext),
+                    MenuItem::Button(fl!("menu-shader-tweaks"), None, Action::ShaderTweaks),
+                    MenuItem::Button(fl!("toggle-overlay"), None, Action::ToggleOverlay),
+                    MenuItem::Button(fl!("toggle-pip"), None, Action::TogglePip),
+                    MenuItem::CheckBox(fl!("compact-mode"), None, config.compact, Action::ToggleCompact),
+                    MenuItem::Button(fl!("next-config-profile"), None, Action::ConfigProfileNext),
                     MenuItem::Button(fl!("menu-settings"), None, Action::Settings),
                     MenuItem::Divider,
+                    MenuItem::Button(fl!("export-metrics"), None, Action::ExportMetrics),
+                    MenuItem::Button(
+                        fl!("export-metrics-graphs"),
+                        None,
+                        Action::ExportMetricsGraphs,
+                    ),
+                    MenuItem::Button(fl!("export-background"), None, Action::ExportBackground),
+                    MenuItem::Divider,
                     MenuItem::Button(fl!("menu-about"), None, Action::About),
                 ],
             ),
@@ -0,0 +1,178 @@
+//! Log of `Config::metric_log_metrics`, sampled every `Config::metric_log_interval_secs` and
+//! appended to a CSV or JSON lines file per day in `$XDG_DATA_HOME/eos/metrics`, for analysis
+//! after the fact. A background thread writes the samples, and deletes the files older than
+//! `Config::metric_log_retention_days` when it starts and whenever it begins a new day.
+
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::config::{Config, MetricLogFormat};
+use crate::worker::{Export, Sampler};
+
+/// Names of the metrics that can be logged, in the order of the columns
+pub const METRICS:[&str; 6] = ["cpu_avg", "cpu_max", "gpu_util", "gpu_temp", "gpu_mem", "ram"];
+
+/// Directory the files are written to
+fn dir()->io::Result<PathBuf>{
+    dirs::data_dir()
+        .map(|dir| dir.join("eos").join("metrics"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))
+}
+
+/// What the logger was started with, to restart it when one of them changes
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    format: MetricLogFormat,
+    metrics: Vec<&'static str>,
+    interval: Duration,
+    retention: Duration,
+}
+
+impl Settings {
+    fn new(config:&Config)->Self{
+        Self {
+            format: config.metric_log,
+            // in the order of the columns, whatever the order in the config
+            metrics: METRICS.into_iter().filter(|name| config.metric_log_metrics.iter().any(|metric| metric == name)).collect(),
+            interval: Duration::from_secs(config.metric_log_interval_secs.into()),
+            retention: Duration::from_secs(u64::from(config.metric_log_retention_days) * 24 * 3600),
+        }
+    }
+
+    fn extension(&self)->&'static str{
+        match self.format {
+            MetricLogFormat::Csv => "csv",
+            MetricLogFormat::Off | MetricLogFormat::Jsonl => "jsonl",
+        }
+    }
+
+    fn header(&self)->String{
+        format!("timestamp,{}", self.metrics.join(","))
+    }
+
+    /// Line of one sample, leaving out or leaving empty the metrics that are unknown
+    fn line(&self, time:DateTime<Local>, values:&[(&'static str, f32)])->String{
+        let timestamp = time.to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+        let value = |name:&str| values.iter().find(|(known, _)| *known == name).map(|(_, value)| *value).filter(|value| value.is_finite());
+        let mut line = String::new();
+        match self.format {
+            MetricLogFormat::Csv => {
+                line.push_str(&timestamp);
+                for &name in &self.metrics {
+                    line.push(',');
+                    if let Some(value) = value(name) {
+                        let _ = write!(line, "{:.2}", value);
+                    }
+                }
+            },
+            MetricLogFormat::Off | MetricLogFormat::Jsonl => {
+                let _ = write!(line, "{{\"timestamp\":\"{}\"", timestamp);
+                for &name in &self.metrics {
+                    if let Some(value) = value(name) {
+                        let _ = write!(line, ",\"{}\":{:.2}", name, value);
+                    }
+                }
+                line.push('}');
+            },
+        }
+        line
+    }
+}
+
+/// Delete the files written longer than `retention` ago
+fn prune(dir:&Path, retention:Duration){
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let old = entry.metadata().and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age > retention);
+        if old {
+            if let Err(err) = fs::remove_file(entry.path()) {
+                log::warn!("failed to delete the old metric log {}: {}", entry.path().display(), err);
+            }
+        }
+    }
+}
+
+/// File of the day to append to. A CSV file of the day that has other columns is left alone
+/// and a new one is begun next to it, named after the time.
+fn open(dir:&Path, settings:&Settings, time:DateTime<Local>)->io::Result<File>{
+    fs::create_dir_all(dir)?;
+    let extension = settings.extension();
+    let mut path = dir.join(format!("{}.{}", time.format("%Y-%m-%d"), extension));
+    let csv = settings.format == MetricLogFormat::Csv;
+    if csv && path.exists() {
+        let mut first = String::new();
+        BufReader::new(File::open(&path)?).read_line(&mut first)?;
+        if first.trim_end() != settings.header() {
+            path = dir.join(format!("{}.{}", time.format("%Y-%m-%d_%H-%M-%S"), extension));
+        }
+    }
+    let new = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    if csv && new {
+        writeln!(file, "{}", settings.header())?;
+    }
+    Ok(file)
+}
+
+/// Write the samples until the logger is dropped, beginning a new file every day
+fn write(settings:Settings, samples:mpsc::Receiver<(DateTime<Local>, Vec<(&'static str, f32)>)>){
+    let dir = match dir() {
+        Ok(dir) => dir,
+        Err(err) => {
+            log::warn!("failed to log the metrics: {}", err);
+            return;
+        },
+    };
+    let mut current:Option<(NaiveDate, File)> = None;
+    for (time, values) in samples {
+        if current.as_ref().map(|(day, _)| *day) != Some(time.date_naive()) {
+            prune(&dir, settings.retention);
+            current = match open(&dir, &settings, time) {
+                Ok(file) => Some((time.date_naive(), file)),
+                Err(err) => {
+                    log::warn!("failed to open the metric log in {}: {}", dir.display(), err);
+                    None
+                },
+            };
+        }
+        let Some((_, file)) = &mut current else { continue };
+        if let Err(err) = writeln!(file, "{}", settings.line(time, &values)) {
+            log::warn!("failed to write to the metric log: {}", err);
+        }
+    }
+}
+
+/// Logs a sample every interval while `Config::metric_log` is set
+pub type Logger = Sampler<Log>;
+
+pub struct Log;
+
+impl Export for Log {
+    type Settings = Settings;
+    type Sample = (DateTime<Local>, Vec<(&'static str, f32)>);
+
+    fn settings(config:&Config)->Settings{
+        Settings::new(config)
+    }
+
+    fn interval(settings:&Settings)->Duration{
+        settings.interval
+    }
+
+    fn sample(settings:&Settings, values:impl Iterator<Item = (&'static str, f32)>)->Self::Sample{
+        (Local::now(), values.filter(|(name, _)| settings.metrics.contains(name)).collect())
+    }
+
+    fn run(settings:Settings, samples:mpsc::Receiver<Self::Sample>){
+        write(settings, samples);
+    }
+}
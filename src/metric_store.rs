@@ -0,0 +1,348 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, history::History};
+
+/// Magic bytes at the start of the history file
+const MAGIC: &[u8; 4] = b"EOSH";
+/// Bump whenever the on-disk layout changes, old files are then discarded
+const FILE_VERSION: u32 = 1;
+const FILE_NAME: &str = "history.bin";
+
+/// Metrics that are kept in long-term history
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Metric {
+    CpuAvg,
+    CpuMax,
+    GpuUtil,
+    GpuTemp,
+    RamUsed,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 5] = [
+        Metric::CpuAvg, Metric::CpuMax, Metric::GpuUtil, Metric::GpuTemp, Metric::RamUsed,
+    ];
+
+    /// Name of the metric in exported files, e.g. `cpu_avg`
+    pub fn key(self) -> &'static str {
+        match self {
+            Metric::CpuAvg => "cpu_avg",
+            Metric::CpuMax => "cpu_max",
+            Metric::GpuUtil => "gpu_util",
+            Metric::GpuTemp => "gpu_temp",
+            Metric::RamUsed => "ram_used",
+        }
+    }
+
+    /// Stable identifier used in the history file
+    fn id(self) -> u32 {
+        match self {
+            Metric::CpuAvg => 0,
+            Metric::CpuMax => 1,
+            Metric::GpuUtil => 2,
+            Metric::GpuTemp => 3,
+            Metric::RamUsed => 4,
+        }
+    }
+
+    fn from_id(id: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.id() == id)
+    }
+}
+
+/// Long-term, low resolution history of all [`Metric`]s.
+///
+/// Samples are recorded at whatever rate they are collected and averaged into
+/// one entry per `resolution`. The store can be written to and restored from
+/// the XDG data directory so graphs survive restarts.
+pub struct MetricStore {
+    resolution: Duration,
+    capacity: usize,
+    series: BTreeMap<Metric, History<f32>>,
+    pending: BTreeMap<Metric, (f32, u32)>,
+    last_flush: Instant,
+}
+
+impl MetricStore {
+    pub fn new(config: &Config) -> Self {
+        let (resolution, capacity) = Self::layout(config);
+        Self {
+            resolution,
+            capacity,
+            series: Metric::ALL
+                .into_iter()
+                .map(|m| (m, History::new(capacity)))
+                .collect(),
+            pending: BTreeMap::new(),
+            last_flush: Instant::now(),
+        }
+    }
+
+    /// Resolution and number of retained entries described by the config
+    fn layout(config: &Config) -> (Duration, usize) {
+        let resolution_secs = u64::from(config.history_resolution_secs.max(1));
+        let retention_secs = u64::from(config.history_retention_hours) * 3600;
+        (
+            Duration::from_secs(resolution_secs),
+            (retention_secs / resolution_secs) as usize,
+        )
+    }
+
+    /// Apply a changed retention window or resolution. Changing the resolution
+    /// discards the history, since old entries would no longer line up in time.
+    pub fn configure(&mut self, config: &Config) {
+        let (resolution, capacity) = Self::layout(config);
+        if resolution != self.resolution {
+            *self = Self::new(config);
+        } else if capacity != self.capacity {
+            self.capacity = capacity;
+            for history in self.series.values_mut() {
+                history.set_capacity(capacity);
+            }
+        }
+    }
+
+    pub fn resolution(&self) -> Duration {
+        self.resolution
+    }
+
+    pub fn get(&self, metric: Metric) -> Option<&History<f32>> {
+        self.series.get(&metric)
+    }
+
+    /// Record a raw sample, to be averaged into the next history entry
+    pub fn record(&mut self, metric: Metric, value: f32) {
+        let (sum, count) = self.pending.entry(metric).or_insert((0., 0));
+        *sum += value;
+        *count += 1;
+    }
+
+    /// Push the averaged pending samples once a full resolution step has passed, for each
+    /// step that passed, so that samples recorded rarely, like while the window is hidden,
+    /// keep the entries evenly spaced in time
+    pub fn tick(&mut self) {
+        let steps = (self.last_flush.elapsed().as_secs_f64() / self.resolution.as_secs_f64()) as usize;
+        if steps == 0 {
+            return;
+        }
+        self.last_flush = Instant::now();
+        for (metric, (sum, count)) in std::mem::take(&mut self.pending) {
+            if let Some(history) = self.series.get_mut(&metric) {
+                history.push_for(sum / count.max(1) as f32, steps);
+            }
+        }
+    }
+
+    /// Location of the history file in the XDG data directory
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("eos").join(FILE_NAME))
+    }
+
+    /// Write all series to disk
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // write to a temporary file first so a crash never leaves a truncated history
+        let tmp = path.with_extension("tmp");
+        fs::File::create(&tmp)?.write_all(&self.encode(unix_now()))?;
+        fs::rename(tmp, path)
+    }
+
+    /// Restore series from disk. Entries for the time eos was not running are
+    /// filled with zeros so the history stays aligned with wall-clock time.
+    pub fn load(&mut self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        let mut bytes = Vec::new();
+        fs::File::open(path)?.read_to_end(&mut bytes)?;
+        self.decode(&bytes, unix_now())
+    }
+
+    /// Contents of the history file, saved at `now` in seconds since the epoch
+    fn encode(&self, now: u64) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&FILE_VERSION.to_le_bytes());
+        buf.extend_from_slice(&(self.resolution.as_secs() as u32).to_le_bytes());
+        buf.extend_from_slice(&now.to_le_bytes());
+        buf.extend_from_slice(&(self.series.len() as u32).to_le_bytes());
+        for (metric, history) in &self.series {
+            buf.extend_from_slice(&metric.id().to_le_bytes());
+            buf.extend_from_slice(&(history.len() as u32).to_le_bytes());
+            for value in history.iter() {
+                buf.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        buf
+    }
+
+    /// Restore the series from the contents of a history file, read at `now`
+    fn decode(&mut self, bytes: &[u8], now: u64) -> io::Result<()> {
+        let mut reader = ByteReader(bytes);
+        if reader.take(4)? != MAGIC {
+            return Err(invalid_data("not a history file"));
+        }
+        let version = reader.u32()?;
+        let resolution = reader.u32()?;
+        if version != FILE_VERSION || u64::from(resolution) != self.resolution.as_secs() {
+            return Err(invalid_data("history file has an incompatible layout"));
+        }
+        let saved_at = reader.u64()?;
+        let series_count = reader.u32()?;
+        let gap = (now.saturating_sub(saved_at) / self.resolution.as_secs().max(1)) as usize;
+
+        for _ in 0..series_count {
+            let id = reader.u32()?;
+            let len = reader.u32()? as usize;
+            let values = reader.take(len.saturating_mul(4))?
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect::<Vec<f32>>();
+            let Some(metric) = Metric::from_id(id) else {
+                continue;
+            };
+            let mut history = History::new(self.capacity);
+            // stored newest first, so replay from the oldest entry
+            for value in values.into_iter().rev() {
+                history.push(value);
+            }
+            for _ in 0..gap.min(self.capacity) {
+                history.push(0.);
+            }
+            self.series.insert(metric, history);
+        }
+        Ok(())
+    }
+}
+
+/// Seconds since the epoch
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Minimal little-endian reader over the bytes of a history file
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(invalid_data("truncated history file"));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        let b = self.take(8)?;
+        Ok(u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> MetricStore {
+        MetricStore::new(&Config {
+            history_retention_hours: 1,
+            history_resolution_secs: 60,
+            ..Config::default()
+        })
+    }
+
+    fn values(store: &MetricStore, metric: Metric) -> Vec<f32> {
+        store.get(metric).unwrap().iter().collect()
+    }
+
+    #[test]
+    fn tick_averages_the_pending_samples() {
+        let mut store = store();
+        store.record(Metric::CpuAvg, 10.);
+        store.record(Metric::CpuAvg, 30.);
+        store.tick();
+        // less than a resolution step has passed
+        assert!(values(&store, Metric::CpuAvg).is_empty());
+        store.last_flush -= store.resolution();
+        store.tick();
+        assert_eq!(values(&store, Metric::CpuAvg), [20.]);
+        assert!(values(&store, Metric::GpuUtil).is_empty());
+    }
+
+    #[test]
+    fn tick_fills_the_steps_that_passed() {
+        let mut store = store();
+        store.record(Metric::CpuAvg, 10.);
+        store.last_flush -= store.resolution() * 3;
+        store.tick();
+        assert_eq!(values(&store, Metric::CpuAvg), [10., 10., 10.]);
+    }
+
+    #[test]
+    fn layout_follows_the_config() {
+        let store = store();
+        assert_eq!(store.resolution(), Duration::from_secs(60));
+        assert_eq!(store.get(Metric::RamUsed).unwrap().capacity(), 60);
+    }
+
+    #[test]
+    fn round_trip_fills_the_gap_with_zeros() {
+        let mut saved = store();
+        for value in [1., 2., 3.] {
+            saved.series.get_mut(&Metric::GpuTemp).unwrap().push(value);
+        }
+        let bytes = saved.encode(1000);
+        let mut restored = store();
+        // two resolution steps after saving
+        restored.decode(&bytes, 1000 + 150).unwrap();
+        assert_eq!(values(&restored, Metric::GpuTemp), [0., 0., 3., 2., 1.]);
+        assert!(values(&restored, Metric::CpuAvg).iter().all(|&v| v == 0.));
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        let mut store = store();
+        let bytes = store.encode(0);
+        assert!(store.decode(b"NOPE", 0).is_err());
+        assert!(store.decode(&bytes[..bytes.len() - 2], 0).is_err());
+        assert!(store.decode(&[], 0).is_err());
+        let mut other = MetricStore::new(&Config {
+            history_resolution_secs: 10,
+            ..Config::default()
+        });
+        assert!(other.decode(&bytes, 0).is_err());
+    }
+
+    #[test]
+    fn huge_lengths_are_truncated_files() {
+        let mut store = store();
+        let mut bytes = store.encode(0);
+        // the length of the first series
+        let at = 4 + 4 + 4 + 8 + 4 + 4;
+        bytes[at..at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(store.decode(&bytes, 0).is_err());
+    }
+}
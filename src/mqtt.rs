@@ -0,0 +1,196 @@
+//! Publisher of the metrics to the MQTT broker at `Config::mqtt_broker`, for home automation.
+//! Every `Config::mqtt_interval_secs` the values are published as one JSON object to
+//! `eos/<host>/state`, and `eos/<host>/availability` tells whether eos runs. Home Assistant
+//! discovers each metric as a sensor of a device named after the machine from the retained
+//! configs under `Config::mqtt_discovery_prefix`, which are published again whenever the
+//! connection is made, leaving out the metrics this machine does not have, like those of the GPU.
+//!
+//! The password of `Config::mqtt_user` is looked up in the system keyring through
+//! `secret-tool`, under the attributes `service eos-mqtt`, `host` and `user`. With
+//! `Config::mqtt_tls` the connection is encrypted and the broker verified against the
+//! certificate authorities of the system, or only against `Config::mqtt_ca` if it is set.
+
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use rumqttc::{Client, Event, LastWill, MqttOptions, Packet, QoS, Transport};
+use serde_json::{json, Map, Value};
+
+use crate::config::Config;
+use crate::util;
+use crate::worker::{Export, Sampler};
+
+const DEFAULT_PORT:u16 = 1883;
+const DEFAULT_TLS_PORT:u16 = 8883;
+const KEEP_ALIVE:Duration = Duration::from_secs(30);
+/// Time before connecting again after the connection failed or dropped
+const RETRY_INTERVAL:Duration = Duration::from_secs(30);
+
+/// Key in the state, name, unit and Home Assistant device class of each sensor
+const SENSORS:[(&str, &str, &str, Option<&str>); 8] = [
+    ("cpu_avg", "CPU usage", "%", None),
+    ("cpu_max", "Busiest CPU usage", "%", None),
+    ("cpu_freq", "CPU frequency", "MHz", Some("frequency")),
+    ("ram", "Memory usage", "%", None),
+    ("gpu_util", "GPU usage", "%", None),
+    ("gpu_temp", "GPU temperature", "°C", Some("temperature")),
+    ("gpu_power", "GPU power", "W", Some("power")),
+    ("gpu_mem", "GPU memory usage", "%", None),
+];
+
+/// What the publisher was started with, to restart it when one of them changes
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    broker: String,
+    user: String,
+    interval: Duration,
+    discovery_prefix: String,
+    tls: bool,
+    /// File of the certificate authorities the broker is verified against, empty for those
+    /// of the system
+    ca: String,
+}
+
+impl Settings {
+    fn new(config:&Config)->Self{
+        Self {
+            broker: config.mqtt_broker.clone(),
+            user: config.mqtt_user.clone(),
+            interval: Duration::from_secs(config.mqtt_interval_secs.into()),
+            discovery_prefix: config.mqtt_discovery_prefix.clone(),
+            tls: config.mqtt_tls,
+            ca: config.mqtt_ca.clone(),
+        }
+    }
+
+    /// Host and port of the broker, like `broker.lan:1883` or just `broker.lan`
+    fn address(&self)->(String, u16){
+        match self.broker.rsplit_once(':').and_then(|(host, port)| Some((host, port.parse().ok()?))) {
+            Some((host, port)) => (host.to_string(), port),
+            None => (self.broker.clone(), if self.tls {DEFAULT_TLS_PORT} else {DEFAULT_PORT}),
+        }
+    }
+
+    fn transport(&self)->Result<Transport, String>{
+        if !self.tls {return Ok(Transport::tcp());}
+        if self.ca.is_empty() {return Ok(Transport::tls_with_default_config());}
+        let ca = fs::read(util::expand(&self.ca)).map_err(|err| format!("failed to read {}: {}", self.ca, err))?;
+        Ok(Transport::tls(ca, None, None))
+    }
+}
+
+/// Publish the retained discovery configs of the sensors with a value
+fn announce(client:&Client, settings:&Settings, host:&str, values:&[(&'static str, f32)]){
+    let device = json!({
+        "identifiers": [format!("eos_{}", host)],
+        "name": host,
+        "manufacturer": "eos",
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+    for (key, name, unit, class) in SENSORS {
+        if !values.iter().any(|(known, _)| *known == key) {continue;}
+        let mut config = json!({
+            "name": name,
+            "unique_id": format!("eos_{}_{}", host, key),
+            "object_id": format!("{}_{}", host, key),
+            "state_topic": format!("eos/{}/state", host),
+            "value_template": format!("{{{{ value_json.{} }}}}", key),
+            "availability_topic": format!("eos/{}/availability", host),
+            "unit_of_measurement": unit,
+            "state_class": "measurement",
+            "device": device,
+        });
+        if let Some(class) = class {
+            config["device_class"] = class.into();
+        }
+        let topic = format!("{}/sensor/eos_{}/{}/config", settings.discovery_prefix, host, key);
+        if let Err(err) = client.publish(topic, QoS::AtLeastOnce, true, config.to_string()) {
+            log::warn!("failed to announce the MQTT sensor {}: {}", key, err);
+        }
+    }
+    let _ = client.publish(format!("eos/{}/availability", host), QoS::AtLeastOnce, true, "online");
+}
+
+/// Publish the samples until the publisher is dropped, while a second thread keeps up the
+/// connection
+fn publish(settings:Settings, samples:mpsc::Receiver<Vec<(&'static str, f32)>>){
+    let host = util::host_name();
+    let (address, port) = settings.address();
+    let transport = match settings.transport() {
+        Ok(transport) => transport,
+        Err(err) => {
+            log::warn!("failed to set up TLS for the MQTT broker {}: {}", settings.broker, err);
+            return;
+        },
+    };
+    let mut options = MqttOptions::new(format!("eos-{}", host), address.clone(), port);
+    options.set_transport(transport);
+    options.set_keep_alive(KEEP_ALIVE);
+    options.set_last_will(LastWill::new(format!("eos/{}/availability", host), "offline", QoS::AtLeastOnce, true));
+    if !settings.user.is_empty() {
+        match util::password("eos-mqtt", &address, &settings.user) {
+            Ok(password) => {options.set_credentials(settings.user.clone(), password);},
+            Err(err) => log::warn!("connecting to the MQTT broker without a password: {}", err),
+        }
+    }
+    let (client, mut connection) = Client::new(options, 16);
+    // the configs are published again after every reconnection, as the broker may have lost them
+    let announced = Arc::new(AtomicBool::new(false));
+    let closed = Arc::new(AtomicBool::new(false));
+    let (connected, stopped, broker) = (announced.clone(), closed.clone(), settings.broker.clone());
+    std::thread::spawn(move || {
+        for event in connection.iter() {
+            match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => connected.store(false, Ordering::Relaxed),
+                Ok(_) => (),
+                Err(err) => {
+                    if stopped.load(Ordering::Relaxed) {break;}
+                    log::warn!("failed to reach the MQTT broker {}, trying again in {:?}: {}", broker, RETRY_INTERVAL, err);
+                    std::thread::sleep(RETRY_INTERVAL);
+                },
+            }
+        }
+    });
+    for values in samples {
+        if !announced.swap(true, Ordering::Relaxed) {
+            announce(&client, &settings, &host, &values);
+        }
+        let state:Map<String, Value> = values.iter()
+            .filter(|(_, value)| value.is_finite())
+            .map(|(key, value)| (key.to_string(), json!((value * 100.).round() / 100.)))
+            .collect();
+        // dropped rather than queued while the broker cannot be reached
+        let _ = client.try_publish(format!("eos/{}/state", host), QoS::AtMostOnce, false, Value::Object(state).to_string());
+    }
+    closed.store(true, Ordering::Relaxed);
+    let _ = client.publish(format!("eos/{}/availability", host), QoS::AtLeastOnce, true, "offline");
+    let _ = client.disconnect();
+}
+
+/// Publishes a sample every interval while `Config::mqtt_broker` is set
+pub type Publisher = Sampler<Publish>;
+
+pub struct Publish;
+
+impl Export for Publish {
+    type Settings = Settings;
+    type Sample = Vec<(&'static str, f32)>;
+
+    fn settings(config:&Config)->Settings{
+        Settings::new(config)
+    }
+
+    fn interval(settings:&Settings)->Duration{
+        settings.interval
+    }
+
+    fn sample(_:&Settings, values:impl Iterator<Item = (&'static str, f32)>)->Self::Sample{
+        values.collect()
+    }
+
+    fn run(settings:Settings, samples:mpsc::Receiver<Self::Sample>){
+        publish(settings, samples);
+    }
+}
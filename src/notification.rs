@@ -0,0 +1,33 @@
+//! Desktop notifications through `notify-send`, which every notification daemon understands,
+//! sent from a background thread so a slow daemon cannot stall the interface
+
+use std::process::{Command, Stdio};
+
+/// Show a notification with a `summary` line and a `body`
+pub fn send(summary:String, body:String){
+    std::thread::spawn(move || {
+        let status = Command::new("notify-send")
+            .args(["--app-name=eos", "--icon=de.juliankarrer.eos", &summary, &body])
+            .stdin(Stdio::null())
+            .status();
+        match status {
+            Ok(status) if !status.success() => log::warn!("notify-send exited with {}", status),
+            Err(err) => log::warn!("failed to run notify-send: {}", err),
+            _ => (),
+        }
+    });
+}
+
+/// Play a sound file through PipeWire, or PulseAudio where `pw-play` is missing
+pub fn play(path:String){
+    std::thread::spawn(move || {
+        let played = ["pw-play", "paplay"].iter().any(|player| Command::new(player)
+            .arg(&path)
+            .stdin(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success()));
+        if !played {
+            log::warn!("failed to play {} with pw-play or paplay", path);
+        }
+    });
+}
@@ -0,0 +1,65 @@
+//! Overlay for gaming: a small, semi-transparent layer surface above all windows, including
+//! fullscreen ones, with a one-line readout of the CPU and GPU. The pointer passes through it
+//! and it never takes the keyboard, so it can stay shown while playing.
+//!
+//! It is toggled by `Action::ToggleOverlay` or from outside with `eos --toggle-overlay`, which
+//! sends SIGUSR1 to the running instances, see [`crate::signals`], so a shortcut of the
+//! compositor can toggle it while eos is not focused.
+
+use cosmic::app::Task;
+use cosmic::iced::window;
+#[cfg(feature = "wayland")]
+use cosmic::iced::platform_specific::{
+    runtime::wayland::layer_surface::{IcedMargin, IcedOutput, SctkLayerSurfaceSettings},
+    shell::commands::layer_surface::{
+        destroy_layer_surface, get_layer_surface, KeyboardInteractivity, Layer,
+    },
+};
+
+use crate::config::Config;
+use crate::Message;
+
+/// Namespace compositors may match the surface by
+#[cfg(feature = "wayland")]
+const NAMESPACE:&str = "eos-overlay";
+/// Distance from the edges of the output in logical pixels
+#[cfg(feature = "wayland")]
+const MARGIN:i32 = 8;
+
+/// Create the overlay surface on the active output, returning its id for `view_window`
+#[cfg(feature = "wayland")]
+pub fn open(config:&Config)->Option<(window::Id, Task<Message>)>{
+    let id = window::Id::unique();
+    let task = get_layer_surface(SctkLayerSurfaceSettings {
+        id,
+        layer: Layer::Overlay,
+        keyboard_interactivity: KeyboardInteractivity::None,
+        // clicks go to the game below
+        pointer_interactivity: false,
+        anchor: crate::desktop_widget::anchor(config.overlay_anchor),
+        output: IcedOutput::Active,
+        namespace: NAMESPACE.to_string(),
+        margin: IcedMargin { top: MARGIN, right: MARGIN, bottom: MARGIN, left: MARGIN },
+        // sized to the readout
+        size: Some((None, None)),
+        exclusive_zone: -1,
+        ..Default::default()
+    });
+    Some((id, task))
+}
+
+#[cfg(not(feature = "wayland"))]
+pub fn open(_config:&Config)->Option<(window::Id, Task<Message>)>{
+    log::warn!("eos was built without Wayland support, so it cannot show the overlay");
+    None
+}
+
+#[cfg(feature = "wayland")]
+pub fn close(id:window::Id)->Task<Message>{
+    destroy_layer_surface(id)
+}
+
+#[cfg(not(feature = "wayland"))]
+pub fn close(_id:window::Id)->Task<Message>{
+    Task::none()
+}
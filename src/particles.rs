@@ -0,0 +1,210 @@
+//! Sparks rising over the background scene, simulated in a compute pass.
+//! More of them spawn the busier the CPU is, and they rise faster the busier the GPU is.
+
+use std::time::Instant;
+
+use bytemuck::Zeroable;
+use cosmic::iced::wgpu::{self, util::DeviceExt, BlendState, PipelineCompilationOptions};
+
+/// Number of simulated particles, a multiple of the workgroup size
+const PARTICLES:u32 = 4096;
+const WORKGROUP_SIZE:u32 = 64;
+/// Colour of the sparks, matching the halo of the earth scene
+const COLOUR:[f32;4] = [235./255., 155./255., 0., 0.8];
+
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct ParticleCRepr {
+    pos: [f32;2],
+    vel: [f32;2],
+    age: f32,
+    lifetime: f32,
+}
+
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct ParamsCRepr {
+    dt: f32,
+    time: f32,
+    spawn: f32,
+    speed: f32,
+    aspect: f32,
+    /// Pads the colour to the 16 byte alignment of `vec4f`
+    _pad: [f32;3],
+    colour: [f32;4],
+}
+
+pub struct Particles {
+    compute_pipeline: wgpu::ComputePipeline,
+    render_pipeline: wgpu::RenderPipeline,
+    params_buffer: wgpu::Buffer,
+    params_bind_group: wgpu::BindGroup,
+    particle_buffer: wgpu::Buffer,
+    particle_bind_group: wgpu::BindGroup,
+    last_step: Instant,
+    time: f32,
+}
+
+impl Particles {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Particles shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!("particles.wgsl"))),
+        });
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("particle_params_buffer"),
+            size: std::mem::size_of::<ParamsCRepr>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        // all particles start out dead, with an age and lifetime of zero
+        let particle_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("particle_buffer"),
+            contents: bytemuck::cast_slice(&[ParticleCRepr::zeroed(); PARTICLES as usize]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+        });
+
+        let params_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT | wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("particle_params_layout"),
+        });
+        let particle_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("particle_layout"),
+        });
+        let params_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &params_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+            label: Some("particle_params_bind_group"),
+        });
+        let particle_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &particle_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: particle_buffer.as_entire_binding(),
+            }],
+            label: Some("particle_bind_group"),
+        });
+
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Particles compute"),
+            layout: Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("particle_compute_layout"),
+                bind_group_layouts: &[&params_layout, &particle_layout],
+                push_constant_ranges: &[],
+            })),
+            module: &shader,
+            entry_point: "cs_main",
+            compilation_options: PipelineCompilationOptions::default(),
+            cache: None,
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Particles render"),
+            layout: Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("particle_render_layout"),
+                bind_group_layouts: &[&params_layout],
+                push_constant_ranges: &[],
+            })),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                // the simulated particles double as per-instance vertex data
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<ParticleCRepr>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32, 3 => Float32],
+                }],
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            compute_pipeline,
+            render_pipeline,
+            params_buffer,
+            params_bind_group,
+            particle_buffer,
+            particle_bind_group,
+            last_step: Instant::now(),
+            time: 0.,
+        }
+    }
+
+    /// Advance the clock and upload the parameters of the next step, utilizations in 0 to 1
+    pub fn update(&mut self, queue: &wgpu::Queue, cpu_util: f32, gpu_util: f32, aspect: f32) {
+        // cap the step so particles do not jump after the window was hidden for a while
+        let dt = self.last_step.elapsed().as_secs_f32().min(0.1);
+        self.last_step = Instant::now();
+        self.time += dt;
+        let params = ParamsCRepr {
+            dt,
+            time: self.time,
+            spawn: 0.02 + 0.6 * cpu_util,
+            speed: 0.1 + 0.4 * gpu_util,
+            aspect,
+            _pad: [0.;3],
+            colour: COLOUR,
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    /// Run the simulation step, before the render pass drawing the particles begins
+    pub fn compute(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Particles compute pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.compute_pipeline);
+        pass.set_bind_group(0, &self.params_bind_group, &[]);
+        pass.set_bind_group(1, &self.particle_bind_group, &[]);
+        pass.dispatch_workgroups(PARTICLES / WORKGROUP_SIZE, 1, 1);
+    }
+
+    /// Draw the particles over whatever the pass drew so far
+    pub fn draw(&self, pass: &mut wgpu::RenderPass<'_>) {
+        pass.set_pipeline(&self.render_pipeline);
+        pass.set_bind_group(0, &self.params_bind_group, &[]);
+        pass.set_vertex_buffer(0, self.particle_buffer.slice(..));
+        pass.draw(0..4, 0..PARTICLES);
+    }
+}
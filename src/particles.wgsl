@@ -0,0 +1,89 @@
+// ------------- UNIFORMS -------------
+struct Params {
+	// seconds since the last step and in total
+	dt: f32,
+	t: f32,
+	// chance per second that a dead particle respawns, driven by the CPU utilization
+	spawn: f32,
+	// rise speed in widget heights per second, driven by the GPU utilization
+	speed: f32,
+	// width over height of the widget
+	aspect: f32,
+	colour: vec4f,
+}
+@group(0) @binding(0) var<uniform> params: Params;
+
+struct Particle {
+	pos: vec2f,
+	vel: vec2f,
+	age: f32,
+	lifetime: f32,
+}
+
+// ------------ SIMULATION ------------
+@group(1) @binding(0) var<storage, read_write> particles: array<Particle>;
+
+// integer hash to a float in 0 to 1
+fn hash(n: u32) -> f32 {
+	var x = n;
+	x ^= x >> 16u;
+	x *= 0x7feb352du;
+	x ^= x >> 15u;
+	x *= 0x846ca68bu;
+	x ^= x >> 16u;
+	return f32(x) / 4294967295.0;
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+	let i = id.x;
+	if (i >= arrayLength(&particles)) { return; }
+	var p = particles[i];
+	p.age += params.dt;
+	if (p.age >= p.lifetime) {
+		// dead particles respawn as sparks rising from the bottom edge
+		let seed = i * 5u + u32(params.t * 1000.0) * 7919u;
+		if (hash(seed) < params.spawn * params.dt) {
+			p.pos = vec2f(hash(seed + 1u) * 2.0 - 1.0, -1.0);
+			p.vel = vec2f((hash(seed + 2u) - 0.5) * 0.3, 0.5 + hash(seed + 3u)) * params.speed * 2.0;
+			p.age = 0.0;
+			p.lifetime = 2.0 + 3.0 * hash(seed + 4u);
+		}
+	} else {
+		// drift sideways a little so the sparks do not move in straight lines
+		p.vel.x += sin(params.t * 1.3 + f32(i)) * 0.05 * params.dt;
+		p.pos += p.vel * params.dt;
+	}
+	particles[i] = p;
+}
+
+// ------------- RENDERING -------------
+// size of a particle in widget heights
+const SIZE: f32 = 0.008;
+
+struct VertexOut {
+	@builtin(position) position: vec4f,
+	@location(0) corner: vec2f,
+	@location(1) alpha: f32,
+}
+
+// one quad per particle instance, drawn as a triangle strip
+@vertex
+fn vs_main(
+	@builtin(vertex_index) vertex_index: u32,
+	@location(0) pos: vec2f,
+	@location(1) vel: vec2f,
+	@location(2) age: f32,
+	@location(3) lifetime: f32,
+) -> VertexOut {
+	let corner = vec2f(f32(vertex_index & 1u), f32((vertex_index >> 1u) & 1u)) * 2.0 - 1.0;
+	let life = select(0.0, 1.0 - age / lifetime, lifetime > 0.0 && age < lifetime);
+	let offset = corner * vec2f(SIZE / params.aspect, SIZE) * 2.0;
+	return VertexOut(vec4f(pos + offset * life, 0.0, 1.0), corner, life);
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4f {
+	let glow = 1.0 - smoothstep(0.2, 1.0, length(in.corner));
+	return vec4f(params.colour.rgb, params.colour.a * glow * in.alpha);
+}
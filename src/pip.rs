@@ -0,0 +1,96 @@
+//! Picture-in-picture mini monitor: a small surface above all windows with the graph of the one
+//! metric chosen by `Config::pip_metric`, e.g. to watch the GPU while benchmarking a fullscreen
+//! game. A right click closes it.
+//!
+//! On Wayland regular windows cannot stay above fullscreen ones, so like the overlay, see
+//! [`crate::overlay`], it is a layer surface in a corner of the active output, which a click
+//! moves to the next corner. Elsewhere it is a borderless always-on-top window that is dragged.
+
+use cosmic::app::Task;
+use cosmic::iced::window;
+#[cfg(not(feature = "wayland"))]
+use cosmic::iced::Size;
+#[cfg(feature = "wayland")]
+use cosmic::iced::platform_specific::{
+    runtime::wayland::layer_surface::{IcedMargin, IcedOutput, SctkLayerSurfaceSettings},
+    shell::commands::layer_surface::{
+        destroy_layer_surface, get_layer_surface, KeyboardInteractivity, Layer,
+    },
+};
+
+#[cfg(feature = "wayland")]
+use crate::config::WidgetAnchor;
+use crate::config::Config;
+use crate::Message;
+
+/// Size of the surface in logical pixels
+const WIDTH:u32 = 240;
+const HEIGHT:u32 = 120;
+/// Namespace compositors may match the surface by
+#[cfg(feature = "wayland")]
+const NAMESPACE:&str = "eos-pip";
+/// Distance from the edges of the output in logical pixels
+#[cfg(feature = "wayland")]
+const MARGIN:i32 = 8;
+
+/// Create the surface, returning its id for `view_window`
+#[cfg(feature = "wayland")]
+pub fn open(config:&Config)->(window::Id, Task<Message>){
+    let id = window::Id::unique();
+    let task = get_layer_surface(SctkLayerSurfaceSettings {
+        id,
+        layer: Layer::Overlay,
+        keyboard_interactivity: KeyboardInteractivity::None,
+        // takes the pointer to be moved and closed
+        pointer_interactivity: true,
+        anchor: crate::desktop_widget::anchor(config.pip_anchor),
+        output: IcedOutput::Active,
+        namespace: NAMESPACE.to_string(),
+        margin: IcedMargin { top: MARGIN, right: MARGIN, bottom: MARGIN, left: MARGIN },
+        size: Some((Some(WIDTH), Some(HEIGHT))),
+        exclusive_zone: -1,
+        ..Default::default()
+    });
+    (id, task)
+}
+
+#[cfg(not(feature = "wayland"))]
+pub fn open(_config:&Config)->(window::Id, Task<Message>){
+    let (id, task) = window::open(window::Settings {
+        size: Size::new(WIDTH as f32, HEIGHT as f32),
+        level: window::Level::AlwaysOnTop,
+        decorations: false,
+        resizable: false,
+        exit_on_close_request: false,
+        ..Default::default()
+    });
+    (id, task.discard())
+}
+
+#[cfg(feature = "wayland")]
+pub fn close(id:window::Id)->Task<Message>{
+    destroy_layer_surface(id)
+}
+
+#[cfg(not(feature = "wayland"))]
+pub fn close(id:window::Id)->Task<Message>{
+    window::close(id)
+}
+
+/// Corner a click moves the layer surface to, clockwise from the one it is in
+#[cfg(feature = "wayland")]
+pub fn next_corner(anchor:WidgetAnchor)->WidgetAnchor{
+    match anchor {
+        WidgetAnchor::TopLeft => WidgetAnchor::TopRight,
+        WidgetAnchor::TopRight => WidgetAnchor::BottomRight,
+        WidgetAnchor::BottomRight => WidgetAnchor::BottomLeft,
+        _ => WidgetAnchor::TopLeft,
+    }
+}
+
+/// Close the surface and open it again at the configured place, returning the new id
+#[cfg(feature = "wayland")]
+pub fn reopen(id:window::Id, config:&Config)->(window::Id, Task<Message>){
+    let (reopened, open) = open(config);
+    (reopened, Task::batch([close(id), open]))
+}
@@ -0,0 +1,127 @@
+//! Pomodoro focus timer: work sessions of `Config::pomodoro_work_minutes` alternate with short
+//! breaks, and every `Config::pomodoro_rounds` sessions with a long one. Each change of phase is
+//! announced by a desktop notification, and with `Config::pomodoro_shader` the background
+//! calms down during breaks through the `focus` uniform, see [`crate::shader`].
+
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+/// Time the background takes to calm down at the start of a break and to come back at its end
+const SHADER_FADE:Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    fn length(self, config:&Config)->Duration{
+        let minutes = match self {
+            Phase::Work => config.pomodoro_work_minutes,
+            Phase::ShortBreak => config.pomodoro_break_minutes,
+            Phase::LongBreak => config.pomodoro_long_break_minutes,
+        };
+        Duration::from_secs(60 * u64::from(minutes))
+    }
+}
+
+#[derive(Debug)]
+pub struct Pomodoro {
+    phase: Phase,
+    /// End of the phase while the timer runs
+    end: Option<Instant>,
+    /// Time left of the phase while the timer is paused
+    left: Duration,
+    /// Length of the phase when it started
+    length: Duration,
+    /// Work sessions finished since the last long break
+    sessions: u8,
+}
+
+impl Pomodoro {
+    pub fn new(config:&Config)->Self{
+        let length = Phase::Work.length(config);
+        Self { phase: Phase::Work, end: None, left: length, length, sessions: 0 }
+    }
+
+    /// To be called when the config changes, so a phase that has not started yet takes the
+    /// configured length
+    pub fn update_config(&mut self, config:&Config){
+        if self.end.is_none() && self.left == self.length {
+            self.length = self.phase.length(config);
+            self.left = self.length;
+        }
+    }
+
+    pub fn phase(&self)->Phase{
+        self.phase
+    }
+
+    pub fn running(&self)->bool{
+        self.end.is_some()
+    }
+
+    /// Work sessions finished since the last long break
+    pub fn sessions(&self)->u8{
+        self.sessions
+    }
+
+    pub fn remaining(&self)->Duration{
+        match self.end {
+            Some(end) => end.saturating_duration_since(Instant::now()),
+            None => self.left,
+        }
+    }
+
+    /// Start the timer, or pause it where it is
+    pub fn start_pause(&mut self){
+        match self.end.take() {
+            Some(end) => self.left = end.saturating_duration_since(Instant::now()),
+            None => self.end = Some(Instant::now() + self.left),
+        }
+    }
+
+    /// Stop the timer and go back to the start of the first work session
+    pub fn reset(&mut self, config:&Config){
+        *self = Self::new(config);
+    }
+
+    /// Move on to the next phase once the current one is over, returning the new phase.
+    /// To be called every second or so.
+    pub fn tick(&mut self, config:&Config)->Option<Phase>{
+        let end = self.end?;
+        if Instant::now() < end {return None;}
+        self.phase = match self.phase {
+            Phase::Work => {
+                self.sessions += 1;
+                if self.sessions >= config.pomodoro_rounds {Phase::LongBreak} else {Phase::ShortBreak}
+            },
+            Phase::LongBreak => {
+                self.sessions = 0;
+                Phase::Work
+            },
+            Phase::ShortBreak => Phase::Work,
+        };
+        // the next phase runs on without waiting for the user
+        self.length = self.phase.length(config);
+        self.end = Some(end + self.length);
+        Some(self.phase)
+    }
+
+    /// How far the background is calmed down, 0 while working and 1 during a break, fading
+    /// in and out at its ends, and the fraction of the phase that has passed
+    pub fn shader_focus(&self)->[f32;2]{
+        let remaining = self.remaining();
+        let passed = self.length.saturating_sub(remaining);
+        let calm = match self.phase {
+            Phase::Work => 0.,
+            Phase::ShortBreak | Phase::LongBreak => {
+                (passed.min(remaining).as_secs_f32() / SHADER_FADE.as_secs_f32()).min(1.)
+            },
+        };
+        [calm, passed.as_secs_f32() / self.length.as_secs_f32().max(1.)]
+    }
+}
@@ -0,0 +1,175 @@
+//! Endpoint serving the metrics of the resource monitor in the Prometheus text format at
+//! `http://<Config::prometheus_address>/metrics`, so they can be scraped into Grafana. The
+//! interface renders the page after every poll, and a background thread accepts the requests
+//! until the [`Exporter`] is dropped, answering each on a thread of its own with the latest one.
+
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// Time between two looks for connections, and to notice the exporter was dropped
+const ACCEPT_INTERVAL:Duration = Duration::from_millis(200);
+/// Longest wait for a client to send its whole request
+const READ_TIMEOUT:Duration = Duration::from_secs(5);
+/// Most bytes read of a request, the rest of longer ones is ignored
+const MAX_REQUEST_BYTES:u64 = 8192;
+
+/// Page in the Prometheus text format, written metric by metric
+#[derive(Default)]
+pub struct Page(String);
+
+impl Page {
+    /// A gauge without labels
+    pub fn gauge(&mut self, name:&str, help:&str, value:f64){
+        self.family(name, help);
+        let _ = writeln!(self.0, "{} {}", name, value);
+    }
+
+    /// A gauge with one sample per label value
+    pub fn gauges(&mut self, name:&str, help:&str, label:&str, samples:impl IntoIterator<Item = (String, f64)>){
+        self.family(name, help);
+        for (value_of_label, value) in samples {
+            let escaped = value_of_label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            let _ = writeln!(self.0, "{}{{{}=\"{}\"}} {}", name, label, escaped, value);
+        }
+    }
+
+    fn family(&mut self, name:&str, help:&str){
+        let _ = writeln!(self.0, "# HELP {} {}", name, help);
+        let _ = writeln!(self.0, "# TYPE {} gauge", name);
+    }
+}
+
+/// Serves the last page given to [`Exporter::update`]
+pub struct Exporter {
+    address: String,
+    page: Arc<Mutex<String>>,
+}
+
+/// Reads from a stream until a deadline, however slowly the client sends
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf:&mut [u8])->io::Result<usize>{
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+fn respond(stream:TcpStream, page:&Mutex<String>)->io::Result<()>{
+    let deadline = Deadline { stream: &stream, deadline: Instant::now() + READ_TIMEOUT };
+    let mut reader = BufReader::new(deadline.take(MAX_REQUEST_BYTES));
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // the headers are read so the client is not reset while still sending them
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let path = request.split_whitespace().nth(1).unwrap_or_default();
+    let (status, body) = match path.split('?').next() {
+        Some("/metrics") => ("200 OK", page.lock().map(|page| page.clone()).unwrap_or_default()),
+        _ => ("404 Not Found", "Not found, the metrics are at /metrics\n".to_string()),
+    };
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body,
+    )?;
+    stream.flush()
+}
+
+fn serve(listener:TcpListener, page:Weak<Mutex<String>>){
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let Some(page) = page.upgrade() else {return};
+                let _ = stream.set_nonblocking(false);
+                // a slow client does not hold up the others
+                std::thread::spawn(move || {
+                    if let Err(err) = respond(stream, &page) {
+                        log::debug!("failed to answer a request for the metrics: {}", err);
+                    }
+                });
+            },
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                if page.strong_count() == 0 {return;}
+                std::thread::sleep(ACCEPT_INTERVAL);
+            },
+            Err(err) => log::warn!("failed to accept a request for the metrics: {}", err),
+        }
+    }
+}
+
+impl Exporter {
+    /// Listen at an address like `127.0.0.1:9187`
+    pub fn start(address:&str)->io::Result<Self>{
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let page = Arc::new(Mutex::new(String::new()));
+        let weak = Arc::downgrade(&page);
+        std::thread::spawn(move || serve(listener, weak));
+        Ok(Self { address: address.to_string(), page })
+    }
+
+    pub fn address(&self)->&str{
+        &self.address
+    }
+
+    pub fn update(&self, page:Page){
+        if let Ok(mut current) = self.page.lock() {
+            *current = page.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_gauges_in_the_text_format(){
+        let mut page = Page::default();
+        page.gauge("eos_cpu_usage_percent", "Average CPU usage", 12.5);
+        page.gauges("eos_disk_used_percent", "Used space", "mount", [("/home/\"a\"\\b".to_string(), 40.0)]);
+        assert_eq!(page.0, "# HELP eos_cpu_usage_percent Average CPU usage\n\
+            # TYPE eos_cpu_usage_percent gauge\n\
+            eos_cpu_usage_percent 12.5\n\
+            # HELP eos_disk_used_percent Used space\n\
+            # TYPE eos_disk_used_percent gauge\n\
+            eos_disk_used_percent{mount=\"/home/\\\"a\\\"\\\\b\"} 40\n");
+    }
+
+    /// Response to a request sent over a loopback connection
+    fn request(request:&str, page:&str)->String{
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        respond(stream, &Mutex::new(page.to_string())).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_the_page_at_metrics(){
+        let response = request("GET /metrics?x=1 HTTP/1.1\r\nHost: localhost\r\n\r\n", "up 1\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Length: 5\r\n"));
+        assert!(response.ends_with("\r\n\r\nup 1\n"));
+        let response = request("GET / HTTP/1.1\r\n\r\n", "up 1\n");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert!(!response.contains("up 1"));
+    }
+}
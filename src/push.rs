@@ -0,0 +1,161 @@
+//! Exporter pushing the metrics to `Config::push_address` over TCP, in the InfluxDB line
+//! protocol or the Graphite plaintext protocol, for servers that collect them rather than
+//! scrape them like [`crate::prometheus`]. The interface takes a sample every
+//! `Config::push_interval_secs`, and a background thread sends them in batches of
+//! `Config::push_batch`. While the server cannot be reached the samples are kept, up to
+//! [`MAX_PENDING`], and the thread tries again after a delay doubling up to [`MAX_BACKOFF`].
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::{Config, PushProtocol};
+use crate::util;
+use crate::worker::{Export, Sampler};
+
+/// Samples kept while the server cannot be reached, the oldest are dropped beyond
+const MAX_PENDING:usize = 8640;
+const MIN_BACKOFF:Duration = Duration::from_secs(1);
+const MAX_BACKOFF:Duration = Duration::from_secs(300);
+/// Longest wait for the server to accept the data
+const TIMEOUT:Duration = Duration::from_secs(5);
+
+/// What the exporter was started with, to restart it when one of them changes
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    address: String,
+    protocol: PushProtocol,
+    prefix: String,
+    interval: Duration,
+    batch: usize,
+}
+
+impl Settings {
+    fn new(config:&Config)->Self{
+        Self {
+            address: config.push_address.clone(),
+            protocol: config.push_protocol,
+            prefix: config.push_prefix.clone(),
+            interval: Duration::from_secs(config.push_interval_secs.into()),
+            batch: config.push_batch.into(),
+        }
+    }
+}
+
+/// Metrics at a time
+pub struct Sample {
+    time: Duration,
+    values: Vec<(&'static str, f32)>,
+}
+
+/// Lines of a sample in the protocol, like `eos,host=desk cpu_avg=12.5,ram=41.2 1738330000000000000`
+/// or `eos.desk.cpu_avg 12.5 1738330000`
+fn lines(text:&mut String, settings:&Settings, host:&str, sample:&Sample){
+    let values:Vec<_> = sample.values.iter().filter(|(_, value)| value.is_finite()).collect();
+    match settings.protocol {
+        PushProtocol::Influx => {
+            // a line needs at least one field
+            if values.is_empty() {return;}
+            let _ = write!(text, "{},host={} ", settings.prefix, host);
+            for (n, (name, value)) in values.into_iter().enumerate() {
+                if n > 0 {text.push(',');}
+                let _ = write!(text, "{}={}", name, value);
+            }
+            let _ = writeln!(text, " {}", sample.time.as_nanos());
+        },
+        PushProtocol::Graphite => {
+            for (name, value) in values {
+                let _ = writeln!(text, "{}.{}.{} {} {}", settings.prefix, host, name, value, sample.time.as_secs());
+            }
+        },
+    }
+}
+
+fn send(stream:&mut Option<TcpStream>, address:&str, text:&str)->io::Result<()>{
+    let mut connected = match stream.take() {
+        Some(connected) => connected,
+        None => {
+            let connected = TcpStream::connect(address)?;
+            connected.set_write_timeout(Some(TIMEOUT))?;
+            connected
+        },
+    };
+    connected.write_all(text.as_bytes())?;
+    connected.flush()?;
+    // kept for the next batch, and connected anew after an error
+    *stream = Some(connected);
+    Ok(())
+}
+
+/// Send the samples in batches until the exporter is dropped
+fn push(settings:Settings, samples:mpsc::Receiver<Sample>){
+    let host = util::host_name();
+    let mut pending = VecDeque::new();
+    let mut stream = None;
+    let mut backoff = MIN_BACKOFF;
+    let mut retry:Option<Instant> = None;
+    loop {
+        let received = match retry {
+            Some(retry) => samples.recv_timeout(retry.saturating_duration_since(Instant::now())),
+            None => samples.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match received {
+            Ok(sample) => {
+                if pending.len() == MAX_PENDING {
+                    pending.pop_front();
+                }
+                pending.push_back(sample);
+            },
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        let waiting = retry.is_some_and(|retry| Instant::now() < retry);
+        if waiting || (retry.is_none() && pending.len() < settings.batch) {continue;}
+        let mut text = String::new();
+        for sample in &pending {
+            lines(&mut text, &settings, &host, sample);
+        }
+        match send(&mut stream, &settings.address, &text) {
+            Ok(()) => {
+                pending.clear();
+                backoff = MIN_BACKOFF;
+                retry = None;
+            },
+            Err(err) => {
+                log::warn!("failed to push the metrics to {}, trying again in {:?}: {}", settings.address, backoff, err);
+                retry = Some(Instant::now() + backoff);
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            },
+        }
+    }
+}
+
+/// Pushes a sample every interval while `Config::push_address` is set
+pub type Pusher = Sampler<Push>;
+
+pub struct Push;
+
+impl Export for Push {
+    type Settings = Settings;
+    type Sample = Sample;
+
+    fn settings(config:&Config)->Settings{
+        Settings::new(config)
+    }
+
+    fn interval(settings:&Settings)->Duration{
+        settings.interval
+    }
+
+    fn sample(_:&Settings, values:impl Iterator<Item = (&'static str, f32)>)->Sample{
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        Sample { time, values: values.collect() }
+    }
+
+    fn run(settings:Settings, samples:mpsc::Receiver<Sample>){
+        push(settings, samples);
+    }
+}
@@ -0,0 +1,101 @@
+//! Quote of [`crate::config::Section::Quote`], drawn at random every
+//! `Config::quote_refresh_minutes` on a background thread, from `Config::quote_file` or
+//! otherwise from `fortune`. The file is in the format of the fortune database, quotes
+//! separated by lines holding only `%`, or has one quote per line where there are none.
+
+use std::any::TypeId;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::BuildHasher;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use tokio::sync::mpsc;
+
+use crate::{util, Message};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    pub text: String,
+    /// Author or source from a last line like `-- Mark Twain`
+    pub attribution: Option<String>,
+}
+
+/// Quote with its attribution split off, `None` if it is empty
+fn parse(quote:&str)->Option<Quote>{
+    let lines:Vec<&str> = quote.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    let (last, rest) = lines.split_last()?;
+    let attribution = ["--", "—", "―"].iter().find_map(|dash| last.strip_prefix(dash)).map(str::trim);
+    let (lines, attribution) = match attribution {
+        Some(attribution) if !rest.is_empty() => (rest, Some(attribution.to_string())),
+        _ => (lines.as_slice(), None),
+    };
+    // lines of a quote are wrapped for the terminal, so they are joined to be wrapped again
+    Some(Quote { text: lines.join(" "), attribution })
+}
+
+/// Quotes of a file in the fortune format, or one per line
+fn read_file(path:&str)->Result<Vec<Quote>, String>{
+    let path = util::expand(path);
+    let text = fs::read_to_string(&path).map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+    if text.lines().any(|line| line.trim() == "%") {
+        Ok(text.split("\n%").filter_map(|quote| parse(quote.trim_start_matches('%'))).collect())
+    } else {
+        Ok(text.lines().filter_map(parse).collect())
+    }
+}
+
+/// Short quote from the fortune database
+fn fortune()->Result<Quote, String>{
+    let output = Command::new("fortune")
+        .arg("-s")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run fortune: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("fortune exited with {}", output.status));
+    }
+    parse(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| "fortune printed nothing".to_string())
+}
+
+/// Quote other than the last one if there is another
+fn draw(file:&str, last:Option<&Quote>)->Result<Quote, String>{
+    if file.is_empty() {
+        return fortune();
+    }
+    let quotes:Vec<Quote> = read_file(file)?.into_iter().filter(|quote| Some(quote) != last).collect();
+    if quotes.is_empty() {
+        return last.cloned().ok_or_else(|| format!("no quotes in {}", file));
+    }
+    // seeded anew for each hasher, which is random enough to pick a quote
+    let index = RandomState::new().hash_one(quotes.len()) as usize % quotes.len();
+    Ok(quotes[index].clone())
+}
+
+/// Emits a quote drawn at random, and another every `refresh`, see [`Message::Quote`]
+pub fn listen(file:String, refresh:Duration)->Subscription<Message>{
+    struct QuoteSubscription;
+    Subscription::run_with_id(
+        (TypeId::of::<QuoteSubscription>(), file.clone(), refresh),
+        stream::channel(1, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            std::thread::spawn(move || {
+                let mut last = None;
+                loop {
+                    match draw(&file, last.as_ref()) {
+                        Ok(quote) => last = Some(quote),
+                        Err(err) => log::warn!("failed to draw a quote: {}", err),
+                    }
+                    if tx.send(last.clone()).is_err() {break;}
+                    std::thread::sleep(refresh);
+                }
+            });
+            while let Some(quote) = rx.recv().await {
+                let _ = output.send(Message::Quote(quote)).await;
+            }
+        }),
+    )
+}
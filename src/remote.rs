@@ -0,0 +1,181 @@
+//! Remote hosts of [`crate::config::Section::Remote`], watched over SSH. For each host of
+//! `Config::remote_hosts` a background thread keeps `ssh` running a small shell loop that
+//! prints the CPU line of `/proc/stat`, the memory of `/proc/meminfo` and `/proc/loadavg`
+//! every `Config::remote_interval_secs`, so nothing has to be installed on the host beyond a
+//! Linux shell. The login has to work without a prompt, with a key or the SSH agent, as `ssh`
+//! runs in batch mode. A connection that fails or drops is tried again after a while.
+
+use std::any::TypeId;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use cosmic::iced::Subscription;
+use tokio::sync::mpsc;
+
+use crate::{worker, Message};
+
+/// Time before connecting again after the connection failed or dropped
+const RETRY_INTERVAL:Duration = Duration::from_secs(30);
+/// Line the loop prints after each sample
+const END:&str = "--";
+
+/// Latest sample of a host
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Status {
+    /// Usage of all CPUs since the previous sample in percent
+    pub cpu: f32,
+    pub mem_used: u64,
+    pub mem_total: u64,
+    /// Load average over 1, 5 and 15 minutes
+    pub load: [f32; 3],
+}
+
+impl Status {
+    pub fn mem_percent(&self)->f32{
+        if self.mem_total == 0 {0.} else {self.mem_used as f32 / self.mem_total as f32 * 100.}
+    }
+}
+
+/// Shell loop run on the host
+fn script(interval:Duration)->String{
+    format!(
+        "while :; do head -n 1 /proc/stat; grep -E '^(MemTotal|MemAvailable):' /proc/meminfo; cat /proc/loadavg; echo {}; sleep {}; done",
+        END, interval.as_secs().max(1),
+    )
+}
+
+/// Busy and total time of the CPU line of `/proc/stat`, like `cpu  4705 356 584 3699 23 23 0 0 0 0`
+fn cpu_times(line:&str)->Option<(u64, u64)>{
+    let times:Vec<u64> = line.strip_prefix("cpu ")?.split_whitespace().filter_map(|time| time.parse().ok()).collect();
+    // user, nice, system, idle, iowait, irq, softirq and steal, the guest times are in user
+    let total:u64 = times.iter().take(8).sum();
+    let idle = times.get(3)? + times.get(4).unwrap_or(&0);
+    Some((total - idle, total))
+}
+
+/// Sample of the lines printed before [`END`], with the CPU times of the previous one
+fn parse(lines:&[String], previous:&mut Option<(u64, u64)>)->Option<Status>{
+    let mut status = Status::default();
+    let mut mem_available = None;
+    for line in lines {
+        if let Some((busy, total)) = cpu_times(line) {
+            if let Some((previous_busy, previous_total)) = previous.replace((busy, total)) {
+                let elapsed = total.saturating_sub(previous_total);
+                if elapsed > 0 {
+                    status.cpu = busy.saturating_sub(previous_busy) as f32 / elapsed as f32 * 100.;
+                }
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            // in kB
+            let bytes = value.trim().trim_end_matches("kB").trim().parse::<u64>().ok()? * 1024;
+            match key {
+                "MemTotal" => status.mem_total = bytes,
+                "MemAvailable" => mem_available = Some(bytes),
+                _ => (),
+            }
+        } else {
+            let mut loads = line.split_whitespace().map(|load| load.parse::<f32>());
+            for load in &mut status.load {
+                *load = loads.next()?.ok()?;
+            }
+        }
+    }
+    status.mem_used = status.mem_total.saturating_sub(mem_available?);
+    Some(status)
+}
+
+/// Kills and waits for `ssh` however [`watch`] returns, so it does not linger
+struct Kill(Child);
+
+impl Drop for Kill {
+    fn drop(&mut self){
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Run the loop on the host and send its samples until it ends, returning why
+fn watch(host:&str, interval:Duration, tx:&mpsc::UnboundedSender<Option<Status>>)->Result<(), String>{
+    let child = Command::new("ssh")
+        .args(["-T", "-o", "BatchMode=yes", "-o", "ConnectTimeout=10", "-o", "ServerAliveInterval=15", "-o", "ServerAliveCountMax=2", "--", host])
+        .arg(script(interval))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to run ssh: {}", err))?;
+    let mut child = Kill(child);
+    let stdout = child.0.stdout.take().ok_or("no output of ssh")?;
+    // read as it comes, so that ssh does not block on a full pipe, keeping the last line
+    let stderr = child.0.stderr.take().ok_or("no output of ssh")?;
+    let errors = std::thread::spawn(move || {
+        BufReader::new(stderr).lines().map_while(Result::ok).filter(|line| !line.trim().is_empty()).last()
+    });
+    let mut previous = None;
+    let mut lines = Vec::new();
+    for line in BufReader::new(stdout).lines() {
+        let line = line.map_err(|err| err.to_string())?;
+        if line != END {
+            lines.push(line);
+            continue;
+        }
+        let first = previous.is_none();
+        let status = parse(&lines, &mut previous).ok_or("unexpected output, the host may not run Linux")?;
+        lines.clear();
+        // the first sample has no CPU usage yet, as it needs the times of a previous one
+        if first {continue;}
+        if tx.send(Some(status)).is_err() {
+            return Ok(());
+        }
+    }
+    let exit = child.0.wait().map_err(|err| err.to_string())?;
+    match errors.join().ok().flatten() {
+        Some(error) => Err(error),
+        None => Err(format!("the connection closed, ssh exited with {}", exit)),
+    }
+}
+
+/// Emits the samples of the host at `index` of `Config::remote_hosts`, and `None` while it
+/// cannot be reached, see [`Message::Remote`]
+pub fn listen(index:usize, host:String, interval:Duration)->Subscription<Message>{
+    struct RemoteSubscription;
+    worker::listen(
+        (TypeId::of::<RemoteSubscription>(), index, host.clone(), interval),
+        move |status| Message::Remote(index, status),
+        move |tx| worker::retry(&tx, RETRY_INTERVAL, &format!("remote host {}", host), |tx| watch(&host, interval, tx)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(cpu:&str, available_kb:u64)->Vec<String>{
+        [cpu, "MemTotal:       16000000 kB", &format!("MemAvailable:   {} kB", available_kb), "0.52 0.58 0.59 2/1024 12345"]
+            .map(str::to_string)
+            .to_vec()
+    }
+
+    #[test]
+    fn parses_samples(){
+        let mut previous = None;
+        let first = parse(&sample("cpu  100 0 100 700 100 0 0 0 0 0", 4000000), &mut previous).unwrap();
+        // no CPU usage without a previous sample
+        assert_eq!(first, Status { cpu: 0., mem_used: 12000000 * 1024, mem_total: 16000000 * 1024, load: [0.52, 0.58, 0.59] });
+        assert_eq!(first.mem_percent(), 75.);
+        assert_eq!(previous, Some((200, 1000)));
+        let second = parse(&sample("cpu  200 0 200 1500 100 0 0 0 0 0", 8000000), &mut previous).unwrap();
+        assert_eq!(second.cpu, 20.);
+        assert_eq!(second.mem_used, 8000000 * 1024);
+    }
+
+    #[test]
+    fn rejects_unexpected_output(){
+        let mut previous = None;
+        assert_eq!(parse(&["sh: 1: head: not found".to_string()], &mut previous), None);
+        let mut lines = sample("cpu  1 0 1 7 1 0 0 0 0 0", 1);
+        lines.retain(|line| !line.starts_with("MemAvailable"));
+        assert_eq!(parse(&lines, &mut previous), None);
+    }
+}
@@ -1,20 +1,60 @@
-use std::{cmp::Ordering, collections::HashMap, ffi::OsString};
+use std::{cmp::Ordering, collections::HashMap, ffi::OsString, fs, io, path::PathBuf, sync::OnceLock, time::{Duration, Instant}};
 
+use chrono::{Datelike, Local, NaiveDate, Utc, Weekday};
 use cosmic::iced::{self, alignment::Horizontal, Length, Padding};
 use itertools::Itertools;
-use nvml_wrapper::{enum_wrappers::device::Clock, error::NvmlError, Nvml};
-use sysinfo::{CpuRefreshKind, MemoryRefreshKind, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System};
+use serde::{Deserialize, Serialize};
+use nvml_wrapper::{enum_wrappers::device::{Clock, TemperatureSensor}, error::NvmlError, Nvml};
+use sysinfo::{CpuRefreshKind, MemoryRefreshKind, Pid, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, Signal, System};
 
-use cosmic::iced_widget::{column, container, text, row, horizontal_rule, scrollable, Column, Text};
-use crate::{shader::FragmentShaderProgram, App, Message};
+use cosmic::iced_widget::{column, container, text, row, horizontal_rule, mouse_area, scrollable, Column, Row, Text};
+#[cfg(feature = "mpris")]
+use crate::media;
+#[cfg(feature = "wayland")]
+use crate::screen_time;
+use crate::{accessible, alarm, alert, touch, fl, localize::number, config::{self, ByteUnit, ClockFormat, Config, DEFAULT_FONT, DEFAULT_FONT_NAME, GraphStyle, MetricView, MonitorLayout, MonitorPage, Section, ShaderInput}, export, github, graph_shader::GraphProgram, history::{History, Stats}, metric_store::{Metric, MetricStore}, pomodoro::Phase, prometheus, shader::{FragmentShaderProgram, MetricUniforms}, sun::{self, Daylight}, todo, wal::Palette, weather::{self, Condition}, App, Message};
 
 const MAX_CPU_FREQ:f32 = 5500.;
 const GRAPH_CHAR_WIDTH:usize = 28;
+/// Advance of a monospace glyph relative to the text size
+const CHAR_ASPECT:f32 = 0.6;
+/// Characters of the sparklines of the compact mode, and of its lines with the label and value
+const SPARKLINE_WIDTH:usize = 10;
+/// Samples of the live history averaged to tell whether a metric is rising or falling
+const TREND_SAMPLES:usize = 5;
+/// Extra padding in logical pixels above and below the headers, tabs and buttons with
+/// `Config::touch_targets`, so a finger hits them
+const TOUCH_PADDING:u16 = 12;
+/// Id of the graph in the picture-in-picture surface, apart from the ids of the metrics
+const PIP_GRAPH_ID:u64 = 1 << 32;
+/// Id of the smooth audio spectrum
+const SPECTRUM_GRAPH_ID:u64 = PIP_GRAPH_ID + 1;
+/// Entries the selection of the process list moves by a page
+const PROCESS_PAGE:isize = 10;
+const COMPACT_CHARS:usize = SPARKLINE_WIDTH + 10;
+/// Characters reserved next to a graph for its min/max labels
+const AXIS_LABEL_WIDTH:usize = 5;
+/// Height of smooth graphs, roughly matching two lines of braille characters
+const GRAPH_HEIGHT:f32 = 36.;
+/// Time span kept at full resolution, sampled at `Config::cpu_gpu_interval_ms`
+const LIVE_SPAN:Duration = Duration::from_secs(60);
+/// Polling interval of the metrics while the window is minimized or occluded
+pub const HIDDEN_UPDATE_INTERVAL:Duration = Duration::from_secs(5);
 const BLOCK_GRAPH_GLYPHS : [char; 9] = [' ','▁','▂','▃','▄','▅','▆','▇','█'];
+const BAR_METER_GLYPHS : [char; 9] = [' ','▏','▎','▍','▌','▋','▊','▉','█'];
+/// Sizes of the clock and of graph annotations relative to `Config::monitor_font_size`
+const CLOCK_SCALE:f32 = 30. / 14.;
+const SMALL_TEXT_SCALE:f32 = 10. / 14.;
+/// Size of radial gauges
+const GAUGE_SIZE:f32 = 64.;
+/// Size of the cover art next to the title and artist of the track, in lines of monitor text
+const ART_LINES:f32 = 2.5;
+/// Horizontal resolution of the graphs rendered by [`ResourceMonitor::export_metrics`]
+const EXPORT_GRAPH_SAMPLES:usize = 640;
 
 
+/// Decimal gigabytes, the unit memory is recorded in regardless of `Config::byte_unit`
 fn byte_to_gb(x:u64)->f32{(x/(1_000_000)) as f32/1000.}
-fn byte_to_mb(x:u64)->u64{x/1_000_000}
 fn truncate(s: &str, max_chars: usize) -> &str {
     match s.char_indices().nth(max_chars) {
         None => s,
@@ -22,6 +62,44 @@ fn truncate(s: &str, max_chars: usize) -> &str {
     }
 }
 
+/// Configured interval between CPU and GPU polls, which the live histories are sampled at
+fn live_interval(config:&Config)->Duration{
+    Duration::from_millis(config.cpu_gpu_interval_ms.into()).max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL)
+}
+
+/// Number of samples covering [`LIVE_SPAN`] at `interval`
+fn live_samples(interval:Duration)->usize{
+    (LIVE_SPAN.as_millis() / interval.as_millis().max(1)).max(2) as usize
+}
+
+/// Number of bars of the audio spectrum that fill the width of the monitor in a style
+pub fn spectrum_bars(style:GraphStyle)->usize{
+    match style {
+        GraphStyle::Block => GRAPH_CHAR_WIDTH,
+        GraphStyle::Braille | GraphStyle::Smooth => GRAPH_CHAR_WIDTH * 2,
+    }
+}
+
+/// Group logical CPUs by the physical core they run on, using the sysfs topology.
+/// Falls back to one group per logical CPU if the topology is unavailable.
+fn physical_core_groups(cpu_count:usize)->Vec<Vec<usize>>{
+    let read = |cpu:usize, file:&str| std::fs::read_to_string(
+        format!("/sys/devices/system/cpu/cpu{}/topology/{}", cpu, file)
+    ).ok().and_then(|s| s.trim().parse::<u32>().ok());
+
+    let mut groups: Vec<((u32, u32), Vec<usize>)> = vec![];
+    for cpu in 0..cpu_count {
+        let Some(key) = read(cpu, "physical_package_id").zip(read(cpu, "core_id")) else {
+            return (0..cpu_count).map(|cpu| vec![cpu]).collect();
+        };
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, cpus)) => cpus.push(cpu),
+            None => groups.push((key, vec![cpu])),
+        }
+    }
+    groups.into_iter().map(|(_, cpus)| cpus).collect()
+}
+
 #[derive(Clone)]
 pub struct CpuInfo{
     physical_cores:usize,
@@ -38,6 +116,11 @@ pub struct GpuInfo{
     clock:f32,
     power:f32,
     util:f32,
+    /// `None` on cards without a temperature sensor
+    temp:Option<f32>,
+    /// Maximum graphics clock in MHz and power limit in mW, zero if unknown
+    clock_max:f32,
+    power_limit:f32,
 }
 
 #[derive(Default)]
@@ -54,32 +137,203 @@ pub struct InterpolatedInfo{
 }
 
 
+/// Processes of the same name, shown as one entry with the usage of the busiest one
 #[derive(Default)]
 pub struct ProcessInfo{
     name:OsString,
     cpu:f32,
     mem:u64,
-    pid:u32,
+    /// Ids of all processes of the name, in ascending order
+    pids:Vec<u32>,
 }
-impl ToString for ProcessInfo {
-    fn to_string(&self) -> String {
-        let cpu = format!("{:.1}", self.cpu);
-        let cpu = if cpu.len() <= 3 {cpu} else {format!("{:3.0}", self.cpu)};
+impl ProcessInfo {
+    /// Id of the oldest process of the name, which is usually the one that started the others
+    fn pid(&self)->u32{
+        self.pids.first().copied().unwrap_or_default()
+    }
+
+    fn kill_request(&self)->KillRequest{
+        KillRequest{name: self.name.clone(), pids: self.pids.clone()}
+    }
+
+    /// Line of the process list, with the memory in `unit`
+    fn label(&self, unit:ByteUnit) -> String {
+        let cpu = number(self.cpu, 1);
+        let cpu = if cpu.chars().count() <= 3 {cpu} else {format!("{:>3}", number(self.cpu, 0))};
         format!(
-            "{:^15}|{}% {:4}MB", 
+            "{:^15}|{}% {:>4}{}", 
             truncate(self.name.to_str().unwrap_or_default(), 15), 
             cpu, 
-            byte_to_mb(self.mem),
+            number(unit.megabytes(self.mem) as f32, 0),
+            unit.megabyte_symbol(),
         )
     }
 }
 
-#[derive(Default, Clone, Copy, Debug)]
+/// Processes of an entry of the process list, to be terminated once confirmed. Taken when
+/// terminating is requested, so that refreshes of the list do not change what is confirmed.
+#[derive(Clone, Debug)]
+pub struct KillRequest{
+    name:OsString,
+    pids:Vec<u32>,
+}
+
+/// Vertical range of a graph
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GraphScale {
+    /// Values are clamped to an explicit `(min, max)` range
+    Range(f32, f32),
+    /// The range follows the minimum and maximum of the plotted data
+    Auto,
+}
+impl GraphScale {
+    pub const PERCENT: GraphScale = GraphScale::Range(0., 100.);
+
+    /// Apply a monotonic conversion, e.g. to another unit, to an explicit range
+    fn map(self, f:impl Fn(f32)->f32)->Self{
+        match self {
+            GraphScale::Range(lo, hi) => GraphScale::Range(f(lo), f(hi)),
+            GraphScale::Auto => GraphScale::Auto,
+        }
+    }
+
+    /// Resolve to a concrete, non-empty `(min, max)` range for the given data
+    fn resolve(self, data:&[f32])->(f32, f32){
+        match self {
+            GraphScale::Range(lo, hi) if hi > lo => (lo, hi),
+            GraphScale::Range(lo, _) => (lo, lo + 1.),
+            GraphScale::Auto => {
+                let lo = data.iter().copied().fold(f32::INFINITY, f32::min);
+                let hi = data.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                if !lo.is_finite() || !hi.is_finite() {(0., 1.)}
+                else if hi - lo < f32::EPSILON {(lo - 0.5, hi + 0.5)}
+                else {(lo, hi)}
+            },
+        }
+    }
+}
+
+/// Colours of the monitor, taken from the active theme so graphs and separators
+/// match the rest of the desktop
+#[derive(Clone, Copy, Debug)]
+pub struct ThemeColours{
+    accent: iced::Color,
+    success: iced::Color,
+    warning: iced::Color,
+    critical: iced::Color,
+}
+impl ThemeColours {
+    pub fn new(theme:&cosmic::Theme)->Self{
+        let cosmic = theme.cosmic();
+        Self {
+            accent: cosmic.accent_color().into(),
+            success: cosmic.success_color().into(),
+            warning: cosmic.warning_color().into(),
+            critical: cosmic.destructive_color().into(),
+        }
+    }
+}
+
+/// Colour a text if a threshold colour is given
+fn coloured<'a>(t:Text<'a, cosmic::Theme>, colour:Option<iced::Color>)->Text<'a, cosmic::Theme>{
+    match colour {
+        Some(colour) => t.class(cosmic::theme::Text::Color(colour)),
+        None => t,
+    }
+}
+
+/// Convert a value of `metric` from the units of the history, decimal gigabytes and degrees
+/// Celsius, to the units chosen in the config
+fn display_value(config:&Config, metric:Metric, value:f32)->f32{
+    match metric {
+        Metric::RamUsed => config.byte_unit.gigabytes((value * 1e9) as u64),
+        Metric::GpuTemp => config.temperature_unit.convert(value),
+        Metric::CpuAvg | Metric::CpuMax | Metric::GpuUtil => value,
+    }
+}
+
+/// Difference of a time zone to the local time, like `+7h` or `-5:30h`, empty if there is none
+fn zone_offset(seconds:i32)->String{
+    let (sign, minutes) = (if seconds < 0 {'-'} else {'+'}, seconds.abs() / 60);
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => String::new(),
+        (hours, 0) => format!("{}{}h", sign, hours),
+        (hours, minutes) => format!("{}{}:{:02}h", sign, hours, minutes),
+    }
+}
+
+/// Time until an event, like `45m`, `2h 15m` or `3d 4h`
+fn countdown(time:chrono::Duration)->String{
+    let minutes = time.num_minutes().max(1);
+    match (minutes / (24 * 60), minutes / 60 % 24, minutes % 60) {
+        (0, 0, minutes) => format!("{}m", minutes),
+        (0, hours, minutes) => format!("{}h {}m", hours, minutes),
+        (days, hours, _) => format!("{}d {}h", days, hours),
+    }
+}
+
+/// Style of the graphs of a metric
+fn graph_style(config:&Config, metric:Metric)->GraphStyle{
+    match metric {
+        Metric::CpuAvg | Metric::CpuMax => config.cpu_graph_style,
+        Metric::GpuUtil | Metric::GpuTemp => config.gpu_graph_style,
+        Metric::RamUsed => config.mem_graph_style,
+    }
+}
+
+/// Compact axis label, e.g. `5.2k` or `42`
+fn axis_label(v:f32)->String{
+    if v.abs() >= 10_000. {format!("{}k", number(v/1000., 0))}
+    else if v.abs() >= 1000. {format!("{}k", number(v/1000., 1))}
+    else if v.abs() >= 10. {number(v, 0)}
+    else {number(v, 1)}
+}
+
+/// Line of the monitor with a label, a right-aligned value and its unit, e.g. `CPU AVG   42 %`
+fn metric_line(label:String, value:String, unit:&str)->String{
+    format!("{:<7} {:>4} {}", label, value, unit)
+}
+
+/// Time window shown by the graphs
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphRange {
+    #[default] OneMinute,
+    TenMinutes,
+    OneHour,
+    OneDay,
+}
+impl GraphRange {
+    pub const ALL: [GraphRange; 4] = [
+        GraphRange::OneMinute, GraphRange::TenMinutes, GraphRange::OneHour, GraphRange::OneDay
+    ];
+
+    pub fn label(self)->&'static str{
+        match self {
+            GraphRange::OneMinute => "1m",
+            GraphRange::TenMinutes => "10m",
+            GraphRange::OneHour => "1h",
+            GraphRange::OneDay => "24h",
+        }
+    }
+
+    pub fn duration(self)->Duration{
+        Duration::from_secs(match self {
+            GraphRange::OneMinute => 60,
+            GraphRange::TenMinutes => 600,
+            GraphRange::OneHour => 3600,
+            GraphRange::OneDay => 86400,
+        })
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum ProcessBy {
     #[default] Cpu,
     Ram,
 }
 impl ProcessBy {
+    pub const ALL:[ProcessBy;2] = [ProcessBy::Cpu, ProcessBy::Ram];
+
     pub fn compare(self, a:&ProcessInfo, b:&ProcessInfo)->Ordering{
         match self{
             ProcessBy::Cpu => b.cpu.partial_cmp(&a.cpu)
@@ -93,7 +347,6 @@ impl ProcessBy {
 pub struct ResourceMonitor {
     // INTERNAL
     sys:System,
-    refreshkind:RefreshKind,
     nv:Option<Nvml>,
 
     // GENERAL INFO
@@ -111,15 +364,57 @@ pub struct ResourceMonitor {
     smooth:InterpolatedInfo,
     process_info: Vec<ProcessInfo>,
     process_sort_by:ProcessBy,
+    /// Name of the entry of the process list selected with the keyboard or the pointer
+    selected_process: Option<OsString>,
+    /// The details of the selected process are shown in a popover
+    process_detail: bool,
+    /// Processes waiting for the confirmation to terminate them
+    kill_request: Option<KillRequest>,
+    /// Outcome of the last attempt to terminate processes, successful or an error
+    kill_status: Option<Result<String, String>>,
+    /// The process list is scrolled to its top, where pulling it down refreshes it
+    process_list_at_top: bool,
+    touch_targets: bool,
+    /// Polling is suspended and the last measurements are kept on screen
+    paused: bool,
     ram_used:u64,
 
     // HISTORY
-    cpu_avgs: [f32; GRAPH_CHAR_WIDTH],
-    gpu_avgs: [f32; GRAPH_CHAR_WIDTH],
+    cpu_avgs: History<f32>,
+    cpu_maxs: History<f32>,
+    gpu_avgs: History<f32>,
+    gpu_temps: History<f32>,
+    ram_useds: History<f32>,
+    core_groups: Vec<Vec<usize>>,
+    core_utils: Vec<History<f32>>,
+    /// Interval between the samples of the live histories
+    live_interval: Duration,
+    /// Time of the last sample of the live histories, which are polled less often while hidden
+    last_live_sample: Instant,
+    store: MetricStore,
+    graph_range: GraphRange,
+    /// Graph id and horizontal cursor position over it, as a fraction of its width
+    graph_hover: Option<(u64, f32)>,
+
+    // STYLE
+    colours: ThemeColours,
+    /// Metrics passed to the background shader, see `Config::uniform_slots`
+    uniform_slots: Vec<ShaderInput>,
+    on_battery: bool,
+    byte_unit: ByteUnit,
+    clock_format: ClockFormat,
+    /// Time constants of the values shown in the monitor and of those passed to the shader
+    display_smoothing: Duration,
+    shader_smoothing: Duration,
+    last_visual_update: Instant,
+    /// Monospace font and size of all text in the monitor, see [`Self::set_font`]
+    font: iced::Font,
+    font_name: String,
+    text_size: f32,
 }
 
 impl ResourceMonitor{
-    pub fn new()->Self{
+    pub fn new(config:&Config)->Self{
         // set up sysinfo
         let refreshkind = RefreshKind::nothing()
             .with_cpu(CpuRefreshKind::everything())
@@ -149,16 +444,25 @@ impl ResourceMonitor{
             cpu_freq: 0., 
         };
         let mem_total = sys.total_memory();
+        let ram_used = sys.used_memory();
+        let core_groups = physical_core_groups(sys.cpus().len());
         let gpu_name = gpu_name(&nv).ok().unwrap_or_default();
 
+        // restore long-term history from the previous session
+        let mut store = MetricStore::new(config);
+        if let Err(err) = store.load(){
+            log::info!("no metric history restored: {}", err);
+        }
+        let live_interval = live_interval(config);
+        let live_samples = live_samples(live_interval);
+
         Self { 
             sys: sys, 
-            refreshkind: refreshkind,
             cpu_info: cpu_info.clone(),
             os_name: System::name().unwrap_or_default(),
             kernel_name: System::kernel_version().unwrap_or_default(),
             os_version: System::os_version().unwrap_or_default(),
-            ram_used: 0,
+            ram_used,
             mem_total: mem_total,
             nv: nv,
             gpu_name,
@@ -167,19 +471,458 @@ impl ResourceMonitor{
             cpu_name: cpu_name,
             architecture: System::cpu_arch(),
             process_info: vec![],
-            process_sort_by: ProcessBy::default(),
-            cpu_avgs: [0.0; GRAPH_CHAR_WIDTH],
-            gpu_avgs: [0.0; GRAPH_CHAR_WIDTH],
+            process_sort_by: config.process_sort_by,
+            selected_process: None,
+            process_detail: false,
+            kill_request: None,
+            kill_status: None,
+            process_list_at_top: true,
+            touch_targets: config.touch_targets,
+            paused: false,
+            cpu_avgs: History::filled(live_samples, 0.),
+            cpu_maxs: History::filled(live_samples, 0.),
+            gpu_avgs: History::filled(live_samples, 0.),
+            gpu_temps: History::filled(live_samples, 0.),
+            ram_useds: History::filled(live_samples, 0.),
+            core_utils: vec![History::filled(live_samples, 0.); core_groups.len()],
+            core_groups,
+            live_interval,
+            last_live_sample: Instant::now(),
+            store,
+            graph_range: GraphRange::default(),
+            graph_hover: None,
+            colours: ThemeColours::new(&cosmic::theme::active()),
+            uniform_slots: config.uniform_slots.clone(),
+            on_battery: on_battery(),
+            byte_unit: config.byte_unit,
+            clock_format: config.clock_format,
+            display_smoothing: Duration::from_millis(config.display_smoothing_ms.into()),
+            shader_smoothing: Duration::from_millis(config.shader_smoothing_ms.into()),
+            last_visual_update: Instant::now(),
+            font: DEFAULT_FONT,
+            font_name: DEFAULT_FONT_NAME.to_string(),
+            text_size: config.monitor_font_size.into(),
+        }
+    }
+
+    /// To be called when the config changes, e.g. to resize the long-term history
+    pub fn apply_config(&mut self, config:&Config){
+        self.store.configure(config);
+        if live_interval(config) != self.live_interval {
+            self.live_interval = live_interval(config);
+            let samples = live_samples(self.live_interval);
+            for history in [&mut self.cpu_avgs, &mut self.cpu_maxs, &mut self.gpu_avgs, &mut self.gpu_temps, &mut self.ram_useds]
+                .into_iter()
+                .chain(self.core_utils.iter_mut())
+            {
+                // the samples at the old interval would be shown stretched or squeezed
+                *history = History::filled(samples, 0.);
+            }
+        }
+        self.uniform_slots = config.uniform_slots.clone();
+        self.byte_unit = config.byte_unit;
+        self.clock_format = config.clock_format;
+        self.touch_targets = config.touch_targets;
+        self.display_smoothing = Duration::from_millis(config.display_smoothing_ms.into());
+        self.shader_smoothing = Duration::from_millis(config.shader_smoothing_ms.into());
+    }
+
+    /// To be called when the config changes with a monospace font, which the braille and block
+    /// graphs need to line up with the text around them
+    pub fn set_font(&mut self, name:&str, size:u16){
+        if self.font_name != name {
+            // iced refers to fonts by static names, so each font picked is leaked once
+            self.font = iced::Font::with_name(Box::leak(name.to_string().into_boxed_str()));
+            self.font_name = name.to_string();
+        }
+        self.text_size = size.into();
+    }
+
+    /// Text in the font and size of the monitor
+    fn text<'a>(&self, content:impl cosmic::iced_core::text::IntoFragment<'a>)->Text<'a, cosmic::Theme>{
+        text(content).font(self.font).size(self.text_size)
+    }
+
+    /// To be called when the theme changes, so the monitor picks up its accent and status colours,
+    /// or those of the pywal or wallust `palette` if one is given
+    pub fn apply_theme(&mut self, theme:&cosmic::Theme, palette:Option<&Palette>){
+        self.colours = match palette {
+            Some(palette) => ThemeColours {
+                accent: palette.accent(),
+                success: palette.success(),
+                warning: palette.warning(),
+                critical: palette.critical(),
+            },
+            None => ThemeColours::new(theme),
+        };
+    }
+
+    /// Warning or critical colour for a metric value crossing its configured threshold
+    fn alert_colour(&self, app:&App, metric:Metric, value:f32)->Option<iced::Color>{
+        let threshold = app.config.thresholds.get(&metric)?;
+        if value >= threshold.critical as f32 {
+            Some(self.colours.critical)
+        } else if value >= threshold.warning as f32 {
+            Some(self.colours.warning)
+        } else {
+            None
+        }
+    }
+
+    /// Colour of the visualization of a metric value, the accent colour unless a threshold is crossed
+    fn metric_colour(&self, app:&App, metric:Metric, value:f32)->iced::Color{
+        self.alert_colour(app, metric, value).unwrap_or(self.colours.accent)
+    }
+
+    /// Horizontal separator between sections, tinted with the accent colour
+    fn separator(&self)->iced::widget::Rule<'static, cosmic::Theme>{
+        let colour = iced::Color{a: 0.5, ..self.colours.accent};
+        horizontal_rule(2).class(cosmic::theme::Rule::Custom(Box::new(move |_| iced::widget::rule::Style{
+            color: colour,
+            width: 2,
+            radius: 0.0.into(),
+            fill_mode: iced::widget::rule::FillMode::Full,
+        })))
+    }
+
+    /// Write the long-term history to disk
+    pub fn save_history(&self){
+        if let Err(err) = self.store.save(){
+            log::warn!("failed to save metric history: {}", err);
+        }
+    }
+
+    /// Dump the live and long-term history to CSV and JSON files in the data directory and,
+    /// if `graphs` is set, render the graphs of the selected range to a PNG in `~/Pictures`.
+    /// Returns the paths of all written files.
+    pub fn export_metrics(&self, graphs:bool)->io::Result<Vec<PathBuf>>{
+        let now = chrono::Local::now();
+        let stem = export::file_stem(now);
+        let dir = export::data_dir()?;
+        fs::create_dir_all(&dir)?;
+
+        let live = export::Samples{
+            interval: self.live_interval,
+            series: Metric::ALL.into_iter().map(|m| (m, self.live_history(m))).collect(),
+        };
+        let history = export::Samples{
+            interval: self.store.resolution(),
+            series: Metric::ALL.into_iter()
+                .filter_map(|m| self.store.get(m).map(|h| (m, h)))
+                .collect(),
+        };
+        let mut paths = vec![
+            dir.join(format!("{}-live.csv", stem)),
+            dir.join(format!("{}-history.csv", stem)),
+            dir.join(format!("{}.json", stem)),
+        ];
+        export::write_csv(&paths[0], now, &live)?;
+        export::write_csv(&paths[1], now, &history)?;
+        export::write_json(&paths[2], now, &live, &history)?;
+
+        if graphs {
+            let colour = |c:iced::Color| c.into_rgba8();
+            let plots = Metric::ALL.into_iter().map(|metric| {
+                let data = self.range_data(self.live_history(metric), metric, EXPORT_GRAPH_SAMPLES);
+                let scale = match metric {
+                    Metric::RamUsed => GraphScale::Range(0., byte_to_gb(self.mem_total)),
+                    _ => GraphScale::PERCENT,
+                };
+                export::Plot{ range: scale.resolve(&data), data, colour: colour(self.colours.accent) }
+            }).collect::<Vec<_>>();
+            let dir = export::picture_dir()?;
+            fs::create_dir_all(&dir)?;
+            let path = dir.join(format!("{}.png", stem));
+            export::write_png(&path, &plots, colour(cosmic::theme::active().cosmic().bg_color().into()))
+                .map_err(io::Error::other)?;
+            paths.push(path);
         }
+        Ok(paths)
     }
 
+    pub fn set_graph_range(&mut self, range:GraphRange){
+        self.graph_range = range
+    }
+
+    /// Samples of a metric covering the selected graph range, downsampled to the graph width.
+    /// The last minute is served from the live history, longer ranges from the long-term store.
+    fn graph_data(&self, live:&History<f32>, metric:Metric)->Vec<f32>{
+        self.range_data(live, metric, GRAPH_CHAR_WIDTH * 2)
+    }
+
+    /// Samples of a metric covering the selected graph range, downsampled to at most `buckets` values
+    fn range_data(&self, live:&History<f32>, metric:Metric, buckets:usize)->Vec<f32>{
+        match self.graph_range {
+            GraphRange::OneMinute => live.downsample(live.capacity(), buckets),
+            range => self.store.get(metric).map(|history| {
+                let window = range.duration().as_secs() / self.store.resolution().as_secs().max(1);
+                history.downsample(window as usize, buckets)
+            }).unwrap_or_default(),
+        }
+    }
+
+    pub fn set_graph_hover(&mut self, id:u64, fraction:Option<f32>){
+        match fraction {
+            Some(fraction) => self.graph_hover = Some((id, fraction)),
+            None => if self.graph_hover.is_some_and(|(hovered, _)| hovered == id) {
+                self.graph_hover = None;
+            },
+        }
+    }
+
+    /// Time covered by the graph of a metric, less than the selected range while the long-term
+    /// store is still filling up
+    fn graph_span(&self, metric:Metric)->Duration{
+        match self.graph_range {
+            GraphRange::OneMinute => LIVE_SPAN,
+            range => {
+                let resolution = self.store.resolution();
+                let window = range.duration().as_secs() / resolution.as_secs().max(1);
+                let len = self.store.get(metric).map_or(0, |history| history.len());
+                resolution * (window as usize).min(len) as u32
+            },
+        }
+    }
+
+    /// Timestamp and value below the cursor if graph `id`, showing `data` over `span`, is hovered
+    fn graph_hover_text(&self, id:u64, data:&[f32], span:Duration)->Option<String>{
+        let (hovered, fraction) = self.graph_hover?;
+        if hovered != id || data.is_empty() {return None;}
+        // the most recent sample is drawn on the left
+        let x = fraction * (data.len() - 1) as f32;
+        let (i0, i1) = (x.floor() as usize, (x.ceil() as usize).min(data.len() - 1));
+        let value = data[i0] + (data[i1] - data[i0]) * x.fract();
+        let ago = span.mul_f32(fraction);
+        let time = chrono::Local::now() - chrono::Duration::from_std(ago).unwrap_or_default();
+        Some(format!("{}  {}", time.format(&self.clock_format.time()), number(value, 1)))
+    }
+
+    /// Statistics of a metric over the selected graph range
+    fn graph_stats(&self, live:&History<f32>, metric:Metric)->Option<Stats>{
+        match self.graph_range {
+            GraphRange::OneMinute => live.stats(live.capacity()),
+            range => self.store.get(metric).and_then(|history| {
+                let window = range.duration().as_secs() / self.store.resolution().as_secs().max(1);
+                history.stats(window as usize)
+            }),
+        }
+    }
+
+    /// Small line of current, minimum, average and maximum values to put below a graph
+    fn view_graph_stats(&self, stats:Stats)->Text<'static, cosmic::Theme>{
+        self.text(format!("{} {}  {} {}  {} {}  {} {}",
+            fl!("graph-now"), axis_label(stats.current),
+            fl!("graph-min"), axis_label(stats.min),
+            fl!("graph-avg"), axis_label(stats.avg),
+            fl!("graph-max"), axis_label(stats.max),
+        )).size(self.text_size * SMALL_TEXT_SCALE).width(Length::Fill).align_x(Horizontal::Right)
+    }
+
+    /// Padding above and below a control that is tapped
+    fn tap_padding(&self)->u16{
+        if self.touch_targets {TOUCH_PADDING} else {0}
+    }
+
+    /// Enlarge a button with `Config::touch_targets`
+    fn tap_target<'a>(&self, btn:cosmic::widget::Button<'a, Message>)->cosmic::widget::Button<'a, Message>{
+        if self.touch_targets {btn.padding([TOUCH_PADDING, 16])} else {btn}
+    }
+
+    /// Row of buttons to select the time window of the graphs
+    fn view_graph_range(&self)->iced::widget::Row<'_, Message, cosmic::Theme>{
+        let mut res = row![self.text(format!("{} ", fl!("monitor-range"))).width(Length::Fill)];
+        for range in GraphRange::ALL {
+            let btn = if range == self.graph_range {
+                cosmic::widget::button::suggested(range.label())
+            } else {
+                cosmic::widget::button::text(range.label())
+            };
+            res = res.push(self.tap_target(btn.on_press(Message::GraphRange(range))));
+        }
+        res
+    }
+
+    /// Tabs switching the page of the side bar
+    pub fn view_page_tabs(&self, selected:MonitorPage)->Row<'_, Message, cosmic::Theme>{
+        MonitorPage::ALL.into_iter().fold(Row::new().spacing(4).padding([0, 10]), |row, page| {
+            let label = match page {
+                MonitorPage::Overview => fl!("monitor-page-overview"),
+                MonitorPage::Processes => fl!("monitor-page-processes"),
+                MonitorPage::Cores => fl!("monitor-page-cores"),
+            };
+            let btn = if page == selected {
+                cosmic::widget::button::suggested(label)
+            } else {
+                cosmic::widget::button::text(label)
+            };
+            row.push(self.tap_target(btn.on_press(Message::MonitorPage(page))))
+        })
+    }
+
+    /// Condensed monitor for a narrow strip: the clock and a line for the CPU, the memory and the
+    /// GPU with a sparkline of the live history
+    pub fn view_compact(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        // sparklines of values scaled to 0 to 100 by `full`
+        let sparkline = |history:&History<f32>, full:f32| {
+            let data:Vec<f32> = history.downsample(history.capacity(), SPARKLINE_WIDTH)
+                .into_iter()
+                .map(|value| 100. * value / full.max(f32::EPSILON))
+                .collect();
+            Self::block_graph(&data)
+        };
+        let line = |label:String, value:String, sparkline:String, alert:Option<iced::Color>| {
+            coloured(self.text(format!("{:<3} {:>5} {}", label, value, sparkline)), alert)
+        };
+        Column::new()
+            .push(self.text(format!("{}", app.current_time.format(&app.config.clock_strftime())))
+                .size(self.text_size * CLOCK_SCALE))
+            .push(line(
+                fl!("monitor-overlay-cpu"),
+                format!("{}%", number(self.smooth.cpu_avg, 0)),
+                sparkline(&self.cpu_avgs, 100.),
+                self.alert_colour(app, Metric::CpuAvg, self.smooth.cpu_avg),
+            ))
+            .push(line(
+                fl!("monitor-overlay-mem"),
+                format!("{}{}", number(app.config.byte_unit.gigabytes(self.ram_used), 1), app.config.byte_unit.gigabyte_symbol()),
+                sparkline(&self.ram_useds, byte_to_gb(self.mem_total)),
+                None,
+            ))
+            .push_maybe(self.nv.is_some().then(|| line(
+                fl!("monitor-overlay-gpu"),
+                format!("{}%", number(self.smooth.gpu_util, 0)),
+                sparkline(&self.gpu_avgs, 100.),
+                self.alert_colour(app, Metric::GpuUtil, self.smooth.gpu_util),
+            )))
+            .padding(10)
+    }
+
+    /// The CPU section with a graph of each core, even if `Config::per_core_graphs` is off
+    pub fn view_cores(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        self.view_cpu(app)
+            .push_maybe((!app.config.per_core_graphs).then(|| self.view_core_graphs(app)))
+            .padding(Padding{left:10.,right:10.,bottom:10.,..Default::default()})
+    }
+
+    pub fn process_sorting(&self)->ProcessBy{
+        self.process_sort_by
+    }
+
+    /// Change the order of the process list, which is re-sorted right away
     pub fn set_process_sorting(&mut self, sort_by:ProcessBy){
-        self.process_sort_by = sort_by
+        self.process_sort_by = sort_by;
+        self.process_info.sort_by(|a, b| sort_by.compare(a, b));
     }
 
-    pub fn update_cpu_gpu_mem(&mut self){
+    pub fn select_process(&mut self, name:OsString){
+        self.selected_process = Some(name);
+    }
+
+    fn selected_index(&self)->Option<usize>{
+        self.selected_process.as_ref()
+            .and_then(|name| self.process_info.iter().position(|pi| &pi.name == name))
+    }
+
+    /// Move the selection `offset` entries down the process list, or up if negative, starting
+    /// at the top
+    pub fn select_adjacent_process(&mut self, offset:isize){
+        let last = self.process_info.len().saturating_sub(1);
+        let index = match self.selected_index() {
+            None => 0,
+            Some(i) => i.saturating_add_signed(offset).min(last),
+        };
+        self.selected_process = self.process_info.get(index).map(|pi| pi.name.clone());
+    }
+
+    /// Move the selection by a page of the process list
+    pub fn select_process_page(&mut self, down:bool){
+        self.select_adjacent_process(if down {PROCESS_PAGE} else {-PROCESS_PAGE});
+    }
+
+    /// Position of the selection in the process list from 0 at the top to 1 at the bottom,
+    /// which the list is scrolled to
+    pub fn selected_fraction(&self)->Option<f32>{
+        let index = self.selected_index()?;
+        Some(index as f32 / self.process_info.len().saturating_sub(1).max(1) as f32)
+    }
+
+    /// To be called when the process list is scrolled, whether it is at its top
+    pub fn set_process_list_at_top(&mut self, at_top:bool){
+        self.process_list_at_top = at_top;
+    }
+
+    /// Show or hide the details and actions of the selected process, dropping a confirmation
+    /// asked for in them
+    pub fn toggle_process_detail(&mut self){
+        self.process_detail = !self.process_detail && self.selected_process.is_some();
+        self.kill_request = None;
+    }
+
+    /// Ask to confirm terminating all processes of the selected entry
+    pub fn request_kill(&mut self){
+        self.process_detail = false;
+        self.kill_request = self.selected_index().map(|i| self.process_info[i].kill_request());
+        self.kill_status = match self.kill_request {
+            Some(_) => None,
+            None => Some(Err(fl!("process-none-selected"))),
+        };
+    }
+
+    /// Ask to confirm terminating the processes of the details shown, as they were shown
+    pub fn request_kill_of(&mut self, request:KillRequest){
+        self.kill_request = Some(request);
+        self.kill_status = None;
+    }
+
+    pub fn cancel_kill(&mut self){
+        self.kill_request = None;
+    }
+
+    /// Ask the processes whose termination was confirmed to terminate, skipping those that
+    /// exited since or whose id now belongs to another program. The outcome is shown below the
+    /// process list.
+    pub fn kill_requested(&mut self){
+        let Some(request) = self.kill_request.take() else {return;};
+        self.selected_process = None;
+        let name = request.name.to_string_lossy();
+        let mut signalled = 0;
+        let mut failed = Vec::new();
+        for pid in &request.pids {
+            let process = self.sys.process(Pid::from_u32(*pid)).filter(|process| process.name() == request.name);
+            match process.map(|process| process.kill_with(Signal::Term)) {
+                None => {},
+                Some(Some(true)) => signalled += 1,
+                Some(Some(false)) => failed.push(pid.to_string()),
+                Some(None) => {
+                    log::warn!("terminating processes is not supported on this system");
+                    self.kill_status = Some(Err(fl!("process-terminate-unsupported")));
+                    return;
+                },
+            }
+        }
+        self.kill_status = Some(if failed.is_empty() {
+            log::info!("asked {} processes of {} to terminate", signalled, name);
+            Ok(fl!("process-terminated", name = name.as_ref(), count = signalled))
+        } else {
+            log::warn!("failed to signal {} ({})", name, failed.join(", "));
+            Err(fl!("process-terminate-failed", name = name.as_ref(), pids = failed.join(", ")))
+        });
+    }
+
+    pub fn paused(&self)->bool{
+        self.paused
+    }
+
+    /// Suspend or resume polling, see [`Self::paused`]
+    pub fn toggle_paused(&mut self){
+        self.paused = !self.paused;
+    }
+
+    /// Poll the CPU and GPU and sample all live histories, at `Config::cpu_gpu_interval_ms`
+    pub fn update_cpu_gpu(&mut self){
+        self.on_battery = on_battery();
         // CPU
-        self.sys.refresh_specifics(self.refreshkind);
+        self.sys.refresh_cpu_specifics(CpuRefreshKind::everything());
 
         let cpu_avg = self.sys.global_cpu_usage();
         self.cpu_info = CpuInfo {
@@ -193,20 +936,49 @@ impl ResourceMonitor{
             ..self.cpu_info
         };
         
-        // MEMORY
-        self.ram_used = self.sys.used_memory();
+        // a poll after a longer time, like while hidden, stands for all the intervals it covers
+        let interval = self.live_interval.as_secs_f32();
+        let steps = (self.last_live_sample.elapsed().as_secs_f32() / interval).round().max(1.) as usize;
+        self.last_live_sample = Instant::now();
+
+        // PER CORE
+        // the busiest thread of each core is tracked, so a single saturated thread stands out
+        // even if its SMT sibling is idle
+        let cpus = self.sys.cpus();
+        for (group, history) in self.core_groups.iter().zip(self.core_utils.iter_mut()) {
+            history.push_for(group.iter()
+                .filter_map(|&i| cpus.get(i).map(|cpu| cpu.cpu_usage()))
+                .fold(0., f32::max), steps);
+        }
+
+        // MEMORY, polled less often and sampled here so all graphs share the time axis
+        self.ram_useds.push_for(byte_to_gb(self.ram_used), steps);
 
         // GPU
         let gpudat = gpu_update(&self.nv).ok();
         self.gpu_info = gpudat.unwrap_or(self.gpu_info);
 
         // GRAPHS
-        self.cpu_avgs.rotate_right(1);
-        self.cpu_avgs[0] = cpu_avg;
+        self.cpu_avgs.push_for(cpu_avg, steps);
+        self.cpu_maxs.push_for(self.cpu_info.cpu_max, steps);
+        self.store.record(Metric::CpuAvg, cpu_avg);
+        self.store.record(Metric::CpuMax, self.cpu_info.cpu_max);
         if let Some(gpudat) = gpudat{
-            self.gpu_avgs.rotate_right(1);
-            self.gpu_avgs[0] = gpudat.util;
+            self.gpu_avgs.push_for(gpudat.util, steps);
+            self.store.record(Metric::GpuUtil, gpudat.util);
+            if let Some(temp) = gpudat.temp {
+                self.gpu_temps.push_for(temp, steps);
+                self.store.record(Metric::GpuTemp, temp);
+            }
         }
+        self.store.tick();
+    }
+
+    /// Poll the memory usage, at `Config::memory_interval_ms`
+    pub fn update_memory(&mut self){
+        self.sys.refresh_memory_specifics(MemoryRefreshKind::nothing().with_ram());
+        self.ram_used = self.sys.used_memory();
+        self.store.record(Metric::RamUsed, byte_to_gb(self.ram_used));
     }
 
     pub fn update_processes(&mut self){
@@ -220,22 +992,16 @@ impl ResourceMonitor{
 
         let mut processes: HashMap<OsString, ProcessInfo> = HashMap::new();
         for (pid, process) in self.sys.processes(){
-            let pi = ProcessInfo{
+            let pi = processes.entry(process.name().to_owned()).or_insert_with(|| ProcessInfo{
                 name: process.name().to_owned(),
-                cpu: process.cpu_usage(),
-                mem: process.memory(),
-                pid: pid.as_u32(),
-            };
-            if let Some(pi_old) = processes.get(&pi.name){
-                processes.insert(pi.name, ProcessInfo{
-                    name: pi_old.name.clone(),
-                    pid: (*pi_old).pid,
-                    cpu: f32::max(pi.cpu , (*pi_old).cpu),
-                    mem: u64::max(pi.mem , (*pi_old).mem),
-                });
-            } else {
-                processes.insert(pi.name.clone(), pi);
-            }
+                ..ProcessInfo::default()
+            });
+            pi.cpu = f32::max(pi.cpu, process.cpu_usage());
+            pi.mem = u64::max(pi.mem, process.memory());
+            pi.pids.push(pid.as_u32());
+        }
+        for pi in processes.values_mut() {
+            pi.pids.sort_unstable();
         }
 
         self.process_info = processes.into_values()
@@ -245,14 +1011,17 @@ impl ResourceMonitor{
 
 
     pub fn update_visual(&mut self, frag:&mut FragmentShaderProgram){
-        const ALPHA:f32 = 0.95;
-        const ALPHA_SMOOTH:f32 = 0.99;
+        // exponential smoothing by the time since the last update, independent of the frame rate
+        let dt = self.last_visual_update.elapsed().as_secs_f32();
+        self.last_visual_update = Instant::now();
+        let decay = |time_constant:Duration| if time_constant.is_zero() {0.} else {(-dt / time_constant.as_secs_f32()).exp()};
+        let (alpha, alpha_smooth) = (decay(self.display_smoothing), decay(self.shader_smoothing));
 
         let to = |from:f32, to:f32| {
-            ALPHA * from + (1.-ALPHA) * to
+            alpha * from + (1.-alpha) * to
         };
         let to_smooth = |from:f32, to:f32| {
-            ALPHA_SMOOTH * from + (1.-ALPHA_SMOOTH) * to
+            alpha_smooth * from + (1.-alpha_smooth) * to
         };
 
         self.smooth = InterpolatedInfo{
@@ -267,11 +1036,281 @@ impl ResourceMonitor{
             gpu_util: to(self.smooth.gpu_util, self.gpu_info.util),
         };
 
-        frag.update_uniforms_tick(
-            (self.smooth.cpu_avg_smooth/100.).clamp(0.0, 1.0), 
-            (self.smooth.cpu_max_smooth/100.).clamp(0.0, 1.0), 
-            (self.smooth.cpu_freq_smooth/MAX_CPU_FREQ).clamp(0.0, 1.0)
+        if !frag.rendered() {return;}
+        frag.update_uniforms_tick(MetricUniforms {
+            cpu_util: (self.smooth.cpu_avg_smooth/100.).clamp(0.0, 1.0),
+            cpu_max: (self.smooth.cpu_max_smooth/100.).clamp(0.0, 1.0),
+            cpu_freq: (self.smooth.cpu_freq_smooth/MAX_CPU_FREQ).clamp(0.0, 1.0),
+            gpu_util: self.shader_input(ShaderInput::GpuUtil),
+            gpu_power: self.shader_input(ShaderInput::GpuPower),
+            ram_used: self.shader_input(ShaderInput::RamUsed),
+            gpu_mem: self.shader_input(ShaderInput::GpuMem),
+            slots: std::array::from_fn(|i| self.uniform_slots.get(i).map_or(0., |input| self.shader_input(*input))),
+        });
+    }
+
+    /// Current value of a source of [`alert::Rule`]s in percent or °C, `None` for the GPU
+    /// without one and for disks, which the alerts look at themselves
+    pub fn alert_value(&self, source:&alert::Source)->Option<f32>{
+        let percent = |used:f32, total:f32| (total > 0.).then(|| used / total * 100.);
+        let gpu = self.nv.is_some();
+        match source {
+            alert::Source::CpuAvg => Some(self.cpu_info.cpu_avg),
+            alert::Source::CpuMax => Some(self.cpu_info.cpu_max),
+            alert::Source::GpuUtil => gpu.then_some(self.gpu_info.util),
+            alert::Source::GpuTemp => self.gpu_info.temp.filter(|_| gpu),
+            alert::Source::GpuMem => percent(self.gpu_info.mem_used as f32, self.gpu_info.mem_total as f32).filter(|_| gpu),
+            alert::Source::Ram => percent(self.ram_used as f32, self.mem_total as f32),
+            alert::Source::Disk(_) => None,
+        }
+    }
+
+    /// Current metrics by name like `cpu_avg`, for the D-Bus service, the control socket, the
+    /// metric log and the push exporter, leaving out those that are unknown
+    pub fn metrics(&self)->impl Iterator<Item = (&'static str, f32)> + '_ {
+        let sources = [
+            ("cpu_avg", alert::Source::CpuAvg),
+            ("cpu_max", alert::Source::CpuMax),
+            ("gpu_util", alert::Source::GpuUtil),
+            ("gpu_temp", alert::Source::GpuTemp),
+            ("gpu_mem", alert::Source::GpuMem),
+            ("ram", alert::Source::Ram),
+        ];
+        sources.into_iter().filter_map(|(name, source)| Some((name, self.alert_value(&source)?)))
+    }
+
+    /// [`Self::metrics`] with the CPU frequency in MHz and the GPU power in W, for the sensors
+    /// of [`crate::mqtt`]
+    #[cfg(feature = "mqtt")]
+    pub fn mqtt(&self)->impl Iterator<Item = (&'static str, f32)> + '_ {
+        let gpu_power = self.nv.is_some().then(|| self.gpu_info.power / 1000.);
+        self.metrics().chain([("cpu_freq", Some(self.cpu_info.cpu_freq)), ("gpu_power", gpu_power)]
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value?))))
+    }
+
+    /// Latest measurements of everything the monitor polls, for [`prometheus::Exporter`]
+    pub fn prometheus(&self)->prometheus::Page{
+        let mut page = prometheus::Page::default();
+        page.gauge("eos_cpu_usage_percent", "Average utilization of all CPUs", self.cpu_info.cpu_avg.into());
+        page.gauge("eos_cpu_max_usage_percent", "Utilization of the busiest CPU", self.cpu_info.cpu_max.into());
+        page.gauge("eos_cpu_frequency_mhz", "Average frequency of the CPUs", self.cpu_info.cpu_freq.into());
+        page.gauges(
+            "eos_cpu_core_usage_percent", "Utilization of the busiest thread of each physical core", "core",
+            self.core_utils.iter().enumerate()
+                .filter_map(|(core, history)| Some((core.to_string(), history.latest()?.into()))),
         );
+        page.gauge("eos_memory_used_bytes", "Used memory", self.ram_used as f64);
+        page.gauge("eos_memory_total_bytes", "Total memory", self.mem_total as f64);
+        if self.nv.is_some() {
+            page.gauge("eos_gpu_usage_percent", "Utilization of the GPU", self.gpu_info.util.into());
+            if let Some(temp) = self.gpu_info.temp {
+                page.gauge("eos_gpu_temperature_celsius", "Temperature of the GPU", temp.into());
+            }
+            page.gauge("eos_gpu_power_watts", "Power draw of the GPU", f64::from(self.gpu_info.power) / 1000.);
+            page.gauge("eos_gpu_clock_mhz", "Graphics clock of the GPU", self.gpu_info.clock.into());
+            page.gauge("eos_gpu_memory_used_bytes", "Used memory of the GPU", self.gpu_info.mem_used as f64);
+            page.gauge("eos_gpu_memory_total_bytes", "Total memory of the GPU", self.gpu_info.mem_total as f64);
+        }
+        page.gauge("eos_on_battery", "1 while the machine runs on battery", if self.on_battery {1.} else {0.});
+        let process = |pi:&ProcessInfo| format!("{} ({})", pi.name.to_string_lossy(), pi.pid());
+        page.gauges(
+            "eos_process_cpu_percent", "CPU usage of the busiest processes", "process",
+            self.process_info.iter().map(|pi| (process(pi), pi.cpu.into())),
+        );
+        page.gauges(
+            "eos_process_memory_bytes", "Memory of the busiest processes", "process",
+            self.process_info.iter().map(|pi| (process(pi), pi.mem as f64)),
+        );
+        page
+    }
+
+    /// Whether the machine runs on battery rather than mains power
+    pub fn on_battery(&self)->bool{
+        self.on_battery
+    }
+
+    /// Current value of a metric for the background shader, normalized to 0 to 1
+    fn shader_input(&self, input:ShaderInput)->f32{
+        let fraction = |value:f32, max:f32| if max > 0. {value / max} else {0.};
+        match input {
+            ShaderInput::None => 0.,
+            ShaderInput::CpuAvg => self.smooth.cpu_avg / 100.,
+            ShaderInput::CpuMax => self.smooth.cpu_max / 100.,
+            ShaderInput::CpuFreq => self.smooth.cpu_freq / MAX_CPU_FREQ,
+            ShaderInput::GpuUtil => self.smooth.gpu_util / 100.,
+            ShaderInput::GpuPower => fraction(self.smooth.gpu_power, self.gpu_info.power_limit),
+            ShaderInput::GpuClock => fraction(self.smooth.gpu_clock, self.gpu_info.clock_max),
+            ShaderInput::GpuTemp => self.gpu_info.temp.unwrap_or_default() / 100.,
+            ShaderInput::GpuMem => fraction(self.gpu_info.mem_used as f32, self.gpu_info.mem_total as f32),
+            ShaderInput::RamUsed => fraction(self.ram_used as f32, self.mem_total as f32),
+        }.clamp(0.0, 1.0)
+    }
+
+    /// Render a graph of `data` in the configured style. `id` must be unique per graph.
+    /// Graphs that are not on a fixed percentage scale get min/max labels to their right.
+    /// An optional `secondary` series is overlaid dotted (braille) or dimmed (smooth).
+    /// The graph is drawn in `colour`, see [`Self::metric_colour`].
+    /// Hovering the graph shows the time and value below the cursor in a tooltip, with `data`
+    /// covering the time `span` up to now, see [`Self::graph_span`].
+    fn view_graph(&self, id:u64, data:&[f32], span:Duration, secondary:Option<&[f32]>, style:GraphStyle, scale:GraphScale, colour:iced::Color)->cosmic::Element<'_, Message>{
+        let range = match secondary {
+            Some(secondary) => scale.resolve(&[data, secondary].concat()),
+            None => scale.resolve(data),
+        };
+        let labelled = scale != GraphScale::PERCENT;
+        let width = if labelled {GRAPH_CHAR_WIDTH - AXIS_LABEL_WIDTH} else {GRAPH_CHAR_WIDTH};
+        let graph: cosmic::Element<'_, Message> = match style {
+            GraphStyle::Braille | GraphStyle::Block => {
+                let glyphs = match (style, secondary) {
+                    (GraphStyle::Block, _) => Self::block_graph_lines(data, width, 2, range),
+                    (_, Some(secondary)) => Self::braille_graph(&[data, secondary], width, 2, range),
+                    (_, None) => Self::braille_graph(&[data], width, 2, range),
+                };
+                // the glyphs are monospace, so the cursor is placed by the width of the characters
+                let pixels = width as f32 * CHAR_ASPECT * self.text_size;
+                mouse_area(coloured(self.text(glyphs), Some(colour)))
+                    .on_move(move |position| Message::GraphHover(id, Some((position.x / pixels).clamp(0., 1.))))
+                    .on_exit(Message::GraphHover(id, None))
+                    .into()
+            },
+            GraphStyle::Smooth => {
+                iced::widget::shader(GraphProgram::new(id, data, secondary, range, colour.into_linear()))
+                    .width(Length::Fill)
+                    .height(Length::Fixed(GRAPH_HEIGHT))
+                    .into()
+            },
+        };
+        // always wrapped, so the widget tree and the hover state of the graph stay stable
+        let graph: cosmic::Element<'_, Message> = cosmic::widget::tooltip(
+            graph,
+            self.text(self.graph_hover_text(id, data, span).unwrap_or_default()),
+            cosmic::widget::tooltip::Position::FollowCursor,
+        ).into();
+        if !labelled {return graph;}
+        row![
+            graph,
+            self.text(format!("{:>w$}\n{:>w$}", axis_label(range.1), axis_label(range.0), w = AXIS_LABEL_WIDTH)),
+        ].into()
+    }
+
+    /// Full-resolution history of the last minute of a metric
+    fn live_history(&self, metric:Metric)->&History<f32>{
+        match metric {
+            Metric::CpuAvg => &self.cpu_avgs,
+            Metric::CpuMax => &self.cpu_maxs,
+            Metric::GpuUtil => &self.gpu_avgs,
+            Metric::GpuTemp => &self.gpu_temps,
+            Metric::RamUsed => &self.ram_useds,
+        }
+    }
+
+    /// Visualization of a metric below its value, as chosen in the config,
+    /// or `None` if the metric is shown as plain text
+    fn view_metric_visual(&self, app:&App, metric:Metric, value:f32, scale:GraphScale)->Option<cosmic::Element<'_, Message>>{
+        let view = app.config.metric_views.get(&metric).copied().unwrap_or_default();
+        let colour = self.metric_colour(app, metric, value);
+        // thresholds apply to the recorded values, the visuals are labelled in the configured units
+        let unit = |value| display_value(&app.config, metric, value);
+        let value = unit(value);
+        let scale = scale.map(unit);
+        let style = graph_style(&app.config, metric);
+        match view {
+            MetricView::Text => None,
+            MetricView::Graph => {
+                let data = self.graph_data(self.live_history(metric), metric).into_iter().map(unit).collect_vec();
+                let secondary = (metric == Metric::CpuAvg && app.config.cpu_graph_overlay_max)
+                    .then(|| self.graph_data(&self.cpu_maxs, Metric::CpuMax));
+                let graph = self.view_graph(metric as u64, &data, self.graph_span(metric), secondary.as_deref(), style, scale, colour);
+                let stats = app.config.graph_annotations
+                    .then(|| self.graph_stats(self.live_history(metric), metric))
+                    .flatten()
+                    .map(|stats| stats.map(unit));
+                Some(match stats {
+                    Some(stats) => column![graph, self.view_graph_stats(stats)].into(),
+                    None => graph,
+                })
+            },
+            MetricView::Bar => {
+                let range = scale.resolve(&self.live_history(metric).iter().map(unit).collect_vec());
+                Some(coloured(self.text(Self::bar_meter(value, range, GRAPH_CHAR_WIDTH)), Some(colour)).into())
+            },
+            MetricView::Gauge => {
+                let range = scale.resolve(&self.live_history(metric).iter().map(unit).collect_vec());
+                Some(
+                    container(
+                        iced::widget::shader(GraphProgram::gauge(metric as u64, value, range, colour.into_linear()))
+                            .width(Length::Fixed(GAUGE_SIZE))
+                            .height(Length::Fixed(GAUGE_SIZE))
+                    ).width(Length::Fill).align_x(Horizontal::Center).into()
+                )
+            },
+        }
+    }
+
+    /// Whether the recent samples of a metric are above, below or about the ones before
+    fn trend(&self, metric:Metric)->Ordering{
+        let history = self.live_history(metric);
+        if history.len() < 2 * TREND_SAMPLES {return Ordering::Equal;}
+        let mean = |skip:usize| history.iter().rev().skip(skip).take(TREND_SAMPLES).sum::<f32>() / TREND_SAMPLES as f32;
+        let (recent, before) = (mean(0), mean(TREND_SAMPLES));
+        // changes within a twentieth or a single unit are noise
+        if (recent - before).abs() <= (before.abs() * 0.05).max(1.) {Ordering::Equal}
+        else {recent.total_cmp(&before)}
+    }
+
+    /// Spoken description of a metric, e.g. "CPU average 43 percent, rising"
+    fn accessible_name(&self, app:&App, metric:Metric, value:f32)->String{
+        let (name, unit) = match metric {
+            Metric::CpuAvg => (fl!("a11y-cpu-avg"), fl!("a11y-percent")),
+            Metric::CpuMax => (fl!("a11y-cpu-max"), fl!("a11y-percent")),
+            Metric::GpuUtil => (fl!("a11y-gpu-util"), fl!("a11y-percent")),
+            Metric::GpuTemp => (fl!("a11y-gpu-temp"), app.config.temperature_unit.symbol().to_string()),
+            Metric::RamUsed => (fl!("a11y-mem-used"), app.config.byte_unit.gigabyte_symbol().to_string()),
+        };
+        let trend = match self.trend(metric) {
+            Ordering::Greater => fl!("a11y-rising"),
+            Ordering::Less => fl!("a11y-falling"),
+            Ordering::Equal => fl!("a11y-steady"),
+        };
+        let decimals = if metric == Metric::RamUsed {1} else {0};
+        let value = number(display_value(&app.config, metric, value), decimals);
+        fl!("a11y-metric", name = name, value = value, unit = unit, trend = trend)
+    }
+
+    /// Line of a metric and its visual, which assistive technology reads as one node with
+    /// the value and its trend
+    fn view_metric<'a>(&'a self, app:&App, metric:Metric, line:impl Into<cosmic::Element<'a, Message>>, value:f32, scale:GraphScale)->cosmic::Element<'a, Message>{
+        let content = Column::new()
+            .push(line)
+            .push_maybe(self.view_metric_visual(app, metric, value, scale));
+        accessible::labelled(content, format!("metric-{}", metric as u64), self.accessible_name(app, metric, value))
+    }
+
+    /// Horizontal bar of `width` characters, filled in eighths of a character
+    fn bar_meter(value:f32, (lo, hi):(f32, f32), width:usize)->String{
+        let eighths = (((value - lo) / (hi - lo)).clamp(0., 1.) * (width * 8) as f32).round() as usize;
+        (0..width).map(|i| {
+            let filled = eighths.saturating_sub(i * 8).min(8);
+            BAR_METER_GLYPHS[filled]
+        }).collect()
+    }
+
+    /// One block graph row per physical core, for spotting single-threaded bottlenecks
+    fn view_core_graphs(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        const LABEL_WIDTH:usize = 4;
+        let mut res = Column::new();
+        for (i, history) in self.core_utils.iter().enumerate() {
+            let data = history.downsample(history.capacity(), GRAPH_CHAR_WIDTH - LABEL_WIDTH);
+            // idle cores are shown in the success colour, busy ones in their threshold colour
+            let colour = self.alert_colour(app, Metric::CpuMax, history.latest().unwrap_or_default())
+                .unwrap_or(self.colours.success);
+            res = res.push(coloured(
+                self.text(format!("C{:<w$}{}", i, Self::block_graph(&data), w = LABEL_WIDTH - 1)),
+                Some(colour),
+            ));
+        }
+        res
     }
 
     fn block_graph(data: &[f32])->String{
@@ -282,75 +1321,115 @@ impl ResourceMonitor{
         }).collect()
     }
 
-    fn braille_graph(data: &[f32], vertical_lines: usize) -> String {
-        if data.is_empty() || vertical_lines == 0 {return String::new();}
+    /// Draw `data` as bars of block characters, `width` characters wide and `lines` high,
+    /// mapping the `(min, max)` range to the full height. Samples are averaged to fit the width.
+    fn block_graph_lines(data: &[f32], width: usize, lines: usize, (lo, hi): (f32, f32)) -> String {
+        let chunk = data.len().div_ceil(width.max(1)).max(1);
+        let eighths = data.chunks(chunk).map(|samples| {
+            let value = samples.iter().sum::<f32>() / samples.len() as f32;
+            (((value - lo) / (hi - lo)).clamp(0., 1.) * (lines * 8) as f32).round() as usize
+        }).collect_vec();
+        (0..lines).rev().map(|line| eighths.iter()
+            .map(|eighths| BLOCK_GRAPH_GLYPHS[eighths.saturating_sub(line * 8).min(8)])
+            .collect::<String>()
+        ).join("\n")
+    }
+
+    /// Draw `data` as bars of braille dots rising from the bottom, two bars per character,
+    /// `width` characters wide and `lines` high, with 0 to 1 mapped to the full height
+    fn braille_bars(data: &[f32], width: usize, lines: usize) -> String {
+        // dots of the left and right column of a braille cell from the bottom row up
+        const COLUMNS: [[u32; 4]; 2] = [[0x40, 0x04, 0x02, 0x01], [0x80, 0x20, 0x10, 0x08]];
+        let dots = data.iter().take(width * 2)
+            .map(|value| (value.clamp(0., 1.) * (lines * 4) as f32).round() as usize)
+            .collect_vec();
+        (0..lines).rev().map(|line| (0..width).map(|x| {
+            let cell = (0..2).fold(0, |cell, column| {
+                let height = dots.get(x * 2 + column).map_or(0, |dots| dots.saturating_sub(line * 4).min(4));
+                COLUMNS[column][..height].iter().fold(cell, |cell, dot| cell | dot)
+            });
+            char::from_u32(0x2800 + cell).unwrap_or(' ')
+        }).collect::<String>()).join("\n")
+    }
 
-        let px_w = GRAPH_CHAR_WIDTH.saturating_mul(2);
+    /// Draw each series in `series` as a line of braille dots, `width` characters wide and
+    /// `vertical_lines` high, mapping the `(min, max)` range to the full height.
+    /// The first series is drawn solid, all following ones with every other dot left out
+    /// so overlaid series remain distinguishable.
+    fn braille_graph(series: &[&[f32]], width: usize, vertical_lines: usize, (lo, hi): (f32, f32)) -> String {
+        if series.iter().all(|data| data.is_empty()) || vertical_lines == 0 {return String::new();}
+
+        let px_w = width.saturating_mul(2);
         let px_h = vertical_lines.saturating_mul(4);
 
         // Create pixel buffer
         let mut pix = vec![0u8; px_w * px_h];
 
-        // Helper to set a pixel
-        let mut set_pixel = |x: isize, y: isize| {
-            if x >= 0 && (x as usize) < px_w && y >= 0 && (y as usize) < px_h {
-                pix[(y as usize) * px_w + (x as usize)] = 1;
-            }
-        };
+        for (k, data) in series.iter().enumerate() {
+            let dotted = k > 0;
 
-        // Map data points to pixel coordinates
-        let n = data.len();
-        let coords: Vec<(isize, isize)> = if n == 1 {
-            let x = (px_w as isize - 1) / 2;
-            let v = data[0].clamp(0.0, 100.0);
-            let y = ((1.0 - v / 100.0) * (px_h as f32 - 1.0)).round() as isize;
-            vec![(x, y)]
-        } else {
-            (0..n)
-                .map(|i| {
-                    let x = ((i as f32) * ((px_w - 1) as f32) / ((n - 1) as f32)).round() as isize;
-                    let v = data[i].clamp(0.0, 100.0);
-                    let y = ((1.0 - v / 100.0) * (px_h as f32 - 1.0)).round() as isize;
-                    (x, y)
-                })
-                .collect()
-        };
-
-        // Draw lines between consecutive coords
-        let mut it = coords.iter();
-        if let Some(&first) = it.next() {
-            set_pixel(first.0, first.1);
-            let mut last = first;
-            for &pt in it {
-                // Bresenham line between last and pt
-                let (mut x0, mut y0) = (last.0, last.1);
-                let (x1, y1) = (pt.0, pt.1);
-                let dx = (x1 - x0).abs();
-                let sx = if x0 < x1 { 1 } else { -1 };
-                let dy = -(y1 - y0).abs();
-                let sy = if y0 < y1 { 1 } else { -1 };
-                let mut err = dx + dy;
-                loop {
-                    set_pixel(x0, y0);
-                    if x0 == x1 && y0 == y1 { break; }
-                    let e2 = 2 * err;
-                    if e2 >= dy {
-                        err += dy;
-                        x0 += sx;
-                    }
-                    if e2 <= dx {
-                        err += dx;
-                        y0 += sy;
+            // Helper to set a pixel
+            let mut set_pixel = |x: isize, y: isize| {
+                if dotted && (x + y) % 2 != 0 {return;}
+                if x >= 0 && (x as usize) < px_w && y >= 0 && (y as usize) < px_h {
+                    pix[(y as usize) * px_w + (x as usize)] = 1;
+                }
+            };
+
+            // Map data points to pixel coordinates
+            let n = data.len();
+            let coords: Vec<(isize, isize)> = if n == 1 {
+                let x = (px_w as isize - 1) / 2;
+                let v = ((data[0] - lo) / (hi - lo)).clamp(0.0, 1.0);
+                let y = ((1.0 - v) * (px_h as f32 - 1.0)).round() as isize;
+                vec![(x, y)]
+            } else {
+                (0..n)
+                    .map(|i| {
+                        let x = ((i as f32) * ((px_w - 1) as f32) / ((n - 1) as f32)).round() as isize;
+                        let v = ((data[i] - lo) / (hi - lo)).clamp(0.0, 1.0);
+                        let y = ((1.0 - v) * (px_h as f32 - 1.0)).round() as isize;
+                        (x, y)
+                    })
+                    .collect()
+            };
+
+            // Draw lines between consecutive coords
+            let mut it = coords.iter();
+            if let Some(&first) = it.next() {
+                set_pixel(first.0, first.1);
+                let mut last = first;
+                for &pt in it {
+                    // Bresenham line between last and pt
+                    let (mut x0, mut y0) = (last.0, last.1);
+                    let (x1, y1) = (pt.0, pt.1);
+                    let dx = (x1 - x0).abs();
+                    let sx = if x0 < x1 { 1 } else { -1 };
+                    let dy = -(y1 - y0).abs();
+                    let sy = if y0 < y1 { 1 } else { -1 };
+                    let mut err = dx + dy;
+                    loop {
+                        set_pixel(x0, y0);
+                        if x0 == x1 && y0 == y1 { break; }
+                        let e2 = 2 * err;
+                        if e2 >= dy {
+                            err += dy;
+                            x0 += sx;
+                        }
+                        if e2 <= dx {
+                            err += dx;
+                            y0 += sy;
+                        }
                     }
+                    last = pt;
                 }
-                last = pt;
             }
         }
 
         // Convert pixel grid to braille characters
         let mut out = String::new();
         for char_row in 0..vertical_lines {
-            for char_col in 0..GRAPH_CHAR_WIDTH {
+            for char_col in 0..width {
                 let mut bits: u32 = 0;
                 let top_py = (char_row * 4) as isize;
                 let left_px = (char_col * 2) as isize;
@@ -390,92 +1469,923 @@ impl ResourceMonitor{
     }
     
 
+    /// The sections of `Config::sections` other than the process list in their configured order
+    /// and `Config::monitor_layout`, each row followed by a separator
     pub fn view_monitor(&self, app:&App)->iced::widget::Column<'_, Message, cosmic::Theme>{
-        let res: iced::widget::Column<'_, Message, cosmic::Theme> = column!(
-            // CLOCK
-            container(
-                text(
-                    format!("{}", app.current_time.format("%H : %M : %S"))
-                ).size(30).width(Length::Fill).align_x(Horizontal::Center)
-            ).padding(Padding{bottom:10., ..Default::default()}).width(Length::Fill),
-            horizontal_rule(2),
-            // SYSTEM
-            text(format!("OS {} {} \nKERNEL {}\n", 
+        let sections = app.config.sections.iter().copied()
+            .filter(|section| *section != Section::Processes)
+            .filter(|section| *section != Section::Agenda || !app.config.agenda_sources.is_empty())
+            .filter(|section| *section != Section::Headlines || !app.config.feeds.is_empty())
+            .filter(|section| *section != Section::GitHub || github::configured(&app.config))
+            .filter(|section| *section != Section::Mail || !app.config.mail_accounts.is_empty())
+            .filter(|section| *section != Section::Todo || todo::configured(&app.config))
+            .filter(|section| *section != Section::Ticker || !app.config.tickers.is_empty());
+        let start = Column::new().push_maybe(self.paused.then(|| {
+            coloured(self.text(fl!("monitor-paused")), Some(self.colours.warning))
+                .width(Length::Fill)
+                .align_x(Horizontal::Center)
+        }));
+        let column = match app.config.monitor_layout {
+            MonitorLayout::OneColumn => sections
+                .fold(start, |column, section| column
+                    .push(self.view_collapsible(app, section))
+                    .push(self.separator())),
+            MonitorLayout::TwoColumns => sections.chunks(2).into_iter()
+                .fold(start, |column, pair| column
+                    .push(pair.fold(Row::new().spacing(20), |row, section| row
+                        .push(container(self.view_collapsible(app, section)).width(Length::FillPortion(1)))))
+                    .push(self.separator())),
+            MonitorLayout::Horizontal => start.push(sections
+                .fold(Row::new().spacing(20), |row, section| row
+                    .push(container(self.view_collapsible(app, section))
+                        .width(Length::Fixed(column_width(app.config.monitor_font_size)))))),
+        };
+        column.padding(Padding{left:10.,right:10.,bottom:10.,..Default::default()})
+    }
+
+    /// The section below a header that collapses or expands it when clicked
+    fn view_collapsible(&self, app:&App, section:Section)->Column<'_, Message, cosmic::Theme>{
+        let collapsed = app.config.collapsed_sections.contains(&section);
+        let label = match section {
+            Section::Clock => fl!("monitor-header-clock"),
+            Section::Agenda => fl!("monitor-header-agenda"),
+            Section::Headlines => fl!("monitor-header-headlines"),
+            Section::GitHub => fl!("monitor-header-github"),
+            Section::Mail => fl!("monitor-header-mail"),
+            Section::Todo => fl!("monitor-header-todo"),
+            Section::Ticker => fl!("monitor-header-ticker"),
+            Section::Keyboard => fl!("monitor-header-keyboard"),
+            Section::Workspaces => fl!("monitor-header-workspaces"),
+            Section::ScreenTime => fl!("monitor-header-screen-time"),
+            Section::Quote => fl!("monitor-header-quote"),
+            Section::Weather => fl!("monitor-header-weather"),
+            Section::Pomodoro => fl!("monitor-header-pomodoro"),
+            Section::Media => fl!("monitor-header-media"),
+            Section::Spectrum => fl!("monitor-header-spectrum"),
+            Section::System => fl!("monitor-header-system"),
+            Section::GraphRange => fl!("monitor-header-graph-range"),
+            Section::Cpu => fl!("monitor-header-cpu"),
+            Section::Memory => fl!("monitor-header-memory"),
+            Section::Gpu => fl!("monitor-header-gpu"),
+            Section::Remote => fl!("monitor-header-remote"),
+            Section::Agents => fl!("monitor-header-agents"),
+            Section::Processes => fl!("monitor-header-processes"),
+        };
+        let header = self.text(format!("{} {}", if collapsed {'▸'} else {'▾'}, label))
+            .size(self.text_size * SMALL_TEXT_SCALE)
+            .width(Length::Fill);
+        Column::new()
+            .push(mouse_area(container(header).padding([self.tap_padding(), 0]))
+                .on_press(Message::SectionCollapse(section)))
+            .push_maybe((!collapsed).then(|| self.view_section(app, section)))
+    }
+
+    fn view_section(&self, app:&App, section:Section)->cosmic::Element<'_, Message>{
+        match section {
+            Section::Clock => self.view_clock(app).into(),
+            Section::Agenda => self.view_agenda(app).into(),
+            Section::Headlines => self.view_headlines(app).into(),
+            Section::GitHub => self.view_github(app).into(),
+            #[cfg(feature = "mail")]
+            Section::Mail => self.view_mail(app).into(),
+            #[cfg(not(feature = "mail"))]
+            Section::Mail => self.text(fl!("mail-unavailable")).into(),
+            Section::Todo => self.view_todo(app).into(),
+            Section::Ticker => self.view_ticker(app).into(),
+            #[cfg(feature = "wayland")]
+            Section::Keyboard => self.view_keyboard(app).into(),
+            #[cfg(not(feature = "wayland"))]
+            Section::Keyboard => self.text(fl!("workspaces-unavailable")).into(),
+            #[cfg(feature = "wayland")]
+            Section::Workspaces => self.view_workspaces(app).into(),
+            #[cfg(not(feature = "wayland"))]
+            Section::Workspaces => self.text(fl!("workspaces-unavailable")).into(),
+            #[cfg(feature = "wayland")]
+            Section::ScreenTime => self.view_screen_time(app).into(),
+            #[cfg(not(feature = "wayland"))]
+            Section::ScreenTime => self.text(fl!("screen-time-unavailable")).into(),
+            Section::Quote => self.view_quote(app).into(),
+            Section::Weather => self.view_weather(app).into(),
+            Section::Pomodoro => self.view_pomodoro(app).into(),
+            #[cfg(feature = "mpris")]
+            Section::Media => self.view_media(app).into(),
+            #[cfg(not(feature = "mpris"))]
+            Section::Media => self.text(fl!("media-unavailable")).into(),
+            Section::Spectrum => self.view_spectrum(app),
+            Section::System => self.view_system(app).into(),
+            Section::GraphRange => self.view_graph_range().into(),
+            Section::Cpu => self.view_cpu(app).into(),
+            Section::Memory => self.view_memory(app).into(),
+            Section::Gpu => self.view_gpu(app).into(),
+            Section::Remote => self.view_remote(app).into(),
+            #[cfg(feature = "agent")]
+            Section::Agents => self.view_agents(app).into(),
+            #[cfg(not(feature = "agent"))]
+            Section::Agents => self.text(fl!("agents-unavailable")).into(),
+            Section::Processes => self.view_processes().into(),
+        }
+    }
+
+    fn view_clock(&self, app:&App)->iced::widget::Container<'_, Message, cosmic::Theme>{
+        // clicking the date shows or hides the calendar
+        let date = app.config.date_strftime().map(|format| {
+            mouse_area(self.text(format!("{}", app.current_time.format(&format)))
+                .width(Length::Fill).align_x(Horizontal::Center))
+                .on_press(Message::ToggleCalendar)
+        });
+        container(
+            Column::new()
+                .push(self.text(format!("{}", app.current_time.format(&app.config.clock_strftime())))
+                    .size(self.text_size * CLOCK_SCALE).width(Length::Fill).align_x(Horizontal::Center))
+                .push_maybe(date)
+                .push_maybe(app.config.sun_moon.then(|| self.view_sun_moon(app)))
+                .push(self.view_alarms(app))
+                .push_maybe(app.config.calendar.then(|| self.view_calendar(app)))
+                .push_maybe(self.view_world_clocks(app))
+        ).padding(Padding{bottom:10., ..Default::default()}).width(Length::Fill)
+    }
+
+    /// Next alarm within a day and the countdowns that have not ended, each on a line
+    fn view_alarms(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let now = app.current_time;
+        let format = app.config.clock_format.short_time();
+        let alarm = alarm::next(&app.config, now)
+            .filter(|(at, _)| *at - now <= chrono::Duration::days(1))
+            .map(|(at, label)| fl!("alarm-next", time = at.format(&format).to_string(), label = label));
+        let countdowns = app.config.countdowns.iter().filter_map(|entry| {
+            let left = entry.until()?.signed_duration_since(now.naive_local());
+            (left > chrono::Duration::zero())
+                .then(|| fl!("countdown-in", label = entry.label.clone(), time = countdown(left)))
+        });
+        alarm.into_iter().chain(countdowns)
+            .fold(Column::new(), |column, line| column.push(self.text(line).width(Length::Fill).align_x(Horizontal::Center)))
+    }
+
+    /// Times of sunrise and sunset today at the configured location and the phase of the moon
+    fn view_sun_moon(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let latitude = app.config.latitude_mul_100 as f32 / 100.;
+        let longitude = app.config.longitude_mul_100 as f32 / 100.;
+        let format = app.config.clock_format.short_time();
+        let daylight = match sun::daylight(app.current_time.date_naive(), latitude, longitude) {
+            Daylight::Times { sunrise, sunset } => fl!("sun-times",
+                sunrise = sunrise.with_timezone(&Local).format(&format).to_string(),
+                sunset = sunset.with_timezone(&Local).format(&format).to_string()),
+            Daylight::AlwaysUp => fl!("sun-always-up"),
+            Daylight::AlwaysDown => fl!("sun-always-down"),
+        };
+        let phase = sun::moon_phase(app.current_time.with_timezone(&Utc));
+        // eight phases centred on the new, quarter and full moons
+        let name = match ((phase * 8.).round() as u8) % 8 {
+            0 => fl!("moon-new"),
+            1 => fl!("moon-waxing-crescent"),
+            2 => fl!("moon-first-quarter"),
+            3 => fl!("moon-waxing-gibbous"),
+            4 => fl!("moon-full"),
+            5 => fl!("moon-waning-gibbous"),
+            6 => fl!("moon-last-quarter"),
+            _ => fl!("moon-waning-crescent"),
+        };
+        let illumination = (sun::moon_illumination(phase) * 100.).round() as u8;
+        [daylight, fl!("moon-phase", phase = name, illumination = illumination)].into_iter()
+            .fold(Column::new(), |column, line| column.push(self.text(line).width(Length::Fill).align_x(Horizontal::Center)))
+    }
+
+    /// Month of today as a grid of days starting on Monday, today in the accent colour and the
+    /// days of `Config::calendar_events` and of the agenda in the success colour
+    fn view_calendar(&self, app:&App)->iced::widget::Container<'_, Message, cosmic::Theme>{
+        let today = app.current_time.date_naive();
+        let first = today.with_day(1).unwrap_or(today);
+        let events:Vec<NaiveDate> = app.config.calendar_events.iter()
+            .filter_map(|entry| config::event_day(entry, today.year()))
+            .chain(app.agenda.iter().map(|event| event.start.with_timezone(&Local).date_naive()))
+            .collect();
+        let mut column = Column::new()
+            .push(self.text(first.format(&fl!("calendar-title")).to_string()).width(Length::Fill).align_x(Horizontal::Center))
+            .push(self.text(fl!("calendar-weekdays")));
+        // days before the first of the month are left blank
+        let mut week = Row::new().push(self.text(" ".repeat(3 * first.weekday().num_days_from_monday() as usize)));
+        for day in first.iter_days().take_while(|day| day.month() == first.month()) {
+            let colour = if day == today {Some(self.colours.accent)}
+                else if events.contains(&day) {Some(self.colours.success)}
+                else {None};
+            week = week.push(coloured(self.text(format!("{:>2} ", day.day())), colour));
+            if day.weekday() == Weekday::Sun {
+                column = column.push(std::mem::replace(&mut week, Row::new()));
+            }
+        }
+        container(column.push(week)).width(Length::Fill).align_x(Horizontal::Center).padding(Padding{top:5., ..Default::default()})
+    }
+
+    /// A line per clock of `Config::world_clocks` with the time and the difference to the
+    /// local time, skipping unknown time zones
+    fn view_world_clocks(&self, app:&App)->Option<Column<'_, Message, cosmic::Theme>>{
+        if app.config.world_clocks.is_empty() {return None;}
+        let format = app.config.clock_format.short_time();
+        let width = app.config.world_clocks.iter().map(|clock| clock.label.chars().count()).max().unwrap_or(0);
+        let local = app.current_time.offset().local_minus_utc();
+        let lines = app.config.world_clocks.iter().filter_map(|clock| {
+            let time = app.current_time.with_timezone(&clock.time_zone.parse::<chrono_tz::Tz>().ok()?);
+            let offset = chrono::Offset::fix(time.offset()).local_minus_utc() - local;
+            Some(self.text(format!("{:<width$} {} {}", clock.label, time.format(&format), zone_offset(offset))))
+        });
+        Some(lines.fold(Column::new().padding(Padding{top:5., ..Default::default()}), Column::push))
+    }
+
+    /// The next `Config::agenda_events` events, each with the day and time it starts and how
+    /// long until then
+    fn view_agenda(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        if app.agenda.is_empty() {
+            return Column::new().push(self.text(fl!("agenda-empty")));
+        }
+        let now = app.current_time.with_timezone(&Utc);
+        let time_format = app.config.clock_format.short_time();
+        let day_format = fl!("agenda-day-format");
+        app.agenda.iter().take(app.config.agenda_events.into()).fold(Column::new().spacing(5), |column, event| {
+            let start = event.start.with_timezone(&Local);
+            let time = if event.all_day {fl!("agenda-all-day")} else {start.format(&time_format).to_string()};
+            let countdown = if event.start <= now {fl!("agenda-now")}
+                else {fl!("agenda-in", time = countdown(event.start - now))};
+            column.push(Column::new()
+                .push(self.text(truncate(&event.summary, GRAPH_CHAR_WIDTH).to_string()))
+                .push(coloured(
+                    self.text(format!("{} {} · {}", start.format(&day_format), time, countdown))
+                        .size(self.text_size * SMALL_TEXT_SCALE),
+                    (event.start <= now).then_some(self.colours.accent),
+                )))
+        })
+    }
+
+    /// One of the newest headlines, changing every `Config::feed_cycle_seconds`, which opens
+    /// in the browser when clicked
+    fn view_headlines(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let count = app.headlines.len().min(app.config.feed_headlines.into());
+        if count == 0 {
+            return Column::new().push(self.text(fl!("headlines-empty")));
+        }
+        let index = (app.current_time.timestamp() / i64::from(app.config.feed_cycle_seconds)) as usize % count;
+        let headline = &app.headlines[index];
+        let now = app.current_time.with_timezone(&Utc);
+        let details = [
+            Some(truncate(&headline.source, GRAPH_CHAR_WIDTH / 2).to_string()).filter(|source| !source.is_empty()),
+            headline.published.filter(|published| *published <= now)
+                .map(|published| fl!("headlines-ago", time = countdown(now - published))),
+            Some(format!("{}/{}", index + 1, count)),
+        ].into_iter().flatten().join(" · ");
+        let title = mouse_area(self.text(truncate(&headline.title, GRAPH_CHAR_WIDTH * 2).to_string()));
+        let title = if headline.link.is_empty() {title} else {
+            title.on_press(Message::LaunchUrl(headline.link.clone())).interaction(iced::mouse::Interaction::Pointer)
+        };
+        Column::new()
+            .push(title)
+            .push(self.text(details).size(self.text_size * SMALL_TEXT_SCALE))
+    }
+
+    /// Unread notifications and a line per repository with the outcome of its latest run,
+    /// each opening its page in the browser when clicked
+    fn view_github(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let Some(status) = &app.github else {
+            return Column::new().push(self.text(fl!("github-loading")));
+        };
+        let link = |content:Text<'static, cosmic::Theme>, url:String| mouse_area(content)
+            .on_press(Message::LaunchUrl(url))
+            .interaction(iced::mouse::Interaction::Pointer);
+        let notifications = status.notifications.map(|count| {
+            let count = if count >= github::MAX_NOTIFICATIONS {format!("{}+", count)} else {count.to_string()};
+            link(self.text(fl!("github-notifications", count = count)), "https://github.com/notifications".to_string())
+        });
+        let runs = status.runs.iter().map(|(repo, run)| {
+            let Some(run) = run else {
+                let line = format!("  {} {}", truncate(repo, GRAPH_CHAR_WIDTH / 2), fl!("github-no-runs"));
+                let element:cosmic::Element<'_, Message> = self.text(line).into();
+                return element;
+            };
+            let (mark, colour) = match run.state {
+                github::RunState::Pending => ('◌', Some(self.colours.warning)),
+                github::RunState::Success => ('●', Some(self.colours.success)),
+                github::RunState::Failure => ('✕', Some(self.colours.critical)),
+                github::RunState::Cancelled => ('○', None),
+            };
+            let line = format!("{} {} {} {}", mark, repo, run.branch, run.workflow);
+            link(coloured(self.text(truncate(&line, GRAPH_CHAR_WIDTH).to_string()), colour), run.url.clone()).into()
+        });
+        Column::new()
+            .push_maybe(notifications)
+            .extend(runs)
+            .push_maybe(status.offline.then(|| self.text(fl!("github-offline")).size(self.text_size * SMALL_TEXT_SCALE)))
+    }
+
+    /// First pending tasks with a checkbox completing them, the priority before and due dates
+    /// below them, in the warning colour once due
+    fn view_todo(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        if app.todo.is_empty() {
+            return Column::new().push(self.text(fl!("todo-empty")));
+        }
+        let today = app.current_time.date_naive();
+        app.todo.iter().take(app.config.todo_tasks.into()).fold(Column::new().spacing(5), |column, task| {
+            let description = match task.priority {
+                Some(priority) => format!("({}) {}", priority, task.description),
+                None => task.description.clone(),
+            };
+            let due = task.due.map(|due| coloured(
+                self.text(fl!("todo-due", date = due.format("%Y-%m-%d").to_string())).size(self.text_size * SMALL_TEXT_SCALE),
+                (due <= today).then_some(self.colours.warning),
+            ));
+            let task = task.clone();
+            column.push(row![
+                cosmic::widget::checkbox("", false).on_toggle(move |_| Message::TodoComplete(task.clone())),
+                Column::new()
+                    .push(self.text(truncate(&description, GRAPH_CHAR_WIDTH - 3).to_string()))
+                    .push_maybe(due),
+            ].spacing(5).align_y(iced::Alignment::Center))
+        })
+    }
+
+    /// Line per symbol with its price and change, green when rising and red when falling, and
+    /// the time of the quote where it could not be updated
+    fn view_ticker(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        if app.quotes.is_empty() {
+            return Column::new().push(self.text(fl!("ticker-loading")));
+        }
+        let time_format = app.config.clock_format.short_time();
+        app.quotes.iter().fold(Column::new(), |column, entry| {
+            let Some(quote) = &entry.quote else {
+                return column.push(row![
+                    self.text(truncate(&entry.symbol, GRAPH_CHAR_WIDTH / 2).to_string()).width(Length::Fill),
+                    self.text("–"),
+                ]);
+            };
+            let decimals = if quote.price.abs() < 1. {4} else {2};
+            let (arrow, colour) = if quote.change >= 0. {('▲', self.colours.success)} else {('▼', self.colours.critical)};
+            column
+                .push(row![
+                    self.text(truncate(&entry.symbol, GRAPH_CHAR_WIDTH / 2).to_string()).width(Length::Fill),
+                    self.text(number(quote.price as f32, decimals)),
+                    coloured(self.text(format!(" {}{}%", arrow, number(quote.change.abs() as f32, 1))), Some(colour)),
+                ])
+                .push_maybe(entry.stale.then(|| coloured(
+                    self.text(fl!("ticker-stale", time = quote.time.format(&time_format).to_string()))
+                        .size(self.text_size * SMALL_TEXT_SCALE),
+                    Some(self.colours.warning),
+                )))
+        })
+    }
+
+    /// Active layout in the accent colour before the others, and the lock keys, lit while on
+    #[cfg(feature = "wayland")]
+    fn view_keyboard(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let keyboard = &app.keyboard;
+        let layouts = keyboard.layouts.iter().enumerate().fold(
+            row![self.text(format!("{} ", fl!("keyboard-layout"))).width(Length::Fill)],
+            |row, (index, layout)| row.push(coloured(
+                self.text(format!(" {}", layout.to_uppercase())),
+                (index == 0).then_some(self.colours.accent),
+            )),
+        );
+        let lock = |name:String, on:bool| coloured(self.text(name), on.then_some(self.colours.warning))
+            .width(Length::Fill)
+            .align_x(Horizontal::Center);
+        Column::new()
+            .push_maybe((!keyboard.layouts.is_empty()).then_some(layouts))
+            .push(row![
+                lock(fl!("keyboard-caps-lock"), keyboard.caps_lock),
+                lock(fl!("keyboard-num-lock"), keyboard.num_lock),
+            ])
+    }
+
+    /// Line per group of workspaces with the active one highlighted, switching to a workspace
+    /// when clicked
+    #[cfg(feature = "wayland")]
+    fn view_workspaces(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        if app.workspaces.iter().all(Vec::is_empty) {
+            return Column::new().push(self.text(fl!("workspaces-none")));
+        }
+        app.workspaces.iter().filter(|group| !group.is_empty()).fold(Column::new(), |column, group| {
+            column.push(group.iter().fold(row![], |row, workspace| {
+                let name = truncate(&workspace.name, GRAPH_CHAR_WIDTH / 4).to_string();
+                let name = mouse_area(
+                    coloured(self.text(name), workspace.active.then_some(self.colours.accent))
+                        .width(Length::Fill)
+                        .align_x(Horizontal::Center),
+                );
+                row.push(if app.config.workspaces_clickable && !workspace.active {
+                    name.on_press(Message::WorkspaceActivate(workspace.id)).interaction(iced::mouse::Interaction::Pointer)
+                } else {name})
+            }))
+        })
+    }
+
+    /// Quote wrapped over as many lines as it takes, and its attribution aligned to the right
+    fn view_quote(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let Some(quote) = &app.quote else {
+            return Column::new().push(self.text(fl!("quote-none")));
+        };
+        Column::new()
+            .push(self.text(quote.text.clone()))
+            .push_maybe(quote.attribution.as_ref().map(|source| {
+                self.text(fl!("quote-attribution", source = source.clone()))
+                    .size(self.text_size * SMALL_TEXT_SCALE)
+                    .width(Length::Fill)
+                    .align_x(Horizontal::Right)
+            }))
+    }
+
+    /// Line per application focused the longest today with its time, and the time of all of
+    /// them
+    #[cfg(feature = "wayland")]
+    fn view_screen_time(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        if app.screen_time.is_empty() {
+            return Column::new().push(self.text(fl!("screen-time-none")));
+        }
+        let duration = |time:Duration| {
+            let minutes = time.as_secs() / 60;
+            if minutes < 60 {fl!("screen-time-minutes", minutes = minutes.to_string())} else {
+                fl!("screen-time-hours", hours = (minutes / 60).to_string(), minutes = format!("{:02}", minutes % 60))
+            }
+        };
+        let total:Duration = app.screen_time.iter().map(|(_, time)| *time).sum();
+        app.screen_time.iter().take(usize::from(app.config.screen_time_apps)).fold(Column::new(), |column, (app_id, time)| {
+            column.push(row![
+                self.text(truncate(screen_time::name(app_id), GRAPH_CHAR_WIDTH / 2).to_string()).width(Length::Fill),
+                self.text(duration(*time)),
+            ])
+        }).push(row![
+            coloured(self.text(fl!("screen-time-total")), Some(self.colours.accent)).width(Length::Fill),
+            coloured(self.text(duration(total)), Some(self.colours.accent)),
+        ])
+    }
+
+    /// Line per mail account with its unread messages, highlighted while there are any,
+    /// starting the mail client when clicked
+    #[cfg(feature = "mail")]
+    fn view_mail(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        app.config.mail_accounts.iter().enumerate().fold(Column::new(), |column, (index, account)| {
+            let (unread, colour) = match app.mail_unread.get(&index) {
+                Some(Some(count)) => (fl!("mail-unread", count = count.to_string()), (*count > 0).then_some(self.colours.accent)),
+                Some(None) => (fl!("mail-offline"), Some(self.colours.warning)),
+                None => (String::new(), None),
+            };
+            let line = mouse_area(row![
+                self.text(truncate(&account.label, GRAPH_CHAR_WIDTH / 2).to_string()).width(Length::Fill),
+                coloured(self.text(unread), colour),
+            ]);
+            column.push(if app.config.mail_client.is_empty() {line} else {
+                line.on_press(Message::MailOpen).interaction(iced::mouse::Interaction::Pointer)
+            })
+        })
+    }
+
+    /// Line per host of `Config::remote_hosts` with its load, and below it the usage of its CPU
+    /// and memory, coloured like the local CPU once it crosses a threshold
+    fn view_remote(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        if app.config.remote_hosts.is_empty() {
+            return Column::new().push(self.text(fl!("remote-none")));
+        }
+        app.config.remote_hosts.iter().enumerate().fold(Column::new(), |column, (index, host)| {
+            let name = self.text(truncate(host, GRAPH_CHAR_WIDTH / 2).to_string()).width(Length::Fill);
+            match app.remote.get(&index) {
+                Some(Some(status)) => column.push(row![
+                    name,
+                    self.text(fl!("remote-load", load = number(status.load[0], 2))),
+                ]).push(coloured(
+                    self.text(fl!("remote-usage", cpu = number(status.cpu, 0), memory = number(status.mem_percent(), 0)))
+                        .size(self.text_size * SMALL_TEXT_SCALE),
+                    self.alert_colour(app, Metric::CpuAvg, status.cpu),
+                )),
+                Some(None) => column.push(row![name, coloured(self.text(fl!("remote-offline")), Some(self.colours.warning))]),
+                None => column.push(name),
+            }
+        })
+    }
+
+    /// Button per agent of `Config::agents` switching between them, named after its host once
+    /// it is connected, and the metrics of the selected one
+    #[cfg(feature = "agent")]
+    fn view_agents(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        if app.config.agents.is_empty() {
+            return Column::new().push(self.text(fl!("agents-none")));
+        }
+        let width = GRAPH_CHAR_WIDTH / app.config.agents.len().clamp(2, 4);
+        let selected = app.agent_selected.min(app.config.agents.len() - 1);
+        let name = |index:usize, address:&str| match app.agents.get(&index) {
+            Some(Some(status)) if !status.host.is_empty() => status.host.clone(),
+            _ => address.rsplit_once(':').map_or(address, |(host, _)| host).to_string(),
+        };
+        let switcher = app.config.agents.iter().enumerate().fold(row![], |row, (index, address)| {
+            let label = truncate(&name(index, address), width).to_string();
+            let btn = if index == selected {
+                cosmic::widget::button::suggested(label)
+            } else {
+                cosmic::widget::button::text(label)
+            };
+            row.push(self.tap_target(btn.on_press(Message::AgentSelect(index))))
+        });
+        let column = Column::new().push(switcher);
+        let Some(Some(status)) = app.agents.get(&selected) else {
+            let agent = name(selected, &app.config.agents[selected]);
+            return column.push(coloured(self.text(fl!("agents-offline", agent = agent)), Some(self.colours.warning)));
+        };
+        let unit = app.config.temperature_unit;
+        let lines = [
+            ("cpu_avg", fl!("agents-cpu"), Some(Metric::CpuAvg)),
+            ("cpu_max", fl!("agents-cpu-max"), Some(Metric::CpuMax)),
+            ("ram", fl!("agents-memory"), None),
+            ("gpu_util", fl!("agents-gpu"), Some(Metric::GpuUtil)),
+            ("gpu_temp", fl!("agents-gpu-temp"), Some(Metric::GpuTemp)),
+            ("gpu_mem", fl!("agents-gpu-memory"), None),
+        ];
+        let column = lines.into_iter().fold(column, |column, (key, label, metric)| {
+            let Some(&value) = status.metrics.get(key) else { return column };
+            let text = if key == "gpu_temp" {
+                format!("{}{}", number(unit.convert(value), 0), unit.symbol())
+            } else {
+                format!("{}%", number(value, 0))
+            };
+            column.push(row![
+                self.text(label).width(Length::Fill),
+                coloured(self.text(text), metric.and_then(|metric| self.alert_colour(app, metric, value))),
+            ])
+        });
+        let uptime = status.uptime_secs / 3600;
+        column.push(row![
+            self.text(fl!("agents-load")).width(Length::Fill),
+            self.text(status.load.iter().map(|load| number(*load, 2)).collect::<Vec<_>>().join(" ")),
+        ]).push(
+            self.text(fl!("agents-uptime", days = (uptime / 24).to_string(), hours = (uptime % 24).to_string()))
+                .size(self.text_size * SMALL_TEXT_SCALE),
+        )
+    }
+
+    /// Current temperature and conditions with their icon, and a column per hour of the
+    /// forecast, noting the time of the last forecast while offline
+    fn view_weather(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let Some(forecast) = &app.forecast else {
+            return Column::new().push(self.text(fl!("weather-loading")));
+        };
+        let unit = app.config.temperature_unit;
+        let icon = |conditions:&weather::Conditions, size:f32| crate::icon_cache_get(conditions.condition.icon(conditions.day), size as u16);
+        let condition = match forecast.current.condition {
+            Condition::Clear => fl!("weather-clear"),
+            Condition::PartlyCloudy => fl!("weather-partly-cloudy"),
+            Condition::Overcast => fl!("weather-overcast"),
+            Condition::Fog => fl!("weather-fog"),
+            Condition::Drizzle => fl!("weather-drizzle"),
+            Condition::Rain => fl!("weather-rain"),
+            Condition::Snow => fl!("weather-snow"),
+            Condition::Thunderstorm => fl!("weather-thunderstorm"),
+        };
+        let current = row![
+            icon(&forecast.current, self.text_size * 2.),
+            self.text(format!("{:.0}{}", unit.convert(forecast.current.temperature), unit.symbol())).size(self.text_size * 1.5),
+            self.text(condition),
+        ].spacing(10).align_y(iced::Alignment::Center);
+        let hour_format = app.config.clock_format.hour();
+        let hours = forecast.hours.iter()
+            .filter(|hour| hour.time > app.current_time)
+            .take(app.config.weather_forecast_hours.into())
+            .fold(Row::new().spacing(5), |row, hour| row.push(column![
+                self.text(hour.time.with_timezone(&Local).format(&hour_format).to_string()).size(self.text_size * SMALL_TEXT_SCALE),
+                icon(hour, self.text_size),
+                self.text(format!("{:.0}°", unit.convert(hour.temperature))),
+            ].align_x(Horizontal::Center).width(Length::Fill)));
+        let offline = forecast.offline.then(|| {
+            let time = forecast.current.time.with_timezone(&Local).format(&app.config.clock_format.short_time()).to_string();
+            coloured(self.text(fl!("weather-offline", time = time)).size(self.text_size * SMALL_TEXT_SCALE), Some(self.colours.warning))
+        });
+        Column::new().spacing(5).push(current).push(hours).push_maybe(offline)
+    }
+
+    /// Time left of the pomodoro phase, green during breaks, with the sessions done before the
+    /// next long break and buttons to start, pause and reset the timer
+    fn view_pomodoro(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let pomodoro = &app.pomodoro;
+        let (phase, colour) = match pomodoro.phase() {
+            Phase::Work => (fl!("pomodoro-work"), None),
+            Phase::ShortBreak => (fl!("pomodoro-break"), Some(self.colours.success)),
+            Phase::LongBreak => (fl!("pomodoro-long-break"), Some(self.colours.success)),
+        };
+        // counted up so the timer shows 00:00 only once the phase is over
+        let seconds = pomodoro.remaining().as_millis().div_ceil(1000);
+        let sessions:String = (0..app.config.pomodoro_rounds)
+            .map(|session| if session < pomodoro.sessions() {'●'} else {'○'})
+            .collect();
+        let start_pause = if pomodoro.running() {
+            cosmic::widget::button::text(fl!("pomodoro-pause"))
+        } else {
+            cosmic::widget::button::suggested(fl!("pomodoro-start"))
+        };
+        Column::new()
+            .push(row![
+                self.text(phase).width(Length::Fill),
+                self.text(sessions),
+            ])
+            .push(coloured(self.text(format!("{:02}:{:02}", seconds / 60, seconds % 60)), colour)
+                .size(self.text_size * CLOCK_SCALE).width(Length::Fill).align_x(Horizontal::Center))
+            .push(row![
+                self.tap_target(start_pause.on_press(Message::PomodoroStartPause)),
+                self.tap_target(cosmic::widget::button::text(fl!("pomodoro-reset")).on_press(Message::PomodoroReset)),
+            ].spacing(10))
+    }
+
+    /// Title and artist of the active media player, its position in the track and buttons for
+    /// the previous track, play or pause and the next track
+    #[cfg(feature = "mpris")]
+    fn view_media(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        use cosmic::widget::{button, icon};
+        let Some(playing) = &app.media else {
+            return Column::new().push(self.text(fl!("media-nothing")));
+        };
+        let minutes = |time:Duration| format!("{}:{:02}", time.as_secs() / 60, time.as_secs() % 60);
+        let position = playing.position();
+        let progress = match playing.length {
+            Some(length) => format!(
+                "{} {} {}",
+                minutes(position),
+                Self::bar_meter(position.as_secs_f32(), (0., length.as_secs_f32()), GRAPH_CHAR_WIDTH - 12),
+                minutes(length),
+            ),
+            None => minutes(position),
+        };
+        let control = |name:&'static str, control:media::Control| {
+            self.tap_target(button::icon(icon::from_name(name)).on_press(Message::MediaControl(control)))
+        };
+        let play_pause = if playing.playing {"media-playback-pause-symbolic"} else {"media-playback-start-symbolic"};
+        // the title and artist make room for the cover art and the space after it
+        let art_size = self.text_size * ART_LINES;
+        let art = app.media_art.as_ref().map(|art| {
+            cosmic::widget::image(art.handle.clone()).width(art_size).height(art_size)
+        });
+        let width = match art {
+            Some(_) => GRAPH_CHAR_WIDTH.saturating_sub((art_size / (self.text_size * CHAR_ASPECT)).ceil() as usize + 1),
+            None => GRAPH_CHAR_WIDTH,
+        };
+        Column::new()
+            .push(Row::new()
+                .push_maybe(art)
+                .push(column![
+                    self.text(truncate(&playing.title, width).to_string()),
+                    self.text(truncate(&playing.artist, width).to_string()).size(self.text_size * SMALL_TEXT_SCALE),
+                ])
+                .spacing(self.text_size * CHAR_ASPECT)
+                .align_y(iced::Alignment::Center))
+            .push(self.text(progress))
+            .push(row![
+                control("media-skip-backward-symbolic", media::Control::Previous),
+                control(play_pause, media::Control::PlayPause),
+                control("media-skip-forward-symbolic", media::Control::Next),
+            ].spacing(10))
+    }
+
+    fn view_spectrum(&self, app:&App)->cosmic::Element<'_, Message>{
+        let lines = app.config.spectrum_lines as usize;
+        match app.config.spectrum_style {
+            GraphStyle::Block => coloured(
+                self.text(Self::block_graph_lines(&app.spectrum, GRAPH_CHAR_WIDTH, lines, (0., 1.))),
+                Some(self.colours.accent),
+            ).into(),
+            GraphStyle::Braille => coloured(
+                self.text(Self::braille_bars(&app.spectrum, GRAPH_CHAR_WIDTH, lines)),
+                Some(self.colours.accent),
+            ).into(),
+            GraphStyle::Smooth => iced::widget::shader(GraphProgram::new(
+                    SPECTRUM_GRAPH_ID, &app.spectrum, None, (0., 1.), self.colours.accent.into_linear(),
+                ))
+                .width(Length::Fill)
+                .height(Length::Fixed(GRAPH_HEIGHT / 2. * lines as f32))
+                .into(),
+        }
+    }
+
+    fn view_system(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        Column::new()
+            .push(self.text(format!("{} {} {} \n{} {}\n", 
+                fl!("monitor-os"),
                 self.os_name,
                 self.os_version,
+                fl!("monitor-kernel"),
                 self.kernel_name,
-            )),
-            horizontal_rule(2),
-            // CPU
-            text(format!("{} {} @{}C/{}T", 
+            )))
+            .push_maybe(app.frag_shader_program.gpu_error().map(|err| coloured(
+                self.text(format!("{}: {}", fl!("monitor-render-unavailable"), err)),
+                Some(self.colours.critical),
+            )))
+    }
+
+    fn view_cpu(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let util_scale = if app.config.graph_auto_scale {GraphScale::Auto} else {GraphScale::PERCENT};
+        let cpu_avg_alert = self.alert_colour(app, Metric::CpuAvg, self.smooth.cpu_avg);
+        let cpu_max_alert = self.alert_colour(app, Metric::CpuMax, self.smooth.cpu_max);
+        Column::new()
+            .push(self.text(format!("{} {} @{}C/{}T", 
                 self.cpu_name,
                 self.architecture,
                 self.cpu_info.physical_cores,
                 self.cpu_info.cpu_count,
-            )),
-            text(format!("CPU AVG   {:2.0} %\nCPU MAX   {:2.0} %\nCPU FRQ {:4} MHz", 
-                self.smooth.cpu_avg,
-                self.smooth.cpu_max,
-                self.smooth.cpu_freq as u64,
-            )),
-            text(Self::braille_graph(&self.cpu_avgs, 2)),
-            horizontal_rule(2),
-            // MEMORY
+            )))
+            .push(self.view_metric(app, Metric::CpuAvg,
+                coloured(self.text(metric_line(fl!("monitor-cpu-avg"), number(self.smooth.cpu_avg, 0), "%")), cpu_avg_alert),
+                self.smooth.cpu_avg, util_scale))
+            .push(self.view_metric(app, Metric::CpuMax,
+                coloured(self.text(metric_line(fl!("monitor-cpu-max"), number(self.smooth.cpu_max, 0), "%")), cpu_max_alert),
+                self.smooth.cpu_max, util_scale))
+            .push(self.text(metric_line(fl!("monitor-cpu-freq"), number(self.smooth.cpu_freq, 0), "MHz")))
+            .push_maybe(app.config.per_core_graphs.then(|| self.view_core_graphs(app)))
+    }
+
+    fn view_memory(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        Column::new()
+            .push(self.view_metric(app, Metric::RamUsed,
+                row![
+                    self.text(format!("{} ", fl!("monitor-mem-used"))),
+                    self.text(format!("{}/{}",
+                        number(app.config.byte_unit.gigabytes(self.ram_used), 1),
+                        number(app.config.byte_unit.gigabytes(self.mem_total), 1),
+                    )),
+                    self.text(app.config.byte_unit.gigabyte_symbol())
+                ],
+                byte_to_gb(self.ram_used),
+                GraphScale::Range(0., byte_to_gb(self.mem_total)),
+            ))
+    }
+
+    fn view_gpu(&self, app:&App)->Column<'_, Message, cosmic::Theme>{
+        let util_scale = if app.config.graph_auto_scale {GraphScale::Auto} else {GraphScale::PERCENT};
+        let gpu_util_alert = self.alert_colour(app, Metric::GpuUtil, self.smooth.gpu_util);
+        Column::new()
+            .push(self.text(format!("{}", self.gpu_name)))
+            .push(self.view_metric(app, Metric::GpuUtil,
+                coloured(self.text(metric_line(fl!("monitor-gpu-util"), number(self.smooth.gpu_util, 0), "%")), gpu_util_alert),
+                self.smooth.gpu_util, util_scale))
+            .push(self.text(metric_line(fl!("monitor-gpu-freq"), number(self.smooth.gpu_clock, 0), "MHz")))
+            .push(self.text(format!("{} {}/{} {}",
+                fl!("monitor-gpu-mem"),
+                number(app.config.byte_unit.gigabytes(self.gpu_info.mem_used), 1),
+                number(app.config.byte_unit.gigabytes(self.gpu_info.mem_total), 1),
+                app.config.byte_unit.gigabyte_symbol())))
+            .push(self.text(metric_line(fl!("monitor-gpu-power"), number(self.smooth.gpu_power/1000., 0), "W")))
+            .push_maybe(self.gpu_info.temp.map(|temp| self.view_metric(app, Metric::GpuTemp,
+                coloured(self.text(metric_line(
+                    fl!("monitor-gpu-temp"),
+                    number(app.config.temperature_unit.convert(temp), 0),
+                    app.config.temperature_unit.symbol())), self.alert_colour(app, Metric::GpuTemp, temp)),
+                temp, GraphScale::PERCENT)))
+    }
+
+    /// Line and graph of a single metric for the picture-in-picture surface, see [`crate::pip`],
+    /// in the style of its graphs in the monitor
+    pub fn view_pip(&self, app:&App, metric:Metric)->cosmic::Element<'_, Message>{
+        let util_scale = if app.config.graph_auto_scale {GraphScale::Auto} else {GraphScale::PERCENT};
+        let (label, value, scale, symbol) = match metric {
+            Metric::CpuAvg => (fl!("monitor-cpu-avg"), self.smooth.cpu_avg, util_scale, "%"),
+            Metric::CpuMax => (fl!("monitor-cpu-max"), self.smooth.cpu_max, util_scale, "%"),
+            Metric::GpuUtil => (fl!("monitor-gpu-util"), self.smooth.gpu_util, util_scale, "%"),
+            Metric::GpuTemp => (fl!("monitor-gpu-temp"), self.gpu_info.temp.unwrap_or_default(), GraphScale::PERCENT,
+                app.config.temperature_unit.symbol()),
+            Metric::RamUsed => (fl!("monitor-mem-used"), byte_to_gb(self.ram_used),
+                GraphScale::Range(0., byte_to_gb(self.mem_total)), app.config.byte_unit.gigabyte_symbol()),
+        };
+        let colour = self.metric_colour(app, metric, value);
+        let unit = |value| display_value(&app.config, metric, value);
+        let decimals = if metric == Metric::RamUsed {1} else {0};
+        let line = coloured(self.text(metric_line(label, number(unit(value), decimals), symbol)), Some(colour));
+        let data = self.graph_data(self.live_history(metric), metric).into_iter().map(unit).collect_vec();
+        let graph = self.view_graph(PIP_GRAPH_ID, &data, self.graph_span(metric), None, graph_style(&app.config, metric), scale.map(unit), colour);
+        accessible::labelled(column![line, graph].spacing(4), "pip", self.accessible_name(app, metric, value))
+    }
+
+    /// Single line of the CPU and GPU utilization, GPU temperature and memory usage for the
+    /// overlay, coloured by the alert thresholds like the full monitor
+    pub fn view_overlay(&self, app:&App)->Row<'_, Message, cosmic::Theme>{
+        Row::new()
+            .spacing(self.text_size)
+            .push(coloured(
+                self.text(format!("{} {}%", fl!("monitor-overlay-cpu"), number(self.smooth.cpu_avg, 0))),
+                self.alert_colour(app, Metric::CpuAvg, self.smooth.cpu_avg),
+            ))
+            .push_maybe(self.nv.is_some().then(|| coloured(
+                self.text(format!("{} {}%", fl!("monitor-overlay-gpu"), number(self.smooth.gpu_util, 0))),
+                self.alert_colour(app, Metric::GpuUtil, self.smooth.gpu_util),
+            )))
+            .push_maybe(self.gpu_info.temp.filter(|_| self.nv.is_some()).map(|temp| coloured(
+                self.text(format!("{}{}", number(app.config.temperature_unit.convert(temp), 0), app.config.temperature_unit.symbol())),
+                self.alert_colour(app, Metric::GpuTemp, temp),
+            )))
+            .push(self.text(format!("{} {}{}",
+                fl!("monitor-overlay-mem"),
+                number(app.config.byte_unit.gigabytes(self.ram_used), 1),
+                app.config.byte_unit.gigabyte_symbol(),
+            )))
+    }
+
+    /// CPU and memory usage on one line, for the tooltip of the tray icon
+    pub fn summary(&self, config:&Config)->String{
+        format!("{} {}% · {} {}/{} {}",
+            fl!("monitor-overlay-cpu"),
+            number(self.smooth.cpu_avg, 0),
+            fl!("monitor-overlay-mem"),
+            number(config.byte_unit.gigabytes(self.ram_used), 1),
+            number(config.byte_unit.gigabytes(self.mem_total), 1),
+            config.byte_unit.gigabyte_symbol(),
+        )
+    }
+
+    /// Details of a process and the actions on it, terminating it once confirmed
+    fn view_process_detail(&self, pi:&ProcessInfo)->cosmic::Element<'_, Message>{
+        let actions = match self.kill_request.as_ref().filter(|request| request.name == pi.name) {
+            Some(request) => self.view_kill_confirmation(request),
+            None => row![
+                cosmic::widget::button::destructive(fl!("process-terminate")).on_press(Message::ProcessKillRequest(pi.kill_request())),
+                cosmic::widget::button::text(fl!("process-detail-close")).on_press(Message::ProcessDetail),
+            ].spacing(8).into(),
+        };
+        let pid = match pi.pids.len() {
+            0 | 1 => fl!("process-pid", pid = pi.pid()),
+            count => fl!("process-pids", pid = pi.pid(), count = count - 1),
+        };
+        container(column![
+            self.text(pi.name.to_string_lossy().into_owned()),
+            self.text(pid),
+            self.text(format!("{} {}%", fl!("monitor-process-cpu"), number(pi.cpu, 1))),
+            self.text(format!("{} {}{}",
+                fl!("monitor-process-ram"),
+                number(self.byte_unit.megabytes(pi.mem) as f32, 0),
+                self.byte_unit.megabyte_symbol())),
+            actions,
+        ].spacing(4))
+        .padding(10)
+        .class(cosmic::theme::Container::Dropdown)
+        .into()
+    }
+
+    /// Question whether to terminate the processes of `request`, below its entry
+    fn view_kill_confirmation(&self, request:&KillRequest)->cosmic::Element<'_, Message>{
+        column![
+            self.text(fl!("process-terminate-confirm", name = request.name.to_string_lossy().as_ref(), count = request.pids.len())),
             row![
-                text("MEM USE "),
-                text(format!("{:.1}/{:.1}",
-                    byte_to_gb(self.ram_used),
-                    byte_to_gb(self.mem_total),
-                )),
-                text("GB")
-            ],
-            horizontal_rule(2),
-            // GPU
-            text(format!("{}", self.gpu_name)),
-            text(format!("GPU UTL   {:2.0} %", self.smooth.gpu_util)),
-            text(format!("GPU FRQ {:4} MHz",self.smooth.gpu_clock as u64)),
-            text(format!("GPU MEM {:3.1}/{:3.1} GB",
-                byte_to_gb(self.gpu_info.mem_used),
-                byte_to_gb(self.gpu_info.mem_total))),
-            text(format!("GPU PWR  {:3.0} W", self.smooth.gpu_power/1000.)),
-            text(Self::braille_graph(&self.gpu_avgs, 2)),
-            horizontal_rule(2),
-        ).padding(Padding{left:10.,right:10.,bottom:10.,..Default::default()});
-        res
+                cosmic::widget::button::destructive(fl!("process-terminate")).on_press(Message::ProcessKillConfirm),
+                cosmic::widget::button::text(fl!("process-terminate-cancel")).on_press(Message::ProcessKillCancel),
+            ].spacing(8),
+        ].spacing(4)
+        .padding([4, 0])
+        .into()
     }
 
     pub fn view_processes(&self)->cosmic::iced_widget::Column<'_, Message, cosmic::Theme, cosmic::Renderer>{
         
+        // the column headers sort the list when clicked, the sorting one is marked
+        let sort = |process_by:ProcessBy, indent:&str, label:String| {
+            let mark = if self.process_sort_by == process_by {'▾'} else {' '};
+            mouse_area(container(self.text(format!("{}{}{}", indent, mark, label))).padding([self.tap_padding(), 0]))
+                .on_press(Message::ProcessSortBy(process_by))
+        };
         let header =  row![
-            Text::new("      NAME     |"),
-            // cosmic::iced_widget::Button::new(text(match self.process_sort_by{
-            //     ProcessBy::Cpu => ">CPU",
-            //     ProcessBy::Ram => " CPU",
-            // })),
-            // button(text(match self.process_sort_by{
-            //     ProcessBy::Cpu => " RAM",
-            //     ProcessBy::Ram => ">RAM",
-            // }))
-            // .on_press(Message::ProcessSortBy(ProcessBy::Ram)),
-            text(" CPU"),
-            text("   RAM"),
+            self.text(format!("{:^15}|", fl!("monitor-process-name"))),
+            sort(ProcessBy::Cpu, "", fl!("monitor-process-cpu")),
+            sort(ProcessBy::Ram, "  ", fl!("monitor-process-ram")),
         ];
 
         let mut column: Column<'_, Message, cosmic::Theme, cosmic::Renderer> = Column::new();
         for pi in &self.process_info {
-            column = column.push(Text::new(pi.to_string()));
+            // entries are selected by clicking them, to be terminated with a key binding once confirmed
+            let selected = self.selected_process.as_ref() == Some(&pi.name);
+            let label = coloured(self.text(pi.label(self.byte_unit)), selected.then_some(self.colours.accent));
+            let entry = mouse_area(label).on_press(Message::ProcessSelect(pi.name.clone()));
+            column = if selected && self.process_detail {
+                column.push(cosmic::widget::popover(entry)
+                    .popup(self.view_process_detail(pi))
+                    .on_close(Message::ProcessDetail))
+            } else {
+                column.push(entry)
+            };
+            // the details confirm in their popover
+            let confirm = self.kill_request.as_ref().filter(|request| request.name == pi.name && !(selected && self.process_detail));
+            if let Some(request) = confirm {
+                column = column.push(self.view_kill_confirmation(request));
+            }
         }
+        let status = self.kill_status.as_ref().map(|status| match status {
+            Ok(text) => coloured(self.text(text.clone()), Some(self.colours.success)),
+            Err(text) => coloured(self.text(text.clone()), Some(self.colours.critical)),
+        });
 
         column![
-            horizontal_rule(2),
+            self.separator(),
             // header:
             header.width(Length::Fill).height(Length::Shrink)
                 .padding(Padding{top:30., bottom:5., ..Default::default()}),
+        ]
+        .push_maybe(status)
+        .push(
             // scrollable:
-            container(scrollable(column).width(Length::Fill))
+            container(touch::kinetic(
+                scrollable(column)
+                    .id(process_list_id())
+                    .on_scroll(|viewport| Message::ProcessListScrolled(viewport.absolute_offset().y <= 0.))
+                    .width(Length::Fill),
+                self.process_list_at_top,
+            ).on_pull(Message::Refresh))
                 .height(Length::FillPortion(4))
                 .padding(Padding{bottom:30., ..Default::default()}),
-        ]
+        )
         .width(Length::Fill).height(Length::Fill)
     }
 }
@@ -505,6 +2415,57 @@ impl ResourceMonitor{
 //     }
 // }
 
+/// Narrowest width of the monitor at which the graphs and the lines of the process list fit
+/// in the monitor font of `text_size`, with the padding and the gap between columns
+pub fn min_width(text_size:u16, layout:MonitorLayout)->f32{
+    let column = column_width(text_size);
+    match layout {
+        // a row of sections is scrolled rather than widened
+        MonitorLayout::OneColumn | MonitorLayout::Horizontal => column + 20.,
+        MonitorLayout::TwoColumns => 2. * column + 40.,
+    }
+}
+
+/// Width of a section, which fits the graphs in the monitor font of `text_size`
+fn column_width(text_size:u16)->f32{
+    GRAPH_CHAR_WIDTH as f32 * CHAR_ASPECT * f32::from(text_size)
+}
+
+/// Id of the scrollable process list, which is scrolled to keep the selection in view
+pub fn process_list_id()->scrollable::Id{
+    scrollable::Id::new("process-list")
+}
+
+/// Width of the compact mode in the monitor font of `text_size`, see [`ResourceMonitor::view_compact`]
+pub fn compact_width(text_size:u16)->f32{
+    COMPACT_CHARS as f32 * CHAR_ASPECT * f32::from(text_size) + 20.
+}
+
+/// Whether a battery is discharging and no mains supply is online, according to sysfs
+pub fn on_battery()->bool{
+    let Ok(supplies) = fs::read_dir("/sys/class/power_supply") else {return false;};
+    let mut discharging = false;
+    for supply in supplies.flatten() {
+        let read = |file:&str| fs::read_to_string(supply.path().join(file)).unwrap_or_default();
+        match read("type").trim() {
+            "Mains" if read("online").trim() == "1" => return false,
+            "Battery" if read("status").trim() == "Discharging" => discharging = true,
+            _ => {},
+        }
+    }
+    discharging
+}
+
+/// Whether the chassis type reported by the firmware is portable, read once
+pub fn is_laptop()->bool{
+    /// SMBIOS chassis types of portables, notebooks, tablets, convertibles and detachables
+    const PORTABLE:[u8;8] = [8, 9, 10, 11, 14, 30, 31, 32];
+    static LAPTOP:OnceLock<bool> = OnceLock::new();
+    *LAPTOP.get_or_init(|| fs::read_to_string("/sys/class/dmi/id/chassis_type")
+        .ok()
+        .and_then(|chassis| chassis.trim().parse().ok())
+        .is_some_and(|chassis| PORTABLE.contains(&chassis)))
+}
 
 fn gpu_name(nv:& Option<Nvml>)-> Result<String, NvmlError>{
     if let Some(nv) = nv{
@@ -520,12 +2481,20 @@ fn gpu_update(nv:& Option<Nvml>)-> Result<GpuInfo, NvmlError>{
         let clock = device.clock_info(Clock::Graphics)?;
         let utilization = device.utilization_rates()?;
         let power = device.power_usage()?;
+        // not every card has a sensor, which leaves only the temperature unknown
+        let temp = device.temperature(TemperatureSensor::Gpu).ok();
+        // not every device reports its limits
+        let clock_max = device.max_clock_info(Clock::Graphics).unwrap_or_default();
+        let power_limit = device.enforced_power_limit().unwrap_or_default();
         Ok(GpuInfo { 
             mem_used: mem.used,
             mem_total: mem.total,
             clock: clock as f32,
             power: power as f32,
-            util: utilization.gpu as f32
+            util: utilization.gpu as f32,
+            temp: temp.map(|temp| temp as f32),
+            clock_max: clock_max as f32,
+            power_limit: power_limit as f32,
         })
     } else {Err(NvmlError::NoData)}
 }
@@ -0,0 +1,25 @@
+// ------------ MAIN PROGRAM -----------
+// Classic sine plasma. CPU utilization speeds up the motion and warms up the palette.
+const PI: f32 = 3.1415926535;
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4f {
+	// widget coordinates in [-1,1], y pointing up
+	let p = vec2f(1., -1.) * (2. * (in.position.xy - u.top_left) / u.res - 1.);
+	let t = u.t * (0.5 + u.cpu_u);
+
+	var v = sin(p.x * 3. + t);
+	v += sin(3. * (p.x * sin(t * 0.5) + p.y * cos(t * 0.3)) + t);
+	let c = p + 0.5 * vec2f(sin(t * 0.2), cos(t * 0.3));
+	v += sin(sqrt(100. * dot(c, c) + 1.) + t);
+	v *= 0.5 * PI;
+
+	let cool = vec3f(0.5 + 0.5 * sin(v), 0.5 + 0.5 * sin(v + 2.), 1.);
+	let warm = vec3f(1., 0.5 + 0.5 * sin(v), 0.3 + 0.3 * sin(v + 4.));
+	let colour = mix(cool, warm, u.cpu_m);
+
+	// fade into the background towards the edges
+	let fade = 1. - smoothstep(0.6, 1., length(p));
+	let bg = vec3f(u.r, u.g, u.b);
+	return vec4f(weather(tweak(mix(bg, colour, 0.6 * fade), bg), in.position.xy - u.top_left), u.a);
+}
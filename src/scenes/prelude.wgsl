@@ -0,0 +1,81 @@
+// Uniforms, vertex stage and helpers shared by the built-in scenes, prepended to each of them
+// when it is loaded. User-supplied shaders have to declare the same uniforms and vertex stage.
+
+// ------------- UNIFORMS -------------
+@group(0) @binding(0) var<uniform> u: Uniforms;
+struct Uniforms {
+	res: vec2f,
+	top_left: vec2f,
+	t: f32,
+	cpu_u: f32,
+	cpu_m: f32,
+	r: f32,
+	g: f32,
+	b: f32,
+	a: f32,
+	// GPU utilization and power draw, RAM and GPU memory usage in 0 to 1
+	gpu_u: f32,
+	gpu_p: f32,
+	ram_u: f32,
+	gpu_mem: f32,
+	// metrics from `uniform_slots` in the config, slot i is slots[i / 4][i % 4]
+	slots: array<vec4f, 2>,
+	// bass, low mid, high mid and treble level of the playing audio if `audio_reactive` is set
+	audio: vec4f,
+	// cursor position in 0 to 1 of the widget or -1 outside, left button held, seconds since the last click or -1
+	mouse: vec4f,
+	// sun elevation at the configured location, subsolar latitude and longitude in radians
+	sun: vec4f,
+	// speed, intensity and hue shift in radians from the tweak panel, time already runs at the speed
+	params: vec4f,
+	// cloud cover, precipitation and fog in 0 to 1 if `weather_reactive` is set
+	weather: vec4f,
+	// accent colour of the wallpaper palette and the strength of its tint, 0 unless `wal_palette` is set
+	tint: vec4f,
+	// calm of the scene in 0 to 1 during a pomodoro break and the progress of the phase, 0 unless `pomodoro_shader` is set
+	focus: vec4f,
+}
+
+// ---------- VERTEX CREATION ----------
+struct VertexIn {@builtin(vertex_index) vertex_index: u32,}
+struct VertexOut {@builtin(position) position: vec4f,}
+@vertex
+fn vs_main(in: VertexIn) -> VertexOut {
+	let uv = vec2f(vec2u((in.vertex_index << 1) & 2, in.vertex_index & 2));
+	let position = vec4f(uv * 2. - 1., 0., 1.);
+	return VertexOut(position);
+}
+
+// -------------- UTILITIES -------------
+// fog and rain over a colour at pixel px of the widget, from the weather if `weather_reactive` is set
+fn weather(colour: vec3f, px: vec2f) -> vec3f {
+	// fog veils the scene in grey, thicker under a full cloud cover
+	let fog = u.weather.z * (0.3 + 0.3 * u.weather.x);
+	let c = mix(colour, vec3f(0.6), fog);
+	// rain falls in slanted streaks through more columns the heavier it rains
+	let column = floor((px.x + 0.2 * px.y) / 4.);
+	let h = fract(sin(column * 12.9898) * 43758.5453);
+	let y = fract(px.y / 120. - u.t * (1. + h) + h * 10.);
+	let streak = step(1. - 0.5 * u.weather.y, h) * smoothstep(0.8, 1., y);
+	return mix(c, vec3f(0.8, 0.85, 0.9), 0.4 * streak);
+}
+
+// apply the tweak panel to a colour drawn over the background: rotate its difference from the
+// background by the hue shift and scale it by the intensity
+fn tweak(colour: vec3f, bg: vec3f) -> vec3f {
+	let k = vec3f(0.57735);
+	let d = colour - bg;
+	let c = cos(u.params.z);
+	let rotated = d * c + cross(k, d) * sin(u.params.z) + k * dot(k, d) * (1. - c);
+	let shifted = tint(rotated);
+	// desaturate and dim the scene during pomodoro breaks
+	let calm = mix(shifted, vec3f(dot(shifted, vec3f(1. / 3.))), 0.6 * u.focus.x) * (1. - 0.3 * u.focus.x);
+	return clamp(bg + calm * u.params.y, vec3f(0.), vec3f(1.));
+}
+
+// shift the hue of a colour towards the accent colour of the wallpaper palette or the cover art,
+// keeping its brightness, by the strength of the tint
+fn tint(colour: vec3f) -> vec3f {
+	let tinted = u.tint.rgb / max(dot(u.tint.rgb, vec3f(1. / 3.)), 0.01) * dot(colour, vec3f(1. / 3.));
+	return mix(colour, tinted, 0.5 * u.tint.w);
+}
@@ -0,0 +1,37 @@
+// ------------ MAIN PROGRAM -----------
+// Flight through layers of stars, faster the busier the CPU is.
+const LAYERS: i32 = 6;
+
+fn hash(p: vec2f) -> f32 {
+	return fract(sin(dot(p, vec2f(127.1, 311.7))) * 43758.5453);
+}
+
+// single star per grid cell of the given layer
+fn stars(uv: vec2f, layer: f32) -> f32 {
+	let cell = floor(uv);
+	let offset = vec2f(hash(cell + layer), hash(cell + layer + 17.)) - 0.5;
+	let d = length(fract(uv) - 0.5 - 0.8 * offset);
+	let size = 0.02 + 0.05 * hash(cell + layer + 31.);
+	let twinkle = 0.6 + 0.4 * sin(u.t * 3. + 6.28 * hash(cell + layer + 5.));
+	return (1. - smoothstep(0., size, d)) * twinkle;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4f {
+	let p = vec2f(1., -1.) * (2. * (in.position.xy - u.top_left) / u.res - 1.);
+	let speed = 0.1 + 0.6 * u.cpu_u;
+
+	var light = 0.;
+	for (var i: i32 = 0; i < LAYERS; i = i + 1) {
+		// each layer moves from far away towards the viewer and wraps around
+		let depth = fract(f32(i) / f32(LAYERS) + u.t * speed * 0.1);
+		let scale = mix(20., 0.5, depth);
+		let fade = smoothstep(0., 0.5, depth) * (1. - smoothstep(0.9, 1., depth));
+		light += stars(p * scale + f32(i) * 7.3, f32(i)) * fade;
+	}
+
+	// the busiest core tints the stars from white to orange
+	let tint = mix(vec3f(0.85, 0.9, 1.), vec3f(1., 0.6, 0.2), u.cpu_m);
+	let bg = vec3f(u.r, u.g, u.b);
+	return vec4f(weather(tweak(bg + tint * light, bg), in.position.xy - u.top_left), u.a);
+}
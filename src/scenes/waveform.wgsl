@@ -0,0 +1,27 @@
+// ------------ MAIN PROGRAM -----------
+// Oscilloscope-like waves whose amplitude follows the CPU utilization.
+const WAVES: i32 = 4;
+const LINE_WIDTH: f32 = 2.;
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4f {
+	let px = in.position.xy - u.top_left;
+	let p = vec2f(px.x / u.res.x, 1. - 2. * px.y / u.res.y);
+
+	var light = vec3f(0.);
+	for (var i: i32 = 0; i < WAVES; i = i + 1) {
+		let k = f32(i);
+		let amplitude = (0.15 + 0.6 * u.cpu_u) / (1. + k * 0.5);
+		let frequency = 2. + k * 1.7 + 4. * u.cpu_m;
+		let y = amplitude * sin(6.2831 * frequency * p.x + u.t * (1. + k * 0.4))
+			* sin(3.1415 * p.x);
+		// distance in pixels between the pixel and the wave
+		let dist = abs(p.y - y) * 0.5 * u.res.y;
+		let glow = 1. - smoothstep(0., LINE_WIDTH + 6. * u.cpu_u, dist);
+		let colour = 0.5 + 0.5 * cos(vec3f(0., 2., 4.) + k + u.t * 0.2);
+		light += colour * glow * (1. - 0.2 * k);
+	}
+
+	let bg = vec3f(u.r, u.g, u.b);
+	return vec4f(weather(tweak(bg + light, bg), px), u.a);
+}
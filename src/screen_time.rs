@@ -0,0 +1,188 @@
+//! Time each application was focused today for [`crate::config::Section::ScreenTime`]. The
+//! focused window is followed through the cosmic-toplevel-info-unstable-v1 protocol on a
+//! Wayland connection of its own, on a background thread, and the time is added to its app id.
+//! The totals are kept per day in `~/.local/share/eos/screen-time/<date>.json`, so they carry
+//! over restarts of eos and start from zero at midnight.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDate};
+use cosmic::cctk::cosmic_protocols::toplevel_info::v1::client::zcosmic_toplevel_handle_v1;
+use cosmic::cctk::sctk::{
+    self,
+    output::{OutputHandler, OutputState},
+    reexports::{calloop, calloop_wayland_source::WaylandSource},
+    registry::{ProvidesRegistryState, RegistryState},
+};
+use cosmic::cctk::toplevel_info::{ToplevelInfoHandler, ToplevelInfoState};
+use cosmic::cctk::wayland_client::{globals::registry_queue_init, protocol::wl_output, Connection, QueueHandle};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use tokio::sync::mpsc;
+
+use crate::Message;
+
+/// Longest wait for an event, after which the time of the focused application is counted and
+/// sent anyway
+const TICK:Duration = Duration::from_secs(5);
+/// Time between two writes of the totals
+const SAVE_INTERVAL:Duration = Duration::from_secs(60);
+
+/// Readable name of an app id, `CosmicTerm` for `com.system76.CosmicTerm`
+pub fn name(app_id:&str)->&str{
+    app_id.rsplit('.').next().filter(|name| !name.is_empty()).unwrap_or(app_id)
+}
+
+fn path(day:NaiveDate)->Option<PathBuf>{
+    Some(dirs::data_dir()?.join("eos").join("screen-time").join(format!("{}.json", day.format("%Y-%m-%d"))))
+}
+
+/// Totals of a day in seconds by app id, empty for a day without a file
+fn load(day:NaiveDate)->HashMap<String, u64>{
+    path(day)
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save(day:NaiveDate, totals:&HashMap<String, Duration>)->io::Result<()>{
+    let path = path(day).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let seconds:HashMap<&String, u64> = totals.iter().map(|(app, time)| (app, time.as_secs())).collect();
+    // written to a temporary file first so a crash never leaves a truncated day
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_vec(&seconds).map_err(io::Error::other)?)?;
+    fs::rename(tmp, path)
+}
+
+/// Applications by the time they were focused, the longest first
+fn ranked(totals:&HashMap<String, Duration>)->Vec<(String, Duration)>{
+    let mut ranked:Vec<(String, Duration)> = totals.iter().map(|(app, time)| (app.clone(), *time)).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
+
+struct State {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    toplevel_info_state: ToplevelInfoState,
+    /// App id of the activated window, if any
+    focused: Option<String>,
+}
+
+impl State {
+    fn update_focus(&mut self){
+        self.focused = self.toplevel_info_state.toplevels()
+            .filter_map(|(_, info)| info)
+            .find(|info| info.state.contains(&zcosmic_toplevel_handle_v1::State::Activated))
+            .map(|info| info.app_id.clone())
+            .filter(|app_id| !app_id.is_empty());
+    }
+}
+
+impl ToplevelInfoHandler for State {
+    fn toplevel_info_state(&mut self)->&mut ToplevelInfoState{
+        &mut self.toplevel_info_state
+    }
+    fn new_toplevel(&mut self, _:&Connection, _:&QueueHandle<Self>, _:&zcosmic_toplevel_handle_v1::ZcosmicToplevelHandleV1){
+        self.update_focus();
+    }
+    fn update_toplevel(&mut self, _:&Connection, _:&QueueHandle<Self>, _:&zcosmic_toplevel_handle_v1::ZcosmicToplevelHandleV1){
+        self.update_focus();
+    }
+    fn toplevel_closed(&mut self, _:&Connection, _:&QueueHandle<Self>, _:&zcosmic_toplevel_handle_v1::ZcosmicToplevelHandleV1){
+        self.update_focus();
+    }
+}
+
+impl OutputHandler for State {
+    fn output_state(&mut self)->&mut OutputState{
+        &mut self.output_state
+    }
+    fn new_output(&mut self, _:&Connection, _:&QueueHandle<Self>, _:wl_output::WlOutput){}
+    fn update_output(&mut self, _:&Connection, _:&QueueHandle<Self>, _:wl_output::WlOutput){}
+    fn output_destroyed(&mut self, _:&Connection, _:&QueueHandle<Self>, _:wl_output::WlOutput){}
+}
+
+impl ProvidesRegistryState for State {
+    fn registry(&mut self)->&mut RegistryState{
+        &mut self.registry_state
+    }
+    sctk::registry_handlers!(OutputState);
+}
+
+cosmic::cctk::delegate_toplevel_info!(State);
+sctk::delegate_output!(State);
+sctk::delegate_registry!(State);
+
+/// Count the time of the focused application until the receiver is dropped
+fn run(tx:&mpsc::UnboundedSender<Vec<(String, Duration)>>)->Result<(), String>{
+    let connection = Connection::connect_to_env().map_err(|err| err.to_string())?;
+    let (globals, event_queue) = registry_queue_init::<State>(&connection).map_err(|err| err.to_string())?;
+    let qh = event_queue.handle();
+    let registry_state = RegistryState::new(&globals);
+    let mut state = State {
+        output_state: OutputState::new(&globals, &qh),
+        toplevel_info_state: ToplevelInfoState::new(&registry_state, &qh),
+        registry_state,
+        focused: None,
+    };
+    let mut event_loop = calloop::EventLoop::<State>::try_new().map_err(|err| err.to_string())?;
+    WaylandSource::new(connection, event_queue).insert(event_loop.handle()).map_err(|err| err.to_string())?;
+
+    let mut day = Local::now().date_naive();
+    let mut totals:HashMap<String, Duration> = load(day).into_iter()
+        .map(|(app, seconds)| (app, Duration::from_secs(seconds)))
+        .collect();
+    let mut counted = Instant::now();
+    let mut saved = Instant::now();
+    loop {
+        // the time until an event is counted for the application focused before it
+        let focused = state.focused.clone();
+        let dispatched = event_loop.dispatch(Some(TICK), &mut state).map_err(|err| err.to_string());
+        if let Some(app) = focused {
+            *totals.entry(app).or_default() += counted.elapsed();
+        }
+        counted = Instant::now();
+        let today = Local::now().date_naive();
+        if today != day || saved.elapsed() >= SAVE_INTERVAL || dispatched.is_err() || tx.is_closed() {
+            if let Err(err) = save(day, &totals) {
+                log::warn!("failed to save the screen time: {}", err);
+            }
+            saved = Instant::now();
+        }
+        dispatched?;
+        if today != day {
+            day = today;
+            totals.clear();
+        }
+        if tx.send(ranked(&totals)).is_err() {return Ok(());}
+    }
+}
+
+/// Emits the applications focused today with their time, the longest first, whenever the
+/// focus changes and every few seconds while one is focused, see [`Message::ScreenTime`]
+pub fn listen()->Subscription<Message>{
+    struct ScreenTimeSubscription;
+    Subscription::run_with_id(
+        TypeId::of::<ScreenTimeSubscription>(),
+        stream::channel(1, |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            std::thread::spawn(move || {
+                if let Err(err) = run(&tx) {
+                    log::warn!("failed to follow the focused window: {}", err);
+                }
+            });
+            while let Some(ranked) = rx.recv().await {
+                let _ = output.send(Message::ScreenTime(ranked)).await;
+            }
+        }),
+    )
+}
@@ -1,5 +1,11 @@
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
 
+use cosmic::app::{message, Task};
 use cosmic::iced::wgpu::{BlendState, PipelineCompilationOptions};
 use cosmic::iced::window::RedrawRequest;
 use cosmic::iced_wgpu::graphics::Viewport;
@@ -7,24 +13,315 @@ use cosmic::iced::advanced::Shell;
 use cosmic::iced::event::Status;
 use cosmic::iced::mouse;
 use cosmic::iced::mouse::Cursor;
-use crate::config::Config;
+use crate::config::{Config, Scene};
 use crate::iced::wgpu;
-use crate::{get_term_bg_colour, Message};
+use crate::{animation::Animation, audio::{self, AUDIO_BANDS}, capture::Capture, get_term_bg_colour, particles::Particles, render_scale::ScaledTarget, shadertoy, sun, wal::Palette, Message};
 use cosmic::iced::widget::shader::Event;
 use cosmic::iced::widget::shader;
-use cosmic::iced::Rectangle;
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Rectangle, Subscription};
+use image::RgbaImage;
+use notify::Watcher;
+use tokio::sync::mpsc;
 
-/// Milliseconds until next redraw of the fragment shader is requested
-pub const FRAME_TIME:u64 = 33;
+/// Redraw interval of the background while idle, see `Config::idle_throttling`
+pub const IDLE_FRAME_TIME:Duration = Duration::from_secs(1);
+
+/// WGSL source of a built-in scene, compiled into the binary
+fn scene_source(scene:Scene)->&'static str{
+    // the helpers every scene uses are kept in one place
+    macro_rules! scene {
+        ($path:literal) => { concat!(include_str!("scenes/prelude.wgsl"), include_str!($path)) };
+    }
+    match scene {
+        Scene::Earth => scene!("shader.wgsl"),
+        Scene::Plasma => scene!("scenes/plasma.wgsl"),
+        Scene::Starfield => scene!("scenes/starfield.wgsl"),
+        Scene::Waveform => scene!("scenes/waveform.wgsl"),
+    }
+}
+
+/// Source of a user-supplied shader, `None` to draw the configured scene instead
+type ShaderSource = Option<Arc<str>>;
+
+/// Whether two sources are the same load of a shader, compared by pointer
+fn same_source(a:&ShaderSource, b:&ShaderSource)->bool{
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+        _ => false,
+    }
+}
+
+/// Which shader a pipeline was built from
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum ShaderKey {
+    Scene(Scene),
+    Custom(Arc<str>),
+}
+
+impl ShaderKey {
+    fn new(scene:Scene, custom:&ShaderSource)->Self{
+        match custom {
+            Some(source) => ShaderKey::Custom(source.clone()),
+            None => ShaderKey::Scene(scene),
+        }
+    }
+
+    fn source(&self)->&str{
+        match self {
+            ShaderKey::Scene(scene) => scene_source(*scene),
+            ShaderKey::Custom(source) => source,
+        }
+    }
+}
+
+/// Duration of the crossfade when the scene, shader or background colour changes
+const TRANSITION_TIME:Duration = Duration::from_secs(1);
+
+/// Blends the outgoing shader of a transition over the incoming one,
+/// weighted by the blend constant which runs from 1 to 0 during the transition
+const FADE_BLENDING:BlendState = BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusConstant,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusConstant,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+/// What was drawn before a change, faded out over [`TRANSITION_TIME`]
+#[derive(Clone, Debug)]
+struct Transition {
+    scene: Scene,
+    source: ShaderSource,
+    bg: [f32;4],
+    start: Instant,
+}
+
+/// Parse and validate a WGSL shader before it is handed to wgpu, which panics on invalid shaders.
+/// Returns a human readable error message pointing at the offending source location.
+fn validate(source:&str)->Result<(), String>{
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|err| err.emit_to_string(source))?;
+    let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+        .validate(&module)
+        .map_err(|err| err.emit_to_string(source))?;
+    check_uniform_layout(&module)?;
+    for (name, stage) in [("vs_main", naga::ShaderStage::Vertex), ("fs_main", naga::ShaderStage::Fragment)] {
+        let Some(index) = module.entry_points.iter().position(|ep| ep.name == name && ep.stage == stage) else {
+            return Err(format!("missing entry point `{}`", name));
+        };
+        check_bindings(&module, info.get_entry_point(index), stage)?;
+    }
+    Ok(())
+}
+
+/// Check the resources an entry point uses against the bind group layout of
+/// [`FragmentShaderPipeline`], which wgpu would otherwise reject when the pipeline is created
+fn check_bindings(module:&naga::Module, info:&naga::valid::FunctionInfo, stage:naga::ShaderStage)->Result<(), String>{
+    for (handle, var) in module.global_variables.iter() {
+        let Some(binding) = &var.binding else {continue};
+        if info[handle].is_empty() {continue;}
+        let name = format!("`@group({}) @binding({})`", binding.group, binding.binding);
+        if stage != naga::ShaderStage::Fragment {
+            return Err(format!("{} is only bound for the fragment shader, not in `vs_main`", name));
+        }
+        let ty = &module.types[var.ty].inner;
+        let texture = (0..TEXTURES).any(|i| texture_binding(i) == binding.binding);
+        let expected = match (binding.group, binding.binding) {
+            (0, 0) => Some((var.space == naga::AddressSpace::Uniform, "a `var<uniform>`")),
+            (0, 2) => Some((matches!(ty, naga::TypeInner::Sampler { comparison: false }), "a filtering `sampler`")),
+            (0, _) if texture => Some((matches!(ty, naga::TypeInner::Image {
+                dim: naga::ImageDimension::D2,
+                arrayed: false,
+                class: naga::ImageClass::Sampled { kind: naga::ScalarKind::Float, multi: false },
+            }), "a `texture_2d<f32>`")),
+            _ => None,
+        };
+        match expected {
+            None => return Err(format!("{} is not provided by eos, see the bindings in the README", name)),
+            Some((false, expected)) => return Err(format!("{} must be {}", name, expected)),
+            Some((true, _)) => {},
+        }
+    }
+    Ok(())
+}
+
+/// Read and validate the shader at `path`, translating it first if it is a ShaderToy shader
+fn load(path:&str)->Result<Arc<str>, String>{
+    let mut source = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    if shadertoy::is_shadertoy(path) {
+        source = shadertoy::translate(&source)?;
+    }
+    validate(&source)?;
+    Ok(source.into())
+}
+
+/// Decode the image or video for texture `i` on a thread, which may take seconds, and pass it
+/// to [`FragmentShaderProgram::set_texture`] with [`Message::TextureDecoded`]
+fn decode_texture(i:usize, path:String)->Task<Message>{
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let decoded = path.clone();
+    std::thread::spawn(move || {
+        let _ = tx.send(Animation::open(&decoded).map(Arc::new));
+    });
+    Task::perform(rx, move |texture| {
+        let texture = texture.unwrap_or_else(|_| Err("the decoder stopped".to_string()));
+        message::app(Message::TextureDecoded(i, path.clone(), texture))
+    })
+}
+
+/// Frames drawn without the renderer preparing them, after which it is taken to be the
+/// software fallback without a GPU device
+const UNPREPARED_DRAWS:u32 = 3;
+
+/// Run `f` in a validation error scope, returning the error wgpu reports instead of panicking on it
+fn catch_gpu_error<T>(device:&wgpu::Device, f:impl FnOnce()->T)->Result<T, String>{
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let value = f();
+    match cosmic::iced::futures::executor::block_on(device.pop_error_scope()) {
+        Some(err) => Err(err.to_string()),
+        None => Ok(value),
+    }
+}
+
+/// Number of additional user textures, bound after the sampler like ShaderToy's `iChannel0` to `iChannel3`
+pub const TEXTURE_CHANNELS:usize = 4;
+/// The main texture followed by the texture channels
+const TEXTURES:usize = 1 + TEXTURE_CHANNELS;
+type Textures = [Arc<RgbaImage>; TEXTURES];
+
+/// Binding of texture `i` of [`Textures`]: the main texture at 1, the channels from 3 on
+fn texture_binding(i:usize)->u32{
+    if i == 0 {1} else {2 + i as u32}
+}
+
+/// Texture bound to the background shaders when no other one is configured
+fn embedded_texture()->Arc<RgbaImage>{
+    static TEXTURE:OnceLock<Arc<RgbaImage>> = OnceLock::new();
+    TEXTURE.get_or_init(|| {
+        let image_data = include_bytes!("../res/textures/earth_lights.jpg");
+        Arc::new(image::load_from_memory(image_data)
+            .expect("Failed to load texture")
+            .to_rgba8())
+    }).clone()
+}
+
+/// Single black pixel bound to texture channels that are not configured
+fn blank_texture()->Arc<RgbaImage>{
+    static TEXTURE:OnceLock<Arc<RgbaImage>> = OnceLock::new();
+    TEXTURE.get_or_init(|| Arc::new(RgbaImage::from_pixel(1, 1, image::Rgba([0, 0, 0, 255])))).clone()
+}
+
+/// Fallback for texture `i` of [`Textures`]
+fn default_texture(i:usize)->Arc<RgbaImage>{
+    if i == 0 {embedded_texture()} else {blank_texture()}
+}
+
+/// Configured paths of the main texture on `output` and the texture channels, empty for the defaults
+fn texture_paths(config:&Config, output:Option<&str>)->[String; TEXTURES]{
+    std::array::from_fn(|i| match i {
+        0 => config.background(output).texture_path,
+        i => config.texture_channels.get(i - 1).cloned().unwrap_or_default(),
+    })
+}
+
+/// Configured render scale as a fraction, at least a tenth of the full resolution
+fn render_scale(config:&Config)->f32{
+    config.render_scale_percent.clamp(10, 100) as f32 / 100.
+}
+
+/// Speed, intensity and hue shift in radians from the tweak panel
+fn tweaks(config:&Config)->[f32;3]{
+    [
+        config.shader_speed_percent as f32 / 100.,
+        config.shader_intensity_percent as f32 / 100.,
+        (config.shader_hue_degrees % 360) as f32 * std::f32::consts::PI / 180.,
+    ]
+}
+
+/// Decode an sRGB colour component to linear light, as the GPU does when sampling an sRGB texture
+fn srgb_to_linear(c:f32)->f32{
+    if c <= 0.04045 {c / 12.92} else {((c + 0.055) / 1.055).powf(2.4)}
+}
+
+/// Configured latitude and longitude in degrees
+fn location(config:&Config)->[f32;2]{
+    [config.latitude_mul_100 as f32 / 100., config.longitude_mul_100 as f32 / 100.]
+}
+
+/// Emits `message` whenever the file at `path` is written, e.g. to reload a shader, texture or the config file.
+/// The parent directory is watched, since many editors save by replacing the file.
+pub fn watch(path:&str, message:Message)->Subscription<Message>{
+    struct FileWatchSubscription;
+    let path = PathBuf::from(path);
+    Subscription::run_with_id(
+        (TypeId::of::<FileWatchSubscription>(), path.clone()),
+        stream::channel(4, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let watcher = notify::recommended_watcher(move |event:notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    if event.kind.is_create() || event.kind.is_modify() {
+                        let _ = tx.send(event.paths);
+                    }
+                }
+            });
+            let (Ok(mut watcher), Some(dir)) = (watcher, path.parent()) else {
+                log::warn!("failed to watch {}", path.display());
+                return;
+            };
+            if let Err(err) = watcher.watch(dir, notify::RecursiveMode::NonRecursive) {
+                log::warn!("failed to watch {}: {}", path.display(), err);
+                return;
+            }
+            while let Some(paths) = rx.recv().await {
+                if paths.iter().any(|p| p.file_name() == path.file_name()) {
+                    let _ = output.send(message.clone()).await;
+                }
+            }
+        }),
+    )
+}
+
+/// Number of generic uniforms driven by the metrics in `Config::uniform_slots`
+pub const UNIFORM_SLOTS:usize = 8;
+
+/// System metrics passed to the shader each visual update tick, all normalized to 0 to 1
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MetricUniforms {
+    pub cpu_util: f32,
+    pub cpu_max: f32,
+    pub cpu_freq: f32,
+    pub gpu_util: f32,
+    pub gpu_power: f32,
+    pub ram_used: f32,
+    pub gpu_mem: f32,
+    /// Metrics in `Config::uniform_slots`
+    pub slots: [f32;UNIFORM_SLOTS],
+}
 
 #[derive(Debug, Clone, Copy)]
 struct Uniforms {
     time: f32,
     delta_time: Instant,
-    cpu_util: f32,
-    cpu_max: f32,
-    cpu_freq: f32,
+    metrics: MetricUniforms,
     bg: [f32;4],
+    /// Sun elevation at the configured location, subsolar latitude and longitude, see [`sun::position`]
+    sun: [f32;3],
+    /// Speed, intensity and hue shift, see [`tweaks`]
+    tweaks: [f32;3],
+    /// Cloud cover, precipitation and fog in 0 to 1, see [`crate::weather`]
+    weather: [f32;3],
+    /// Accent colour of the pywal or wallust palette, see [`crate::wal`], and how much it tints the scene
+    tint: [f32;4],
+    /// How calm the scene is during a pomodoro break and the progress of the phase, see
+    /// [`crate::pomodoro::Pomodoro::shader_focus`]
+    focus: [f32;2],
 }
 
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -39,54 +336,245 @@ pub struct UniformsCRepr {
     g: f32,
     b: f32,
     a: f32,
+    gpu_util: f32,
+    gpu_power: f32,
+    ram_used: f32,
+    gpu_mem: f32,
+    /// Pads the slots to the 16 byte alignment of arrays in uniform buffers
+    _pad: f32,
+    slots: [[f32;4];UNIFORM_SLOTS/4],
+    audio: [f32;AUDIO_BANDS],
+    mouse: [f32;4],
+    sun: [f32;4],
+    params: [f32;4],
+    weather: [f32;4],
+    tint: [f32;4],
+    focus: [f32;4],
 }
 
 impl UniformsCRepr{
-    /// Get the size of the structure in bytes. Used to create a uniform buffer
-    fn size_in_bytes()-> usize {
-        std::mem::size_of::<UniformsCRepr>() + std::mem::align_of::<UniformsCRepr>()
-        // 48
+    /// Size of the uniform buffer. Structs in the uniform address space are padded to a multiple of 16 bytes.
+    const SIZE:usize = std::mem::size_of::<UniformsCRepr>().next_multiple_of(16);
+
+    /// Name of each member of the `Uniforms` struct in the shaders and the offset wgpu expects
+    /// it at. Every member of `vec4f` or array type must be aligned to 16 bytes.
+    const LAYOUT:[(&'static str, usize);21] = [
+        ("res", std::mem::offset_of!(UniformsCRepr, resolution)),
+        ("top_left", std::mem::offset_of!(UniformsCRepr, top_left)),
+        ("t", std::mem::offset_of!(UniformsCRepr, time)),
+        ("cpu_u", std::mem::offset_of!(UniformsCRepr, cpu_util)),
+        ("cpu_m", std::mem::offset_of!(UniformsCRepr, cpu_max)),
+        ("r", std::mem::offset_of!(UniformsCRepr, r)),
+        ("g", std::mem::offset_of!(UniformsCRepr, g)),
+        ("b", std::mem::offset_of!(UniformsCRepr, b)),
+        ("a", std::mem::offset_of!(UniformsCRepr, a)),
+        ("gpu_u", std::mem::offset_of!(UniformsCRepr, gpu_util)),
+        ("gpu_p", std::mem::offset_of!(UniformsCRepr, gpu_power)),
+        ("ram_u", std::mem::offset_of!(UniformsCRepr, ram_used)),
+        ("gpu_mem", std::mem::offset_of!(UniformsCRepr, gpu_mem)),
+        ("slots", std::mem::offset_of!(UniformsCRepr, slots)),
+        ("audio", std::mem::offset_of!(UniformsCRepr, audio)),
+        ("mouse", std::mem::offset_of!(UniformsCRepr, mouse)),
+        ("sun", std::mem::offset_of!(UniformsCRepr, sun)),
+        ("params", std::mem::offset_of!(UniformsCRepr, params)),
+        ("weather", std::mem::offset_of!(UniformsCRepr, weather)),
+        ("tint", std::mem::offset_of!(UniformsCRepr, tint)),
+        ("focus", std::mem::offset_of!(UniformsCRepr, focus)),
+    ];
+}
+
+// vectors of two floats are aligned to 8 bytes, vectors of four and arrays to 16
+const _: () = {
+    assert!(std::mem::offset_of!(UniformsCRepr, resolution) % 8 == 0);
+    assert!(std::mem::offset_of!(UniformsCRepr, top_left) % 8 == 0);
+    assert!(std::mem::offset_of!(UniformsCRepr, slots) % 16 == 0);
+    assert!(std::mem::offset_of!(UniformsCRepr, audio) % 16 == 0);
+    assert!(std::mem::offset_of!(UniformsCRepr, mouse) % 16 == 0);
+    assert!(std::mem::offset_of!(UniformsCRepr, sun) % 16 == 0);
+    assert!(std::mem::offset_of!(UniformsCRepr, params) % 16 == 0);
+    assert!(std::mem::offset_of!(UniformsCRepr, weather) % 16 == 0);
+    assert!(std::mem::offset_of!(UniformsCRepr, tint) % 16 == 0);
+    assert!(std::mem::offset_of!(UniformsCRepr, focus) % 16 == 0);
+};
+
+/// Check that the `Uniforms` struct a shader binds at `@group(0) @binding(0)` matches [`UniformsCRepr`].
+/// Shaders may declare only the leading members they use. Members are matched by name,
+/// or by position if they were renamed.
+fn check_uniform_layout(module:&naga::Module)->Result<(), String>{
+    let binding = naga::ResourceBinding { group: 0, binding: 0 };
+    let Some(var) = module.global_variables.iter()
+        .map(|(_, var)| var)
+        .find(|var| var.binding.as_ref() == Some(&binding)) else {return Ok(());};
+    let naga::TypeInner::Struct { members, span } = &module.types[var.ty].inner else {
+        return Err("`@group(0) @binding(0)` must be the `Uniforms` struct".to_string());
+    };
+    if *span as usize > UniformsCRepr::SIZE {
+        return Err(format!("`Uniforms` is {} bytes, but eos only provides {}", span, UniformsCRepr::SIZE));
+    }
+    for (i, member) in members.iter().enumerate() {
+        let (name, offset) = UniformsCRepr::LAYOUT.iter()
+            .find(|(name, _)| member.name.as_deref() == Some(*name))
+            .or(UniformsCRepr::LAYOUT.get(i))
+            .copied()
+            .unwrap_or_default();
+        if member.offset as usize != offset {
+            return Err(format!(
+                "`Uniforms` member `{}` is at offset {}, but `{}` is expected at offset {}",
+                member.name.as_deref().unwrap_or("?"), member.offset, name, offset,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Scale `image` down if it exceeds the largest texture the device supports, which would panic
+fn fit_texture<'a>(device: &wgpu::Device, image: &'a RgbaImage) -> std::borrow::Cow<'a, RgbaImage> {
+    let max = device.limits().max_texture_dimension_2d;
+    let (width, height) = image.dimensions();
+    if width <= max && height <= max {
+        return std::borrow::Cow::Borrowed(image);
     }
+    let scale = max as f64 / width.max(height) as f64;
+    let size = |side: u32| ((side as f64 * scale) as u32).clamp(1, max);
+    log::warn!("texture of {}x{} exceeds the GPU limit of {}, scaling it down", width, height, max);
+    std::borrow::Cow::Owned(image::imageops::resize(image, size(width), size(height), image::imageops::FilterType::Triangle))
+}
+
+/// Create a texture holding `image`
+fn upload_texture(device: &wgpu::Device, queue: &wgpu::Queue, image: &RgbaImage) -> wgpu::Texture {
+    let dimensions = image.dimensions();
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("texture"),
+        size: wgpu::Extent3d {
+            width: dimensions.0,
+            height: dimensions.1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    write_texture(queue, &texture, image);
+    texture
+}
+
+/// Replace the contents of `texture` with `image` of the same size, e.g. the next frame of an animation
+fn write_texture(queue: &wgpu::Queue, texture: &wgpu::Texture, image: &RgbaImage) {
+    let dimensions = image.dimensions();
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        image.as_raw(),
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * dimensions.0),
+            rows_per_image: Some(dimensions.1),
+        },
+        wgpu::Extent3d {
+            width: dimensions.0,
+            height: dimensions.1,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+/// Bind the uniforms in `buffer`, the textures and their sampler
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    buffer: &wgpu::Buffer,
+    texture_views: &[wgpu::TextureView],
+    sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+    let mut entries = vec![
+        wgpu::BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        },
+        wgpu::BindGroupEntry {
+            binding: 2,
+            resource: wgpu::BindingResource::Sampler(sampler),
+        },
+    ];
+    entries.extend(texture_views.iter().enumerate().map(|(i, view)| wgpu::BindGroupEntry {
+        binding: texture_binding(i),
+        resource: wgpu::BindingResource::TextureView(view),
+    }));
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &entries,
+        label: Some("uniform_bind_group"),
+    })
 }
 
+/// Textures shared by all background shaders, with one render pipeline per shader
+/// that has been shown. Pipelines are built once for drawing and once for fading out.
+/// The outgoing shader of a transition has its own uniforms, since the background
+/// colour may be what changed.
 struct FragmentShaderPipeline {
-    pipeline: wgpu::RenderPipeline,
+    format: wgpu::TextureFormat,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    /// Images currently uploaded to the textures, compared by pointer
+    textures: Textures,
+    gpu_textures: Vec<wgpu::Texture>,
+    texture_views: Vec<wgpu::TextureView>,
+    pipelines: HashMap<(ShaderKey, bool), wgpu::RenderPipeline>,
+    /// Shaders whose pipeline failed to build or render, which are not tried again
+    failed: HashSet<(ShaderKey, bool)>,
+    /// Last shader drawn without errors, shown instead of a current one that fails
+    last_good: Option<ShaderKey>,
+    /// Shader drawn in this frame, the current one or the last good one
+    shown: Option<ShaderKey>,
+    /// Error wgpu reported outside of an error scope, e.g. for the commands [`Self::render`]
+    /// records, which are only validated once the renderer submits them
+    render_error: Arc<Mutex<Option<String>>>,
     uniform_buffer: wgpu::Buffer,
     uniform_bind_group: wgpu::BindGroup,
+    fade_buffer: wgpu::Buffer,
+    fade_bind_group: wgpu::BindGroup,
+    /// Particle system drawn over the scene, created when it is first enabled
+    particles: Option<Particles>,
+    /// Offscreen target the scene is drawn to when rendering at a reduced resolution or capturing it
+    scaled: Option<ScaledTarget>,
+    /// Capture of the background in progress, see [`FragmentShaderProgram::capture`]
+    capture: Mutex<Option<Capture>>,
 }
 
 impl FragmentShaderPipeline {
-    fn new(device: &wgpu::Device, format: wgpu::TextureFormat, queue: &wgpu::Queue) -> Self {
-        // create shader
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("FragmentShaderPipeline shader"),
-            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(include_str!(
-                "shader.wgsl"
-            ))),
-        });
-
-        // load texture
-        let image_data = include_bytes!("../res/textures/earth_lights.jpg");
-        let image = image::load_from_memory(image_data)
-            .expect("Failed to load texture")
-            .to_rgba8();
-        let dimensions = image.dimensions();
-        let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("texture"),
-            size: wgpu::Extent3d {
-                width: dimensions.0,
-                height: dimensions.1,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[],
+    /// A lost device is reported to `gpu_error`, which turns the background off for good
+    fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        queue: &wgpu::Queue,
+        textures: &Textures,
+        gpu_error: Arc<Mutex<Option<String>>>,
+    ) -> Self {
+        device.set_device_lost_callback(move |reason, message| {
+            log::error!("lost the GPU device, turning the background shader off: {:?} {}", reason, message);
+            *gpu_error.lock().unwrap() = Some(format!("lost the GPU device: {}", message));
         });
-
-        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // errors would panic by default, instead the shader they came from is dropped
+        let render_error = Arc::new(Mutex::new(None));
+        let uncaptured = render_error.clone();
+        device.on_uncaptured_error(Box::new(move |err| {
+            log::error!("GPU error while rendering: {}", err);
+            *uncaptured.lock().unwrap() = Some(err.to_string());
+        }));
+        let gpu_textures: Vec<_> = textures.iter()
+            .map(|texture| upload_texture(device, queue, &fit_texture(device, texture)))
+            .collect();
+        let texture_views: Vec<_> = gpu_textures.iter()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()))
+            .collect();
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("sampler"),
             address_mode_u: wgpu::AddressMode::ClampToEdge,
@@ -98,88 +586,57 @@ impl FragmentShaderPipeline {
             ..Default::default()
         });
 
-         // upload texture data
-         queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
+        // uniforms
+        let mut layout_entries = vec![
+            // uniforms entry
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
             },
-            image.as_raw(),
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * dimensions.0),
-                rows_per_image: Some(dimensions.1),
+            // sampler entry
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
             },
-            wgpu::Extent3d {
-                width: dimensions.0,
-                height: dimensions.1,
-                depth_or_array_layers: 1,
+        ];
+        // texture entries
+        layout_entries.extend((0..TEXTURES).map(|i| wgpu::BindGroupLayoutEntry {
+            binding: texture_binding(i),
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
             },
-        );
-
-        // uniforms
+            count: None,
+        }));
         let uniform_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                // uniforms entry
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                // texture entry
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
-                // sampler entry
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
+            entries: &layout_entries,
             label: Some("uniform_bind_group_layout"),
         });
 
-        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("uniform_buffer"),
-            size: UniformsCRepr::size_in_bytes() as wgpu::BufferAddress,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-        
-        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &uniform_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: uniform_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(&texture_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-            ],
-            label: Some("uniform_bind_group"),
-        });
+        let create_uniforms = |label| {
+            let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: UniformsCRepr::SIZE as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let uniform_bind_group = create_bind_group(
+                device, &uniform_bind_group_layout, &uniform_buffer, &texture_views, &sampler
+            );
+            (uniform_buffer, uniform_bind_group)
+        };
+        let (uniform_buffer, uniform_bind_group) = create_uniforms("uniform_buffer");
+        let (fade_buffer, fade_bind_group) = create_uniforms("fade_uniform_buffer");
 
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Pipeline Layout"),
@@ -187,9 +644,65 @@ impl FragmentShaderPipeline {
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        Self {
+            format,
+            bind_group_layout: uniform_bind_group_layout,
+            pipeline_layout,
+            sampler,
+            textures: textures.clone(),
+            gpu_textures,
+            texture_views,
+            pipelines: HashMap::new(),
+            failed: HashSet::new(),
+            last_good: None,
+            shown: None,
+            render_error,
+            uniform_buffer,
+            uniform_bind_group, 
+            fade_buffer,
+            fade_bind_group,
+            particles: None,
+            scaled: None,
+            capture: Mutex::new(None),
+        }
+    }
+
+    /// Upload the textures that differ from the current ones. Images of the same size,
+    /// like the frames of an animation, are written into the existing texture, others are rebound.
+    fn set_textures(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, textures: &Textures) {
+        let mut changed = false;
+        for (i, texture) in textures.iter().enumerate() {
+            if Arc::ptr_eq(&self.textures[i], texture) {continue;}
+            let fitted = fit_texture(device, texture);
+            let gpu_texture = &self.gpu_textures[i];
+            if (gpu_texture.width(), gpu_texture.height()) == fitted.dimensions() {
+                write_texture(queue, gpu_texture, &fitted);
+            } else {
+                self.gpu_textures[i] = upload_texture(device, queue, &fitted);
+                self.texture_views[i] = self.gpu_textures[i].create_view(&wgpu::TextureViewDescriptor::default());
+                changed = true;
+            }
+            self.textures[i] = texture.clone();
+        }
+        if !changed {return;}
+        self.uniform_bind_group = create_bind_group(
+            device, &self.bind_group_layout, &self.uniform_buffer, &self.texture_views, &self.sampler
+        );
+        self.fade_bind_group = create_bind_group(
+            device, &self.bind_group_layout, &self.fade_buffer, &self.texture_views, &self.sampler
+        );
+    }
+
+    /// Compile a background shader into a render pipeline, blended for fading out if `fading`
+    fn create_pipeline(&self, device: &wgpu::Device, source: &str, fading: bool) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("FragmentShaderPipeline shader"),
+            source: wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(source)),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("FragmentShaderPipeline"),
-            layout: Some(&pipeline_layout),
+            layout: Some(&self.pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: "vs_main",
@@ -203,41 +716,101 @@ impl FragmentShaderPipeline {
                 module: &shader,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(BlendState::ALPHA_BLENDING),
+                    format: self.format,
+                    blend: Some(if fading {FADE_BLENDING} else {BlendState::ALPHA_BLENDING}),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
                 compilation_options: PipelineCompilationOptions::default(),
             }),
             multiview: None,
             cache: None,
-        });
+        })
+    }
 
+    /// Make sure the pipeline drawing `key`, or fading it out, is built, returning whether it can be used
+    fn prepare_pipeline(&mut self, device: &wgpu::Device, key: &ShaderKey, fading: bool) -> bool {
+        let entry = (key.clone(), fading);
+        if self.failed.contains(&entry) {return false;}
+        if !self.pipelines.contains_key(&entry) {
+            match catch_gpu_error(device, || self.create_pipeline(device, key.source(), fading)) {
+                Ok(pipeline) => {self.pipelines.insert(entry, pipeline);},
+                Err(err) => {
+                    log::warn!("failed to build the background shader, keeping the last one: {}", err);
+                    self.failed.insert(entry);
+                    return false;
+                },
+            }
+        }
+        true
+    }
 
-        Self {
-            pipeline,
-            uniform_buffer,
-            uniform_bind_group, 
+    /// Pick the shader to draw this frame, the last good one if `current` fails, or an error
+    /// if there is none. A render error of the previous frame drops the shader drawn then.
+    fn prepare_shown(&mut self, device: &wgpu::Device, current: &ShaderKey) -> Result<(), String> {
+        if let Some(err) = self.render_error.lock().unwrap().take() {
+            if let Some(shown) = self.shown.take() {
+                log::warn!("dropping the background shader that failed to render: {}", err);
+                self.pipelines.remove(&(shown.clone(), false));
+                self.failed.insert((shown.clone(), false));
+                if self.last_good.as_ref() == Some(&shown) {
+                    self.last_good = None;
+                }
+            }
+        }
+        if self.prepare_pipeline(device, current, false) {
+            self.last_good = Some(current.clone());
         }
+        self.shown = self.last_good.clone();
+        self.shown.as_ref().map(|_| ()).ok_or_else(|| "no background shader could be built".to_string())
+    }
+
+    /// Drop pipelines of user-supplied shaders that are no longer shown, e.g. after a reload
+    fn prune(&mut self, current: &ShaderKey, outgoing: Option<&ShaderKey>) {
+        let last_good = self.last_good.clone();
+        let keep = |key: &ShaderKey| {
+            matches!(key, ShaderKey::Scene(_)) || key == current || Some(key) == outgoing || Some(key) == last_good.as_ref()
+        };
+        self.pipelines.retain(|(key, _), _| keep(key));
+        self.failed.retain(|(key, _)| keep(key));
     }
 
-    fn update(&mut self, queue: &wgpu::Queue, uniforms: &UniformsCRepr) {
+    fn update(&mut self, queue: &wgpu::Queue, uniforms: &UniformsCRepr, fade_uniforms: Option<&UniformsCRepr>) {
         queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(uniforms));
+        if let Some(fade_uniforms) = fade_uniforms {
+            queue.write_buffer(&self.fade_buffer, 0, bytemuck::bytes_of(fade_uniforms));
+        }
     }
 
+    /// Draw the shader picked by [`Self::prepare_shown`], and `outgoing` on top of it with the
+    /// given weight during a transition
     fn render(
         &self,
+        outgoing: Option<(&ShaderKey, f32)>,
         target: &wgpu::TextureView,
         encoder: &mut wgpu::CommandEncoder,
         viewport: Rectangle<u32>,
     ) {
+        if viewport.width == 0 || viewport.height == 0 {return;}
+        let Some(shown) = &self.shown else { return };
+        let Some(pipeline) = self.pipelines.get(&(shown.clone(), false)) else { return };
+        if let Some(particles) = &self.particles {
+            particles.compute(encoder);
+        }
+        // at a reduced render scale, draw into the cleared offscreen target and upscale it afterwards
+        let (view, scene_viewport, load) = match &self.scaled {
+            Some(scaled) => {
+                let (width, height) = scaled.size();
+                (scaled.view(), Rectangle { x: 0, y: 0, width, height }, wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT))
+            },
+            None => (target, viewport, wgpu::LoadOp::Load),
+        };
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("fill color test"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: target,
+                view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Load,
+                    load,
                     store: wgpu::StoreOp::Store,
                 },
             })],
@@ -246,18 +819,40 @@ impl FragmentShaderPipeline {
             occlusion_query_set: None,
         });
 
-        pass.set_pipeline(&self.pipeline);
+        pass.set_pipeline(pipeline);
         pass.set_viewport(
-            viewport.x as f32,
-            viewport.y as f32,
-            viewport.width as f32,
-            viewport.height as f32,
+            scene_viewport.x as f32,
+            scene_viewport.y as f32,
+            scene_viewport.width as f32,
+            scene_viewport.height as f32,
             0.0,
             1.0,
         );
         pass.set_bind_group(0, &self.uniform_bind_group, &[]);
 
         pass.draw(0..3, 0..1);
+
+        if let Some((key, weight)) = outgoing {
+            if let Some(pipeline) = self.pipelines.get(&(key.clone(), true)) {
+                let w = weight as f64;
+                pass.set_pipeline(pipeline);
+                pass.set_blend_constant(wgpu::Color { r: w, g: w, b: w, a: w });
+                pass.set_bind_group(0, &self.fade_bind_group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+        }
+
+        if let Some(particles) = &self.particles {
+            particles.draw(&mut pass);
+        }
+        drop(pass);
+
+        if let Some(scaled) = &self.scaled {
+            scaled.blit(encoder, target, viewport);
+            if let Some(capture) = self.capture.lock().unwrap().as_mut().filter(|c| !c.copied()) {
+                capture.copy(encoder, scaled.texture());
+            }
+        }
     }
 }
 
@@ -266,41 +861,146 @@ impl FragmentShaderPipeline {
 #[derive(Debug)]
 pub struct FragmentShaderPrimitive {
     uniforms: Uniforms,
+    /// Cursor position in 0 to 1 of the widget, or -1 outside of it, whether the left button
+    /// is held and seconds since the last click, or -1 before the first one
+    mouse: [f32;4],
+    scene: Scene,
+    source: ShaderSource,
+    textures: Textures,
+    /// Outgoing side of a running transition and its remaining weight
+    fade_out: Option<(Transition, f32)>,
+    particles: bool,
+    render_scale: f32,
+    /// Where to save this frame, see [`FragmentShaderProgram::capture`]
+    capture: Option<PathBuf>,
+    /// Shared with the program, set when the GPU device is lost or no shader can be built
+    gpu_error: Arc<Mutex<Option<String>>>,
+    /// Shared with the program, reset whenever the renderer prepares a frame
+    unprepared_draws: Arc<AtomicU32>,
 }
 
 impl FragmentShaderPrimitive {
-    fn new(uniforms: Uniforms) -> Self {
-        Self { uniforms }
-    }
-}
-
-impl shader::Primitive for FragmentShaderPrimitive {
-    fn prepare(
+    /// Set up the pipelines and upload the uniforms of this frame, failing only if there is no
+    /// shader to draw at all
+    fn prepare_gpu(
         &self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         format: wgpu::TextureFormat,
         storage: &mut shader::Storage,
         bounds: &cosmic::iced::Rectangle,
-        _viewport: &Viewport,
-    ) {
+        viewport: &Viewport,
+    ) -> Result<(), String> {
         if !storage.has::<FragmentShaderPipeline>() {
-            storage.store(FragmentShaderPipeline::new(device, format, queue));
+            storage.store(FragmentShaderPipeline::new(device, format, queue, &self.textures, self.gpu_error.clone()));
         }
 
         let pipeline = storage.get_mut::<FragmentShaderPipeline>().unwrap();
-        let [r,g,b,a] = self.uniforms.bg;
-        pipeline.update(
-            queue,
-            &UniformsCRepr {
-                resolution: [bounds.width, bounds.height],
-                top_left: [bounds.x, bounds.y],
+        // the frame holding the copy of a capture has been submitted by now
+        let copied = pipeline.capture.get_mut().unwrap().take_if(|c| c.copied());
+        if let Some(capture) = copied {
+            capture.read_back();
+        }
+        pipeline.set_textures(device, queue, &self.textures);
+        let current = ShaderKey::new(self.scene, &self.source);
+        let outgoing = self.fade_out.as_ref().map(|(t, _)| ShaderKey::new(t.scene, &t.source));
+        pipeline.prepare_shown(device, &current)?;
+        if let Some(outgoing) = &outgoing {
+            pipeline.prepare_pipeline(device, outgoing, true);
+        }
+        pipeline.prune(&current, outgoing.as_ref());
+
+        // the offscreen target of a reduced render scale or a capture starts at the origin,
+        // and a captured frame is drawn at the full resolution
+        let (resolution, top_left) = if self.render_scale < 1. || self.capture.is_some() {
+            let scale = if self.capture.is_some() {1.} else {self.render_scale};
+            let size = (
+                ((bounds.width * viewport.scale_factor() as f32 * scale).ceil() as u32).max(1),
+                ((bounds.height * viewport.scale_factor() as f32 * scale).ceil() as u32).max(1),
+            );
+            pipeline.scaled.get_or_insert_with(|| ScaledTarget::new(device, format, size)).resize(device, size);
+            if let Some(path) = &self.capture {
+                match Capture::new(device, path.clone(), size, format) {
+                    Ok(capture) => *pipeline.capture.lock().unwrap() = Some(capture),
+                    Err(err) => log::warn!("failed to capture the background: {}", err),
+                }
+            }
+            ([size.0 as f32, size.1 as f32], [0., 0.])
+        } else {
+            pipeline.scaled = None;
+            ([bounds.width, bounds.height], [bounds.x, bounds.y])
+        };
+        // the shaders work in linear light and sRGB targets encode what they write, so the
+        // background has to be decoded to come out as the colour the terminal draws
+        let linear = |rgb:[f32;3]| if format.is_srgb() {rgb.map(srgb_to_linear)} else {rgb};
+        let [tint_r, tint_g, tint_b, tint_a] = self.uniforms.tint;
+        let [tint_r, tint_g, tint_b] = linear([tint_r, tint_g, tint_b]);
+        let uniforms = |[r,g,b,a]:[f32;4]| {
+            let [r,g,b] = linear([r,g,b]);
+            UniformsCRepr {
+                resolution,
+                top_left,
                 time: self.uniforms.time,
                 r,g,b,a,
-                cpu_util: self.uniforms.cpu_util,
-                cpu_max: self.uniforms.cpu_max,
-            },
+                cpu_util: self.uniforms.metrics.cpu_util,
+                cpu_max: self.uniforms.metrics.cpu_max,
+                gpu_util: self.uniforms.metrics.gpu_util,
+                gpu_power: self.uniforms.metrics.gpu_power,
+                ram_used: self.uniforms.metrics.ram_used,
+                gpu_mem: self.uniforms.metrics.gpu_mem,
+                _pad: 0.,
+                slots: bytemuck::cast(self.uniforms.metrics.slots),
+                audio: audio::levels(),
+                mouse: self.mouse,
+                sun: [self.uniforms.sun[0], self.uniforms.sun[1], self.uniforms.sun[2], 0.],
+                params: [self.uniforms.tweaks[0], self.uniforms.tweaks[1], self.uniforms.tweaks[2], 0.],
+                weather: [self.uniforms.weather[0], self.uniforms.weather[1], self.uniforms.weather[2], 0.],
+                tint: [tint_r, tint_g, tint_b, tint_a],
+                focus: [self.uniforms.focus[0], self.uniforms.focus[1], 0., 0.],
+            }
+        };
+        pipeline.update(
+            queue,
+            &uniforms(self.uniforms.bg),
+            self.fade_out.as_ref().map(|(t, _)| uniforms(t.bg)).as_ref(),
         );
+
+        if self.particles {
+            pipeline.particles.get_or_insert_with(|| Particles::new(device, format)).update(
+                queue,
+                self.uniforms.metrics.cpu_util,
+                self.uniforms.metrics.gpu_util,
+                bounds.width / bounds.height.max(1.),
+            );
+        } else {
+            pipeline.particles = None;
+        }
+        Ok(())
+    }
+}
+
+impl shader::Primitive for FragmentShaderPrimitive {
+    fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        storage: &mut shader::Storage,
+        bounds: &cosmic::iced::Rectangle,
+        viewport: &Viewport,
+    ) {
+        self.unprepared_draws.store(0, Ordering::Relaxed);
+        // once the GPU failed, the widget is replaced by a plain background
+        if self.gpu_error.lock().unwrap().is_some() {return;}
+        match catch_gpu_error(device, || self.prepare_gpu(device, queue, format, storage, bounds, viewport)) {
+            Ok(Ok(())) => {},
+            Ok(Err(err)) => {
+                log::error!("failed to render the background shader: {}", err);
+                *self.gpu_error.lock().unwrap() = Some(err);
+            },
+            // e.g. a texture upload, the frame is drawn with what is there already
+            Err(err) => log::warn!("error preparing a frame of the background shader: {}", err),
+        }
     }
 
     fn render(
@@ -310,72 +1010,405 @@ impl shader::Primitive for FragmentShaderPrimitive {
         target: &wgpu::TextureView,
         clip_bounds: &Rectangle<u32>,
     ) {
-        let pipeline = storage.get::<FragmentShaderPipeline>().unwrap();
-        pipeline.render(target, encoder, *clip_bounds);
+        if self.gpu_error.lock().unwrap().is_some() {return;}
+        let Some(pipeline) = storage.get::<FragmentShaderPipeline>() else {return};
+        let outgoing = self.fade_out.as_ref().map(|(t, weight)| (ShaderKey::new(t.scene, &t.source), *weight));
+        pipeline.render(
+            outgoing.as_ref().map(|(key, weight)| (key, *weight)),
+            target,
+            encoder,
+            *clip_bounds,
+        );
     }
 }
 
 
 
+/// Full-screen background shader.
+///
+/// By default the configured built-in [`Scene`] is drawn, or the one configured for the output
+/// the window is on in `Config::output_backgrounds`. `Config::shader_path` may point at
+/// a user-supplied WGSL file instead, which has to provide the same interface:
+///
+/// - `@vertex fn vs_main(@builtin(vertex_index) vertex_index: u32)`, called for the three
+///   vertices of a triangle covering the widget
+/// - `@fragment fn fs_main(...) -> @location(0) vec4f`, alpha-blended over the window
+/// - `@group(0) @binding(0) var<uniform>` of the `Uniforms` struct in `shader.wgsl`:
+///   resolution and top left corner of the widget in pixels, time in seconds, CPU
+///   utilization and busiest core utilization, the background colour, GPU utilization and
+///   power draw, RAM and GPU memory usage, the metrics in `Config::uniform_slots` and the
+///   audio band levels if `Config::audio_reactive` is set, followed by the pointer position,
+///   button state and seconds since the last click, and the sun elevation at the configured
+///   location and the subsolar latitude and longitude in radians, and the speed, intensity
+///   and hue shift from the tweak panel, and the cloud cover, precipitation and fog if
+///   `Config::weather_reactive` is set, the accent colour of the wallpaper palette with
+///   the strength of its tint, zero unless `Config::wal_palette` is set, and how calm the
+///   scene is during a pomodoro break with the progress of the phase, zero unless
+///   `Config::pomodoro_shader` is set
+/// - `@group(0) @binding(1)` a filterable `texture_2d<f32>` with the earth texture,
+///   or the image at `Config::texture_path`, which may be animated or a video
+/// - `@group(0) @binding(3)` to `@group(0) @binding(6)` filterable `texture_2d<f32>`s with the
+///   images in `Config::texture_channels`, a black pixel for channels that are not configured
+/// - `@group(0) @binding(2)` a filtering `sampler` for it
 #[derive(Debug)]
 pub struct FragmentShaderProgram {
-    uniforms: Uniforms
+    uniforms: Uniforms,
+    /// Name of the output the window is shown on, which may have its own background
+    output: Option<String>,
+    scene: Scene,
+    /// Path the current shader source was loaded from, empty to draw the scene
+    shader_path: String,
+    source: ShaderSource,
+    /// Why the configured shader could not be loaded, shown on top of the previous shader
+    error: Option<String>,
+    transition: Option<Transition>,
+    /// Paths the main texture and the texture channels were loaded from, empty for the defaults
+    texture_paths: [String; TEXTURES],
+    textures: Textures,
+    /// Animations playing in place of the textures, whose frames are uploaded as they are drawn
+    animations: [Option<Arc<Animation>>; TEXTURES],
+    /// Latitude and longitude in degrees the sun elevation is computed for
+    location: [f32;2],
+    /// Draw the particle system over the scene
+    particles: bool,
+    /// Fraction of the widget resolution the scene is rendered at
+    render_scale: f32,
+    /// Interval between redraws, longer while idle
+    frame_time: Duration,
+    /// Time of the last frame the compositor asked the window for, which it stops doing while
+    /// the window is minimized, occluded or on another workspace
+    last_frame: Mutex<Instant>,
+    /// Why the background cannot be rendered on this system, if it cannot
+    gpu_error: Arc<Mutex<Option<String>>>,
+    /// Frames drawn since the renderer last prepared one, which it never does without a GPU
+    unprepared_draws: Arc<AtomicU32>,
+    /// Turned off with `--no-shader`, in which case a plain background is shown like without a GPU
+    disabled: bool,
+    /// Where to save the next frame
+    capture: Mutex<Option<PathBuf>>,
 }
 
 impl FragmentShaderProgram{
-    pub fn new(config:&Config)->Self{
-        Self { 
+    /// A program that is `disabled` shows a plain background and loads no shader or texture files
+    pub fn new(config:&Config, output:Option<String>, disabled:bool)->Self{
+        let background = config.background(output.as_deref());
+        let mut program = Self { 
             uniforms: Uniforms{ 
                 time: 0., 
                 delta_time: Instant::now(),
                 bg: get_term_bg_colour(config),
-                cpu_util: 0.,
-                cpu_freq: 0.,
-                cpu_max: 0.,
-            } 
+                metrics: MetricUniforms::default(),
+                sun: [0.;3],
+                tweaks: tweaks(config),
+                weather: [0.;3],
+                tint: [0.;4],
+                focus: [0.;2],
+            },
+            scene: background.scene,
+            shader_path: background.shader_path,
+            source: None,
+            error: None,
+            transition: None,
+            // loaded by the first `update_texture`, which decodes videos in the background
+            texture_paths: Default::default(),
+            textures: std::array::from_fn(default_texture),
+            animations: Default::default(),
+            location: location(config),
+            particles: config.shader_particles,
+            render_scale: render_scale(config),
+            frame_time: Duration::from_millis(config.frame_time_ms.into()),
+            last_frame: Mutex::new(Instant::now()),
+            gpu_error: Arc::new(Mutex::new(None)),
+            unprepared_draws: Arc::new(AtomicU32::new(0)),
+            disabled,
+            capture: Mutex::new(None),
+            output,
+        };
+        program.load_shader();
+        program
+    }
+
+    /// To be called when the config changes, switches the scene and
+    /// loads the configured shader file if its path changed
+    pub fn update_shader(&mut self, config:&Config){
+        self.particles = config.shader_particles;
+        self.render_scale = render_scale(config);
+        self.uniforms.tweaks = tweaks(config);
+        let background = config.background(self.output.as_deref());
+        let path_changed = background.shader_path != self.shader_path;
+        if background.scene == self.scene && !path_changed {return;}
+        let previous = self.snapshot();
+        self.scene = background.scene;
+        if path_changed {
+            self.shader_path = background.shader_path;
+            self.load_shader();
+        }
+        self.transition = Some(previous);
+    }
+
+    /// Load the configured shader file again, fading over from the previous version
+    pub fn reload_shader(&mut self){
+        let previous = self.snapshot();
+        self.load_shader();
+        if !same_source(&previous.source, &self.source) {
+            self.transition = Some(previous);
+        }
+    }
+
+    /// Load the configured shader file. If it cannot be read or does not compile,
+    /// the previous shader keeps running and the error is kept for [`Self::error`].
+    fn load_shader(&mut self){
+        if self.disabled {return;}
+        if self.shader_path.is_empty() {
+            self.source = None;
+            self.error = None;
+            return;
+        }
+        match load(&self.shader_path) {
+            Ok(source) => {
+                self.source = Some(source);
+                self.error = None;
+            },
+            Err(err) => {
+                log::warn!("failed to load shader {}: {}", self.shader_path, err);
+                self.error = Some(err);
+            },
+        }
+    }
+
+    /// To be called when the config changes, loads the configured textures whose path changed
+    pub fn update_texture(&mut self, config:&Config)->Task<Message>{
+        let mut tasks = Vec::new();
+        for (i, path) in texture_paths(config, self.output.as_deref()).into_iter().enumerate() {
+            if path != self.texture_paths[i] {
+                self.texture_paths[i] = path;
+                tasks.push(self.load_texture(i));
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    /// Load all configured textures again
+    pub fn reload_texture(&mut self)->Task<Message>{
+        Task::batch((0..TEXTURES).map(|i| self.load_texture(i)).collect::<Vec<_>>())
+    }
+
+    /// Load texture `i` of [`Textures`], falling back to its default if it cannot be read.
+    /// Animated images and videos are played in a loop. Files are decoded by the returned
+    /// task, and the current texture is shown until [`Self::set_texture`] is called with them.
+    fn load_texture(&mut self, i:usize)->Task<Message>{
+        if self.disabled {return Task::none();}
+        if self.texture_paths[i].is_empty() {
+            self.animations[i] = None;
+            self.textures[i] = default_texture(i);
+            return Task::none();
+        }
+        decode_texture(i, self.texture_paths[i].clone())
+    }
+
+    /// Show the image or animation decoded for texture `i` at `path`, unless another texture was
+    /// set meanwhile
+    pub fn set_texture(&mut self, i:usize, path:&str, texture:Result<Arc<Animation>, String>){
+        if self.texture_paths.get(i).map_or(true, |current| current != path) {return;}
+        match texture {
+            Ok(texture) => {
+                self.textures[i] = texture.frame();
+                self.animations[i] = texture.is_animated().then_some(texture);
+            },
+            Err(err) => {
+                log::warn!("failed to load texture {}, using the default: {}", path, err);
+                self.textures[i] = default_texture(i);
+                self.animations[i] = None;
+            },
+        }
+    }
+
+    /// What is drawn right now, to start a transition from
+    fn snapshot(&self)->Transition{
+        Transition {
+            scene: self.scene,
+            source: self.source.clone(),
+            bg: self.uniforms.bg,
+            start: Instant::now(),
         }
     }
 
+    /// Save the next frame of the background as a PNG at `path`
+    pub fn capture(&self, path:PathBuf){
+        *self.capture.lock().unwrap() = Some(path);
+    }
+
+    /// Why the background cannot be rendered, in which case a plain background is shown instead
+    pub fn gpu_error(&self)->Option<String>{
+        self.gpu_error.lock().unwrap().clone()
+    }
+
+    /// Whether the background is rendered, so that its inputs like the audio levels, the
+    /// weather and smoothed metrics are needed
+    pub fn rendered(&self)->bool{
+        !self.disabled && self.gpu_error.lock().unwrap().is_none()
+    }
+
+    /// Error of the last attempt to load the configured shader
+    pub fn error(&self)->Option<&str>{
+        self.error.as_deref()
+    }
+
     /// To be called from `ResourceMonitor` at least once per visual update tick
-    pub fn update_uniforms_tick(&mut self, cpu_util:f32, cpu_max:f32, cpu_freq:f32){
-        self.uniforms.cpu_util = cpu_util;
-        self.uniforms.cpu_max = cpu_max;
-        self.uniforms.cpu_freq = cpu_freq;
+    pub fn update_uniforms_tick(&mut self, metrics:MetricUniforms){
+        self.uniforms.metrics = metrics;
+        self.uniforms.sun = sun::position(chrono::Utc::now(), self.location[0], self.location[1]);
         self.uniforms.time +=  self.uniforms.delta_time.elapsed().as_secs_f32() 
-            * (self.uniforms.cpu_freq.clamp(0.0, 1.0).powi(2) * 0.5 + 0.5);
+            * (self.uniforms.metrics.cpu_freq.clamp(0.0, 1.0).powi(2) * 0.5 + 0.5)
+            * self.uniforms.tweaks[0];
         self.uniforms.delta_time = Instant::now();
     }
 
+    /// To be called when the config changes, to follow the configured location
+    /// and to clear the weather once `Config::weather_reactive` is unset
+    pub fn update_location(&mut self, config:&Config){
+        self.location = location(config);
+        if !config.weather_reactive {
+            self.uniforms.weather = [0.;3];
+        }
+    }
+
+    /// To be called when the config or the pywal or wallust palette changes, to tint the scene
+    /// with the palette's accent colour or to stop tinting it
+    pub fn update_tint(&mut self, palette:Option<&Palette>){
+        self.uniforms.tint = match palette {
+            Some(palette) => {
+                let accent = palette.accent();
+                [accent.r, accent.g, accent.b, 1.]
+            },
+            None => [0.;4],
+        };
+    }
+
+    /// Tint the scene with an sRGB colour in place of the palette's accent, like the dominant
+    /// colour of the cover art while `Config::media_art_tint` is set
+    pub fn set_tint(&mut self, [r,g,b]:[f32;3]){
+        self.uniforms.tint = [r, g, b, 1.];
+    }
+
+    /// To be called with the current weather while `Config::weather_reactive` is set
+    pub fn update_weather(&mut self, weather:[f32;3]){
+        self.uniforms.weather = weather;
+    }
+
+    /// To be called on visual updates with [`crate::pomodoro::Pomodoro::shader_focus`] while
+    /// `Config::pomodoro_shader` is set, or with zeros to stop calming the scene
+    pub fn update_focus(&mut self, focus:[f32;2]){
+        self.uniforms.focus = focus;
+    }
+
+    /// Interval between redraws of the background and visual updates of the uniforms
+    pub fn frame_time(&self)->Duration{
+        self.frame_time
+    }
+
+    pub fn set_frame_time(&mut self, frame_time:Duration){
+        self.frame_time = frame_time;
+    }
+
+    /// Whether the compositor delivered a frame callback recently. Redraws are requested every
+    /// frame time, so the callbacks only stop while the compositor hides the window, and resume
+    /// with the first frame once it is shown again.
+    /// Without a GPU or with the shader disabled the widget is not in the window, so the window is
+    /// assumed to be visible.
+    pub fn visible(&self)->bool{
+        if self.disabled || self.gpu_error.lock().unwrap().is_some() {return true;}
+        let last_frame = *self.last_frame.lock().unwrap();
+        last_frame.elapsed() < self.frame_time * 3 + Duration::from_secs(1)
+    }
+
     /// To be called when the background colour of the terminal theme changes
     pub fn update_bg(&mut self, config:&Config){
-        self.uniforms.bg = get_term_bg_colour(config);
+        let bg = get_term_bg_colour(config);
+        if bg != self.uniforms.bg {
+            self.transition = Some(self.snapshot());
+            self.uniforms.bg = bg;
+        }
+    }
+}
+
+/// Pointer interaction with the background, passed to the shader as `mouse`
+#[derive(Debug, Default)]
+pub struct MouseState {
+    /// Cursor position relative to the widget size, `None` if it is outside
+    position: Option<[f32;2]>,
+    pressed: bool,
+    click: Option<Instant>,
+}
+
+impl MouseState {
+    fn uniform(&self)->[f32;4]{
+        let [x, y] = self.position.unwrap_or([-1., -1.]);
+        let pressed = if self.pressed {1.} else {0.};
+        let click = self.click.map_or(-1., |click| click.elapsed().as_secs_f32());
+        [x, y, pressed, click]
     }
 }
 
 impl shader::Program<Message> for FragmentShaderProgram {
-    // type State = SomeEnum;
-    type State = ();
+    type State = MouseState;
     type Primitive = FragmentShaderPrimitive;
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         _cursor: mouse::Cursor,
         _bounds: Rectangle,
     ) -> Self::Primitive {
-        FragmentShaderPrimitive::new(self.uniforms)
+        // the software renderer draws the widget but never prepares it
+        if self.unprepared_draws.fetch_add(1, Ordering::Relaxed) >= UNPREPARED_DRAWS {
+            self.gpu_error.lock().unwrap().get_or_insert_with(|| "the renderer does not use a GPU".to_string());
+        }
+        let fade_out = self.transition.as_ref().and_then(|transition| {
+            let weight = 1. - transition.start.elapsed().as_secs_f32() / TRANSITION_TIME.as_secs_f32();
+            (weight > 0.).then(|| (transition.clone(), weight))
+        });
+        FragmentShaderPrimitive {
+            uniforms: self.uniforms,
+            mouse: state.uniform(),
+            scene: self.scene,
+            source: self.source.clone(),
+            textures: std::array::from_fn(|i| match &self.animations[i] {
+                Some(animation) => animation.frame(),
+                None => self.textures[i].clone(),
+            }),
+            fade_out,
+            particles: self.particles,
+            render_scale: self.render_scale,
+            capture: self.capture.lock().unwrap().take(),
+            gpu_error: self.gpu_error.clone(),
+            unprepared_draws: self.unprepared_draws.clone(),
+        }
     }
 
     fn update(
         &self,
-        _state: &mut Self::State,
-        _event: Event,
-        _bounds: Rectangle,
-        _cursor: Cursor,
+        state: &mut Self::State,
+        event: Event,
+        bounds: Rectangle,
+        cursor: Cursor,
         shell: &mut Shell<'_, Message>,
     ) -> (Status, Option<Message>) {
+        // the terminal on top of the background handles the clicks too, so events are never captured
+        state.position = cursor.position_in(bounds)
+            .map(|p| [p.x / bounds.width, p.y / bounds.height]);
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) if state.position.is_some() => {
+                state.pressed = true;
+                state.click = Some(Instant::now());
+            },
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => state.pressed = false,
+            Event::RedrawRequested(now) => *self.last_frame.lock().unwrap() = now,
+            _ => {},
+        }
+        // requests while hidden are held back by the compositor, so they cost nothing
         shell.request_redraw(RedrawRequest::At(
-            Instant::now()+Duration::from_millis(FRAME_TIME)
+            Instant::now()+self.frame_time
         ));
         // shell.request_redraw(RedrawRequest::NextFrame);
         (Status::Ignored, None)
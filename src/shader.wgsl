@@ -1,32 +1,7 @@
-
-// ------------- UNIFORMS -------------
-@group(0) @binding(0) var<uniform> u: Uniforms;
-struct Uniforms {
-	res: vec2f,
-	top_left: vec2f,
-	t: f32,
-	cpu_u: f32,
-	cpu_m: f32,
-	r: f32,
-	g: f32,
-	b: f32,
-	a: f32,
-	// buff: f32,
-}
+// ------------- TEXTURES -------------
 @group(0) @binding(1) var tex: texture_2d<f32>;
 @group(0) @binding(2) var tex_sampler: sampler;
 
-// ---------- VERTEX CREATION ----------
-struct VertexIn {@builtin(vertex_index) vertex_index: u32,}
-struct VertexOut {@builtin(position) position: vec4f,}
-@vertex
-fn vs_main(in: VertexIn) -> VertexOut {
-	let uv = vec2f(vec2u((in.vertex_index << 1) & 2, in.vertex_index & 2));
-	let position = vec4f(uv * 2. - 1., 0., 1.);
-	return VertexOut(position);
-}
-
-
 // ------------ MAIN PROGRAM -----------
 // PARAMETERS
 const RADIUS:f32 = 2.0;
@@ -185,7 +160,7 @@ fn fs_main(in: VertexOut) -> @location(0) vec4f {
 
         let bg: vec3f = vec3f(u.r, u.g, u.b);
         let out_col = bg + halo_col * glow * surface_fall;
-        let final_col = clamp(out_col, vec3f(0.0), vec3f(1.0));
+        let final_col = weather(tweak(out_col, bg), in.position.xy - u.top_left);
 
         return vec4f(final_col, u.a);
     }
@@ -197,7 +172,7 @@ fn fs_main(in: VertexOut) -> @location(0) vec4f {
     var t: f32 = -1.0;
     if (t0 > 0.0) { t = t0; } else if (t1 > 0.0) { t = t1; } else {
         let bg: vec3f = vec3f(u.r, u.g, u.b);
-        return vec4f(bg, u.a);
+        return vec4f(weather(bg, in.position.xy - u.top_left), u.a);
     }
 
     let hit: vec3f = ray_at(r, t);
@@ -234,7 +209,12 @@ fn fs_main(in: VertexOut) -> @location(0) vec4f {
     tex_col = (tex_col - vec3f(0.5)) * contrast + vec3f(0.5) + vec3f(brightness);
     tex_col = clamp(tex_col, vec3f(0.0), vec3f(1.0));
 
-    return vec4f(tex_col, u.a);
+    // dim the city lights on the side facing the sun, with a soft terminator
+    let sun_dir = vec3f(cos(u.sun.y) * cos(u.sun.z), sin(u.sun.y), cos(u.sun.y) * sin(u.sun.z));
+    let daylight = smoothstep(-0.1, 0.1, dot(normalize(p - C), sun_dir));
+    tex_col = tex_col * mix(1.0, 0.3, daylight);
+
+    return vec4f(weather(tweak(tex_col, vec3f(u.r, u.g, u.b)), in.position.xy - u.top_left), u.a);
 }
 
 
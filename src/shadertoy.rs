@@ -0,0 +1,134 @@
+//! Compatibility layer for ShaderToy shaders, which are written in GLSL and define
+//! `mainImage(out vec4 fragColor, in vec2 fragCoord)` instead of full entry points.
+//! The body is wrapped into a fragment shader with eos' bindings, translated to WGSL
+//! with naga and completed with the vertex stage the pipeline expects.
+
+/// Declarations before the user's code, mapping the ShaderToy inputs onto the eos uniforms and textures
+const PRELUDE:&str = "#version 450
+layout(set = 0, binding = 0) uniform Uniforms {
+    vec2 res;
+    vec2 top_left;
+    float t;
+    float cpu_u;
+    float cpu_m;
+    float r;
+    float g;
+    float b;
+    float a;
+    float gpu_u;
+    float gpu_p;
+    float ram_u;
+    float gpu_mem;
+    vec4 slots[2];
+    vec4 audio;
+    vec4 mouse;
+    vec4 sun;
+    vec4 params;
+    vec4 weather;
+    vec4 tint;
+} u;
+layout(set = 0, binding = 1) uniform texture2D eos_texture;
+layout(set = 0, binding = 2) uniform sampler eos_sampler;
+layout(set = 0, binding = 3) uniform texture2D eos_channel0;
+layout(set = 0, binding = 4) uniform texture2D eos_channel1;
+layout(set = 0, binding = 5) uniform texture2D eos_channel2;
+layout(set = 0, binding = 6) uniform texture2D eos_channel3;
+layout(location = 0) out vec4 eos_colour;
+
+#define iTime u.t
+#define iResolution vec3(u.res, 1.0)
+#define iMouse (u.mouse.z > 0.0 ? vec4(u.mouse.x * u.res.x, (1.0 - u.mouse.y) * u.res.y, 1.0, 1.0) : vec4(0.0))
+#define iChannel0 sampler2D(eos_channel0, eos_sampler)
+#define iChannel1 sampler2D(eos_channel1, eos_sampler)
+#define iChannel2 sampler2D(eos_channel2, eos_sampler)
+#define iChannel3 sampler2D(eos_channel3, eos_sampler)
+";
+
+/// Entry point after the user's code, flipping the y axis since ShaderToy's origin is the bottom left corner
+const MAIN:&str = "
+void main() {
+    vec2 coord = gl_FragCoord.xy - u.top_left;
+    vec4 colour;
+    mainImage(colour, vec2(coord.x, u.res.y - coord.y));
+    eos_colour = vec4(colour.rgb, 1.0);
+}
+";
+
+/// Vertex stage appended to the translated shader, a single triangle covering the widget
+const VERTEX:&str = "
+struct EosVertexOut {@builtin(position) position: vec4f,}
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> EosVertexOut {
+\tlet uv = vec2f(vec2u((vertex_index << 1) & 2, vertex_index & 2));
+\treturn EosVertexOut(vec4f(uv * 2. - 1., 0., 1.));
+}
+";
+
+/// Whether the file at `path` should be imported as a ShaderToy shader rather than read as WGSL
+pub fn is_shadertoy(path:&str)->bool{
+    matches!(
+        std::path::Path::new(path).extension().and_then(|ext| ext.to_str()),
+        Some("glsl" | "frag")
+    )
+}
+
+/// Translate the `mainImage` GLSL of a ShaderToy shader into WGSL with `vs_main` and `fs_main` entry points.
+/// Error messages point at the wrapped source, which starts with the prelude.
+pub fn translate(body:&str)->Result<String, String>{
+    let source = format!("{}{}\n{}", PRELUDE, body, MAIN);
+    let mut module = naga::front::glsl::Frontend::default()
+        .parse(&naga::front::glsl::Options::from(naga::ShaderStage::Fragment), &source)
+        .map_err(|err| err.emit_to_string(&source))?;
+    for entry_point in module.entry_points.iter_mut() {
+        entry_point.name = "fs_main".into();
+    }
+    let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+        .validate(&module)
+        .map_err(|err| err.emit_to_string(&source))?;
+    let wgsl = naga::back::wgsl::write_string(&module, &info, naga::back::wgsl::WriterFlags::empty())
+        .map_err(|err| err.to_string())?;
+    Ok(wgsl + VERTEX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRADIENT:&str = "void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+    vec2 uv = fragCoord / iResolution.xy;
+    fragColor = vec4(uv, 0.5 + 0.5 * sin(iTime), 1.0) * texture(iChannel0, uv);
+}";
+
+    #[test]
+    fn translates_to_valid_wgsl_with_both_entry_points(){
+        let wgsl = translate(GRADIENT).unwrap();
+        let module = naga::front::wgsl::parse_str(&wgsl).unwrap();
+        let names:Vec<_> = module.entry_points.iter().map(|entry_point| entry_point.name.as_str()).collect();
+        assert!(names.contains(&"fs_main"));
+        assert!(names.contains(&"vs_main"));
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+            .validate(&module)
+            .unwrap();
+    }
+
+    #[test]
+    fn rejects_malformed_glsl(){
+        assert!(translate("void mainImage(out vec4 fragColor, in vec2 fragCoord) { fragColor = ; }").is_err());
+        assert!(translate("void mainImage(out vec4 fragColor, in vec2 fragCoord) {").is_err());
+        assert!(translate("").is_err());
+    }
+
+    #[test]
+    fn rejects_shaders_without_main_image(){
+        assert!(translate("void main2(out vec4 colour) { colour = vec4(1.0); }").is_err());
+    }
+
+    #[test]
+    fn recognises_glsl_extensions(){
+        assert!(is_shadertoy("/tmp/sea.glsl"));
+        assert!(is_shadertoy("sea.frag"));
+        assert!(!is_shadertoy("sea.wgsl"));
+        assert!(!is_shadertoy("glsl"));
+        assert!(!is_shadertoy(""));
+    }
+}
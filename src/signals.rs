@@ -0,0 +1,50 @@
+//! Toggles of a running eos from the command line, like `eos --toggle-overlay`, which send
+//! a signal to the other instances of the executable so compositor shortcuts can bind them.
+//! `eos ctl` covers more commands through the control socket, see [`crate::ipc`].
+
+use std::any::TypeId;
+
+use cosmic::iced::{futures::SinkExt, stream, Subscription};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
+use tokio::signal::unix::{signal, SignalKind};
+
+use crate::Message;
+
+/// Send the signal to the other running instances of this executable, returning how many
+/// received it
+pub fn send_running(signal:Signal)->usize{
+    let Ok(exe) = std::env::current_exe() else { return 0 };
+    let own = sysinfo::get_current_pid().ok();
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+    );
+    sys.processes()
+        .values()
+        .filter(|process| Some(process.pid()) != own && process.exe() == Some(exe.as_path()))
+        .filter(|process| process.kill_with(signal).unwrap_or(false))
+        .count()
+}
+
+/// The message whenever the signal is received
+pub fn listen(kind:SignalKind, message:Message)->Subscription<Message>{
+    struct Signals;
+    Subscription::run_with_id(
+        (TypeId::of::<Signals>(), kind.as_raw_value()),
+        stream::channel(1, move |mut output| async move {
+            let mut signals = match signal(kind) {
+                Ok(signals) => signals,
+                Err(err) => {
+                    log::warn!("cannot listen for signal {}: {}", kind.as_raw_value(), err);
+                    return std::future::pending().await;
+                },
+            };
+            while signals.recv().await.is_some() {
+                if output.send(message.clone()).await.is_err() {break;}
+            }
+            std::future::pending().await
+        }),
+    )
+}
@@ -0,0 +1,150 @@
+//! Approximate position of the sun, accurate to a fraction of a degree, which is plenty for
+//! backgrounds, and from it the times of sunrise and sunset to about a minute, and the phase of
+//! the moon, all computed locally
+
+use std::f32::consts::PI;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Timelike, Utc};
+
+/// Elevation of the centre of the sun at sunrise and sunset in degrees, below the horizon by
+/// its radius and the refraction of the atmosphere
+const HORIZON:f32 = -0.833;
+/// Mean length of a lunar month in days
+const SYNODIC_MONTH:f64 = 29.530_588_853;
+/// A new moon, on 2000-01-06 at 18:14 UTC, in seconds since the Unix epoch
+const NEW_MOON:i64 = 947_182_440;
+
+/// Declination of the sun in radians and the equation of time in minutes on the zero-based
+/// `day` of the year
+fn declination_equation_of_time(day:f32)->(f32, f32){
+    let declination = -23.44f32.to_radians() * (2. * PI / 365. * (day + 10.)).cos();
+    let b = 2. * PI / 364. * (day - 81.);
+    (declination, 9.87 * (2. * b).sin() - 7.53 * b.cos() - 1.5 * b.sin())
+}
+
+/// Sun elevation above the horizon at `latitude` and `longitude` in degrees, followed by the
+/// latitude and longitude of the point where the sun is at the zenith, all in radians
+pub fn position(now:DateTime<Utc>, latitude:f32, longitude:f32)->[f32;3]{
+    let day = now.ordinal0() as f32;
+    let hours = now.num_seconds_from_midnight() as f32 / 3600.;
+    let (declination, equation_of_time) = declination_equation_of_time(day);
+    // the sun is at the zenith where the apparent solar time is noon
+    let subsolar_longitude = -15. * (hours - 12. + equation_of_time / 60.);
+    let hour_angle = (longitude - subsolar_longitude).to_radians();
+    let latitude = latitude.to_radians();
+    let elevation = (latitude.sin() * declination.sin()
+        + latitude.cos() * declination.cos() * hour_angle.cos()).clamp(-1., 1.).asin();
+    let subsolar_longitude = (subsolar_longitude + 540.).rem_euclid(360.) - 180.;
+    [elevation, declination, subsolar_longitude.to_radians()]
+}
+
+/// Whether and when the sun rises and sets on a day
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Daylight {
+    Times { sunrise: DateTime<Utc>, sunset: DateTime<Utc> },
+    /// Midnight sun in summer close to the poles
+    AlwaysUp,
+    /// Polar night in winter
+    AlwaysDown,
+}
+
+/// Sunrise and sunset on `date`, in UTC, at `latitude` and `longitude` in degrees
+pub fn daylight(date:NaiveDate, latitude:f32, longitude:f32)->Daylight{
+    let (declination, equation_of_time) = declination_equation_of_time(date.ordinal0() as f32);
+    let latitude = latitude.to_radians();
+    // hour angle of the sun at the horizon, half the length of the day
+    let cos_hour_angle = (HORIZON.to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+    if cos_hour_angle > 1. {return Daylight::AlwaysDown;}
+    if cos_hour_angle < -1. {return Daylight::AlwaysUp;}
+    let half_day = cos_hour_angle.acos().to_degrees() / 15.;
+    let noon = 12. - longitude / 15. - equation_of_time / 60.;
+    let time = |hours:f32| date.and_time(NaiveTime::MIN).and_utc() + chrono::Duration::seconds((hours * 3600.) as i64);
+    Daylight::Times { sunrise: time(noon - half_day), sunset: time(noon + half_day) }
+}
+
+/// Age of the moon as a fraction of the lunar month, 0 at new moon and 0.5 at full moon
+pub fn moon_phase(now:DateTime<Utc>)->f32{
+    let days = (now.timestamp() - NEW_MOON) as f64 / 86400.;
+    (days / SYNODIC_MONTH).rem_euclid(1.) as f32
+}
+
+/// Fraction of the moon's disc that is lit in a phase
+pub fn moon_illumination(phase:f32)->f32{
+    (1. - (2. * PI * phase).cos()) / 2.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year:i32, month:u32, day:u32, hour:u32, minute:u32)->DateTime<Utc>{
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap().and_utc()
+    }
+
+    fn close(actual:f32, expected:f32, tolerance:f32){
+        assert!((actual - expected).abs() <= tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+    }
+
+    #[test]
+    fn sun_is_overhead_at_the_equator_at_the_equinox(){
+        // solar noon at Greenwich is about 7 minutes after 12:00 UTC on 2024-03-20
+        let [elevation, latitude, longitude] = position(at(2024, 3, 20, 12, 7), 0., 0.);
+        close(elevation.to_degrees(), 90., 1.);
+        close(latitude.to_degrees(), 0., 1.);
+        close(longitude.to_degrees(), 0., 1.);
+    }
+
+    #[test]
+    fn subsolar_latitude_follows_the_seasons(){
+        close(position(at(2024, 6, 21, 12, 0), 0., 0.)[1].to_degrees(), 23.44, 0.5);
+        close(position(at(2024, 12, 21, 12, 0), 0., 0.)[1].to_degrees(), -23.44, 0.5);
+    }
+
+    #[test]
+    fn elevation_at_noon_and_midnight(){
+        // Berlin, midsummer: about 90 - 52.5 + 23.4 degrees at noon, below the horizon at midnight
+        close(position(at(2024, 6, 21, 11, 7), 52.5, 13.4)[0].to_degrees(), 60.9, 1.);
+        assert!(position(at(2024, 6, 21, 23, 7), 52.5, 13.4)[0] < 0.);
+    }
+
+    #[test]
+    fn subsolar_longitude_wraps_around(){
+        for hour in 0..24 {
+            let longitude = position(at(2024, 1, 1, hour, 0), 0., 0.)[2];
+            assert!((-PI..=PI).contains(&longitude), "{} at {}:00", longitude, hour);
+        }
+        // midnight UTC puts the sun over the date line
+        close(position(at(2024, 3, 20, 0, 7), 0., 0.)[2].to_degrees().abs(), 180., 1.);
+    }
+
+    #[test]
+    fn out_of_range_coordinates_stay_finite(){
+        for (latitude, longitude) in [(1000., 0.), (-90., 720.), (90., -1e6)] {
+            let position = position(at(2024, 3, 20, 12, 0), latitude, longitude);
+            assert!(position.iter().all(|value| value.is_finite()), "{:?} at {}, {}", position, latitude, longitude);
+            assert!((-PI / 2. ..=PI / 2.).contains(&position[0]));
+        }
+    }
+
+    #[test]
+    fn daylight_at_mid_latitudes_and_the_poles(){
+        let Daylight::Times { sunrise, sunset } = daylight(NaiveDate::from_ymd_opt(2024, 3, 20).unwrap(), 0., 0.) else {
+            panic!("no sunrise at the equator");
+        };
+        close(sunrise.num_seconds_from_midnight() as f32 / 3600., 6., 0.2);
+        close(sunset.num_seconds_from_midnight() as f32 / 3600., 18.2, 0.2);
+        assert_eq!(daylight(NaiveDate::from_ymd_opt(2024, 12, 21).unwrap(), 80., 0.), Daylight::AlwaysDown);
+        assert_eq!(daylight(NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(), 80., 0.), Daylight::AlwaysUp);
+    }
+
+    #[test]
+    fn moon_phase_of_a_known_full_moon(){
+        // full moon on 2024-01-25 at 17:54 UTC
+        close(moon_phase(at(2024, 1, 25, 17, 54)), 0.5, 0.02);
+        close(moon_illumination(0.5), 1., 1e-6);
+        close(moon_illumination(0.), 0., 1e-6);
+        // before the reference new moon
+        assert!((0. ..1.).contains(&moon_phase(at(1970, 1, 1, 0, 0))));
+    }
+}
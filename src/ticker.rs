@@ -0,0 +1,179 @@
+//! Prices of the stocks and currencies in `Config::tickers`, fetched through `curl` on a
+//! background thread every `Config::ticker_refresh_minutes` from the provider chosen with
+//! `Config::ticker_provider`. Where a fetch fails, the last quote is kept and marked stale.
+//!
+//! Providers implement [`Provider`], turning a symbol into a URL and its response into a quote;
+//! `Custom` reads any JSON API through a URL template and JSON pointers from the config.
+
+use std::any::TypeId;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::config::{Config, TickerProvider};
+use crate::Message;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    pub price: f64,
+    /// Change since the previous close, or over the last day, in percent
+    pub change: f64,
+    pub time: DateTime<Local>,
+}
+
+/// Symbol and its last quote, `stale` if the last fetch of it failed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub symbol: String,
+    pub quote: Option<Quote>,
+    pub stale: bool,
+}
+
+/// Source of quotes over HTTP
+trait Provider: Send {
+    fn url(&self, symbol:&str)->String;
+    /// Price and change in percent of the symbol in a response
+    fn parse(&self, symbol:&str, json:&Value)->Option<(f64, f64)>;
+}
+
+/// Chart API of Yahoo Finance, for stocks like `AAPL`, indices like `^GSPC` and currency pairs
+/// like `BTC-USD` or `EURUSD=X`
+struct Yahoo;
+
+impl Provider for Yahoo {
+    fn url(&self, symbol:&str)->String{
+        format!("https://query1.finance.yahoo.com/v8/finance/chart/{}?range=1d&interval=1d", symbol)
+    }
+
+    fn parse(&self, _symbol:&str, json:&Value)->Option<(f64, f64)>{
+        let meta = json.pointer("/chart/result/0/meta")?;
+        let price = meta.get("regularMarketPrice")?.as_f64()?;
+        let close = meta.get("chartPreviousClose").or_else(|| meta.get("previousClose"))?.as_f64()?;
+        Some((price, (price / close - 1.) * 100.))
+    }
+}
+
+/// Simple price API of CoinGecko, for coins by their id like `bitcoin`, in `currency`
+struct CoinGecko {
+    currency: String,
+}
+
+impl Provider for CoinGecko {
+    fn url(&self, symbol:&str)->String{
+        format!(
+            "https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies={}&include_24hr_change=true",
+            symbol, self.currency,
+        )
+    }
+
+    fn parse(&self, symbol:&str, json:&Value)->Option<(f64, f64)>{
+        let coin = json.get(symbol)?;
+        let price = coin.get(&self.currency)?.as_f64()?;
+        let change = coin.get(format!("{}_24h_change", self.currency))?.as_f64()?;
+        Some((price, change))
+    }
+}
+
+/// Any JSON API: `{symbol}` in the URL is replaced, and the price and the change in percent
+/// are found at JSON pointers like `/data/price`
+struct Custom {
+    url: String,
+    price: String,
+    change: String,
+}
+
+impl Provider for Custom {
+    fn url(&self, symbol:&str)->String{
+        self.url.replace("{symbol}", symbol)
+    }
+
+    fn parse(&self, _symbol:&str, json:&Value)->Option<(f64, f64)>{
+        // some APIs give numbers as strings
+        let number = |pointer:&str| json.pointer(pointer).and_then(|value| {
+            value.as_f64().or_else(|| value.as_str()?.trim().parse().ok())
+        });
+        Some((number(&self.price)?, number(&self.change)?))
+    }
+}
+
+fn provider(config:&Config)->Box<dyn Provider>{
+    match config.ticker_provider {
+        TickerProvider::Yahoo => Box::new(Yahoo),
+        TickerProvider::CoinGecko => Box::new(CoinGecko { currency: config.ticker_currency.to_lowercase() }),
+        TickerProvider::Custom => Box::new(Custom {
+            url: config.ticker_url.clone(),
+            price: config.ticker_price_pointer.clone(),
+            change: config.ticker_change_pointer.clone(),
+        }),
+    }
+}
+
+fn quote(provider:&dyn Provider, symbol:&str)->Result<Quote, String>{
+    let url = provider.url(symbol);
+    let output = Command::new("curl")
+        .args(["--silent", "--fail", "--location", "--compressed", "--max-time", "20"])
+        // some APIs turn away clients without a browser-like agent
+        .args(["--user-agent", "Mozilla/5.0 (X11; Linux x86_64) eos", &url])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run curl: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("curl exited with {}", output.status));
+    }
+    let json:Value = serde_json::from_slice(&output.stdout).map_err(|err| format!("invalid response: {}", err))?;
+    let (price, change) = provider.parse(symbol, &json).ok_or("no price in the response")?;
+    Ok(Quote { price, change, time: Local::now() })
+}
+
+/// Emits the quotes of the symbols every `Config::ticker_refresh_minutes`, see
+/// [`Message::Quotes`]
+pub fn listen(config:&Config)->Subscription<Message>{
+    struct TickerSubscription;
+    let symbols = config.tickers.clone();
+    let refresh = Duration::from_secs(60 * u64::from(config.ticker_refresh_minutes));
+    let provider = provider(config);
+    Subscription::run_with_id(
+        (
+            TypeId::of::<TickerSubscription>(),
+            symbols.clone(),
+            config.ticker_provider,
+            config.ticker_currency.clone(),
+            config.ticker_url.clone(),
+            config.ticker_price_pointer.clone(),
+            config.ticker_change_pointer.clone(),
+            refresh,
+        ),
+        stream::channel(1, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            std::thread::spawn(move || {
+                let mut entries:Vec<Entry> = symbols.iter()
+                    .map(|symbol| Entry { symbol: symbol.clone(), quote: None, stale: false })
+                    .collect();
+                loop {
+                    for entry in &mut entries {
+                        match quote(provider.as_ref(), &entry.symbol) {
+                            Ok(quote) => {
+                                entry.quote = Some(quote);
+                                entry.stale = false;
+                            },
+                            Err(err) => {
+                                log::warn!("failed to fetch the price of {}: {}", entry.symbol, err);
+                                entry.stale = true;
+                            },
+                        }
+                    }
+                    if tx.send(entries.clone()).is_err() {break;}
+                    std::thread::sleep(refresh);
+                }
+            });
+            while let Some(entries) = rx.recv().await {
+                let _ = output.send(Message::Quotes(entries)).await;
+            }
+        }),
+    )
+}
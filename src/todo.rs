@@ -0,0 +1,254 @@
+//! Pending tasks of a todo.txt file or of Taskwarrior, shown in
+//! [`crate::config::Section::Todo`]. The file, or the data of Taskwarrior, is watched on a
+//! background thread and read again whenever it changes, so tasks added or done elsewhere show
+//! up right away. Ticking a task off writes it back as done in the todo.txt format, or runs
+//! `task <uuid> done`.
+
+use std::any::TypeId;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use crate::config::{Config, TodoBackend};
+use crate::{util, Message};
+
+/// Time to wait for more changes after the first, as tools often write their files in steps
+const SETTLE:Duration = Duration::from_millis(200);
+
+/// Where a task is kept, to complete it
+#[derive(Clone, Debug, PartialEq)]
+enum Key {
+    /// Index and text of its line in todo.txt, checked before the line is rewritten
+    Line(usize, String),
+    /// UUID in Taskwarrior
+    Uuid(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Task {
+    pub description: String,
+    /// `A` to `Z` in todo.txt, `H`, `M` or `L` in Taskwarrior
+    pub priority: Option<char>,
+    pub due: Option<NaiveDate>,
+    key: Key,
+}
+
+/// Priority like `(A) ` at the start of a todo.txt line, and the rest of the line
+fn priority(line:&str)->Option<(char, &str)>{
+    let (priority, rest) = line.strip_prefix('(')?.split_once(") ")?;
+    let mut chars = priority.chars();
+    chars.next().filter(|c| c.is_ascii_uppercase() && chars.next().is_none()).map(|c| (c, rest))
+}
+
+/// Pending task of a line of todo.txt, leaving the priority, creation date and `key:value`
+/// tags out of its description
+fn parse_line(index:usize, line:&str)->Option<Task>{
+    let line = line.trim_end();
+    if line.trim().is_empty() || line.starts_with("x ") {return None;}
+    let (priority, mut rest) = match priority(line) {
+        Some((priority, rest)) => (Some(priority), rest),
+        None => (None, line),
+    };
+    if let Some((date, after)) = rest.split_once(' ') {
+        if NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() {rest = after;}
+    }
+    let mut due = None;
+    let words:Vec<&str> = rest.split_whitespace().filter(|word| {
+        let Some((key, value)) = word.split_once(':') else {return true};
+        // URLs are kept, tags have a plain key and a value without spaces
+        if value.starts_with("//") || key.is_empty() || value.is_empty() || !key.chars().all(char::is_alphanumeric) {
+            return true;
+        }
+        if key == "due" {
+            due = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok();
+        }
+        false
+    }).collect();
+    Some(Task { description: words.join(" "), priority, due, key: Key::Line(index, line.to_string()) })
+}
+
+/// Pending tasks of a todo.txt, by priority, then due date, then where they are in the file
+fn read_todo_txt(path:&Path)->Result<Vec<Task>, String>{
+    let text = fs::read_to_string(path).map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+    let mut tasks:Vec<Task> = text.lines().enumerate().filter_map(|(index, line)| parse_line(index, line)).collect();
+    tasks.sort_by_key(|task| (task.priority.is_none(), task.priority, task.due.is_none(), task.due));
+    Ok(tasks)
+}
+
+/// Task as written by `task export`
+#[derive(Deserialize)]
+struct Exported {
+    uuid: String,
+    description: String,
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    urgency: f32,
+}
+
+/// Pending tasks of Taskwarrior, the most urgent first
+fn read_taskwarrior()->Result<Vec<Task>, String>{
+    let output = Command::new("task")
+        .args(["rc.verbose=nothing", "rc.hooks=off", "status:pending", "export"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run task: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("task exited with {}", output.status));
+    }
+    let mut exported:Vec<Exported> = serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("failed to read the tasks of task export: {}", err))?;
+    exported.sort_by(|a, b| b.urgency.total_cmp(&a.urgency));
+    Ok(exported.into_iter().map(|task| Task {
+        description: task.description,
+        priority: task.priority.and_then(|priority| priority.chars().next()),
+        due: task.due
+            .and_then(|due| NaiveDateTime::parse_from_str(&due, "%Y%m%dT%H%M%SZ").ok())
+            .map(|due| due.and_utc().with_timezone(&Local).date_naive()),
+        key: Key::Uuid(task.uuid),
+    }).collect())
+}
+
+fn read(backend:TodoBackend, file:&str)->Result<Vec<Task>, String>{
+    match backend {
+        TodoBackend::TodoTxt => read_todo_txt(&util::expand(file)),
+        TodoBackend::Taskwarrior => read_taskwarrior(),
+    }
+}
+
+/// Files whose changes mean the tasks changed
+fn watched(backend:TodoBackend, file:&str)->Vec<PathBuf>{
+    match backend {
+        TodoBackend::TodoTxt => vec![util::expand(file)],
+        TodoBackend::Taskwarrior => {
+            let data = std::env::var("TASKDATA").map_or_else(|_| util::expand("~/.task"), PathBuf::from);
+            // Taskwarrior 2 keeps the pending tasks in a text file, 3 in a database
+            vec![data.join("pending.data"), data.join("taskchampion.sqlite3")]
+        },
+    }
+}
+
+/// Mark a todo.txt line done, with the completion date and the priority kept as a tag
+fn complete_line(path:&Path, index:usize, line:&str)->Result<(), String>{
+    let text = fs::read_to_string(path).map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+    let mut lines:Vec<&str> = text.lines().collect();
+    // the line is looked for elsewhere if the file changed since it was read
+    let index = if lines.get(index).is_some_and(|current| current.trim_end() == line) {index} else {
+        lines.iter().position(|current| current.trim_end() == line)
+            .ok_or_else(|| format!("the task {:?} is no longer in {}", line, path.display()))?
+    };
+    let today = Local::now().format("%Y-%m-%d");
+    let done = match priority(line) {
+        Some((priority, rest)) => format!("x {} {} pri:{}", today, rest, priority),
+        None => format!("x {} {}", today, line),
+    };
+    lines[index] = &done;
+    let mut text = lines.join("\n");
+    text.push('\n');
+    fs::write(path, text).map_err(|err| format!("failed to write {}: {}", path.display(), err))
+}
+
+/// Mark a task done on a background thread, the watcher then picks up the change
+pub fn complete(backend:TodoBackend, file:String, task:Task){
+    std::thread::spawn(move || {
+        let completed = match (&task.key, backend) {
+            (Key::Line(index, line), TodoBackend::TodoTxt) => complete_line(&util::expand(&file), *index, line),
+            (Key::Uuid(uuid), TodoBackend::Taskwarrior) => Command::new("task")
+                .args(["rc.confirmation=off", "rc.verbose=nothing", uuid.as_str(), "done"])
+                .stdin(Stdio::null())
+                .status()
+                .map_err(|err| format!("failed to run task: {}", err))
+                .and_then(|status| if status.success() {Ok(())} else {Err(format!("task exited with {}", status))}),
+            // the backend changed since the task was read
+            _ => Ok(()),
+        };
+        if let Err(err) = completed {
+            log::warn!("failed to complete the task {:?}: {}", task.description, err);
+        }
+    });
+}
+
+/// There is a todo.txt file set or Taskwarrior is used, otherwise the section is left out
+pub fn configured(config:&Config)->bool{
+    config.todo_backend == TodoBackend::Taskwarrior || !config.todo_file.is_empty()
+}
+
+/// Emits the pending tasks, and again whenever they change, see [`Message::Todo`]
+pub fn listen(backend:TodoBackend, file:String)->Subscription<Message>{
+    struct TodoSubscription;
+    Subscription::run_with_id(
+        (TypeId::of::<TodoSubscription>(), backend, file.clone()),
+        stream::channel(1, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            std::thread::spawn(move || {
+                let paths = watched(backend, &file);
+                let (events, changes) = std::sync::mpsc::channel();
+                let watcher = notify::recommended_watcher(events).and_then(|mut watcher| {
+                    for dir in paths.iter().filter_map(|path| path.parent()) {
+                        notify::Watcher::watch(&mut watcher, dir, notify::RecursiveMode::NonRecursive)?;
+                    }
+                    Ok(watcher)
+                });
+                // kept alive while the thread runs
+                let _watcher = watcher.map_err(|err| log::warn!("failed to watch the tasks: {}", err)).ok();
+                loop {
+                    let tasks = read(backend, &file).unwrap_or_else(|err| {
+                        log::warn!("failed to read the tasks: {}", err);
+                        Vec::new()
+                    });
+                    if tx.send(tasks).is_err() {break;}
+                    // wait for a change of one of the files, then for the writes to settle
+                    loop {
+                        let Ok(event) = changes.recv() else {return};
+                        let changed = event.is_ok_and(|event:notify::Event| {
+                            (event.kind.is_create() || event.kind.is_modify())
+                                && event.paths.iter().any(|changed| paths.iter().any(|path| path.file_name() == changed.file_name()))
+                        });
+                        if changed {break;}
+                    }
+                    std::thread::sleep(SETTLE);
+                    while changes.try_recv().is_ok() {}
+                }
+            });
+            while let Some(tasks) = rx.recv().await {
+                let _ = output.send(Message::Todo(tasks)).await;
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pending_lines(){
+        let task = parse_line(3, "(A) 2024-01-02 Call mom +family @phone due:2024-02-03 ").unwrap();
+        assert_eq!(task.description, "Call mom +family @phone");
+        assert_eq!(task.priority, Some('A'));
+        assert_eq!(task.due, NaiveDate::from_ymd_opt(2024, 2, 3));
+        assert_eq!(task.key, Key::Line(3, "(A) 2024-01-02 Call mom +family @phone due:2024-02-03".into()));
+        let task = parse_line(0, "Read https://example.com/a:b t:2024 later due:soon").unwrap();
+        assert_eq!(task.description, "Read https://example.com/a:b later");
+        assert_eq!((task.priority, task.due), (None, None));
+    }
+
+    #[test]
+    fn skips_done_and_empty_lines(){
+        assert_eq!(parse_line(0, "x 2024-01-02 Done task"), None);
+        assert_eq!(parse_line(0, "   "), None);
+        // only a single uppercase letter is a priority
+        assert_eq!(parse_line(0, "(ab) Task").unwrap().priority, None);
+        assert_eq!(parse_line(0, "(a) Task").unwrap().description, "(a) Task");
+    }
+}
@@ -0,0 +1,208 @@
+//! Touch gestures for scrollables on convertibles and tablets. The scrollable follows the
+//! finger by itself, [`Kinetic`] keeps it moving after a flick with decaying speed, and pulling
+//! down past the top of the content publishes a message, e.g. to refresh what it shows.
+
+use std::time::Instant;
+
+use cosmic::iced::{
+    event::{Event, Status},
+    mouse::{self, ScrollDelta},
+    touch, window, Length, Point, Rectangle, Size, Vector,
+};
+use cosmic::iced_core::{
+    clipboard::Clipboard,
+    layout::{self, Layout},
+    overlay, renderer,
+    widget::{operation::Operation, tree, Tree, Widget},
+    Shell,
+};
+use cosmic::{Element, Renderer, Theme};
+
+/// Seconds for the speed of a flick to fall to about a third
+const FLING_DECAY:f32 = 0.325;
+/// Speed in logical pixels per second below which a flick stops, or is not one to begin with
+const MIN_FLING_SPEED:f32 = 50.;
+/// Weight of the newest movement in the smoothed speed of the finger
+const SPEED_SMOOTHING:f32 = 0.4;
+/// Distance in logical pixels the content has to be pulled down past its top to refresh
+const PULL_DISTANCE:f32 = 80.;
+
+/// Adds momentum and pull-to-refresh to the touch scrolling of a scrollable
+pub struct Kinetic<'a, Message> {
+    content: Element<'a, Message>,
+    at_top: bool,
+    on_pull: Option<Message>,
+}
+
+/// Wrap a scrollable, which should report through `at_top` whether it is scrolled to the top
+pub fn kinetic<'a, Message>(content:impl Into<Element<'a, Message>>, at_top:bool)->Kinetic<'a, Message>{
+    Kinetic { content: content.into(), at_top, on_pull: None }
+}
+
+impl<'a, Message> Kinetic<'a, Message> {
+    /// Message published when the content is pulled down past its top and let go
+    pub fn on_pull(mut self, message:Message)->Self{
+        self.on_pull = Some(message);
+        self
+    }
+}
+
+#[derive(Default)]
+struct State {
+    /// Finger on the content, where it was last and when
+    finger: Option<(touch::Finger, Point, Instant)>,
+    /// Smoothed speed of the finger, or of the flick after it was lifted, downwards positive
+    speed:f32,
+    /// Time of the last frame of a flick
+    flinging: Option<Instant>,
+    /// How far the finger moved down while the content was at its top
+    pulled:f32,
+}
+
+impl<'a, Message:Clone> Kinetic<'a, Message> {
+    /// Scroll the content by `dy` pixels, downwards positive like the finger, as the wheel would
+    fn scroll(&mut self, tree:&mut Tree, dy:f32, layout:Layout<'_>, renderer:&Renderer, clipboard:&mut dyn Clipboard, shell:&mut Shell<'_, Message>, viewport:&Rectangle){
+        let wheel = Event::Mouse(mouse::Event::WheelScrolled { delta: ScrollDelta::Pixels { x: 0., y: dy } });
+        let cursor = mouse::Cursor::Available(layout.bounds().center());
+        self.content.as_widget_mut().on_event(&mut tree.children[0], wheel, layout, cursor, renderer, clipboard, shell, viewport);
+    }
+}
+
+impl<'a, Message:Clone> Widget<Message, Theme, Renderer> for Kinetic<'a, Message> {
+    fn tag(&self)->tree::Tag{
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self)->tree::State{
+        tree::State::new(State::default())
+    }
+
+    fn size(&self)->Size<Length>{
+        self.content.as_widget().size()
+    }
+
+    fn size_hint(&self)->Size<Length>{
+        self.content.as_widget().size_hint()
+    }
+
+    fn children(&self)->Vec<Tree>{
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&mut self, tree:&mut Tree){
+        tree.diff_children(std::slice::from_mut(&mut self.content));
+    }
+
+    fn layout(&self, tree:&mut Tree, renderer:&Renderer, limits:&layout::Limits)->layout::Node{
+        self.content.as_widget().layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(&self, tree:&mut Tree, layout:Layout<'_>, renderer:&Renderer, operation:&mut dyn Operation){
+        self.content.as_widget().operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree:&mut Tree,
+        event:Event,
+        layout:Layout<'_>,
+        cursor:mouse::Cursor,
+        renderer:&Renderer,
+        clipboard:&mut dyn Clipboard,
+        shell:&mut Shell<'_, Message>,
+        viewport:&Rectangle,
+    )->Status{
+        let state = tree.state.downcast_mut::<State>();
+        match event {
+            Event::Touch(touch::Event::FingerPressed { id, position }) if layout.bounds().contains(position) => {
+                // touching the content stops a flick
+                *state = State { finger: Some((id, position, Instant::now())), ..State::default() };
+            },
+            Event::Touch(touch::Event::FingerMoved { id, position }) => {
+                if let Some((_, last, time)) = state.finger.as_mut().filter(|(finger, ..)| *finger == id) {
+                    let now = Instant::now();
+                    let dy = position.y - last.y;
+                    let dt = now.duration_since(*time).as_secs_f32().max(f32::EPSILON);
+                    state.speed += SPEED_SMOOTHING * (dy / dt - state.speed);
+                    state.pulled = if self.at_top {(state.pulled + dy).max(0.)} else {0.};
+                    *last = position;
+                    *time = now;
+                }
+            },
+            Event::Touch(touch::Event::FingerLifted { id, .. }) if state.finger.is_some_and(|(finger, ..)| finger == id) => {
+                state.finger = None;
+                if state.pulled >= PULL_DISTANCE {
+                    if let Some(message) = self.on_pull.clone() {
+                        shell.publish(message);
+                    }
+                } else if state.speed.abs() >= MIN_FLING_SPEED {
+                    state.flinging = Some(Instant::now());
+                    shell.request_redraw(window::RedrawRequest::NextFrame);
+                }
+                state.pulled = 0.;
+            },
+            Event::Touch(touch::Event::FingerLost { id, .. }) if state.finger.is_some_and(|(finger, ..)| finger == id) => {
+                *state = State::default();
+            },
+            Event::Mouse(mouse::Event::ButtonPressed(_) | mouse::Event::WheelScrolled { .. }) => {
+                state.flinging = None;
+            },
+            Event::Window(window::Event::RedrawRequested(now)) => {
+                if let Some(last) = state.flinging {
+                    let dt = now.saturating_duration_since(last).as_secs_f32();
+                    let dy = state.speed * dt;
+                    state.speed *= (-dt / FLING_DECAY).exp();
+                    state.flinging = (state.speed.abs() >= MIN_FLING_SPEED).then_some(now);
+                    if state.flinging.is_some() {
+                        shell.request_redraw(window::RedrawRequest::NextFrame);
+                    }
+                    self.scroll(tree, dy, layout, renderer, clipboard, shell, viewport);
+                }
+            },
+            _ => (),
+        }
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0], event, layout, cursor, renderer, clipboard, shell, viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree:&Tree,
+        layout:Layout<'_>,
+        cursor:mouse::Cursor,
+        viewport:&Rectangle,
+        renderer:&Renderer,
+    )->mouse::Interaction{
+        self.content.as_widget().mouse_interaction(&tree.children[0], layout, cursor, viewport, renderer)
+    }
+
+    fn draw(
+        &self,
+        tree:&Tree,
+        renderer:&mut Renderer,
+        theme:&Theme,
+        style:&renderer::Style,
+        layout:Layout<'_>,
+        cursor:mouse::Cursor,
+        viewport:&Rectangle,
+    ){
+        self.content.as_widget().draw(&tree.children[0], renderer, theme, style, layout, cursor, viewport);
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree:&'b mut Tree,
+        layout:Layout<'_>,
+        renderer:&Renderer,
+        translation:Vector,
+    )->Option<overlay::Element<'b, Message, Theme, Renderer>>{
+        self.content.as_widget_mut().overlay(&mut tree.children[0], layout, renderer, translation)
+    }
+}
+
+impl<'a, Message:Clone + 'a> From<Kinetic<'a, Message>> for Element<'a, Message> {
+    fn from(kinetic:Kinetic<'a, Message>)->Self{
+        Element::new(kinetic)
+    }
+}
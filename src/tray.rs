@@ -0,0 +1,140 @@
+//! Optional tray icon, a StatusNotifierItem on D-Bus, whose tooltip shows a summary of the CPU
+//! and memory usage and whose menu shows or hides the window, pauses the monitor and switches
+//! the config profile. The item runs on its own thread and forwards the chosen entries as
+//! messages, while the app pushes [`Status`] updates through a [`Handle`].
+
+use std::any::TypeId;
+use std::fmt;
+
+use cosmic::iced::{futures::SinkExt, stream, Subscription};
+use cosmic::Application;
+use ksni::menu::{CheckmarkItem, RadioGroup, RadioItem, StandardItem, SubMenu};
+use tokio::sync::mpsc;
+
+use crate::{fl, App, Message};
+
+/// What the tray shows
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Status {
+    /// CPU and memory usage for the tooltip
+    pub summary: String,
+    pub paused: bool,
+    pub window_hidden: bool,
+    /// Entries of the profile menu, the first selecting a profile automatically, like in the
+    /// settings, and the index of the selected one
+    pub profiles: Vec<String>,
+    pub profile: usize,
+}
+
+struct StatusItem {
+    status: Status,
+    messages: mpsc::UnboundedSender<Message>,
+}
+
+impl StatusItem {
+    fn send(&self, message:Message){
+        // the app is gone if nobody receives
+        let _ = self.messages.send(message);
+    }
+}
+
+impl ksni::Tray for StatusItem {
+    fn id(&self)->String{
+        App::APP_ID.to_string()
+    }
+
+    fn title(&self)->String{
+        "eos".to_string()
+    }
+
+    fn icon_name(&self)->String{
+        App::APP_ID.to_string()
+    }
+
+    fn tool_tip(&self)->ksni::ToolTip{
+        ksni::ToolTip {
+            title: "eos".to_string(),
+            description: self.status.summary.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// A click on the icon shows or hides the window
+    fn activate(&mut self, _x:i32, _y:i32){
+        self.send(Message::ToggleWindow);
+    }
+
+    fn menu(&self)->Vec<ksni::MenuItem<Self>>{
+        let window = if self.status.window_hidden {fl!("tray-show-window")} else {fl!("tray-hide-window")};
+        vec![
+            StandardItem {
+                label: window,
+                activate: Box::new(|item:&mut Self| item.send(Message::ToggleWindow)),
+                ..Default::default()
+            }.into(),
+            CheckmarkItem {
+                label: fl!("tray-pause"),
+                checked: self.status.paused,
+                activate: Box::new(|item:&mut Self| item.send(Message::PauseUpdates)),
+                ..Default::default()
+            }.into(),
+            SubMenu {
+                label: fl!("config-profile"),
+                submenu: vec![RadioGroup {
+                    selected: self.status.profile,
+                    select: Box::new(|item:&mut Self, index| item.send(Message::ConfigProfile(index))),
+                    options: self.status.profiles.iter()
+                        .map(|name| RadioItem { label: name.clone(), ..Default::default() })
+                        .collect(),
+                }.into()],
+                ..Default::default()
+            }.into(),
+        ]
+    }
+}
+
+/// Sends the status to the tray icon
+#[derive(Clone)]
+pub struct Handle(ksni::Handle<StatusItem>);
+
+impl Handle {
+    pub fn update(&self, status:Status){
+        self.0.update(|item| item.status = status);
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>)->fmt::Result{
+        f.write_str("tray::Handle")
+    }
+}
+
+/// Removes the icon when the subscription ends, e.g. because it was turned off
+struct Shutdown(ksni::Handle<StatusItem>);
+
+impl Drop for Shutdown {
+    fn drop(&mut self){
+        self.0.shutdown();
+    }
+}
+
+/// Show the icon, sending `Message::TrayReady` with its handle and then the chosen entries
+pub fn listen()->Subscription<Message>{
+    struct TraySubscription;
+    Subscription::run_with_id(
+        TypeId::of::<TraySubscription>(),
+        stream::channel(16, |mut output| async move {
+            let (messages, mut received) = mpsc::unbounded_channel();
+            let service = ksni::TrayService::new(StatusItem { status: Status::default(), messages });
+            let handle = service.handle();
+            let _shutdown = Shutdown(handle.clone());
+            service.spawn();
+            if output.send(Message::TrayReady(Handle(handle))).await.is_ok() {
+                while let Some(message) = received.recv().await {
+                    if output.send(message).await.is_err() {break;}
+                }
+            }
+            std::future::pending().await
+        }),
+    )
+}
@@ -0,0 +1,66 @@
+//! Small helpers shared by the modules that read, fetch, cache and authenticate
+
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Path with `~/` standing for the home directory
+pub fn expand(path:&str)->PathBuf{
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(path), Some(home)) => home.join(path),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Name of this machine as an MQTT topic level, an InfluxDB tag value or a Graphite path
+/// segment, with anything but letters, digits, `-` and `_` replaced by `_`
+pub fn host_name()->String{
+    sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string())
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' {c} else {'_'})
+        .collect()
+}
+
+/// Password of `user` on `host` from the keyring, stored through `secret-tool` under the
+/// attributes `service`, `host` and `user`
+pub fn password(service:&str, host:&str, user:&str)->Result<String, String>{
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", service, "host", host, "user", user])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run secret-tool: {}", err))?;
+    let password = String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string();
+    if !output.status.success() || password.is_empty() {
+        return Err(format!("no password for {} on {} in the keyring", user, host));
+    }
+    Ok(password)
+}
+
+/// Name of the cache file of `key`, like the URL it was fetched from. The 64-bit FNV-1a hash
+/// stays the same across builds, unlike that of `DefaultHasher`, so caches survive updates.
+pub fn cache_name(key:&str)->String{
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_names_are_fnv_1a(){
+        assert_eq!(cache_name(""), "cbf29ce484222325");
+        assert_eq!(cache_name("a"), "af63dc4c8601ec8c");
+        assert_ne!(cache_name("https://a.example/feed"), cache_name("https://b.example/feed"));
+    }
+
+    #[test]
+    fn expands_the_home_directory(){
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand("~/notes/todo.txt"), home.join("notes/todo.txt"));
+        assert_eq!(expand("/etc/eos"), PathBuf::from("/etc/eos"));
+        assert_eq!(expand("~user/todo.txt"), PathBuf::from("~user/todo.txt"));
+    }
+}
@@ -0,0 +1,105 @@
+//! Colours generated from the wallpaper by pywal or wallust. Both write the 16 terminal
+//! colours to a `colors` file in their cache directory, one `#rrggbb` per line, which eos
+//! reads for the monitor's accent and status colours and the shader tint.
+
+use std::path::PathBuf;
+
+use cosmic::iced::Color;
+
+/// Cache directories the palette is looked for in, wallust's pywal template writes to `wal`
+const DIRS:[&str;2] = ["wal", "wallust"];
+const FILE_NAME:&str = "colors";
+
+/// Colours of a generated terminal colour scheme, in the order of the ANSI colours
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colours: [Color;16],
+}
+
+impl Palette {
+    /// Colour of the graphs and highlighted text, the generated blue
+    pub fn accent(&self)->Color{
+        self.colours[4]
+    }
+
+    pub fn success(&self)->Color{
+        self.colours[2]
+    }
+
+    pub fn warning(&self)->Color{
+        self.colours[3]
+    }
+
+    pub fn critical(&self)->Color{
+        self.colours[1]
+    }
+}
+
+/// Location of the first palette file that exists, which is watched for a new wallpaper
+pub fn path()->Option<PathBuf>{
+    let cache = dirs::cache_dir()?;
+    DIRS.iter()
+        .map(|dir| cache.join(dir).join(FILE_NAME))
+        .find(|path| path.exists())
+}
+
+/// Read the palette, logging why it could not be read
+pub fn load()->Option<Palette>{
+    let Some(path) = path() else {
+        log::warn!("no pywal or wallust palette found in the cache directory");
+        return None;
+    };
+    let result = std::fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| parse(&text));
+    match result {
+        Ok(palette) => Some(palette),
+        Err(err) => {
+            log::warn!("invalid palette {}: {}", path.display(), err);
+            None
+        },
+    }
+}
+
+fn parse(text:&str)->Result<Palette, String>{
+    let colours:Vec<Color> = text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(16)
+        .map(|line| Color::parse(line).ok_or_else(|| format!("{:?} is not a colour", line)))
+        .collect::<Result<_, _>>()?;
+    let count = colours.len();
+    let colours = colours.try_into().map_err(|_| format!("{} colours instead of 16", count))?;
+    Ok(Palette { colours })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLOURS:&str = "#1d1f21\n#cc6666\n#b5bd68\n#f0c674\n#81a2be\n#b294bb\n#8abeb7\n#c5c8c6\n\
+        #969896\n#cc6666\n#b5bd68\n#f0c674\n#81a2be\n#b294bb\n#8abeb7\n#ffffff\n";
+
+    #[test]
+    fn parses_sixteen_colours(){
+        let palette = parse(COLOURS).unwrap();
+        assert_eq!(palette.accent(), Color::parse("#81a2be").unwrap());
+        assert_eq!(palette.success(), Color::parse("#b5bd68").unwrap());
+        assert_eq!(palette.warning(), Color::parse("#f0c674").unwrap());
+        assert_eq!(palette.critical(), Color::parse("#cc6666").unwrap());
+    }
+
+    #[test]
+    fn skips_blank_lines_and_ignores_extra_colours(){
+        let text = format!("\n  \n{}#000000\nnot a colour\n", COLOURS.replace('\n', " \n\n"));
+        assert_eq!(parse(&text), parse(COLOURS));
+    }
+
+    #[test]
+    fn rejects_short_or_malformed_palettes(){
+        assert!(parse("").is_err());
+        assert!(parse(&COLOURS.replacen("#ffffff\n", "", 1)).is_err());
+        assert!(parse(&COLOURS.replacen("#81a2be", "#81a2bz", 1)).is_err());
+        assert!(parse(&COLOURS.replacen("#81a2be", "blue", 1)).is_err());
+    }
+}
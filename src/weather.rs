@@ -0,0 +1,271 @@
+//! Weather-reactive shader input and the forecast of the weather section. Fetches the current
+//! cloud cover, precipitation and weather code, or the temperature and conditions of the coming
+//! hours, at the configured location from Open-Meteo through `curl`, which needs no API key.
+
+use std::any::TypeId;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use crate::Message;
+
+/// Open-Meteo updates its current conditions every 15 minutes
+const INTERVAL:Duration = Duration::from_secs(15 * 60);
+/// Precipitation in mm that counts as heavy rain, mapped to 1
+const HEAVY_RAIN_MM:f32 = 4.;
+/// Time before a failed fetch of the forecast is retried
+const RETRY_INTERVAL:Duration = Duration::from_secs(2 * 60);
+/// WMO weather codes of fog and depositing rime fog
+const FOG_CODES:[u8;2] = [45, 48];
+
+/// Open-Meteo response for the background, with the units in a `current_units` object left out
+#[derive(Deserialize)]
+struct WeatherResponse {
+    current: CurrentWeather,
+}
+
+#[derive(Deserialize)]
+struct CurrentWeather {
+    /// Percent of the sky
+    cloud_cover: f32,
+    /// mm in the last hour
+    precipitation: f32,
+    weather_code: u8,
+}
+
+/// Open-Meteo response for the forecast, with the times in seconds since the Unix epoch
+#[derive(Deserialize)]
+struct ForecastResponse {
+    current: CurrentConditions,
+    hourly: HourlyConditions,
+}
+
+#[derive(Deserialize)]
+struct CurrentConditions {
+    time: i64,
+    temperature_2m: f32,
+    weather_code: u8,
+    is_day: u8,
+}
+
+/// One array per variable, with an entry for each hour
+#[derive(Deserialize)]
+struct HourlyConditions {
+    time: Vec<i64>,
+    temperature_2m: Vec<f32>,
+    weather_code: Vec<u8>,
+    is_day: Vec<u8>,
+}
+
+/// Kind of weather of a WMO weather code
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Condition {
+    Clear,
+    PartlyCloudy,
+    Overcast,
+    Fog,
+    Drizzle,
+    Rain,
+    Snow,
+    Thunderstorm,
+}
+
+impl Condition {
+    fn from_code(code:u8)->Self{
+        match code {
+            0 => Condition::Clear,
+            1 | 2 => Condition::PartlyCloudy,
+            45 | 48 => Condition::Fog,
+            51..=57 => Condition::Drizzle,
+            61..=67 | 80..=82 => Condition::Rain,
+            71..=77 | 85 | 86 => Condition::Snow,
+            95..=99 => Condition::Thunderstorm,
+            _ => Condition::Overcast,
+        }
+    }
+
+    /// Symbolic icon of the icon theme, with the moon in place of the sun at night
+    pub fn icon(self, day:bool)->&'static str{
+        match (self, day) {
+            (Condition::Clear, true) => "weather-clear-symbolic",
+            (Condition::Clear, false) => "weather-clear-night-symbolic",
+            (Condition::PartlyCloudy, true) => "weather-few-clouds-symbolic",
+            (Condition::PartlyCloudy, false) => "weather-few-clouds-night-symbolic",
+            (Condition::Overcast, _) => "weather-overcast-symbolic",
+            (Condition::Fog, _) => "weather-fog-symbolic",
+            (Condition::Drizzle, _) => "weather-showers-scattered-symbolic",
+            (Condition::Rain, _) => "weather-showers-symbolic",
+            (Condition::Snow, _) => "weather-snow-symbolic",
+            (Condition::Thunderstorm, _) => "weather-storm-symbolic",
+        }
+    }
+}
+
+/// Weather at a time, with the temperature in degrees Celsius
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conditions {
+    pub time: DateTime<Utc>,
+    pub temperature: f32,
+    pub condition: Condition,
+    pub day: bool,
+}
+
+/// Current weather and that of the coming hours
+#[derive(Clone, Debug, PartialEq)]
+pub struct Forecast {
+    pub current: Conditions,
+    pub hours: Vec<Conditions>,
+    /// The forecast could not be fetched and is the last one that was, as of `current.time`
+    pub offline: bool,
+}
+
+impl Forecast {
+    fn parse(json:&str)->Option<Self>{
+        let ForecastResponse { current, hourly } = serde_json::from_str(json).ok()?;
+        let time = |seconds:i64| DateTime::from_timestamp(seconds, 0);
+        let current = Conditions {
+            time: time(current.time)?,
+            temperature: current.temperature_2m,
+            condition: Condition::from_code(current.weather_code),
+            day: current.is_day == 1,
+        };
+        let hours = hourly.time.into_iter()
+            .zip(hourly.temperature_2m)
+            .zip(hourly.weather_code)
+            .zip(hourly.is_day)
+            .map(|(((seconds, temperature), code), day)| Some(Conditions {
+                time: time(seconds)?,
+                temperature,
+                condition: Condition::from_code(code),
+                day: day == 1,
+            }))
+            .collect::<Option<_>>()?;
+        Some(Forecast { current, hours, offline: false })
+    }
+}
+
+/// Cloud cover and precipitation in 0 to 1, and whether there is fog, at `latitude` and `longitude` in degrees
+fn fetch(latitude:f32, longitude:f32)->Result<[f32;3], String>{
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={:.2}&longitude={:.2}&current=cloud_cover,precipitation,weather_code",
+        latitude, longitude,
+    );
+    let output = Command::new("curl")
+        .args(["--silent", "--fail", "--max-time", "20", &url])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run curl: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("curl exited with {}", output.status));
+    }
+    let WeatherResponse { current } = serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("unexpected response: {}", err))?;
+    Ok([
+        current.cloud_cover / 100.,
+        (current.precipitation / HEAVY_RAIN_MM).min(1.),
+        if FOG_CODES.contains(&current.weather_code) {1.} else {0.},
+    ])
+}
+
+/// Emits the weather at `location` in hundredths of a degree every few minutes, see [`Message::Weather`]
+pub fn listen(location:[i32;2])->Subscription<Message>{
+    struct WeatherSubscription;
+    Subscription::run_with_id(
+        (TypeId::of::<WeatherSubscription>(), location),
+        stream::channel(1, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            std::thread::spawn(move || {
+                let [latitude, longitude] = location.map(|degrees| degrees as f32 / 100.);
+                loop {
+                    match fetch(latitude, longitude) {
+                        Ok(weather) => if tx.send(weather).is_err() {break;},
+                        Err(err) => log::warn!("failed to fetch the weather for the background: {}", err),
+                    }
+                    std::thread::sleep(INTERVAL);
+                }
+            });
+            while let Some(weather) = rx.recv().await {
+                let _ = output.send(Message::Weather(weather)).await;
+            }
+        }),
+    )
+}
+
+/// File the last forecast at a location is kept in, to be shown while offline
+fn forecast_cache(location:[i32;2])->Option<PathBuf>{
+    Some(dirs::cache_dir()?.join("eos").join(format!("forecast_{}_{}.json", location[0], location[1])))
+}
+
+/// Forecast for the next `hours` at `location` in hundredths of a degree, or the cached one
+/// marked as offline if it cannot be fetched
+fn fetch_forecast(location:[i32;2], hours:u8)->Result<Forecast, String>{
+    let [latitude, longitude] = location.map(|degrees| degrees as f32 / 100.);
+    let url = format!(
+        "https://api.open-meteo.com/v1/forecast?latitude={:.2}&longitude={:.2}&current=temperature_2m,weather_code,is_day\
+        &hourly=temperature_2m,weather_code,is_day&forecast_hours={}&timeformat=unixtime",
+        // the first hour is the current one
+        latitude, longitude, hours + 1,
+    );
+    let cache = forecast_cache(location);
+    let fetched = Command::new("curl")
+        .args(["--silent", "--fail", "--max-time", "20", &url])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("failed to run curl: {}", err))
+        .and_then(|output| match output.status.success() {
+            true => Ok(String::from_utf8_lossy(&output.stdout).into_owned()),
+            false => Err(format!("curl exited with {}", output.status)),
+        })
+        .and_then(|json| Forecast::parse(&json).map(|forecast| (json, forecast)).ok_or_else(|| "unexpected response".to_string()));
+    match fetched {
+        Ok((json, forecast)) => {
+            if let Some(cache) = &cache {
+                let written = cache.parent().map_or(Ok(()), fs::create_dir_all).and_then(|()| fs::write(cache, json));
+                if let Err(err) = written {
+                    log::warn!("failed to cache the forecast: {}", err);
+                }
+            }
+            Ok(forecast)
+        },
+        Err(err) => {
+            let cached = cache.and_then(|cache| fs::read_to_string(cache).ok()).and_then(|json| Forecast::parse(&json));
+            cached.map(|forecast| Forecast { offline: true, ..forecast }).ok_or(err)
+        },
+    }
+}
+
+/// Emits the forecast for the next `hours` at `location` in hundredths of a degree every few
+/// minutes, see [`Message::Forecast`]
+pub fn listen_forecast(location:[i32;2], hours:u8)->Subscription<Message>{
+    struct ForecastSubscription;
+    Subscription::run_with_id(
+        (TypeId::of::<ForecastSubscription>(), location, hours),
+        stream::channel(1, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            std::thread::spawn(move || loop {
+                let forecast = fetch_forecast(location, hours);
+                let retry = match &forecast {
+                    Ok(forecast) => forecast.offline,
+                    Err(err) => {
+                        log::warn!("failed to fetch the forecast: {}", err);
+                        true
+                    },
+                };
+                if let Ok(forecast) = forecast {
+                    if tx.send(forecast).is_err() {break;}
+                }
+                std::thread::sleep(if retry {RETRY_INTERVAL} else {INTERVAL});
+            });
+            while let Some(forecast) = rx.recv().await {
+                let _ = output.send(Message::Forecast(forecast)).await;
+            }
+        }),
+    )
+}
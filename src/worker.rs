@@ -0,0 +1,99 @@
+//! Background threads shared by the modules that talk to other programs and machines: the
+//! exporters of the metrics, which take a sample every interval and are restarted when their
+//! settings change, and the subscriptions that block on a connection or a poll on a thread of
+//! their own and emit what it sends as messages.
+
+use std::hash::Hash;
+use std::sync::mpsc as std_mpsc;
+use std::time::{Duration, Instant};
+
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use tokio::sync::mpsc;
+
+use crate::config::Config;
+use crate::Message;
+
+/// Exporter handling samples of the metrics on a thread of its own
+pub trait Export {
+    /// What the exporter was started with, it is restarted when one of them changes
+    type Settings: Clone + PartialEq + Send + 'static;
+    type Sample: Send + 'static;
+
+    fn settings(config:&Config)->Self::Settings;
+    /// Least time between two samples
+    fn interval(settings:&Self::Settings)->Duration;
+    fn sample(settings:&Self::Settings, values:impl Iterator<Item = (&'static str, f32)>)->Self::Sample;
+    /// Handle the samples until the [`Sampler`] is dropped
+    fn run(settings:Self::Settings, samples:std_mpsc::Receiver<Self::Sample>);
+}
+
+/// Sends a sample every interval to the thread of an exporter, which ends once this is dropped
+pub struct Sampler<E:Export> {
+    settings: E::Settings,
+    samples: std_mpsc::Sender<E::Sample>,
+    last: Option<Instant>,
+}
+
+impl<E:Export> Sampler<E> {
+    pub fn start(config:&Config)->Self{
+        let settings = E::settings(config);
+        let (samples, rx) = std_mpsc::channel();
+        let thread_settings = settings.clone();
+        std::thread::spawn(move || E::run(thread_settings, rx));
+        Self { settings, samples, last: None }
+    }
+
+    /// Whether the exporter has to be restarted for the config
+    pub fn outdated(&self, config:&Config)->bool{
+        self.settings != E::settings(config)
+    }
+
+    /// Send the current values of the metrics if the interval has passed since the last sample
+    pub fn sample(&mut self, values:impl Iterator<Item = (&'static str, f32)>){
+        let now = Instant::now();
+        if self.last.is_some_and(|last| now.duration_since(last) < E::interval(&self.settings)) {return;}
+        self.last = Some(now);
+        let _ = self.samples.send(E::sample(&self.settings, values));
+    }
+}
+
+/// Subscription running `work` on a thread of its own and emitting what it sends, until the
+/// subscription ends and the thread finds the sender closed
+pub fn listen<T:Send + 'static>(
+    id:impl Hash + 'static,
+    message:impl Fn(T)->Message + Send + 'static,
+    work:impl FnOnce(mpsc::UnboundedSender<T>) + Send + 'static,
+)->Subscription<Message>{
+    Subscription::run_with_id(id, stream::channel(1, move |mut output| async move {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        std::thread::spawn(move || work(tx));
+        while let Some(value) = rx.recv().await {
+            let _ = output.send(message(value)).await;
+        }
+    }))
+}
+
+/// Run `watch` again `interval` after it returns, sending `None` while it is not running and
+/// logging why it ended after `name`, until the receiver is dropped
+pub fn retry<T>(
+    tx:&mpsc::UnboundedSender<Option<T>>,
+    interval:Duration,
+    name:&str,
+    mut watch:impl FnMut(&mpsc::UnboundedSender<Option<T>>)->Result<(), String>,
+){
+    loop {
+        if let Err(err) = watch(tx) {
+            log::warn!("{}: {}", name, err);
+        }
+        if tx.send(None).is_err() {break;}
+        std::thread::sleep(interval);
+    }
+}
+
+/// Send what `poll` returns every `interval`, until the receiver is dropped
+pub fn every<T>(tx:&mpsc::UnboundedSender<T>, interval:Duration, mut poll:impl FnMut()->T){
+    while tx.send(poll()).is_ok() {
+        std::thread::sleep(interval);
+    }
+}
@@ -0,0 +1,151 @@
+//! Workspaces of COSMIC for [`crate::config::Section::Workspaces`], followed through the
+//! cosmic-workspace-unstable-v1 protocol on a Wayland connection of its own, on a background
+//! thread. A group holds the workspaces of one or more outputs, and clicking a workspace
+//! activates it through a [`Handle`].
+
+use std::any::TypeId;
+use std::fmt;
+
+use cosmic::cctk::cosmic_protocols::workspace::v1::client::zcosmic_workspace_handle_v1;
+use cosmic::cctk::sctk::{
+    self,
+    output::{OutputHandler, OutputState},
+    reexports::{calloop, calloop_wayland_source::WaylandSource},
+    registry::{ProvidesRegistryState, RegistryState},
+};
+use cosmic::cctk::wayland_client::{globals::registry_queue_init, protocol::wl_output, Connection, QueueHandle, WEnum};
+use cosmic::cctk::workspace::{WorkspaceHandler, WorkspaceState};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::{stream, Subscription};
+use tokio::sync::mpsc;
+
+use crate::Message;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Workspace {
+    pub name: String,
+    pub active: bool,
+    /// Index of its group and of the workspace in it, to activate it
+    pub id: (usize, usize),
+}
+
+/// Activates workspaces, sent along with the first workspaces
+#[derive(Clone)]
+pub struct Handle(calloop::channel::Sender<(usize, usize)>);
+
+impl Handle {
+    pub fn activate(&self, id:(usize, usize)){
+        let _ = self.0.send(id);
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>)->fmt::Result{
+        f.write_str("workspaces::Handle")
+    }
+}
+
+struct State {
+    registry_state: RegistryState,
+    output_state: OutputState,
+    workspace_state: WorkspaceState,
+    tx: mpsc::UnboundedSender<Vec<Vec<Workspace>>>,
+    closed: bool,
+}
+
+impl State {
+    fn activate(&mut self, (group, workspace):(usize, usize)){
+        let Some(workspace) = self.workspace_state.workspace_groups().get(group)
+            .and_then(|group| group.workspaces.get(workspace)) else {return};
+        workspace.handle.activate();
+        if let Ok(manager) = self.workspace_state.workspace_manager().get() {
+            manager.commit();
+        }
+    }
+}
+
+impl WorkspaceHandler for State {
+    fn workspace_state(&mut self)->&mut WorkspaceState{
+        &mut self.workspace_state
+    }
+
+    /// Called once the compositor has sent all changes of a state
+    fn done(&mut self){
+        let groups = self.workspace_state.workspace_groups().iter().enumerate().map(|(group_index, group)| {
+            group.workspaces.iter().enumerate().map(|(index, workspace)| Workspace {
+                name: workspace.name.clone(),
+                active: workspace.state.contains(&WEnum::Value(zcosmic_workspace_handle_v1::State::Active)),
+                id: (group_index, index),
+            }).collect()
+        }).collect();
+        self.closed = self.tx.send(groups).is_err();
+    }
+}
+
+impl OutputHandler for State {
+    fn output_state(&mut self)->&mut OutputState{
+        &mut self.output_state
+    }
+    fn new_output(&mut self, _:&Connection, _:&QueueHandle<Self>, _:wl_output::WlOutput){}
+    fn update_output(&mut self, _:&Connection, _:&QueueHandle<Self>, _:wl_output::WlOutput){}
+    fn output_destroyed(&mut self, _:&Connection, _:&QueueHandle<Self>, _:wl_output::WlOutput){}
+}
+
+impl ProvidesRegistryState for State {
+    fn registry(&mut self)->&mut RegistryState{
+        &mut self.registry_state
+    }
+    sctk::registry_handlers!(OutputState);
+}
+
+cosmic::cctk::delegate_workspace!(State);
+sctk::delegate_output!(State);
+sctk::delegate_registry!(State);
+
+/// Follow the workspaces until the receiver is dropped, activating those sent to `activate`
+fn run(tx:mpsc::UnboundedSender<Vec<Vec<Workspace>>>, activate:calloop::channel::Channel<(usize, usize)>)->Result<(), String>{
+    let connection = Connection::connect_to_env().map_err(|err| err.to_string())?;
+    let (globals, event_queue) = registry_queue_init::<State>(&connection).map_err(|err| err.to_string())?;
+    let qh = event_queue.handle();
+    let registry_state = RegistryState::new(&globals);
+    let mut state = State {
+        output_state: OutputState::new(&globals, &qh),
+        workspace_state: WorkspaceState::new(&registry_state, &qh),
+        registry_state,
+        tx,
+        closed: false,
+    };
+    let mut event_loop = calloop::EventLoop::<State>::try_new().map_err(|err| err.to_string())?;
+    WaylandSource::new(connection, event_queue).insert(event_loop.handle()).map_err(|err| err.to_string())?;
+    event_loop.handle().insert_source(activate, |event, _, state| {
+        if let calloop::channel::Event::Msg(id) = event {
+            state.activate(id);
+        }
+    }).map_err(|err| err.to_string())?;
+    while !state.closed {
+        event_loop.dispatch(None, &mut state).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+/// Sends `Message::WorkspacesReady` with the handle activating workspaces, and then
+/// `Message::Workspaces` with the workspaces of each group whenever they change
+pub fn listen()->Subscription<Message>{
+    struct WorkspacesSubscription;
+    Subscription::run_with_id(
+        TypeId::of::<WorkspacesSubscription>(),
+        stream::channel(4, |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let (activate, requests) = calloop::channel::channel();
+            if output.send(Message::WorkspacesReady(Handle(activate))).await.is_err() {return;}
+            std::thread::spawn(move || {
+                if let Err(err) = run(tx, requests) {
+                    log::warn!("failed to follow the COSMIC workspaces: {}", err);
+                }
+            });
+            while let Some(groups) = rx.recv().await {
+                let _ = output.send(Message::Workspaces(groups)).await;
+            }
+        }),
+    )
+}